@@ -0,0 +1,146 @@
+use prost::Message;
+use tendermint_proto::{
+    abci::RequestQuery,
+    crypto::{ProofOp, ProofOps},
+};
+
+use crate::{
+    error::{AppError, ERR_UNKNOWN_REQUEST},
+    iavl::IAVLTree,
+    ics23::{
+        CommitmentProof, NonExistenceProof, PROOF_OP_IAVL_COMMITMENT,
+        PROOF_OP_SIMPLE_MERKLE_COMMITMENT,
+    },
+    store::CommitInfo,
+    types::QueryContext,
+};
+
+use super::{BaseApp, APP_VERSION, STORE_KEYS};
+
+/// A key value pair, this mirrors the cosmos SDK `cosmos.base.kv.v1beta1.Pair` message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Pair {
+    #[prost(bytes = "vec", tag = "1")]
+    pub key: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub value: ::prost::alloc::vec::Vec<u8>,
+}
+
+/// The response to a `store/<store key>/subspace` query, this mirrors the cosmos SDK
+/// `cosmos.base.kv.v1beta1.Pairs` message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Pairs {
+    #[prost(message, repeated, tag = "1")]
+    pub pairs: ::prost::alloc::vec::Vec<Pair>,
+}
+
+impl BaseApp {
+    /// Handles the query paths which predate gRPC queries, and are still used by relayers and
+    /// older clients:
+    ///
+    /// - `app/simulate` and `app/version`
+    /// - `store/<store key>/key` and `store/<store key>/subspace`
+    /// - `custom/<module>/...`
+    ///
+    /// Returns the response value along with the proof, against the app hash of the queried
+    /// version's commit info, if one was requested.
+    pub(super) fn run_legacy_query(
+        &self,
+        ctx: &QueryContext,
+        commit_info: &CommitInfo,
+        request: &RequestQuery,
+    ) -> Result<(Vec<u8>, Option<ProofOps>), AppError> {
+        let path: Vec<&str> = request.path.trim_start_matches('/').split('/').collect();
+
+        match path.as_slice() {
+            ["app", "simulate"] => Ok((self.simulate(&request.data)?.encode_to_vec(), None)),
+            ["app", "version"] => Ok((APP_VERSION.as_bytes().to_vec(), None)),
+            ["store", store_key, sub_path] => query_store(
+                ctx,
+                commit_info,
+                store_key,
+                sub_path,
+                &request.data,
+                request.prove,
+            ),
+            ["custom", module, path @ ..] => self
+                .query_router
+                .query_legacy(ctx, module, path, &request.data)
+                .map(|value| (value, None)),
+            _ => Err(ERR_UNKNOWN_REQUEST.wrap(format!("unknown query path: {}", request.path))),
+        }
+    }
+}
+
+/// Returns the value of a key, or the entries under a prefix, of the store with the given key
+fn query_store(
+    ctx: &QueryContext,
+    commit_info: &CommitInfo,
+    store_key: &str,
+    sub_path: &str,
+    data: &[u8],
+    prove: bool,
+) -> Result<(Vec<u8>, Option<ProofOps>), AppError> {
+    if !STORE_KEYS.contains(&store_key.as_bytes()) {
+        return Err(ERR_UNKNOWN_REQUEST.wrap(format!("no such store: {}", store_key)));
+    }
+
+    let store = ctx
+        .get_multi_store()
+        .get_immutable_sub_store(store_key.as_bytes().to_vec());
+
+    match sub_path {
+        "key" => {
+            let value = store.get(data).cloned().unwrap_or_default();
+
+            // the key is proved against the root of an IAVL tree of the store's entries, which
+            // is in turn proved against the app hash
+            let proof_ops = if prove {
+                let entries: Vec<(Vec<u8>, Vec<u8>)> = store.prefix_iter(&[]).collect();
+                let key_proof = match IAVLTree::from_entries(&entries) {
+                    Some(tree) => tree.commitment_proof(data),
+                    // an empty store has no tree, every key is absent with no neighbours
+                    None => CommitmentProof {
+                        exist: None,
+                        nonexist: Some(NonExistenceProof {
+                            key: data.to_vec(),
+                            left: None,
+                            right: None,
+                        }),
+                    },
+                };
+                let store_proof = commit_info
+                    .proof(store_key.as_bytes())
+                    .expect("the commit info has every store");
+
+                Some(ProofOps {
+                    ops: vec![
+                        ProofOp {
+                            r#type: PROOF_OP_IAVL_COMMITMENT.into(),
+                            key: data.to_vec(),
+                            data: key_proof.encode_to_vec(),
+                        },
+                        ProofOp {
+                            r#type: PROOF_OP_SIMPLE_MERKLE_COMMITMENT.into(),
+                            key: store_key.as_bytes().to_vec(),
+                            data: store_proof.encode_to_vec(),
+                        },
+                    ],
+                })
+            } else {
+                None
+            };
+
+            Ok((value, proof_ops))
+        }
+        "subspace" => {
+            let pairs = store
+                .prefix_iter(data)
+                .map(|(key, value)| Pair { key, value })
+                .collect();
+
+            Ok((Pairs { pairs }.encode_to_vec(), None))
+        }
+        _ => Err(ERR_UNKNOWN_REQUEST.wrap(format!("unexpected query path: {}", sub_path))),
+    }
+}
@@ -0,0 +1,2020 @@
+use std::{
+    any::Any,
+    panic::{self, AssertUnwindSafe},
+    sync::{Arc, RwLock},
+};
+
+use ibc_proto::cosmos::{
+    base::v1beta1::Coin,
+    staking::v1beta1::Params as StakingParams,
+    tx::v1beta1::{SimulateRequest, Tx, TxRaw},
+};
+use prost::Message;
+
+use tendermint_abci::Application;
+use tendermint_proto::{
+    abci::{
+        response_apply_snapshot_chunk, response_offer_snapshot, CheckTxType, Event,
+        Evidence as AbciEvidence, RequestApplySnapshotChunk, RequestBeginBlock, RequestCheckTx,
+        RequestDeliverTx, RequestEndBlock, RequestInfo, RequestInitChain, RequestLoadSnapshotChunk,
+        RequestOfferSnapshot, RequestQuery, ResponseApplySnapshotChunk, ResponseBeginBlock,
+        ResponseCheckTx, ResponseCommit, ResponseDeliverTx, ResponseEndBlock, ResponseInfo,
+        ResponseInitChain, ResponseListSnapshots, ResponseLoadSnapshotChunk, ResponseOfferSnapshot,
+        ResponseQuery, VoteInfo,
+    },
+    types::Header,
+};
+use tracing::{debug, error};
+
+use crate::{
+    error::{AppError, SnapshotError, ERR_INVALID_REQUEST, ERR_OUT_OF_GAS, ERR_PANIC, ERR_TX_DECODE},
+    snapshots::SnapshotManager,
+    store::{CommitInfo, CommittedVersion, CommittedVersions, MultiStore, StoreKVPair},
+    streaming::ABCIListener,
+    types::{
+        read_consensus_params, AccAddress, BasicGasMeter, Context, DecCoin, ErrorOutOfGas, GasInfo,
+        GasMeter, InfiniteGasMeter, QueryContext, SimulationResponse, TxResult,
+    },
+    x::{
+        auth::{self, ante_handler, Auth},
+        bank::{Balance, Bank, GenesisState},
+        distribution::{self, Distribution},
+        evidence::Evidence,
+        slashing::{self, Slashing},
+        staking::{self, Staking},
+    },
+};
+
+mod legacy_query;
+mod msg_router;
+mod query_router;
+
+pub use legacy_query::*;
+pub use msg_router::*;
+pub use query_router::*;
+
+pub const BANK_STORE_PREFIX: [u8; 4] = [098, 097, 110, 107]; // "bank"
+pub const AUTH_STORE_PREFIX: [u8; 3] = [097, 099, 099]; // "acc" - use acc even though it's the auth store to match cosmos SDK
+pub const STAKING_STORE_PREFIX: [u8; 7] = [115, 116, 097, 107, 105, 110, 103]; // "staking"
+pub const SLASHING_STORE_PREFIX: [u8; 8] = [115, 108, 097, 115, 104, 105, 110, 103]; // "slashing"
+pub const EVIDENCE_STORE_PREFIX: [u8; 8] = [101, 118, 105, 100, 101, 110, 099, 101]; // "evidence"
+pub const DISTRIBUTION_STORE_PREFIX: [u8; 12] = [100, 105, 115, 116, 114, 105, 098, 117, 116, 105, 111, 110]; // "distribution"
+pub const PARAMS_STORE_PREFIX: [u8; 6] = [112, 097, 114, 097, 109, 115]; // "params" - holds the consensus params, as in the cosmos SDK
+
+const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const STORE_KEYS: [&[u8]; 7] = [
+    &AUTH_STORE_PREFIX,
+    &BANK_STORE_PREFIX,
+    &STAKING_STORE_PREFIX,
+    &SLASHING_STORE_PREFIX,
+    &EVIDENCE_STORE_PREFIX,
+    &DISTRIBUTION_STORE_PREFIX,
+    &PARAMS_STORE_PREFIX,
+];
+
+/// The gRPC method used to estimate the gas of a tx
+const SIMULATE_QUERY_PATH: &str = "/cosmos.tx.v1beta1.Service/Simulate";
+
+/// Number of committed versions of the store which can be queried
+const QUERYABLE_VERSIONS: u32 = 100;
+
+/// The mode a tx is run in, as with the cosmos SDK `runTxMode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunTxMode {
+    Check,
+    ReCheck,
+    Simulate,
+    Deliver,
+}
+
+/// The ordering of a tx in Tendermint's prioritized mempool, returned by check_tx. Txs with a
+/// higher priority are reaped first and the sender's txs are kept in sequence order.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct MempoolInfo {
+    priority: i64,
+    sender: String,
+}
+
+/// The header, header hash, votes and evidence of the block being executed
+#[derive(Debug, Clone, Default)]
+struct BlockInfo {
+    header: Header,
+    hash: Vec<u8>,
+    vote_info: Vec<VoteInfo>,
+    byzantine_validators: Vec<AbciEvidence>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BaseApp {
+    multi_store: Arc<RwLock<MultiStore>>,
+    check_state: Arc<RwLock<MultiStore>>,
+    committed: Arc<RwLock<CommittedVersions>>,
+    height: Arc<RwLock<u32>>,
+    chain_id: Arc<RwLock<String>>,
+    block: Arc<RwLock<BlockInfo>>,
+    block_gas_meter: Arc<RwLock<Arc<dyn GasMeter>>>,
+    min_gas_prices: Arc<Vec<DecCoin>>,
+    query_router: Arc<QueryRouter>,
+    listeners: Arc<Vec<Arc<dyn ABCIListener>>>,
+    snapshot_manager: Option<Arc<SnapshotManager>>,
+}
+
+impl BaseApp {
+    pub fn new() -> Self {
+        let store = MultiStore::new();
+
+        let address = AccAddress::from_bech32(
+            &"cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux".to_string(),
+        )
+        .expect("this won't fail");
+
+        let auth_genesis = auth::GenesisState {
+            params: auth::default_params(),
+            accounts: vec![auth::genesis_account(&address)],
+        };
+
+        let genesis = GenesisState {
+            balances: vec![Balance {
+                address,
+                coins: vec![Coin {
+                    denom: "uatom".to_string(),
+                    amount: cosmwasm_std::Uint256::from(34_u32),
+                }],
+            }],
+        };
+
+        let mut ctx = Context::new(store);
+        Auth::init_genesis(&mut ctx, auth_genesis);
+        Bank::init_genesis(&mut ctx, genesis);
+        Staking::init_genesis(
+            &mut ctx,
+            staking::GenesisState {
+                params: StakingParams {
+                    bond_denom: "uatom".into(),
+                    ..staking::default_params()
+                },
+            },
+        );
+        Slashing::init_genesis(
+            &mut ctx,
+            slashing::GenesisState {
+                params: slashing::default_params(),
+            },
+        );
+        Distribution::init_genesis(
+            &mut ctx,
+            distribution::GenesisState {
+                params: distribution::default_params(),
+            },
+        );
+
+        let block_gas_meter: Arc<dyn GasMeter> = Arc::new(InfiniteGasMeter::new());
+
+        let query_router = Distribution::register_queries(Evidence::register_queries(
+            Slashing::register_queries(Staking::register_queries(Bank::register_queries(
+                Auth::register_queries(QueryRouter::new()),
+            ))),
+        ));
+
+        let mut committed = CommittedVersions::new(QUERYABLE_VERSIONS);
+        committed.commit(
+            0,
+            CommittedVersion {
+                multi_store: Arc::new(ctx.multi_store.clone()),
+                block_time: Default::default(),
+                commit_info: CommitInfo::new(&ctx.multi_store, &STORE_KEYS),
+            },
+        );
+
+        Self {
+            check_state: Arc::new(RwLock::new(ctx.multi_store.clone())),
+            multi_store: Arc::new(RwLock::new(ctx.multi_store)),
+            committed: Arc::new(RwLock::new(committed)),
+            height: Arc::new(RwLock::new(0)),
+            chain_id: Arc::new(RwLock::new("".into())),
+            block: Arc::new(RwLock::new(BlockInfo::default())),
+            block_gas_meter: Arc::new(RwLock::new(block_gas_meter)),
+            min_gas_prices: Arc::new(vec![]),
+            query_router: Arc::new(query_router),
+            listeners: Arc::new(vec![]),
+            snapshot_manager: None,
+        }
+    }
+
+    fn get_block_height(&self) -> u32 {
+        *self.height.read().expect("RwLock will not be poisoned")
+    }
+
+    fn increment_block_height(&self) -> u32 {
+        let mut height = self.height.write().expect("RwLock will not be poisoned");
+        *height += 1;
+        return *height;
+    }
+
+    fn get_block_gas_meter(&self) -> Arc<dyn GasMeter> {
+        self.block_gas_meter
+            .read()
+            .expect("RwLock will not be poisoned")
+            .clone()
+    }
+
+    fn get_chain_id(&self) -> String {
+        self.chain_id
+            .read()
+            .expect("RwLock will not be poisoned")
+            .clone()
+    }
+
+    /// Transactions with fees below these gas prices are rejected by check_tx
+    pub fn with_min_gas_prices(self, min_gas_prices: Vec<DecCoin>) -> Self {
+        Self {
+            min_gas_prices: Arc::new(min_gas_prices),
+            ..self
+        }
+    }
+
+    /// Returns a context over the given store populated with the current block information
+    fn new_context(&self, multi_store: MultiStore) -> Context {
+        let block = self.block.read().expect("RwLock will not be poisoned").clone();
+
+        Context::new(multi_store)
+            .with_header(block.header)
+            .with_header_hash(block.hash)
+            .with_vote_info(block.vote_info)
+            .with_byzantine_validators(block.byzantine_validators)
+            .with_chain_id(self.get_chain_id())
+            .with_min_gas_prices(self.min_gas_prices.as_ref().clone())
+            .with_block_gas_meter(self.get_block_gas_meter())
+    }
+
+    /// Runs f with a context over the deliver state and returns its result and the events it
+    /// emits
+    fn run_block_hook<T>(&self, f: impl FnOnce(&mut Context) -> T) -> (T, Vec<Event>) {
+        let mut multi_store = self.multi_store.write().expect("RwLock will not be poisoned");
+        let mut ctx = self.new_context(std::mem::replace(&mut *multi_store, MultiStore::new()));
+
+        let res = f(&mut ctx);
+
+        let events = ctx.event_manager().take_events();
+        *multi_store = ctx.multi_store;
+        (res, events)
+    }
+
+    /// Streams the request, response and state changes of an ABCI message to every listener
+    pub fn with_listener(self, listener: Arc<dyn ABCIListener>) -> Self {
+        let mut listeners = self.listeners.as_ref().clone();
+        listeners.push(listener);
+
+        let mut multi_store = self.multi_store.write().expect("RwLock will not be poisoned");
+        for store_key in STORE_KEYS {
+            multi_store.add_listening_store_key(store_key.to_vec());
+        }
+        drop(multi_store);
+
+        Self {
+            listeners: Arc::new(listeners),
+            ..self
+        }
+    }
+
+    /// Periodically snapshots the multistore and serves snapshots to, and restores from, state
+    /// sync peers
+    pub fn with_snapshot_manager(self, snapshot_manager: SnapshotManager) -> Self {
+        Self {
+            snapshot_manager: Some(Arc::new(snapshot_manager)),
+            ..self
+        }
+    }
+
+    /// Replaces the multistore and height with those restored from a snapshot
+    fn restore(&self, height: u64, mut multi_store: MultiStore) {
+        if !self.listeners.is_empty() {
+            for store_key in STORE_KEYS {
+                multi_store.add_listening_store_key(store_key.to_vec());
+            }
+        }
+
+        let height = height
+            .try_into()
+            .expect("snapshot height will fit in a u32");
+
+        // the snapshot doesn't hold the block time, so historical queries at the height see the
+        // zero time as they would at genesis
+        self.committed
+            .write()
+            .expect("RwLock will not be poisoned")
+            .commit(
+                height,
+                CommittedVersion {
+                    multi_store: Arc::new(multi_store.clone()),
+                    block_time: Default::default(),
+                    commit_info: CommitInfo::new(&multi_store, &STORE_KEYS),
+                },
+            );
+        *self.check_state.write().expect("RwLock will not be poisoned") = multi_store.clone();
+        *self.multi_store.write().expect("RwLock will not be poisoned") = multi_store;
+        *self.height.write().expect("RwLock will not be poisoned") = height;
+    }
+
+    /// Copies the deliver state into a new committed version, which also becomes the new check
+    /// state, and returns the version
+    fn commit_version(&self, height: u32) -> CommittedVersion {
+        let mut multi_store = self
+            .multi_store
+            .read()
+            .expect("RwLock will not be poisoned")
+            .clone();
+        // the pending change set belongs to the deliver state
+        multi_store.take_change_set();
+
+        *self.check_state.write().expect("RwLock will not be poisoned") = multi_store.clone();
+
+        let commit_info = CommitInfo::new(&multi_store, &STORE_KEYS);
+        let multi_store = Arc::new(multi_store);
+        let block_time = self
+            .block
+            .read()
+            .expect("RwLock will not be poisoned")
+            .header
+            .time
+            .clone()
+            .unwrap_or_default();
+        let version = CommittedVersion {
+            multi_store,
+            block_time,
+            commit_info,
+        };
+        self.committed
+            .write()
+            .expect("RwLock will not be poisoned")
+            .commit(height, version.clone());
+
+        version
+    }
+
+    /// Returns the committed version of the store at the given height, or the latest version if
+    /// the height is zero
+    fn get_committed_version(&self, height: i64) -> Result<(u32, CommittedVersion), AppError> {
+        let committed = self.committed.read().expect("RwLock will not be poisoned");
+        let (latest_height, latest) = committed
+            .latest()
+            .expect("the genesis version is always committed");
+
+        if height == 0 {
+            return Ok((latest_height, latest));
+        }
+
+        if height < 0 || height > latest_height.into() {
+            return Err(ERR_INVALID_REQUEST.wrap(format!(
+                "cannot query with height {}; latest height is {}",
+                height, latest_height
+            )));
+        }
+
+        let height = height as u32;
+        committed
+            .get(height)
+            .map(|version| (height, version))
+            .ok_or(ERR_INVALID_REQUEST.wrap(format!(
+                "failed to load state at height {}; version does not exist or has been pruned",
+                height
+            )))
+    }
+
+    fn stream_changes(
+        &self,
+        stream: impl Fn(&dyn ABCIListener, &[StoreKVPair]) -> std::io::Result<()>,
+    ) {
+        if self.listeners.is_empty() {
+            return;
+        }
+
+        let change_set = self
+            .multi_store
+            .write()
+            .expect("RwLock will not be poisoned")
+            .take_change_set();
+
+        for listener in self.listeners.iter() {
+            if let Err(e) = stream(listener.as_ref(), &change_set) {
+                error!("Failed to stream state changes: {}", e);
+            }
+        }
+    }
+
+    fn run_query(&self, request: RequestQuery) -> ResponseQuery {
+        debug!("Processing query. Path: {}", request.path);
+
+        let (height, version) = match self.get_committed_version(request.height) {
+            Ok(version) => version,
+            Err(e) => return query_error_response(e, request.height),
+        };
+        let ctx =
+            QueryContext::new(&version.multi_store, height).with_block_time(version.block_time);
+
+        let res = if request.path == SIMULATE_QUERY_PATH {
+            self.query_simulate(&request.data).map(|value| (value, None))
+        } else if self.query_router.has_route(&request.path) {
+            self.query_router
+                .query(&ctx, &request.path, &request.data)
+                .map(|value| (value, None))
+        } else {
+            self.run_legacy_query(&ctx, &version.commit_info, &request)
+        };
+
+        match res {
+            Ok((value, proof_ops)) => ResponseQuery {
+                code: 0,
+                log: "exists".to_string(),
+                info: "".to_string(),
+                index: 0,
+                key: request.data,
+                value: value.into(),
+                proof_ops,
+                height: height.into(),
+                codespace: "".to_string(),
+            },
+            Err(e) => query_error_response(e, height.into()),
+        }
+    }
+
+    fn run_check_tx(&self, request: RequestCheckTx) -> ResponseCheckTx {
+        let mode = if request.r#type == CheckTxType::Recheck as i32 {
+            RunTxMode::ReCheck
+        } else {
+            RunTxMode::Check
+        };
+
+        let (gas_info, mempool_info, res) = self.run_tx(mode, &request.tx);
+
+        match res {
+            Ok(result) => ResponseCheckTx {
+                code: 0,
+                data: result.data.into(),
+                log: result.log,
+                gas_wanted: gas_info.gas_wanted as i64,
+                gas_used: gas_info.gas_used as i64,
+                events: result.events,
+                priority: mempool_info.priority,
+                sender: mempool_info.sender,
+                ..Default::default()
+            },
+            Err(e) => check_tx_error_response(e, gas_info.gas_wanted, gas_info.gas_used),
+        }
+    }
+
+    fn run_deliver_tx(&self, request: &RequestDeliverTx) -> ResponseDeliverTx {
+        let (gas_info, _, res) = self.run_tx(RunTxMode::Deliver, &request.tx);
+
+        match res {
+            Ok(result) => ResponseDeliverTx {
+                code: 0,
+                data: result.data.into(),
+                log: result.log,
+                info: "".to_string(),
+                gas_wanted: gas_info.gas_wanted as i64,
+                gas_used: gas_info.gas_used as i64,
+                events: result.events,
+                codespace: "".to_string(),
+            },
+            Err(e) => deliver_tx_error_response(e, gas_info.gas_wanted, gas_info.gas_used),
+        }
+    }
+
+    /// Returns the gas used by, and result of, the tx when run against the latest state
+    fn simulate(&self, tx_bytes: &[u8]) -> Result<SimulationResponse, AppError> {
+        let (gas_info, _, res) = self.run_tx(RunTxMode::Simulate, tx_bytes);
+
+        Ok(SimulationResponse {
+            gas_info: Some(gas_info),
+            result: Some(res?),
+        })
+    }
+
+    /// Handles the `cosmos.tx.v1beta1.Service/Simulate` gRPC method
+    fn query_simulate(&self, data: &[u8]) -> Result<Vec<u8>, AppError> {
+        let req =
+            SimulateRequest::decode(data).map_err(|e| ERR_INVALID_REQUEST.wrap(e.to_string()))?;
+
+        // the tx field is deprecated in favour of tx_bytes, but is still supported
+        let tx_bytes = match req.tx {
+            Some(tx) if req.tx_bytes.is_empty() => tx.encode_to_vec(),
+            _ => req.tx_bytes,
+        };
+
+        // SimulateResponse has the same fields as SimulationResponse
+        self.simulate(&tx_bytes).map(|res| res.encode_to_vec())
+    }
+
+    /// Runs a tx, returning the gas it used and its mempool ordering along with its result. The
+    /// ante handler runs in every mode but the messages are only executed when simulating or
+    /// delivering. Changes made by the ante handler, e.g. fee payment, are kept even if the
+    /// messages fail. Nothing is written when simulating, the tx runs on a throwaway branch of
+    /// the check state with an infinite gas meter.
+    fn run_tx(
+        &self,
+        mode: RunTxMode,
+        tx_bytes: &[u8],
+    ) -> (GasInfo, MempoolInfo, Result<TxResult, AppError>) {
+        let mut mempool_info = MempoolInfo::default();
+
+        let (tx, tx_raw) = match decode_tx(tx_bytes) {
+            Ok(tx) => tx,
+            Err(e) => return (GasInfo::default(), mempool_info, Err(e)),
+        };
+
+        let gas_wanted = tx
+            .auth_info
+            .as_ref()
+            .and_then(|auth_info| auth_info.fee.as_ref())
+            .map_or(0, |fee| fee.gas_limit);
+        let mut gas_info = GasInfo {
+            gas_wanted,
+            gas_used: 0,
+        };
+
+        let msgs = match decode_msgs(&tx) {
+            Ok(msgs) => msgs,
+            Err(e) => return (gas_info, mempool_info, Err(e)),
+        };
+
+        let signers = match get_signers(&tx, &msgs) {
+            Ok(signers) => signers,
+            Err(e) => return (gas_info, mempool_info, Err(e)),
+        };
+
+        let block_gas_meter = self.get_block_gas_meter();
+        if mode == RunTxMode::Deliver && block_gas_meter.is_out_of_gas() {
+            return (
+                gas_info,
+                mempool_info,
+                Err(ERR_OUT_OF_GAS.wrap("no block gas left to run tx")),
+            );
+        }
+
+        let gas_meter: Arc<dyn GasMeter> = match mode {
+            RunTxMode::Simulate => Arc::new(InfiniteGasMeter::new()),
+            _ => Arc::new(BasicGasMeter::new(gas_wanted)),
+        };
+
+        let state = match mode {
+            RunTxMode::Deliver => &self.multi_store,
+            _ => &self.check_state,
+        };
+        let mut state = state.write().expect("RwLock will not be poisoned");
+
+        let mut ctx = self
+            .new_context(state.clone())
+            .with_tx_bytes(tx_bytes.to_vec())
+            .with_check_tx(mode != RunTxMode::Deliver)
+            .with_recheck_tx(mode == RunTxMode::ReCheck)
+            .with_gas_meter(gas_meter.clone());
+
+        let simulate = mode == RunTxMode::Simulate;
+        let mut res = catch_panic(|| ante_handler(&mut ctx, &tx, &tx_raw, &signers, simulate));
+        let mut events = ctx.event_manager().take_events();
+        let mut data = vec![];
+
+        mempool_info.priority = ctx.priority();
+        if let Some(signer) = signers.first() {
+            mempool_info.sender = signer.to_string();
+        }
+
+        if res.is_ok() && mode != RunTxMode::Simulate {
+            *state = ctx.multi_store.clone();
+        }
+
+        if res.is_ok() && (mode == RunTxMode::Simulate || mode == RunTxMode::Deliver) {
+            let msgs_res = catch_panic(|| run_msgs(&mut ctx, msgs));
+
+            res = msgs_res.map(|(msgs_data, mut msgs_events)| {
+                data = msgs_data;
+                events.append(&mut msgs_events);
+            });
+        }
+
+        // as in the cosmos SDK, the gas of every delivered tx counts towards the block gas, even
+        // if its ante handler failed
+        if mode == RunTxMode::Deliver {
+            let block_gas_res = catch_panic(|| {
+                block_gas_meter.consume_gas(gas_meter.gas_consumed_to_limit(), "block gas meter");
+                Ok(())
+            });
+            res = res.and(block_gas_res);
+
+            if res.is_ok() {
+                *state = ctx.multi_store;
+            }
+        }
+
+        gas_info.gas_used = gas_meter.gas_consumed();
+
+        let res = match res {
+            Ok(()) => Ok(TxResult {
+                data,
+                log: "".to_string(),
+                events,
+            }),
+            Err(AppError::OutOfGas(descriptor)) => Err(ERR_OUT_OF_GAS.wrap(format!(
+                "out of gas in location: {}; gasWanted: {}, gasUsed: {}",
+                descriptor, gas_info.gas_wanted, gas_info.gas_used
+            ))),
+            Err(e) => Err(e),
+        };
+
+        (gas_info, mempool_info, res)
+    }
+}
+
+fn decode_tx(tx_bytes: &[u8]) -> Result<(Tx, TxRaw), AppError> {
+    let tx_raw = TxRaw::decode(tx_bytes).map_err(|e| ERR_TX_DECODE.wrap(e.to_string()))?;
+    let tx = Tx::decode(tx_bytes).map_err(|e| ERR_TX_DECODE.wrap(e.to_string()))?;
+    Ok((tx, tx_raw))
+}
+
+/// Runs f converting any panic into an error, as the cosmos SDK does when running a tx. Out of
+/// gas panics become out of gas errors, all other panics become `ERR_PANIC` errors.
+fn catch_panic<T>(f: impl FnOnce() -> Result<T, AppError>) -> Result<T, AppError> {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| Err(panic_error(payload)))
+}
+
+/// Runs an ABCI handler, converting a panic into the response built by on_panic, so that a
+/// malformed request can't crash the node. Only the handlers serving txs and queries are
+/// recovered, a panic in begin_block, end_block or commit leaves the state in doubt so it's
+/// left to halt the node.
+fn recover_handler<T>(handler: impl FnOnce() -> T, on_panic: impl FnOnce(AppError) -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(handler)).unwrap_or_else(|payload| {
+        let err = panic_error(payload);
+        error!("Recovered from panic in ABCI handler: {}", err);
+        on_panic(err)
+    })
+}
+
+fn panic_error(payload: Box<dyn Any + Send>) -> AppError {
+    if let Some(err) = payload.downcast_ref::<ErrorOutOfGas>() {
+        AppError::OutOfGas(err.descriptor.clone())
+    } else if let Some(msg) = payload.downcast_ref::<&str>() {
+        ERR_PANIC.wrap(*msg)
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        ERR_PANIC.wrap(msg.clone())
+    } else {
+        ERR_PANIC.into()
+    }
+}
+
+impl Application for BaseApp {
+    fn info(&self, request: RequestInfo) -> ResponseInfo {
+        debug!(
+            "Got info request. Tendermint version: {}; Block version: {}; P2P version: {}",
+            request.version, request.block_version, request.p2p_version
+        );
+
+        let (_, latest) = self
+            .committed
+            .read()
+            .expect("RwLock will not be poisoned")
+            .latest()
+            .expect("the genesis version is always committed");
+
+        ResponseInfo {
+            data: "gaia-rs".to_string(),
+            version: APP_VERSION.to_string(),
+            app_version: 1,
+            last_block_height: self.get_block_height().into(),
+            last_block_app_hash: latest.commit_info.hash().into(),
+        }
+    }
+
+    fn query(&self, request: RequestQuery) -> ResponseQuery {
+        let height = request.height;
+        recover_handler(
+            || self.run_query(request),
+            |e| query_error_response(e, height),
+        )
+    }
+
+    fn check_tx(&self, request: RequestCheckTx) -> ResponseCheckTx {
+        recover_handler(
+            || self.run_check_tx(request),
+            |e| check_tx_error_response(e, 0, 0),
+        )
+    }
+
+    fn init_chain(&self, request: RequestInitChain) -> ResponseInitChain {
+        let consensus_params = request
+            .consensus_params
+            .expect("Tendermint gives the consensus params in init_chain");
+        *self.chain_id.write().expect("RwLock will not be poisoned") = request.chain_id;
+
+        // stored in both states so that txs can be checked before the first block is committed
+        for state in [&self.multi_store, &self.check_state] {
+            let mut multi_store = state.write().expect("RwLock will not be poisoned");
+            let mut ctx = Context::new(std::mem::replace(&mut *multi_store, MultiStore::new()));
+            ctx.set_consensus_params(&consensus_params);
+            *multi_store = ctx.multi_store;
+        }
+
+        ResponseInitChain::default()
+    }
+
+    fn begin_block(&self, request: RequestBeginBlock) -> ResponseBeginBlock {
+        let header = request.header.clone().unwrap_or_default();
+
+        // a node restored from a state sync snapshot never sees init_chain
+        let mut chain_id = self.chain_id.write().expect("RwLock will not be poisoned");
+        if chain_id.is_empty() {
+            *chain_id = header.chain_id.clone();
+        } else if *chain_id != header.chain_id {
+            panic!(
+                "invalid chain-id on begin_block; expected: {}, got: {}",
+                chain_id, header.chain_id
+            );
+        }
+        drop(chain_id);
+
+        *self.block.write().expect("RwLock will not be poisoned") = BlockInfo {
+            header,
+            hash: request.hash.to_vec(),
+            vote_info: request
+                .last_commit_info
+                .clone()
+                .map(|info| info.votes)
+                .unwrap_or_default(),
+            byzantine_validators: request.byzantine_validators.clone(),
+        };
+
+        let max_gas = read_consensus_params(
+            &self.multi_store.read().expect("RwLock will not be poisoned"),
+        )
+        .block
+        .expect("the block consensus params are set by init_chain")
+        .max_gas;
+        let block_gas_meter: Arc<dyn GasMeter> = if max_gas > 0 {
+            Arc::new(BasicGasMeter::new(max_gas as u64))
+        } else {
+            Arc::new(InfiniteGasMeter::new())
+        };
+        *self
+            .block_gas_meter
+            .write()
+            .expect("RwLock will not be poisoned") = block_gas_meter;
+
+        let ((), events) = self.run_block_hook(|ctx| {
+            Distribution::begin_block(ctx);
+            Slashing::begin_block(ctx);
+            Evidence::begin_block(ctx);
+            Staking::begin_block(ctx);
+        });
+        let response = ResponseBeginBlock { events };
+
+        self.stream_changes(|listener, change_set| {
+            listener.listen_begin_block(&request, &response, change_set)
+        });
+
+        response
+    }
+
+    fn deliver_tx(&self, request: RequestDeliverTx) -> ResponseDeliverTx {
+        let response = recover_handler(
+            || self.run_deliver_tx(&request),
+            |e| deliver_tx_error_response(e, 0, 0),
+        );
+
+        self.stream_changes(|listener, change_set| {
+            listener.listen_deliver_tx(&request, &response, change_set)
+        });
+
+        response
+    }
+
+    fn end_block(&self, request: RequestEndBlock) -> ResponseEndBlock {
+        let (validator_updates, events) = self.run_block_hook(Staking::end_block);
+        let response = ResponseEndBlock {
+            validator_updates,
+            events,
+            ..Default::default()
+        };
+
+        self.stream_changes(|listener, change_set| {
+            listener.listen_end_block(&request, &response, change_set)
+        });
+
+        response
+    }
+
+    fn commit(&self) -> ResponseCommit {
+        let new_height = self.increment_block_height();
+
+        let committed = self.commit_version(new_height);
+
+        let response = ResponseCommit {
+            data: committed.commit_info.hash().into(),
+            retain_height: (new_height - 1).into(),
+        };
+
+        if let Some(snapshot_manager) = &self.snapshot_manager {
+            snapshot_manager.snapshot_if_due(new_height.into(), committed.multi_store);
+        }
+
+        self.stream_changes(|listener, change_set| listener.listen_commit(&response, change_set));
+
+        response
+    }
+
+    fn list_snapshots(&self) -> ResponseListSnapshots {
+        let snapshots = match &self.snapshot_manager {
+            Some(snapshot_manager) => snapshot_manager.list().unwrap_or_else(|e| {
+                error!("Failed to list snapshots: {}", e);
+                vec![]
+            }),
+            None => vec![],
+        };
+
+        ResponseListSnapshots { snapshots }
+    }
+
+    fn offer_snapshot(&self, request: RequestOfferSnapshot) -> ResponseOfferSnapshot {
+        use response_offer_snapshot::Result;
+
+        let result = match (&self.snapshot_manager, request.snapshot) {
+            (None, _) => Result::Abort,
+            (Some(_), None) => Result::Reject,
+            (Some(snapshot_manager), Some(snapshot)) => match snapshot_manager.offer(snapshot) {
+                Ok(()) => Result::Accept,
+                Err(SnapshotError::UnknownFormat(_)) => Result::RejectFormat,
+                Err(SnapshotError::InvalidMetadata(e)) => {
+                    error!("Rejecting snapshot with invalid metadata: {}", e);
+                    Result::Reject
+                }
+                Err(e) => {
+                    error!("Failed to restore snapshot: {}", e);
+                    Result::Abort
+                }
+            },
+        };
+
+        ResponseOfferSnapshot {
+            result: result as i32,
+        }
+    }
+
+    fn load_snapshot_chunk(&self, request: RequestLoadSnapshotChunk) -> ResponseLoadSnapshotChunk {
+        let chunk = self.snapshot_manager.as_ref().and_then(|snapshot_manager| {
+            snapshot_manager
+                .load_chunk(request.height, request.format, request.chunk)
+                .unwrap_or_else(|e| {
+                    error!("Failed to load snapshot chunk: {}", e);
+                    None
+                })
+        });
+
+        ResponseLoadSnapshotChunk {
+            chunk: chunk.unwrap_or_default().into(),
+        }
+    }
+
+    fn apply_snapshot_chunk(
+        &self,
+        request: RequestApplySnapshotChunk,
+    ) -> ResponseApplySnapshotChunk {
+        use response_apply_snapshot_chunk::Result;
+
+        let snapshot_manager = match &self.snapshot_manager {
+            Some(snapshot_manager) => snapshot_manager,
+            None => {
+                return ResponseApplySnapshotChunk {
+                    result: Result::Abort as i32,
+                    ..Default::default()
+                }
+            }
+        };
+
+        match snapshot_manager.apply_chunk(request.index, request.chunk.to_vec()) {
+            Ok(None) => ResponseApplySnapshotChunk {
+                result: Result::Accept as i32,
+                ..Default::default()
+            },
+            Ok(Some((height, multi_store))) => {
+                self.restore(height, multi_store);
+
+                ResponseApplySnapshotChunk {
+                    result: Result::Accept as i32,
+                    ..Default::default()
+                }
+            }
+            Err(SnapshotError::ChunkHashMismatch(index)) => ResponseApplySnapshotChunk {
+                result: Result::Retry as i32,
+                refetch_chunks: vec![index],
+                reject_senders: vec![request.sender],
+            },
+            Err(SnapshotError::InvalidSnapshot(e)) => {
+                error!("Rejecting invalid snapshot: {}", e);
+
+                ResponseApplySnapshotChunk {
+                    result: Result::RejectSnapshot as i32,
+                    ..Default::default()
+                }
+            }
+            Err(e) => {
+                error!("Failed to apply snapshot chunk: {}", e);
+
+                ResponseApplySnapshotChunk {
+                    result: Result::Abort as i32,
+                    ..Default::default()
+                }
+            }
+        }
+    }
+}
+
+fn deliver_tx_error_response(err: AppError, gas_wanted: u64, gas_used: u64) -> ResponseDeliverTx {
+    ResponseDeliverTx {
+        code: err.code(),
+        data: Default::default(),
+        log: err.to_string(),
+        info: "".to_string(),
+        gas_wanted: gas_wanted as i64,
+        gas_used: gas_used as i64,
+        events: vec![],
+        codespace: err.codespace().to_string(),
+    }
+}
+
+fn check_tx_error_response(err: AppError, gas_wanted: u64, gas_used: u64) -> ResponseCheckTx {
+    ResponseCheckTx {
+        code: err.code(),
+        log: err.to_string(),
+        gas_wanted: gas_wanted as i64,
+        gas_used: gas_used as i64,
+        codespace: err.codespace().to_string(),
+        ..Default::default()
+    }
+}
+
+fn query_error_response(err: AppError, height: i64) -> ResponseQuery {
+    ResponseQuery {
+        code: err.code(),
+        log: err.to_string(),
+        info: "".to_string(),
+        index: 0,
+        key: Default::default(),
+        value: Default::default(),
+        proof_ops: None,
+        height,
+        codespace: err.codespace().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::thread;
+
+    use cosmwasm_std::Uint256;
+    use ibc_proto::{
+        cosmos::{
+            bank::v1beta1::{
+                MsgSend, MsgSendResponse, QueryAllBalancesRequest, QueryAllBalancesResponse,
+                QueryBalanceRequest,
+            },
+            distribution::v1beta1::{QueryCommunityPoolRequest, QueryCommunityPoolResponse},
+            tx::{
+                signing::v1beta1::SignMode,
+                v1beta1::{
+                    mode_info::{Single, Sum},
+                    AuthInfo, Fee, ModeInfo, SignDoc, SignerInfo, TxBody,
+                },
+            },
+        },
+        google::protobuf::Any,
+    };
+    use secp256k1::{hashes::sha256, PublicKey, Secp256k1, SecretKey};
+    use tendermint_proto::{
+        abci::{BlockParams, ConsensusParams, LastCommitInfo, Validator as VoteValidator},
+        google::protobuf::Timestamp,
+    };
+
+    use super::*;
+    use crate::{
+        crypto::PubKey,
+        iavl::IAVLTree,
+        ics23::CommitmentProof,
+        types::{new_event, parse_dec_coins, MsgData, TxMsgData},
+        x::staking::testing::consensus_params,
+    };
+
+    const ADDRESS: &str = "cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux";
+
+    /// The secret key of ADDRESS, derived from the mnemonic in the README
+    const SECRET_KEY: [u8; 32] = [
+        246, 253, 208, 232, 142, 57, 136, 204, 16, 134, 144, 226, 129, 132, 80, 132, 113, 244, 142,
+        186, 40, 62, 235, 97, 252, 232, 88, 247, 183, 169, 100, 47,
+    ];
+
+    /// Returns an app whose chain has been initialized with the default consensus params
+    fn initialized_app() -> BaseApp {
+        let app = BaseApp::new();
+        app.init_chain(RequestInitChain {
+            consensus_params: Some(consensus_params()),
+            ..Default::default()
+        });
+        app
+    }
+
+    /// Sets the uatom balance of ADDRESS in the deliver state, as a tx would
+    fn deliver_balance(app: &BaseApp, amount: u32) {
+        let mut multi_store = app.multi_store.write().unwrap();
+        let mut ctx = Context::new(multi_store.clone());
+        Bank::init_genesis(
+            &mut ctx,
+            GenesisState {
+                balances: vec![Balance {
+                    address: AccAddress::from_bech32(ADDRESS).unwrap(),
+                    coins: vec![Coin {
+                        denom: "uatom".into(),
+                        amount: amount.into(),
+                    }],
+                }],
+            },
+        );
+        *multi_store = ctx.multi_store;
+    }
+
+    /// Returns a tx body with a MsgSend of each amount of uatom from ADDRESS
+    fn send_tx_body(amounts: &[u32]) -> TxBody {
+        let messages = amounts
+            .iter()
+            .map(|amount| Any {
+                type_url: "/cosmos.bank.v1beta1.MsgSend".into(),
+                value: MsgSend {
+                    from_address: ADDRESS.into(),
+                    to_address: "cosmos1qyqszqgpqyqszqgpqyqszqgpqyqszqgpjnp7du".into(),
+                    amount: vec![Coin {
+                        denom: "uatom".into(),
+                        amount: (*amount).into(),
+                    }],
+                }
+                .encode_to_vec(),
+            })
+            .collect();
+
+        TxBody {
+            messages,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the signer info of the key which signs the test txs
+    fn signer_info() -> SignerInfo {
+        let secret_key = SecretKey::from_slice(&SECRET_KEY).unwrap();
+        let public_key = PublicKey::from_secret_key(&Secp256k1::new(), &secret_key);
+
+        SignerInfo {
+            public_key: Some(Any {
+                type_url: "/cosmos.crypto.secp256k1.PubKey".into(),
+                value: PubKey {
+                    key: public_key.serialize().to_vec(),
+                }
+                .encode_to_vec(),
+            }),
+            mode_info: Some(ModeInfo {
+                sum: Some(Sum::Single(Single {
+                    mode: SignMode::Direct as i32,
+                })),
+            }),
+            sequence: 0,
+        }
+    }
+
+    /// Returns an encoded tx, with an empty signature, sending uatom from ADDRESS
+    fn unsigned_send_tx(amounts: &[u32]) -> Vec<u8> {
+        TxRaw {
+            body_bytes: send_tx_body(amounts).encode_to_vec(),
+            auth_info_bytes: AuthInfo {
+                signer_infos: vec![signer_info()],
+                fee: Some(Fee {
+                    gas_limit: 200_000,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }
+            .encode_to_vec(),
+            signatures: vec![vec![]],
+        }
+        .encode_to_vec()
+    }
+
+    /// Returns an encoded tx, signed for the empty chain ID, sending uatom from ADDRESS
+    fn signed_send_tx(amounts: &[u32]) -> Vec<u8> {
+        signed_send_tx_with_fee(amounts, 0)
+    }
+
+    /// Returns an encoded tx, as with signed_send_tx, which pays a fee of the given uatom
+    fn signed_send_tx_with_fee(amounts: &[u32], fee: u32) -> Vec<u8> {
+        sign_tx(send_tx_body(amounts), fee)
+    }
+
+    /// Returns the encoded tx with the body, signed for the empty chain ID, which pays a fee of
+    /// the given uatom
+    fn sign_tx(body: TxBody, fee: u32) -> Vec<u8> {
+        let body_bytes = body.encode_to_vec();
+        let auth_info_bytes = AuthInfo {
+            signer_infos: vec![signer_info()],
+            fee: Some(Fee {
+                amount: vec![Coin {
+                    denom: "uatom".into(),
+                    amount: fee.into(),
+                }],
+                gas_limit: 200_000,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+        .encode_to_vec();
+
+        let sign_bytes = SignDoc {
+            body_bytes: body_bytes.clone(),
+            auth_info_bytes: auth_info_bytes.clone(),
+            chain_id: "".into(),
+            account_number: 0,
+        }
+        .encode_to_vec();
+        let signature = Secp256k1::new().sign_ecdsa(
+            &secp256k1::Message::from_hashed_data::<sha256::Hash>(&sign_bytes),
+            &SecretKey::from_slice(&SECRET_KEY).unwrap(),
+        );
+
+        TxRaw {
+            body_bytes,
+            auth_info_bytes,
+            signatures: vec![signature.serialize_compact().to_vec()],
+        }
+        .encode_to_vec()
+    }
+
+    /// Returns the height queried along with the uatom balance of ADDRESS
+    fn query_balance(app: &BaseApp, height: i64) -> (i64, Uint256) {
+        let res = app.query(RequestQuery {
+            data: QueryAllBalancesRequest {
+                address: ADDRESS.into(),
+                pagination: None,
+            }
+            .encode_to_vec()
+            .into(),
+            path: "/cosmos.bank.v1beta1.Query/AllBalances".into(),
+            height,
+            prove: false,
+        });
+        assert_eq!(res.code, 0, "{}", res.log);
+
+        let balances = QueryAllBalancesResponse::decode(res.value).unwrap().balances;
+        (res.height, balances[0].amount)
+    }
+
+    #[test]
+    fn query_uses_committed_versions() {
+        let app = BaseApp::new();
+
+        deliver_balance(&app, 100);
+        assert_eq!(query_balance(&app, 0), (0, 34u32.into()));
+
+        app.commit();
+        deliver_balance(&app, 200);
+        app.commit();
+
+        assert_eq!(query_balance(&app, 0), (2, 200u32.into()));
+        assert_eq!(query_balance(&app, 1), (1, 100u32.into()));
+
+        let res = app.query(RequestQuery {
+            path: "/cosmos.bank.v1beta1.Query/AllBalances".into(),
+            height: 3,
+            ..Default::default()
+        });
+        assert_eq!(res.code, 18);
+        assert_eq!(res.codespace, "sdk");
+    }
+
+    #[test]
+    fn committed_versions_keep_their_block_time() {
+        let app = initialized_app();
+
+        for height in 1..=2 {
+            app.begin_block(RequestBeginBlock {
+                header: Some(Header {
+                    height,
+                    time: Some(Timestamp {
+                        seconds: height * 10,
+                        nanos: 0,
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            });
+            app.end_block(RequestEndBlock { height });
+            app.commit();
+        }
+
+        // historical queries see the time of their own block, not the latest one
+        let (_, version) = app.get_committed_version(1).unwrap();
+        assert_eq!(version.block_time.seconds, 10);
+        let (_, version) = app.get_committed_version(0).unwrap();
+        assert_eq!(version.block_time.seconds, 20);
+    }
+
+    #[test]
+    fn query_unknown_path_fails() {
+        let app = BaseApp::new();
+
+        let res = app.query(RequestQuery {
+            path: "/cosmos.bank.v1beta1.Query/Unknown".into(),
+            ..Default::default()
+        });
+
+        assert_eq!(res.code, 6);
+        assert_eq!(res.codespace, "sdk");
+        assert_eq!(
+            res.log,
+            "unknown query path: /cosmos.bank.v1beta1.Query/Unknown: unknown request"
+        );
+    }
+
+    #[test]
+    fn legacy_queries_work() {
+        let app = BaseApp::new();
+
+        let res = app.query(RequestQuery {
+            path: "/app/version".into(),
+            ..Default::default()
+        });
+        assert_eq!(res.code, 0, "{}", res.log);
+        assert_eq!(&res.value[..], b"0.1.0");
+
+        let res = app.query(RequestQuery {
+            path: "custom/bank/balance".into(),
+            data: format!(r#"{{"address":"{}","denom":"uatom"}}"#, ADDRESS)
+                .into_bytes()
+                .into(),
+            ..Default::default()
+        });
+        assert_eq!(res.code, 0, "{}", res.log);
+        assert_eq!(&res.value[..], br#"{"amount":"34","denom":"uatom"}"#);
+
+        let res = app.query(RequestQuery {
+            path: "custom/gov/proposals".into(),
+            ..Default::default()
+        });
+        assert_eq!(
+            res.log,
+            "no custom querier found for route gov: unknown request"
+        );
+
+        let res = app.query(RequestQuery {
+            path: "/store/foo/key".into(),
+            ..Default::default()
+        });
+        assert_eq!(res.log, "no such store: foo: unknown request");
+
+        let res = app.query(RequestQuery {
+            path: "/app/simulate".into(),
+            data: vec![0xff].into(),
+            ..Default::default()
+        });
+        assert_eq!(res.code, 2);
+    }
+
+    #[test]
+    fn store_queries_work() {
+        let app = BaseApp::new();
+
+        // balances are stored under the prefix 2
+        let res = app.query(RequestQuery {
+            path: "/store/bank/subspace".into(),
+            data: vec![2].into(),
+            ..Default::default()
+        });
+        assert_eq!(res.code, 0, "{}", res.log);
+        let pairs = Pairs::decode(res.value).unwrap().pairs;
+        assert_eq!(pairs.len(), 1);
+
+        let res = app.query(RequestQuery {
+            path: "/store/bank/key".into(),
+            data: pairs[0].key.clone().into(),
+            prove: true,
+            ..Default::default()
+        });
+        assert_eq!(res.code, 0, "{}", res.log);
+        assert_eq!(res.value.to_vec(), pairs[0].value);
+
+        let ops = res.proof_ops.unwrap().ops;
+        assert_eq!(ops[0].r#type, "ics23:iavl");
+        let proof = CommitmentProof::decode(&ops[0].data[..])
+            .unwrap()
+            .exist
+            .unwrap();
+        assert_eq!(proof.key, pairs[0].key);
+        assert_eq!(proof.value, pairs[0].value);
+
+        let entries = vec![(pairs[0].key.clone(), pairs[0].value.clone())];
+        let tree = IAVLTree::from_entries(&entries).unwrap();
+        assert_eq!(proof.calculate_root().unwrap(), tree.root_hash());
+
+        // the store's root is proved against the app hash
+        assert_eq!(ops[1].r#type, "ics23:simple");
+        assert_eq!(ops[1].key, b"bank");
+        let store_proof = CommitmentProof::decode(&ops[1].data[..])
+            .unwrap()
+            .exist
+            .unwrap();
+        assert_eq!(store_proof.value, tree.root_hash());
+        let app_hash = app.info(RequestInfo::default()).last_block_app_hash;
+        assert_eq!(store_proof.calculate_root().unwrap(), app_hash);
+
+        // a missing key is proved absent
+        let res = app.query(RequestQuery {
+            path: "/store/bank/key".into(),
+            data: vec![9].into(),
+            prove: true,
+            ..Default::default()
+        });
+        assert!(res.value.is_empty());
+        let proof = CommitmentProof::decode(&res.proof_ops.unwrap().ops[0].data[..]).unwrap();
+        assert!(proof.nonexist.is_some());
+
+        // as is every key of an empty store
+        let res = app.query(RequestQuery {
+            path: "/store/evidence/key".into(),
+            data: vec![9].into(),
+            prove: true,
+            ..Default::default()
+        });
+        assert_eq!(res.code, 0, "{}", res.log);
+        let ops = res.proof_ops.unwrap().ops;
+        let proof = CommitmentProof::decode(&ops[0].data[..]).unwrap();
+        assert_eq!(proof.nonexist.unwrap().key, vec![9]);
+        let store_proof = CommitmentProof::decode(&ops[1].data[..])
+            .unwrap()
+            .exist
+            .unwrap();
+        assert_eq!(store_proof.calculate_root().unwrap(), app_hash);
+    }
+
+    #[test]
+    fn commit_returns_the_app_hash() {
+        let app = BaseApp::new();
+        let genesis_hash = app.info(RequestInfo::default()).last_block_app_hash;
+
+        deliver_balance(&app, 100);
+        let res = app.commit();
+
+        // the app hash commits to the state, so it changes along with it
+        assert_ne!(res.data, genesis_hash);
+        assert_eq!(res.data, app.info(RequestInfo::default()).last_block_app_hash);
+    }
+
+    #[test]
+    fn simulate_works() {
+        let app = BaseApp::new();
+        let tx_bytes = unsigned_send_tx(&[10]);
+
+        let res = app.query(RequestQuery {
+            path: "/cosmos.tx.v1beta1.Service/Simulate".into(),
+            data: SimulateRequest {
+                tx: None,
+                tx_bytes: tx_bytes.clone(),
+            }
+            .encode_to_vec()
+            .into(),
+            ..Default::default()
+        });
+        assert_eq!(res.code, 0, "{}", res.log);
+
+        let res = SimulationResponse::decode(res.value).unwrap();
+        let gas_info = res.gas_info.unwrap();
+        assert_eq!(gas_info.gas_wanted, 200_000);
+        assert!(gas_info.gas_used > 0);
+
+        let events = res.result.unwrap().events;
+        assert_eq!(events[0].r#type, "tx");
+        assert_eq!(events[1].r#type, "tx");
+        assert_eq!(events[2].r#type, "message");
+        assert!(events.iter().any(|event| event.r#type == "transfer"));
+
+        // the legacy path gives the same response
+        let legacy_res = app.query(RequestQuery {
+            path: "app/simulate".into(),
+            data: tx_bytes.clone().into(),
+            ..Default::default()
+        });
+        let legacy_res = SimulationResponse::decode(legacy_res.value).unwrap();
+        assert_eq!(legacy_res.gas_info.unwrap(), gas_info);
+
+        // nothing is written, and the unsigned tx can't be delivered
+        assert_eq!(query_balance(&app, 0), (0, 34u32.into()));
+        let check_state = app.check_state.read().unwrap().clone();
+        let balance = Bank::query_balance(
+            &QueryContext::new(&check_state, 0),
+            QueryBalanceRequest {
+                address: ADDRESS.into(),
+                denom: "uatom".into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(balance.balance.unwrap().amount, 34u32.into());
+
+        let res = app.deliver_tx(RequestDeliverTx {
+            tx: tx_bytes.into(),
+        });
+        assert_ne!(res.code, 0);
+    }
+
+    #[test]
+    fn simulate_reports_errors() {
+        let app = BaseApp::new();
+
+        let res = app.query(RequestQuery {
+            path: "/cosmos.tx.v1beta1.Service/Simulate".into(),
+            data: SimulateRequest {
+                tx: None,
+                tx_bytes: unsigned_send_tx(&[35]),
+            }
+            .encode_to_vec()
+            .into(),
+            ..Default::default()
+        });
+
+        assert_eq!(res.code, 5);
+        assert_eq!(
+            res.log,
+            "34uatom is smaller than 35uatom: insufficient funds"
+        );
+    }
+
+    #[test]
+    fn deliver_tx_returns_msg_responses() {
+        let app = BaseApp::new();
+
+        let res = app.deliver_tx(RequestDeliverTx {
+            tx: signed_send_tx(&[10, 5]).into(),
+        });
+        assert_eq!(res.code, 0, "{}", res.log);
+
+        let msg_send_response = Any {
+            type_url: "/cosmos.bank.v1beta1.MsgSendResponse".into(),
+            value: MsgSendResponse {}.encode_to_vec(),
+        };
+        let tx_msg_data = TxMsgData::decode(res.data).unwrap();
+        assert_eq!(
+            tx_msg_data.msg_responses,
+            vec![msg_send_response.clone(), msg_send_response.clone()]
+        );
+
+        // the v0.45 data holds the msg type and encoded response of each message
+        let msg_data = MsgData {
+            msg_type: "/cosmos.bank.v1beta1.MsgSend".into(),
+            data: msg_send_response.value,
+        };
+        assert_eq!(tx_msg_data.data, vec![msg_data.clone(), msg_data]);
+
+        // each message's events are preceded by its action
+        let actions: Vec<usize> = res
+            .events
+            .iter()
+            .enumerate()
+            .filter(|(_, event)| {
+                event.r#type == "message" && &event.attributes[0].key[..] == b"action"
+            })
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(actions, vec![2, 8]);
+
+        app.commit();
+        assert_eq!(query_balance(&app, 0), (1, 19u32.into()));
+    }
+
+    #[test]
+    fn failed_msg_reverts_tx() {
+        let app = BaseApp::new();
+
+        // the first message succeeds but the second doesn't
+        let res = app.deliver_tx(RequestDeliverTx {
+            tx: signed_send_tx(&[10, 30]).into(),
+        });
+        assert_eq!(res.code, 5);
+        assert!(res.data.is_empty());
+        assert!(res.events.is_empty());
+
+        app.commit();
+        assert_eq!(query_balance(&app, 0), (1, 34u32.into()));
+    }
+
+    #[test]
+    fn check_tx_enforces_min_gas_prices() {
+        let app =
+            BaseApp::new().with_min_gas_prices(parse_dec_coins("0.0001uatom,0.1stake").unwrap());
+        let check_tx = |fee| {
+            app.check_tx(RequestCheckTx {
+                tx: signed_send_tx_with_fee(&[1], fee).into(),
+                r#type: CheckTxType::New as i32,
+            })
+        };
+
+        // 200,000 gas at 0.0001uatom requires a fee of 20uatom
+        let res = check_tx(19);
+        assert_eq!(res.code, 13);
+        assert_eq!(
+            res.log,
+            "insufficient fees; got: 19uatom required: 20000stake,20uatom: insufficient fee"
+        );
+
+        let res = check_tx(20);
+        assert_eq!(res.code, 0, "{}", res.log);
+
+        // the minimum gas prices are local to the node so aren't checked by deliver_tx
+        let res = app.deliver_tx(RequestDeliverTx {
+            tx: signed_send_tx_with_fee(&[1], 0).into(),
+        });
+        assert_eq!(res.code, 0, "{}", res.log);
+    }
+
+    #[test]
+    fn recheck_tx_uses_updated_check_state() {
+        let app = BaseApp::new();
+        let check_tx = |tx: &[u8], check_type: CheckTxType| {
+            app.check_tx(RequestCheckTx {
+                tx: tx.to_vec().into(),
+                r#type: check_type as i32,
+            })
+        };
+        let tx = signed_send_tx_with_fee(&[1], 20);
+
+        let res = check_tx(&tx, CheckTxType::New);
+        assert_eq!(res.code, 0, "{}", res.log);
+        assert_eq!(res.sender, ADDRESS);
+        assert_eq!(res.priority, 0);
+
+        // the fee has been deducted from the check state, which can't cover it a second time
+        let res = check_tx(&tx, CheckTxType::Recheck);
+        assert_eq!(res.code, 5);
+
+        // commit resets the check state to the committed state
+        app.commit();
+        let res = check_tx(&tx, CheckTxType::Recheck);
+        assert_eq!(res.code, 0, "{}", res.log);
+
+        // signatures aren't verified again when rechecking
+        let mut tx_raw = TxRaw::decode(&tx[..]).unwrap();
+        tx_raw.signatures = vec![vec![1; 64]];
+        let tx = tx_raw.encode_to_vec();
+
+        app.commit();
+        assert_eq!(check_tx(&tx, CheckTxType::New).code, 4);
+        assert_eq!(check_tx(&tx, CheckTxType::Recheck).code, 0);
+    }
+
+    #[test]
+    fn deliver_tx_deducts_fees() {
+        let app = BaseApp::new();
+
+        let res = app.deliver_tx(RequestDeliverTx {
+            tx: signed_send_tx_with_fee(&[10], 4).into(),
+        });
+        assert_eq!(res.code, 0, "{}", res.log);
+        // the fee transfer events are followed by the fee event
+        let fee_event = new_event(
+            "tx",
+            vec![("fee", "4uatom".into()), ("fee_payer", ADDRESS.into())],
+        );
+        assert_eq!(res.events[0].r#type, "coin_spent");
+        assert_eq!(res.events[4], fee_event);
+
+        app.commit();
+        assert_eq!(query_balance(&app, 0), (1, 20u32.into()));
+
+        let multi_store = app.multi_store.read().unwrap().clone();
+        let fee_collector = Bank::query_balance(
+            &QueryContext::new(&multi_store, 1),
+            QueryBalanceRequest {
+                address: AccAddress::new_module_address("fee_collector").to_string(),
+                denom: "uatom".into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(fee_collector.balance.unwrap().amount, 4u32.into());
+    }
+
+    #[test]
+    fn deliver_tx_requires_fee_payer_signature() {
+        let app = BaseApp::new();
+
+        // the fee payer is another account, which hasn't signed the tx
+        let mut tx_raw = TxRaw::decode(&signed_send_tx_with_fee(&[1], 4)[..]).unwrap();
+        let auth_info = AuthInfo::decode(&tx_raw.auth_info_bytes[..]).unwrap();
+        tx_raw.auth_info_bytes = AuthInfo {
+            fee: Some(Fee {
+                payer: "cosmos1qyqszqgpqyqszqgpqyqszqgpqyqszqgpjnp7du".into(),
+                ..auth_info.fee.clone().unwrap()
+            }),
+            ..auth_info
+        }
+        .encode_to_vec();
+
+        let res = app.deliver_tx(RequestDeliverTx {
+            tx: tx_raw.encode_to_vec().into(),
+        });
+        assert_eq!(res.code, 4);
+        assert_eq!(
+            res.log,
+            "wrong number of signers; expected 2, got 1: unauthorized"
+        );
+    }
+
+    #[test]
+    fn deliver_tx_insufficient_funds_for_fees() {
+        let app = BaseApp::new();
+
+        let res = app.deliver_tx(RequestDeliverTx {
+            tx: signed_send_tx_with_fee(&[1], 35).into(),
+        });
+        assert_eq!(res.code, 5);
+        assert_eq!(
+            res.log,
+            "34uatom is smaller than 35uatom: insufficient funds"
+        );
+
+        app.commit();
+        assert_eq!(query_balance(&app, 0), (1, 34u32.into()));
+    }
+
+    #[test]
+    fn block_gas_is_charged_for_failed_ante_handler() {
+        let app = BaseApp::new();
+        app.init_chain(RequestInitChain {
+            consensus_params: Some(ConsensusParams {
+                block: Some(BlockParams {
+                    max_bytes: 22_020_096,
+                    max_gas: 10_000_000,
+                }),
+                ..consensus_params()
+            }),
+            ..Default::default()
+        });
+        app.begin_block(RequestBeginBlock {
+            header: Some(Header {
+                height: 1,
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+        // the fee can't be paid so the ante handler fails
+        let res = app.deliver_tx(RequestDeliverTx {
+            tx: signed_send_tx_with_fee(&[1], 35).into(),
+        });
+        assert_eq!(res.code, 5);
+        assert!(res.gas_used > 0);
+        assert_eq!(
+            app.get_block_gas_meter().gas_consumed(),
+            res.gas_used as u64
+        );
+    }
+
+    #[test]
+    fn deliver_tx_validates_tx() {
+        let app = initialized_app();
+        app.begin_block(RequestBeginBlock {
+            header: Some(Header {
+                height: 5,
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let deliver_tx = |body: TxBody| {
+            app.deliver_tx(RequestDeliverTx {
+                tx: sign_tx(body, 0).into(),
+            })
+        };
+
+        let res = deliver_tx(TxBody {
+            timeout_height: 4,
+            ..send_tx_body(&[1])
+        });
+        assert_eq!(res.code, 30);
+        assert_eq!(
+            res.log,
+            "block height: 5, timeout height: 4: tx timeout height"
+        );
+
+        let res = deliver_tx(TxBody {
+            memo: "m".repeat(257),
+            ..send_tx_body(&[1])
+        });
+        assert_eq!(res.code, 12);
+
+        // the memo is limited in bytes, not characters
+        let res = deliver_tx(TxBody {
+            memo: "é".repeat(129),
+            ..send_tx_body(&[1])
+        });
+        assert_eq!(res.code, 12);
+        assert_eq!(
+            res.log,
+            "maximum number of characters is 256 but received 258 characters: memo too large"
+        );
+
+        let res = deliver_tx(TxBody {
+            extension_options: vec![Any {
+                type_url: "/test.ExtensionOption".into(),
+                value: vec![],
+            }],
+            ..send_tx_body(&[1])
+        });
+        assert_eq!(res.code, 31);
+
+        let res = deliver_tx(send_tx_body(&[0]));
+        assert_eq!(res.code, 10);
+        assert_eq!(res.log, "0uatom: invalid coins");
+
+        // a signature without a signer info
+        let mut tx_raw = TxRaw::decode(&signed_send_tx(&[1])[..]).unwrap();
+        tx_raw.auth_info_bytes = AuthInfo {
+            signer_infos: vec![],
+            ..AuthInfo::decode(&tx_raw.auth_info_bytes[..]).unwrap()
+        }
+        .encode_to_vec();
+        let res = app.deliver_tx(RequestDeliverTx {
+            tx: tx_raw.encode_to_vec().into(),
+        });
+        assert_eq!(res.code, 4);
+        assert_eq!(
+            res.log,
+            "invalid number of signer infos; expected 1, got 0: unauthorized"
+        );
+
+        // the valid txs are still accepted
+        let res = deliver_tx(TxBody {
+            timeout_height: 5,
+            memo: "m".repeat(256),
+            ..send_tx_body(&[1])
+        });
+        assert_eq!(res.code, 0, "{}", res.log);
+    }
+
+    #[test]
+    fn begin_block_handles_genesis_validator_votes() {
+        let app = initialized_app();
+
+        // the chain's only validator is in the Tendermint genesis, so it isn't known to staking
+        let genesis_validator = vec![1; 20];
+        let begin_block = |height| {
+            app.begin_block(RequestBeginBlock {
+                header: Some(Header {
+                    height,
+                    proposer_address: genesis_validator.clone().into(),
+                    ..Default::default()
+                }),
+                last_commit_info: Some(LastCommitInfo {
+                    round: 0,
+                    votes: vec![VoteInfo {
+                        validator: Some(VoteValidator {
+                            address: genesis_validator.clone().into(),
+                            power: 10,
+                        }),
+                        signed_last_block: true,
+                    }],
+                }),
+                ..Default::default()
+            })
+        };
+
+        begin_block(1);
+        let res = app.deliver_tx(RequestDeliverTx {
+            tx: signed_send_tx_with_fee(&[1], 4).into(),
+        });
+        assert_eq!(res.code, 0, "{}", res.log);
+        app.end_block(RequestEndBlock { height: 1 });
+        app.commit();
+
+        for height in 2..=3 {
+            begin_block(height);
+            app.end_block(RequestEndBlock { height });
+            app.commit();
+        }
+
+        // the fees of the first block can't be given to the validator
+        let res = app.query(RequestQuery {
+            data: QueryCommunityPoolRequest {}.encode_to_vec().into(),
+            path: "/cosmos.distribution.v1beta1.Query/CommunityPool".into(),
+            ..Default::default()
+        });
+        assert_eq!(res.code, 0, "{}", res.log);
+        assert_eq!(
+            QueryCommunityPoolResponse::decode(res.value).unwrap().pool,
+            vec![parse_dec_coins("4uatom").unwrap()[0].to_proto()]
+        );
+    }
+
+    #[test]
+    fn init_chain_stores_consensus_params() {
+        let app = initialized_app();
+
+        // the params are in the state, so they're also in its snapshots
+        for state in [&app.multi_store, &app.check_state] {
+            assert_eq!(
+                read_consensus_params(&state.read().unwrap()),
+                consensus_params()
+            );
+        }
+    }
+
+    #[test]
+    fn tx_size_gas_is_charged() {
+        let app = BaseApp::new();
+        let simulate = |memo: &str| {
+            let body = TxBody {
+                memo: memo.into(),
+                ..send_tx_body(&[1])
+            };
+            app.simulate(&sign_tx(body, 0)).unwrap().gas_info.unwrap()
+        };
+
+        // each extra byte of memo costs tx_size_cost_per_byte
+        let gas_used = simulate("memo").gas_used;
+        assert_eq!(simulate("memomemo").gas_used, gas_used + 4 * 10);
+    }
+
+    #[test]
+    fn replayed_tx_is_rejected() {
+        let app = BaseApp::new();
+        let tx = signed_send_tx(&[10]);
+
+        let res = app.deliver_tx(RequestDeliverTx {
+            tx: tx.clone().into(),
+        });
+        assert_eq!(res.code, 0, "{}", res.log);
+        let acc_seq = new_event("tx", vec![("acc_seq", format!("{}/0", ADDRESS))]);
+        assert_eq!(res.events[1], acc_seq);
+
+        // the account sequence has been incremented so the same signed tx is rejected
+        let res = app.deliver_tx(RequestDeliverTx {
+            tx: tx.clone().into(),
+        });
+        assert_eq!(res.code, 32);
+        assert_eq!(
+            res.log,
+            "account sequence mismatch, expected 1, got 0: incorrect account sequence"
+        );
+
+        app.commit();
+        assert_eq!(query_balance(&app, 0), (1, 24u32.into()));
+
+        let res = app.check_tx(RequestCheckTx {
+            tx: tx.into(),
+            r#type: CheckTxType::New as i32,
+        });
+        assert_eq!(res.code, 32);
+    }
+
+    #[test]
+    fn deliver_tx_checks_signer_pub_key() {
+        let app = BaseApp::new();
+
+        // a key which doesn't belong to ADDRESS
+        let secret_key = SecretKey::from_slice(&[7; 32]).unwrap();
+        let public_key = PublicKey::from_secret_key(&Secp256k1::new(), &secret_key);
+        let mut tx_raw = TxRaw::decode(&signed_send_tx(&[1])[..]).unwrap();
+        let auth_info = AuthInfo::decode(&tx_raw.auth_info_bytes[..]).unwrap();
+        tx_raw.auth_info_bytes = AuthInfo {
+            signer_infos: vec![SignerInfo {
+                public_key: Some(Any {
+                    type_url: "/cosmos.crypto.secp256k1.PubKey".into(),
+                    value: PubKey {
+                        key: public_key.serialize().to_vec(),
+                    }
+                    .encode_to_vec(),
+                }),
+                ..signer_info()
+            }],
+            ..auth_info
+        }
+        .encode_to_vec();
+
+        let res = app.deliver_tx(RequestDeliverTx {
+            tx: tx_raw.encode_to_vec().into(),
+        });
+        assert_eq!(res.code, 8);
+        assert_eq!(
+            res.log,
+            format!(
+                "pubKey does not match signer address {} with signer index: 0: invalid pubkey",
+                ADDRESS
+            )
+        );
+    }
+
+    #[test]
+    fn queries_run_concurrently_with_block_execution() {
+        let app = initialized_app();
+
+        let queriers: Vec<_> = (0..4)
+            .map(|_| {
+                let app = app.clone();
+                thread::spawn(move || {
+                    for _ in 0..500 {
+                        // the balance committed at height h is 34 + h, so seeing any other
+                        // balance means the query saw uncommitted state
+                        let (height, amount) = query_balance(&app, 0);
+                        assert_eq!(amount, Uint256::from(34 + height as u32));
+                    }
+                })
+            })
+            .collect();
+
+        for height in 1..=50 {
+            app.begin_block(RequestBeginBlock::default());
+            deliver_balance(&app, 34 + height);
+            app.end_block(RequestEndBlock {
+                height: height.into(),
+            });
+            app.commit();
+        }
+
+        for querier in queriers {
+            querier.join().unwrap();
+        }
+
+        assert_eq!(query_balance(&app, 20), (20, 54u32.into()));
+    }
+
+    /// A xorshift generator, so that the "random" inputs are the same on every run
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn bytes(&mut self) -> Vec<u8> {
+            let len = self.next() % 256;
+            (0..len).map(|_| self.next() as u8).collect()
+        }
+    }
+
+    #[test]
+    fn random_bytes_do_not_crash_the_app() {
+        let paths = [
+            "/cosmos.bank.v1beta1.Query/AllBalances",
+            "/cosmos.auth.v1beta1.Query/Account",
+            "/unknown",
+        ];
+
+        let run = || {
+            let app = BaseApp::new();
+            let mut rng = Rng(0x2545f4914f6cdd1d);
+            let mut responses = vec![];
+
+            for _ in 0..500 {
+                let tx = rng.bytes();
+                let check_res = app.check_tx(RequestCheckTx {
+                    tx: tx.clone().into(),
+                    r#type: CheckTxType::New as i32,
+                });
+                let deliver_res = app.deliver_tx(RequestDeliverTx { tx: tx.into() });
+                let query_res = app.query(RequestQuery {
+                    data: rng.bytes().into(),
+                    path: paths[(rng.next() % 3) as usize].into(),
+                    height: 0,
+                    prove: false,
+                });
+
+                assert_ne!(deliver_res.code, 0);
+                responses.push((check_res, deliver_res, query_res));
+            }
+
+            // the app is still alive and its state is unchanged
+            assert_eq!(query_balance(&app, 0), (0, 34u32.into()));
+
+            responses
+        };
+
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn catch_panic_works() {
+        let err = catch_panic::<()>(|| panic!("boom")).unwrap_err();
+        assert_eq!(err.code(), 111222);
+        assert_eq!(err.to_string(), "boom: panic");
+
+        let err = catch_panic::<()>(|| {
+            std::panic::panic_any(ErrorOutOfGas {
+                descriptor: "WriteFlat".into(),
+            })
+        })
+        .unwrap_err();
+        assert_eq!(err, AppError::OutOfGas("WriteFlat".into()));
+    }
+}
@@ -0,0 +1,358 @@
+use ibc_proto::{
+    cosmos::{
+        bank::v1beta1::MsgSend,
+        base::v1beta1::Coin,
+        distribution::v1beta1::{
+            MsgFundCommunityPool, MsgSetWithdrawAddress, MsgWithdrawDelegatorReward,
+            MsgWithdrawValidatorCommission,
+        },
+        slashing::v1beta1::MsgUnjail,
+        staking::v1beta1::{
+            MsgBeginRedelegate, MsgCreateValidator, MsgDelegate, MsgEditValidator, MsgUndelegate,
+        },
+        tx::v1beta1::Tx,
+        vesting::v1beta1::MsgCreateVestingAccount,
+    },
+    google::protobuf::Any,
+};
+use prost::Message;
+
+use tendermint_proto::abci::Event;
+
+use crate::{
+    error::{
+        AppError, ERR_INVALID_ADDRESS, ERR_INVALID_COINS, ERR_INVALID_REQUEST, ERR_TX_DECODE,
+        ERR_UNKNOWN_REQUEST,
+    },
+    types::{
+        coins_to_string, new_event, validate_coins, AccAddress, Context, MsgData, TxMsgData,
+        ValAddress, ATTRIBUTE_KEY_ACTION, EVENT_TYPE_MESSAGE,
+    },
+    x::{
+        bank::Bank,
+        distribution::{self, Distribution},
+        slashing::{self, Slashing},
+        staking::{self, Staking},
+        vesting::Vesting,
+    },
+};
+
+/// A message which can be included in a tx
+#[derive(Debug, Clone, PartialEq)]
+pub enum Msg {
+    Send(MsgSend),
+    CreateVestingAccount(MsgCreateVestingAccount),
+    CreateValidator(MsgCreateValidator),
+    EditValidator(MsgEditValidator),
+    Delegate(MsgDelegate),
+    Undelegate(MsgUndelegate),
+    BeginRedelegate(MsgBeginRedelegate),
+    Unjail(MsgUnjail),
+    SetWithdrawAddress(MsgSetWithdrawAddress),
+    WithdrawDelegatorReward(MsgWithdrawDelegatorReward),
+    WithdrawValidatorCommission(MsgWithdrawValidatorCommission),
+    FundCommunityPool(MsgFundCommunityPool),
+}
+
+impl Msg {
+    pub fn type_url(&self) -> &'static str {
+        match self {
+            Msg::Send(_) => "/cosmos.bank.v1beta1.MsgSend",
+            Msg::CreateVestingAccount(_) => "/cosmos.vesting.v1beta1.MsgCreateVestingAccount",
+            Msg::CreateValidator(_) => "/cosmos.staking.v1beta1.MsgCreateValidator",
+            Msg::EditValidator(_) => "/cosmos.staking.v1beta1.MsgEditValidator",
+            Msg::Delegate(_) => "/cosmos.staking.v1beta1.MsgDelegate",
+            Msg::Undelegate(_) => "/cosmos.staking.v1beta1.MsgUndelegate",
+            Msg::BeginRedelegate(_) => "/cosmos.staking.v1beta1.MsgBeginRedelegate",
+            Msg::Unjail(_) => "/cosmos.slashing.v1beta1.MsgUnjail",
+            Msg::SetWithdrawAddress(_) => "/cosmos.distribution.v1beta1.MsgSetWithdrawAddress",
+            Msg::WithdrawDelegatorReward(_) => {
+                "/cosmos.distribution.v1beta1.MsgWithdrawDelegatorReward"
+            }
+            Msg::WithdrawValidatorCommission(_) => {
+                "/cosmos.distribution.v1beta1.MsgWithdrawValidatorCommission"
+            }
+            Msg::FundCommunityPool(_) => "/cosmos.distribution.v1beta1.MsgFundCommunityPool",
+        }
+    }
+
+    /// Runs the stateless checks of the message, as with the cosmos SDK `Msg.ValidateBasic`
+    pub fn validate_basic(&self) -> Result<(), AppError> {
+        match self {
+            Msg::Send(msg) => validate_transfer(&msg.from_address, &msg.to_address, &msg.amount),
+            Msg::CreateVestingAccount(msg) => {
+                validate_transfer(&msg.from_address, &msg.to_address, &msg.amount)?;
+
+                if msg.end_time <= 0 {
+                    return Err(ERR_INVALID_REQUEST.wrap("invalid end time"));
+                }
+
+                Ok(())
+            }
+            Msg::CreateValidator(msg) => staking::validate_create_validator(msg),
+            Msg::EditValidator(msg) => staking::validate_edit_validator(msg),
+            Msg::Delegate(msg) => staking::validate_delegate(msg),
+            Msg::Undelegate(msg) => staking::validate_undelegate(msg),
+            Msg::BeginRedelegate(msg) => staking::validate_begin_redelegate(msg),
+            Msg::Unjail(msg) => slashing::validate_unjail(msg),
+            Msg::SetWithdrawAddress(msg) => distribution::validate_set_withdraw_address(msg),
+            Msg::WithdrawDelegatorReward(msg) => {
+                distribution::validate_withdraw_delegator_reward(msg)
+            }
+            Msg::WithdrawValidatorCommission(msg) => {
+                distribution::validate_withdraw_validator_commission(msg)
+            }
+            Msg::FundCommunityPool(msg) => distribution::validate_fund_community_pool(msg),
+        }
+    }
+
+    /// Returns the addresses which must sign the message
+    pub fn get_signers(&self) -> Result<Vec<AccAddress>, AppError> {
+        match self {
+            Msg::Send(msg) => Ok(vec![AccAddress::from_bech32(&msg.from_address)?]),
+            Msg::CreateVestingAccount(msg) => Ok(vec![AccAddress::from_bech32(&msg.from_address)?]),
+            Msg::CreateValidator(msg) => Ok(vec![AccAddress::from_bech32(&msg.delegator_address)?]),
+            Msg::EditValidator(msg) => Ok(vec![AccAddress::from(ValAddress::from_bech32(
+                &msg.validator_address,
+            )?)]),
+            Msg::Delegate(msg) => Ok(vec![AccAddress::from_bech32(&msg.delegator_address)?]),
+            Msg::Undelegate(msg) => Ok(vec![AccAddress::from_bech32(&msg.delegator_address)?]),
+            Msg::BeginRedelegate(msg) => Ok(vec![AccAddress::from_bech32(&msg.delegator_address)?]),
+            Msg::Unjail(msg) => Ok(vec![AccAddress::from(ValAddress::from_bech32(
+                &msg.validator_addr,
+            )?)]),
+            Msg::SetWithdrawAddress(msg) => {
+                Ok(vec![AccAddress::from_bech32(&msg.delegator_address)?])
+            }
+            Msg::WithdrawDelegatorReward(msg) => {
+                Ok(vec![AccAddress::from_bech32(&msg.delegator_address)?])
+            }
+            Msg::WithdrawValidatorCommission(msg) => Ok(vec![AccAddress::from(
+                ValAddress::from_bech32(&msg.validator_address)?,
+            )]),
+            Msg::FundCommunityPool(msg) => Ok(vec![AccAddress::from_bech32(&msg.depositor)?]),
+        }
+    }
+
+    fn from_any(any: &Any) -> Result<Msg, AppError> {
+        let decode_err = |e: prost::DecodeError| ERR_TX_DECODE.wrap(e.to_string());
+
+        match any.type_url.as_str() {
+            "/cosmos.bank.v1beta1.MsgSend" => Ok(Msg::Send(
+                MsgSend::decode(&any.value[..]).map_err(decode_err)?,
+            )),
+            "/cosmos.vesting.v1beta1.MsgCreateVestingAccount" => Ok(Msg::CreateVestingAccount(
+                MsgCreateVestingAccount::decode(&any.value[..]).map_err(decode_err)?,
+            )),
+            "/cosmos.staking.v1beta1.MsgCreateValidator" => Ok(Msg::CreateValidator(
+                MsgCreateValidator::decode(&any.value[..]).map_err(decode_err)?,
+            )),
+            "/cosmos.staking.v1beta1.MsgEditValidator" => Ok(Msg::EditValidator(
+                MsgEditValidator::decode(&any.value[..]).map_err(decode_err)?,
+            )),
+            "/cosmos.staking.v1beta1.MsgDelegate" => Ok(Msg::Delegate(
+                MsgDelegate::decode(&any.value[..]).map_err(decode_err)?,
+            )),
+            "/cosmos.staking.v1beta1.MsgUndelegate" => Ok(Msg::Undelegate(
+                MsgUndelegate::decode(&any.value[..]).map_err(decode_err)?,
+            )),
+            "/cosmos.staking.v1beta1.MsgBeginRedelegate" => Ok(Msg::BeginRedelegate(
+                MsgBeginRedelegate::decode(&any.value[..]).map_err(decode_err)?,
+            )),
+            "/cosmos.slashing.v1beta1.MsgUnjail" => Ok(Msg::Unjail(
+                MsgUnjail::decode(&any.value[..]).map_err(decode_err)?,
+            )),
+            "/cosmos.distribution.v1beta1.MsgSetWithdrawAddress" => Ok(Msg::SetWithdrawAddress(
+                MsgSetWithdrawAddress::decode(&any.value[..]).map_err(decode_err)?,
+            )),
+            "/cosmos.distribution.v1beta1.MsgWithdrawDelegatorReward" => {
+                Ok(Msg::WithdrawDelegatorReward(
+                    MsgWithdrawDelegatorReward::decode(&any.value[..]).map_err(decode_err)?,
+                ))
+            }
+            "/cosmos.distribution.v1beta1.MsgWithdrawValidatorCommission" => {
+                Ok(Msg::WithdrawValidatorCommission(
+                    MsgWithdrawValidatorCommission::decode(&any.value[..]).map_err(decode_err)?,
+                ))
+            }
+            "/cosmos.distribution.v1beta1.MsgFundCommunityPool" => Ok(Msg::FundCommunityPool(
+                MsgFundCommunityPool::decode(&any.value[..]).map_err(decode_err)?,
+            )),
+            _ => {
+                Err(ERR_UNKNOWN_REQUEST
+                    .wrap(format!("unrecognized message type: {}", any.type_url)))
+            }
+        }
+    }
+}
+
+/// Checks the addresses and the amount of a message which transfers coins
+fn validate_transfer(
+    from_address: &str,
+    to_address: &str,
+    amount: &[Coin],
+) -> Result<(), AppError> {
+    AccAddress::from_bech32(from_address)
+        .map_err(|e| ERR_INVALID_ADDRESS.wrap(format!("invalid from address: {}", e)))?;
+    AccAddress::from_bech32(to_address)
+        .map_err(|e| ERR_INVALID_ADDRESS.wrap(format!("invalid to address: {}", e)))?;
+
+    if amount.is_empty() || validate_coins(amount).is_err() {
+        return Err(ERR_INVALID_COINS.wrap(coins_to_string(amount)));
+    }
+
+    Ok(())
+}
+
+/// Returns the messages of the tx, which must contain at least one, after checking that each
+/// passes its stateless validation
+pub fn decode_msgs(tx: &Tx) -> Result<Vec<Msg>, AppError> {
+    let msgs = tx
+        .body
+        .as_ref()
+        .map(|body| body.messages.as_slice())
+        .unwrap_or_default();
+
+    if msgs.is_empty() {
+        return Err(ERR_INVALID_REQUEST.wrap("must contain at least one message"));
+    }
+
+    let msgs = msgs
+        .iter()
+        .map(Msg::from_any)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for msg in &msgs {
+        msg.validate_basic()?;
+    }
+
+    Ok(msgs)
+}
+
+/// Returns the signers of all the messages, without duplicates, in the order they first appear,
+/// followed by the tx's fee payer if it isn't already a signer. As in the cosmos SDK the first
+/// signer pays the fee unless the tx sets a fee payer, which must then sign the tx too.
+pub fn get_signers(tx: &Tx, msgs: &[Msg]) -> Result<Vec<AccAddress>, AppError> {
+    let mut signers = vec![];
+
+    for msg in msgs {
+        for signer in msg.get_signers()? {
+            if !signers.contains(&signer) {
+                signers.push(signer);
+            }
+        }
+    }
+
+    let payer = tx
+        .auth_info
+        .as_ref()
+        .and_then(|auth_info| auth_info.fee.as_ref())
+        .map_or("", |fee| fee.payer.as_str());
+    if !payer.is_empty() {
+        let payer = AccAddress::from_bech32(payer)?;
+        if !signers.contains(&payer) {
+            signers.push(payer);
+        }
+    }
+
+    Ok(signers)
+}
+
+/// Executes the messages in order, returning the encoded `TxMsgData` holding their responses,
+/// which becomes the tx result data, along with their events. Each message's events are preceded
+/// by a message event with the message's type URL as the action.
+pub fn run_msgs(ctx: &mut Context, msgs: Vec<Msg>) -> Result<(Vec<u8>, Vec<Event>), AppError> {
+    let mut data = vec![];
+    let mut msg_responses = vec![];
+    let mut events = vec![];
+
+    for msg in msgs {
+        let type_url = msg.type_url();
+        let msg_response = run_msg(ctx, msg)?;
+
+        data.push(MsgData {
+            msg_type: type_url.into(),
+            data: msg_response.value.clone(),
+        });
+        msg_responses.push(msg_response);
+
+        events.push(new_event(
+            EVENT_TYPE_MESSAGE,
+            vec![(ATTRIBUTE_KEY_ACTION, type_url.into())],
+        ));
+        events.append(&mut ctx.event_manager().take_events());
+    }
+
+    Ok((
+        TxMsgData {
+            data,
+            msg_responses,
+        }
+        .encode_to_vec(),
+        events,
+    ))
+}
+
+/// Executes the message with its module's handler and returns the handler's response
+pub fn run_msg(ctx: &mut Context, msg: Msg) -> Result<Any, AppError> {
+    match msg {
+        Msg::Send(msg) => Bank::send(ctx, msg).map(|res| Any {
+            type_url: "/cosmos.bank.v1beta1.MsgSendResponse".into(),
+            value: res.encode_to_vec(),
+        }),
+        Msg::CreateVestingAccount(msg) => {
+            Vesting::create_vesting_account(ctx, msg).map(|res| Any {
+                type_url: "/cosmos.vesting.v1beta1.MsgCreateVestingAccountResponse".into(),
+                value: res.encode_to_vec(),
+            })
+        }
+        Msg::CreateValidator(msg) => Staking::create_validator(ctx, msg).map(|res| Any {
+            type_url: "/cosmos.staking.v1beta1.MsgCreateValidatorResponse".into(),
+            value: res.encode_to_vec(),
+        }),
+        Msg::EditValidator(msg) => Staking::edit_validator(ctx, msg).map(|res| Any {
+            type_url: "/cosmos.staking.v1beta1.MsgEditValidatorResponse".into(),
+            value: res.encode_to_vec(),
+        }),
+        Msg::Delegate(msg) => Staking::delegate_msg(ctx, msg).map(|res| Any {
+            type_url: "/cosmos.staking.v1beta1.MsgDelegateResponse".into(),
+            value: res.encode_to_vec(),
+        }),
+        Msg::Undelegate(msg) => Staking::undelegate_msg(ctx, msg).map(|res| Any {
+            type_url: "/cosmos.staking.v1beta1.MsgUndelegateResponse".into(),
+            value: res.encode_to_vec(),
+        }),
+        Msg::BeginRedelegate(msg) => Staking::begin_redelegate_msg(ctx, msg).map(|res| Any {
+            type_url: "/cosmos.staking.v1beta1.MsgBeginRedelegateResponse".into(),
+            value: res.encode_to_vec(),
+        }),
+        Msg::Unjail(msg) => Slashing::unjail_msg(ctx, msg).map(|res| Any {
+            type_url: "/cosmos.slashing.v1beta1.MsgUnjailResponse".into(),
+            value: res.encode_to_vec(),
+        }),
+        Msg::SetWithdrawAddress(msg) => {
+            Distribution::set_withdraw_address_msg(ctx, msg).map(|res| Any {
+                type_url: "/cosmos.distribution.v1beta1.MsgSetWithdrawAddressResponse".into(),
+                value: res.encode_to_vec(),
+            })
+        }
+        Msg::WithdrawDelegatorReward(msg) => Distribution::withdraw_delegator_reward_msg(ctx, msg)
+            .map(|res| Any {
+                type_url: "/cosmos.distribution.v1beta1.MsgWithdrawDelegatorRewardResponse".into(),
+                value: res.encode_to_vec(),
+            }),
+        Msg::WithdrawValidatorCommission(msg) => {
+            Distribution::withdraw_validator_commission_msg(ctx, msg).map(|res| Any {
+                type_url: "/cosmos.distribution.v1beta1.MsgWithdrawValidatorCommissionResponse"
+                    .into(),
+                value: res.encode_to_vec(),
+            })
+        }
+        Msg::FundCommunityPool(msg) => {
+            Distribution::fund_community_pool_msg(ctx, msg).map(|res| Any {
+                type_url: "/cosmos.distribution.v1beta1.MsgFundCommunityPoolResponse".into(),
+                value: res.encode_to_vec(),
+            })
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use std::{collections::HashMap, fmt::Debug};
+
+use prost::Message;
+
+use crate::{
+    error::{AppError, ERR_INVALID_REQUEST, ERR_UNKNOWN_REQUEST},
+    types::QueryContext,
+};
+
+type QueryHandler =
+    Box<dyn Fn(&QueryContext, &[u8]) -> Result<Vec<u8>, AppError> + Send + Sync + 'static>;
+
+/// Handles the legacy `custom/<module>/...` queries of a module. It's passed the path segments
+/// following the module name and the JSON encoded request params, and returns the JSON encoded
+/// response.
+pub type LegacyQuerier = fn(&QueryContext, &[&str], &[u8]) -> Result<Vec<u8>, AppError>;
+
+/// Routes ABCI queries to the gRPC query service method with the same full path, e.g.
+/// `/cosmos.bank.v1beta1.Query/AllBalances`, and legacy `custom/<module>/...` queries to the
+/// module's legacy querier
+#[derive(Default)]
+pub struct QueryRouter {
+    routes: HashMap<String, QueryHandler>,
+    legacy_routes: HashMap<String, LegacyQuerier>,
+}
+
+impl QueryRouter {
+    pub fn new() -> Self {
+        QueryRouter {
+            routes: HashMap::new(),
+            legacy_routes: HashMap::new(),
+        }
+    }
+
+    /// Registers the handler of a query service method. Requests are decoded from, and
+    /// responses encoded to, protobuf. Panics if the path is already registered.
+    pub fn register<Req, Res>(
+        mut self,
+        path: &str,
+        handler: fn(&QueryContext, Req) -> Result<Res, AppError>,
+    ) -> Self
+    where
+        Req: Message + Default + 'static,
+        Res: Message + 'static,
+    {
+        let handler: QueryHandler = Box::new(move |ctx, data| {
+            let req = Req::decode(data).map_err(|e| ERR_INVALID_REQUEST.wrap(e.to_string()))?;
+            handler(ctx, req).map(|res| res.encode_to_vec())
+        });
+
+        if self.routes.insert(path.into(), handler).is_some() {
+            panic!("query route {} is already registered", path);
+        }
+
+        self
+    }
+
+    /// Registers the legacy querier of a module. Panics if the module already has a querier.
+    pub fn register_legacy(mut self, module: &str, querier: LegacyQuerier) -> Self {
+        if self.legacy_routes.insert(module.into(), querier).is_some() {
+            panic!("legacy query route {} is already registered", module);
+        }
+
+        self
+    }
+
+    pub fn has_route(&self, path: &str) -> bool {
+        self.routes.contains_key(path)
+    }
+
+    /// Returns the encoded response to the encoded request
+    pub fn query(&self, ctx: &QueryContext, path: &str, data: &[u8]) -> Result<Vec<u8>, AppError> {
+        let handler = self
+            .routes
+            .get(path)
+            .ok_or(ERR_UNKNOWN_REQUEST.wrap(format!("unknown query path: {}", path)))?;
+
+        handler(ctx, data)
+    }
+
+    /// Returns the JSON encoded response to a `custom/<module>/<path>` query
+    pub fn query_legacy(
+        &self,
+        ctx: &QueryContext,
+        module: &str,
+        path: &[&str],
+        data: &[u8],
+    ) -> Result<Vec<u8>, AppError> {
+        let querier = self.legacy_routes.get(module).ok_or(
+            ERR_UNKNOWN_REQUEST.wrap(format!("no custom querier found for route {}", module)),
+        )?;
+
+        querier(ctx, path, data)
+    }
+}
+
+impl Debug for QueryRouter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut paths: Vec<&String> = self.routes.keys().collect();
+        paths.sort();
+        let mut modules: Vec<&String> = self.legacy_routes.keys().collect();
+        modules.sort();
+        f.debug_struct("QueryRouter")
+            .field("routes", &paths)
+            .field("legacy_routes", &modules)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use ibc_proto::cosmos::auth::v1beta1::{QueryAccountRequest, QueryAccountResponse};
+
+    use super::*;
+    use crate::store::MultiStore;
+
+    fn echo_account(
+        _ctx: &QueryContext,
+        req: QueryAccountRequest,
+    ) -> Result<QueryAccountResponse, AppError> {
+        if req.address.is_empty() {
+            return Err(ERR_INVALID_REQUEST.wrap("empty address"));
+        }
+
+        Ok(QueryAccountResponse { account: None })
+    }
+
+    #[test]
+    fn query_router_works() {
+        let router = QueryRouter::new().register("/test.Query/Account", echo_account);
+        let store = MultiStore::new();
+        let ctx = QueryContext::new(&store, 0);
+
+        let data = QueryAccountRequest {
+            address: "cosmos1".into(),
+        }
+        .encode_to_vec();
+        let res = router.query(&ctx, "/test.Query/Account", &data).unwrap();
+        assert_eq!(
+            QueryAccountResponse::decode(&res[..]).unwrap(),
+            QueryAccountResponse { account: None }
+        );
+
+        let err = router.query(&ctx, "/test.Query/Account", &[]).unwrap_err();
+        assert_eq!(err.to_string(), "empty address: invalid request");
+
+        let err = router.query(&ctx, "/test.Query/Unknown", &data).unwrap_err();
+        assert_eq!(err.code(), 6);
+
+        let err = router.query(&ctx, "/test.Query/Account", &[0xff]).unwrap_err();
+        assert_eq!(err.code(), 18);
+    }
+
+    #[test]
+    #[should_panic(expected = "query route /test.Query/Account is already registered")]
+    fn query_router_rejects_duplicate_routes() {
+        QueryRouter::new()
+            .register("/test.Query/Account", echo_account)
+            .register("/test.Query/Account", echo_account);
+    }
+}
@@ -0,0 +1,418 @@
+use std::{
+    fmt::{Display, Formatter},
+    marker::PhantomData,
+    str::FromStr,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use tendermint_proto::google::protobuf::Timestamp;
+
+use crate::types::{AccAddress, ConsAddress, ValAddress};
+
+#[derive(Debug, PartialEq)]
+pub struct CodecError(pub String);
+
+impl Display for CodecError {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "Codec error: {}", self.0)
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+/// Encodes keys so that the byte ordering of encoded keys matches the ordering of the keys.
+///
+/// Keys which are followed by other keys in a composite key are encoded using the non terminal
+/// encoding, which must allow the end of the key to be found (e.g. with a length prefix).
+pub trait KeyCodec: Sized {
+    fn encode(&self) -> Vec<u8>;
+
+    fn decode(bytes: &[u8]) -> Result<Self, CodecError>;
+
+    fn encode_non_terminal(&self) -> Vec<u8>;
+
+    /// Returns the number of bytes read along with the decoded key
+    fn decode_non_terminal(bytes: &[u8]) -> Result<(usize, Self), CodecError>;
+}
+
+/// Addresses are length prefixed, as in the cosmos SDK `address.MustLengthPrefix`, when they
+/// aren't the last part of a key
+macro_rules! impl_address_key_codec {
+    ($address:ty) => {
+        impl KeyCodec for $address {
+            fn encode(&self) -> Vec<u8> {
+                self.clone().into()
+            }
+
+            fn decode(bytes: &[u8]) -> Result<Self, CodecError> {
+                <$address>::try_from(bytes.to_vec()).map_err(|e| CodecError(e.to_string()))
+            }
+
+            fn encode_non_terminal(&self) -> Vec<u8> {
+                let mut bytes = vec![self.len()];
+                bytes.append(&mut self.encode());
+                bytes
+            }
+
+            fn decode_non_terminal(bytes: &[u8]) -> Result<(usize, Self), CodecError> {
+                let (read, bytes) = decode_length_prefixed(bytes)?;
+                Ok((read, <$address>::decode(bytes)?))
+            }
+        }
+    };
+}
+
+impl_address_key_codec!(AccAddress);
+impl_address_key_codec!(ValAddress);
+impl_address_key_codec!(ConsAddress);
+
+impl KeyCodec for String {
+    fn encode(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, CodecError> {
+        String::from_utf8(bytes.to_vec()).map_err(|e| CodecError(e.to_string()))
+    }
+
+    /// Strings are null terminated
+    fn encode_non_terminal(&self) -> Vec<u8> {
+        if self.as_bytes().contains(&0) {
+            panic!("string keys may not contain a null byte: {}", self)
+        }
+        let mut bytes = self.encode();
+        bytes.push(0);
+        bytes
+    }
+
+    fn decode_non_terminal(bytes: &[u8]) -> Result<(usize, Self), CodecError> {
+        let end = bytes
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or(CodecError("string key is not null terminated".into()))?;
+        Ok((end + 1, String::decode(&bytes[..end])?))
+    }
+}
+
+impl KeyCodec for Vec<u8> {
+    fn encode(&self) -> Vec<u8> {
+        self.clone()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, CodecError> {
+        Ok(bytes.to_vec())
+    }
+
+    /// Byte keys are length prefixed and so may be at most 255 bytes long
+    fn encode_non_terminal(&self) -> Vec<u8> {
+        let len: u8 = self
+            .len()
+            .try_into()
+            .expect("non terminal byte keys must be at most 255 bytes");
+        let mut bytes = vec![len];
+        bytes.extend(self);
+        bytes
+    }
+
+    fn decode_non_terminal(bytes: &[u8]) -> Result<(usize, Self), CodecError> {
+        let (read, bytes) = decode_length_prefixed(bytes)?;
+        Ok((read, bytes.to_vec()))
+    }
+}
+
+impl KeyCodec for u64 {
+    fn encode(&self) -> Vec<u8> {
+        self.to_be_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, CodecError> {
+        let bytes: [u8; 8] = bytes
+            .try_into()
+            .map_err(|_| CodecError(format!("expected 8 bytes, found {}", bytes.len())))?;
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    fn encode_non_terminal(&self) -> Vec<u8> {
+        self.encode()
+    }
+
+    fn decode_non_terminal(bytes: &[u8]) -> Result<(usize, Self), CodecError> {
+        if bytes.len() < 8 {
+            return Err(CodecError(format!(
+                "expected at least 8 bytes, found {}",
+                bytes.len()
+            )));
+        }
+        Ok((8, u64::decode(&bytes[..8])?))
+    }
+}
+
+impl KeyCodec for u32 {
+    fn encode(&self) -> Vec<u8> {
+        self.to_be_bytes().to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, CodecError> {
+        let bytes: [u8; 4] = bytes
+            .try_into()
+            .map_err(|_| CodecError(format!("expected 4 bytes, found {}", bytes.len())))?;
+        Ok(u32::from_be_bytes(bytes))
+    }
+
+    fn encode_non_terminal(&self) -> Vec<u8> {
+        self.encode()
+    }
+
+    fn decode_non_terminal(bytes: &[u8]) -> Result<(usize, Self), CodecError> {
+        if bytes.len() < 4 {
+            return Err(CodecError(format!(
+                "expected at least 4 bytes, found {}",
+                bytes.len()
+            )));
+        }
+        Ok((4, u32::decode(&bytes[..4])?))
+    }
+}
+
+/// Signed integers are encoded big endian with the sign bit flipped so that negative values
+/// are ordered before positive values
+impl KeyCodec for i64 {
+    fn encode(&self) -> Vec<u8> {
+        KeyCodec::encode(&((*self as u64) ^ (1 << 63)))
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, CodecError> {
+        Ok((u64::decode(bytes)? ^ (1 << 63)) as i64)
+    }
+
+    fn encode_non_terminal(&self) -> Vec<u8> {
+        self.encode()
+    }
+
+    fn decode_non_terminal(bytes: &[u8]) -> Result<(usize, Self), CodecError> {
+        let (read, value) = u64::decode_non_terminal(bytes)?;
+        Ok((read, (value ^ (1 << 63)) as i64))
+    }
+}
+
+/// Timestamps are encoded as their seconds followed by their nanos so that keys are ordered by
+/// time, e.g. for the queues of the staking module
+impl KeyCodec for Timestamp {
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = self.seconds.encode();
+        bytes.append(&mut (self.nanos as u32).encode());
+        bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, CodecError> {
+        let (read, seconds) = i64::decode_non_terminal(bytes)?;
+        let nanos = u32::decode(&bytes[read..])?;
+        Ok(Timestamp {
+            seconds,
+            nanos: nanos as i32,
+        })
+    }
+
+    fn encode_non_terminal(&self) -> Vec<u8> {
+        self.encode()
+    }
+
+    fn decode_non_terminal(bytes: &[u8]) -> Result<(usize, Self), CodecError> {
+        if bytes.len() < 12 {
+            return Err(CodecError(format!(
+                "expected at least 12 bytes, found {}",
+                bytes.len()
+            )));
+        }
+        Ok((12, Timestamp::decode(&bytes[..12])?))
+    }
+}
+
+impl<K1: KeyCodec, K2: KeyCodec> KeyCodec for (K1, K2) {
+    fn encode(&self) -> Vec<u8> {
+        let mut bytes = self.0.encode_non_terminal();
+        bytes.append(&mut self.1.encode());
+        bytes
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, CodecError> {
+        let (read, k1) = K1::decode_non_terminal(bytes)?;
+        let k2 = K2::decode(&bytes[read..])?;
+        Ok((k1, k2))
+    }
+
+    fn encode_non_terminal(&self) -> Vec<u8> {
+        let mut bytes = self.0.encode_non_terminal();
+        bytes.append(&mut self.1.encode_non_terminal());
+        bytes
+    }
+
+    fn decode_non_terminal(bytes: &[u8]) -> Result<(usize, Self), CodecError> {
+        let (read_1, k1) = K1::decode_non_terminal(bytes)?;
+        let (read_2, k2) = K2::decode_non_terminal(&bytes[read_1..])?;
+        Ok((read_1 + read_2, (k1, k2)))
+    }
+}
+
+fn decode_length_prefixed(bytes: &[u8]) -> Result<(usize, &[u8]), CodecError> {
+    let len = *bytes
+        .first()
+        .ok_or(CodecError("missing length prefix".into()))? as usize;
+
+    if bytes.len() < len + 1 {
+        return Err(CodecError(format!(
+            "expected {} bytes after length prefix, found {}",
+            len,
+            bytes.len() - 1
+        )));
+    }
+
+    Ok((len + 1, &bytes[1..len + 1]))
+}
+
+/// Encodes values of type T
+pub trait ValueCodec<T> {
+    fn encode(value: &T) -> Vec<u8>;
+
+    fn decode(bytes: &[u8]) -> Result<T, CodecError>;
+}
+
+/// Encodes values using their protobuf encoding
+#[derive(Debug, Clone)]
+pub struct ProstCodec;
+
+impl<T: prost::Message + Default> ValueCodec<T> for ProstCodec {
+    fn encode(value: &T) -> Vec<u8> {
+        value.encode_to_vec()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T, CodecError> {
+        T::decode(bytes).map_err(|e| CodecError(e.to_string()))
+    }
+}
+
+/// Encodes values as JSON
+#[derive(Debug, Clone)]
+pub struct JsonCodec;
+
+impl<T: Serialize + DeserializeOwned> ValueCodec<T> for JsonCodec {
+    fn encode(value: &T) -> Vec<u8> {
+        serde_json::to_vec(value).expect("serialization of collection values won't fail")
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T, CodecError> {
+        serde_json::from_slice(bytes).map_err(|e| CodecError(e.to_string()))
+    }
+}
+
+/// Encodes values using their string representation, e.g. for `Uint256` amounts
+#[derive(Debug, Clone)]
+pub struct StringCodec;
+
+impl<T> ValueCodec<T> for StringCodec
+where
+    T: ToString + FromStr,
+    T::Err: Display,
+{
+    fn encode(value: &T) -> Vec<u8> {
+        value.to_string().into_bytes()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<T, CodecError> {
+        let string = std::str::from_utf8(bytes).map_err(|e| CodecError(e.to_string()))?;
+        T::from_str(string).map_err(|e| CodecError(e.to_string()))
+    }
+}
+
+/// Encodes values using a key codec, used to store primary keys as the values of indexes
+#[derive(Debug, Clone)]
+pub struct KeyValueCodec<K>(PhantomData<K>);
+
+impl<K: KeyCodec> ValueCodec<K> for KeyValueCodec<K> {
+    fn encode(value: &K) -> Vec<u8> {
+        value.encode()
+    }
+
+    fn decode(bytes: &[u8]) -> Result<K, CodecError> {
+        K::decode(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use cosmwasm_std::Uint256;
+
+    use super::*;
+
+    #[test]
+    fn composite_key_round_trip_works() {
+        let key = (
+            AccAddress::try_from(vec![97, 98, 99, 100]).unwrap(),
+            "uatom".to_string(),
+        );
+
+        let encoded = key.encode();
+
+        assert_eq!(encoded, vec![4, 97, 98, 99, 100, 117, 97, 116, 111, 109]);
+        assert_eq!(<(AccAddress, String)>::decode(&encoded).unwrap(), key);
+    }
+
+    #[test]
+    fn nested_composite_key_round_trip_works() {
+        let key = ((5u64, "abc".to_string()), vec![1, 2]);
+
+        let encoded = key.encode();
+
+        assert_eq!(encoded, vec![0, 0, 0, 0, 0, 0, 0, 5, 97, 98, 99, 0, 1, 2]);
+        assert_eq!(<((u64, String), Vec<u8>)>::decode(&encoded).unwrap(), key);
+    }
+
+    #[test]
+    fn i64_key_ordering_works() {
+        assert!((-5i64).encode() < 3i64.encode());
+        assert!(3i64.encode() < 300i64.encode());
+        assert_eq!(i64::decode(&(-5i64).encode()).unwrap(), -5);
+    }
+
+    #[test]
+    fn timestamp_key_ordering_works() {
+        let early = Timestamp {
+            seconds: 5,
+            nanos: 999,
+        };
+        let late = Timestamp {
+            seconds: 6,
+            nanos: 0,
+        };
+
+        assert!(early.encode() < late.encode());
+        assert_eq!(
+            <(Timestamp, i64)>::decode(&(late.clone(), 7i64).encode()).unwrap(),
+            (late, 7)
+        );
+    }
+
+    #[test]
+    fn string_codec_works() {
+        let amount = Uint256::from(34u32);
+        let encoded = <StringCodec as ValueCodec<Uint256>>::encode(&amount);
+
+        assert_eq!(encoded, b"34".to_vec());
+        assert_eq!(
+            <StringCodec as ValueCodec<Uint256>>::decode(&encoded).unwrap(),
+            amount
+        );
+    }
+
+    #[test]
+    fn decode_length_prefixed_failure() {
+        let err = AccAddress::decode_non_terminal(&[4, 1, 2]).unwrap_err();
+
+        assert_eq!(
+            err,
+            CodecError("expected 4 bytes after length prefix, found 2".into())
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use crate::store::{ReadKVStore, WriteKVStore};
+
+use super::{KeyCodec, KeySet, KeyValueCodec, Map, ProstCodec, ValueCodec};
+
+/// A secondary index over the values of an IndexedMap
+pub trait Index<K, V> {
+    fn save(&self, store: &mut dyn WriteKVStore, pk: &K, value: &V);
+
+    fn remove(&self, store: &mut dyn WriteKVStore, pk: &K, old_value: &V);
+}
+
+/// Implemented by a struct holding all the indexes of an IndexedMap
+pub trait Indexes<K, V> {
+    fn get_indexes(&self) -> Vec<&dyn Index<K, V>>;
+}
+
+/// A Map whose secondary indexes are kept up to date as entries are set and removed
+pub struct IndexedMap<K, V, I, C = ProstCodec> {
+    map: Map<K, V, C>,
+    pub indexes: I,
+}
+
+impl<K, V, I, C> IndexedMap<K, V, I, C> {
+    pub const fn new(prefix: &'static [u8], indexes: I) -> Self {
+        IndexedMap {
+            map: Map::new(prefix),
+            indexes,
+        }
+    }
+}
+
+impl<K, V, I, C> IndexedMap<K, V, I, C>
+where
+    K: KeyCodec,
+    C: ValueCodec<V>,
+    I: Indexes<K, V>,
+{
+    pub fn get<S: ReadKVStore + ?Sized>(&self, store: &S, key: &K) -> Option<V> {
+        self.map.get(store, key)
+    }
+
+    pub fn has<S: ReadKVStore + ?Sized>(&self, store: &S, key: &K) -> bool {
+        self.map.has(store, key)
+    }
+
+    pub fn set<S: WriteKVStore>(&self, store: &mut S, key: &K, value: &V) {
+        let old_value = self.map.get(&*store, key);
+
+        for index in self.indexes.get_indexes() {
+            if let Some(old_value) = &old_value {
+                index.remove(store, key, old_value);
+            }
+            index.save(store, key, value);
+        }
+
+        self.map.set(store, key, value)
+    }
+
+    pub fn remove<S: WriteKVStore>(&self, store: &mut S, key: &K) {
+        if let Some(old_value) = self.map.get(&*store, key) {
+            for index in self.indexes.get_indexes() {
+                index.remove(store, key, &old_value);
+            }
+            self.map.remove(store, key)
+        }
+    }
+
+    pub fn iter<'s, S: ReadKVStore + ?Sized>(
+        &self,
+        store: &'s S,
+    ) -> Box<dyn Iterator<Item = (K, V)> + 's>
+    where
+        K: 's,
+        V: 's,
+        C: 's,
+    {
+        self.map.iter(store)
+    }
+
+    pub fn prefix_iter<'s, P: KeyCodec, S: ReadKVStore + ?Sized>(
+        &self,
+        store: &'s S,
+        prefix: &P,
+    ) -> Box<dyn Iterator<Item = (K, V)> + 's>
+    where
+        K: 's,
+        V: 's,
+        C: 's,
+    {
+        self.map.prefix_iter(store, prefix)
+    }
+}
+
+/// An index mapping an index key to many primary keys, stored as a set of (index key, primary
+/// key) pairs
+pub struct MultiIndex<IK, K, V> {
+    keys: KeySet<(IK, K)>,
+    index_fn: fn(&K, &V) -> IK,
+}
+
+impl<IK, K, V> MultiIndex<IK, K, V> {
+    pub const fn new(prefix: &'static [u8], index_fn: fn(&K, &V) -> IK) -> Self {
+        MultiIndex {
+            keys: KeySet::new(prefix),
+            index_fn,
+        }
+    }
+}
+
+impl<IK: KeyCodec, K: KeyCodec + Clone, V> MultiIndex<IK, K, V> {
+    /// Returns the primary keys of all entries with the given index key
+    pub fn prefix_iter<'s, S: ReadKVStore + ?Sized>(
+        &self,
+        store: &'s S,
+        index_key: &IK,
+    ) -> Box<dyn Iterator<Item = K> + 's>
+    where
+        IK: 's,
+        K: 's,
+    {
+        Box::new(self.keys.prefix_iter(store, index_key).map(|(_, pk)| pk))
+    }
+
+    /// Returns all (index key, primary key) pairs in index key order
+    pub fn iter<'s, S: ReadKVStore + ?Sized>(
+        &self,
+        store: &'s S,
+    ) -> Box<dyn Iterator<Item = (IK, K)> + 's>
+    where
+        IK: 's,
+        K: 's,
+    {
+        self.keys.iter(store)
+    }
+}
+
+impl<IK: KeyCodec, K: KeyCodec + Clone, V> Index<K, V> for MultiIndex<IK, K, V> {
+    fn save(&self, store: &mut dyn WriteKVStore, pk: &K, value: &V) {
+        let index_key = (self.index_fn)(pk, value);
+        self.keys.insert(store, &(index_key, pk.clone()));
+    }
+
+    fn remove(&self, store: &mut dyn WriteKVStore, pk: &K, old_value: &V) {
+        let index_key = (self.index_fn)(pk, old_value);
+        self.keys.remove(store, &(index_key, pk.clone()));
+    }
+}
+
+/// An index mapping an index key to a single primary key. Uniqueness is not enforced by the
+/// index, saving an entry with an existing index key overwrites it, so modules must check for
+/// duplicates before saving.
+pub struct UniqueIndex<IK, K, V> {
+    map: Map<IK, K, KeyValueCodec<K>>,
+    index_fn: fn(&K, &V) -> IK,
+}
+
+impl<IK, K, V> UniqueIndex<IK, K, V> {
+    pub const fn new(prefix: &'static [u8], index_fn: fn(&K, &V) -> IK) -> Self {
+        UniqueIndex {
+            map: Map::new(prefix),
+            index_fn,
+        }
+    }
+}
+
+impl<IK: KeyCodec, K: KeyCodec, V> UniqueIndex<IK, K, V> {
+    /// Returns the primary key of the entry with the given index key
+    pub fn get<S: ReadKVStore + ?Sized>(&self, store: &S, index_key: &IK) -> Option<K> {
+        self.map.get(store, index_key)
+    }
+}
+
+impl<IK: KeyCodec, K: KeyCodec, V> Index<K, V> for UniqueIndex<IK, K, V> {
+    fn save(&self, store: &mut dyn WriteKVStore, pk: &K, value: &V) {
+        let index_key = (self.index_fn)(pk, value);
+        self.map.set(store, &index_key, pk);
+    }
+
+    fn remove(&self, store: &mut dyn WriteKVStore, pk: &K, old_value: &V) {
+        let index_key = (self.index_fn)(pk, old_value);
+        self.map.remove(store, &index_key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{collections::JsonCodec, store::MultiStore};
+
+    struct PersonIndexes {
+        by_age: MultiIndex<u64, String, (u64, String)>,
+        by_email: UniqueIndex<String, String, (u64, String)>,
+    }
+
+    impl Indexes<String, (u64, String)> for PersonIndexes {
+        fn get_indexes(&self) -> Vec<&dyn Index<String, (u64, String)>> {
+            vec![&self.by_age as &dyn Index<_, _>, &self.by_email]
+        }
+    }
+
+    // maps name to (age, email)
+    const PEOPLE: IndexedMap<String, (u64, String), PersonIndexes, JsonCodec> = IndexedMap::new(
+        &[1],
+        PersonIndexes {
+            by_age: MultiIndex::new(&[2], |_, v| v.0),
+            by_email: UniqueIndex::new(&[3], |_, v| v.1.clone()),
+        },
+    );
+
+    #[test]
+    fn indexed_map_works() {
+        let mut store = MultiStore::new();
+        let mut sub_store = store.get_mutable_sub_store(vec![0]);
+
+        PEOPLE.set(&mut sub_store, &"alice".into(), &(30, "a@x".into()));
+        PEOPLE.set(&mut sub_store, &"bob".into(), &(30, "b@x".into()));
+        PEOPLE.set(&mut sub_store, &"carol".into(), &(40, "c@x".into()));
+
+        let thirty: Vec<String> = PEOPLE
+            .indexes
+            .by_age
+            .prefix_iter(&sub_store, &30)
+            .collect();
+        assert_eq!(thirty, vec!["alice".to_string(), "bob".to_string()]);
+        assert_eq!(
+            PEOPLE.indexes.by_email.get(&sub_store, &"c@x".into()),
+            Some("carol".to_string())
+        );
+
+        // updating a value moves its index entries
+        PEOPLE.set(&mut sub_store, &"alice".into(), &(40, "a@y".into()));
+        let thirty: Vec<String> = PEOPLE
+            .indexes
+            .by_age
+            .prefix_iter(&sub_store, &30)
+            .collect();
+        assert_eq!(thirty, vec!["bob".to_string()]);
+        assert_eq!(PEOPLE.indexes.by_email.get(&sub_store, &"a@x".into()), None);
+
+        PEOPLE.remove(&mut sub_store, &"carol".into());
+        let forty: Vec<String> = PEOPLE
+            .indexes
+            .by_age
+            .prefix_iter(&sub_store, &40)
+            .collect();
+        assert_eq!(forty, vec!["alice".to_string()]);
+        assert_eq!(PEOPLE.indexes.by_email.get(&sub_store, &"c@x".into()), None);
+    }
+}
@@ -0,0 +1,61 @@
+use std::marker::PhantomData;
+
+use crate::store::{ReadKVStore, WriteKVStore};
+
+use super::{ProstCodec, ValueCodec};
+
+/// A single typed value stored under a fixed key
+pub struct Item<V, C = ProstCodec> {
+    key: &'static [u8],
+    _marker: PhantomData<fn() -> (V, C)>,
+}
+
+impl<V, C> Item<V, C> {
+    pub const fn new(key: &'static [u8]) -> Self {
+        Item {
+            key,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<V, C: ValueCodec<V>> Item<V, C> {
+    pub fn get<S: ReadKVStore + ?Sized>(&self, store: &S) -> Option<V> {
+        store
+            .get(self.key)
+            .map(|bytes| C::decode(&bytes).expect("store should contain valid data"))
+    }
+
+    pub fn has<S: ReadKVStore + ?Sized>(&self, store: &S) -> bool {
+        store.get(self.key).is_some()
+    }
+
+    pub fn set<S: WriteKVStore + ?Sized>(&self, store: &mut S, value: &V) {
+        store.set(self.key.to_vec(), C::encode(value))
+    }
+
+    pub fn remove<S: WriteKVStore + ?Sized>(&self, store: &mut S) {
+        store.delete(self.key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{collections::JsonCodec, store::MultiStore};
+
+    #[test]
+    fn item_works() {
+        let item: Item<Vec<String>, JsonCodec> = Item::new(&[7]);
+        let mut store = MultiStore::new();
+        let mut sub_store = store.get_mutable_sub_store(vec![1]);
+
+        assert_eq!(item.get(&sub_store), None);
+
+        item.set(&mut sub_store, &vec!["a".to_string()]);
+
+        assert_eq!(item.get(&sub_store), Some(vec!["a".to_string()]));
+        assert_eq!(store.get(&[1, 7]), Some(&br#"["a"]"#.to_vec()));
+    }
+}
@@ -0,0 +1,107 @@
+use std::marker::PhantomData;
+
+use crate::store::{ReadKVStore, WriteKVStore};
+
+use super::KeyCodec;
+
+/// A typed set of keys stored under a prefix, each key is stored with an empty value
+pub struct KeySet<K> {
+    prefix: &'static [u8],
+    _marker: PhantomData<fn() -> K>,
+}
+
+impl<K> KeySet<K> {
+    pub const fn new(prefix: &'static [u8]) -> Self {
+        KeySet {
+            prefix,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K: KeyCodec> KeySet<K> {
+    /// Returns the full store key for the given key
+    pub fn key(&self, key: &K) -> Vec<u8> {
+        let mut full_key = self.prefix.to_vec();
+        full_key.append(&mut key.encode());
+        full_key
+    }
+
+    pub fn has<S: ReadKVStore + ?Sized>(&self, store: &S, key: &K) -> bool {
+        store.get(&self.key(key)).is_some()
+    }
+
+    pub fn insert<S: WriteKVStore + ?Sized>(&self, store: &mut S, key: &K) {
+        store.set(self.key(key), vec![])
+    }
+
+    pub fn remove<S: WriteKVStore + ?Sized>(&self, store: &mut S, key: &K) {
+        store.delete(&self.key(key))
+    }
+
+    /// Returns all keys in ascending order
+    pub fn iter<'s, S: ReadKVStore + ?Sized>(&self, store: &'s S) -> Box<dyn Iterator<Item = K> + 's>
+    where
+        K: 's,
+    {
+        self.iter_raw_prefix(store, self.prefix.to_vec())
+    }
+
+    /// Returns all keys, in ascending order, which start with the given partial key
+    pub fn prefix_iter<'s, P: KeyCodec, S: ReadKVStore + ?Sized>(
+        &self,
+        store: &'s S,
+        prefix: &P,
+    ) -> Box<dyn Iterator<Item = K> + 's>
+    where
+        K: 's,
+    {
+        let mut raw_prefix = self.prefix.to_vec();
+        raw_prefix.append(&mut prefix.encode_non_terminal());
+        self.iter_raw_prefix(store, raw_prefix)
+    }
+
+    fn iter_raw_prefix<'s, S: ReadKVStore + ?Sized>(
+        &self,
+        store: &'s S,
+        raw_prefix: Vec<u8>,
+    ) -> Box<dyn Iterator<Item = K> + 's>
+    where
+        K: 's,
+    {
+        let prefix_len = self.prefix.len();
+
+        Box::new(
+            store
+                .prefix_iterator(&raw_prefix)
+                .map(move |(k, _)| K::decode(&k[prefix_len..]).expect("store should contain valid keys")),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::store::MultiStore;
+
+    #[test]
+    fn key_set_works() {
+        let set: KeySet<(u64, u64)> = KeySet::new(&[3]);
+        let mut store = MultiStore::new();
+        let mut sub_store = store.get_mutable_sub_store(vec![1]);
+
+        set.insert(&mut sub_store, &(2, 1));
+        set.insert(&mut sub_store, &(1, 5));
+        set.insert(&mut sub_store, &(1, 2));
+
+        assert!(set.has(&sub_store, &(1, 5)));
+        assert_eq!(
+            set.prefix_iter(&sub_store, &1u64).collect::<Vec<_>>(),
+            vec![(1, 2), (1, 5)]
+        );
+
+        set.remove(&mut sub_store, &(1, 5));
+        assert_eq!(set.iter(&sub_store).collect::<Vec<_>>(), vec![(1, 2), (2, 1)]);
+    }
+}
@@ -0,0 +1,161 @@
+use std::marker::PhantomData;
+
+use crate::store::{ReadKVStore, WriteKVStore};
+
+use super::{KeyCodec, ProstCodec, ValueCodec};
+
+/// A typed key value mapping stored under a prefix
+pub struct Map<K, V, C = ProstCodec> {
+    prefix: &'static [u8],
+    _marker: PhantomData<fn() -> (K, V, C)>,
+}
+
+impl<K, V, C> Map<K, V, C> {
+    pub const fn new(prefix: &'static [u8]) -> Self {
+        Map {
+            prefix,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<K: KeyCodec, V, C: ValueCodec<V>> Map<K, V, C> {
+    /// Returns the full store key for the given key
+    pub fn key(&self, key: &K) -> Vec<u8> {
+        let mut full_key = self.prefix.to_vec();
+        full_key.append(&mut key.encode());
+        full_key
+    }
+
+    /// Returns the store prefix shared by all keys which start with the given partial key, e.g.
+    /// the address in an `(AccAddress, String)` key
+    pub fn prefix_key<P: KeyCodec>(&self, prefix: &P) -> Vec<u8> {
+        let mut full_prefix = self.prefix.to_vec();
+        full_prefix.append(&mut prefix.encode_non_terminal());
+        full_prefix
+    }
+
+    pub fn get<S: ReadKVStore + ?Sized>(&self, store: &S, key: &K) -> Option<V> {
+        store
+            .get(&self.key(key))
+            .map(|bytes| C::decode(&bytes).expect("store should contain valid data"))
+    }
+
+    pub fn has<S: ReadKVStore + ?Sized>(&self, store: &S, key: &K) -> bool {
+        store.get(&self.key(key)).is_some()
+    }
+
+    pub fn set<S: WriteKVStore + ?Sized>(&self, store: &mut S, key: &K, value: &V) {
+        store.set(self.key(key), C::encode(value))
+    }
+
+    pub fn remove<S: WriteKVStore + ?Sized>(&self, store: &mut S, key: &K) {
+        store.delete(&self.key(key))
+    }
+
+    /// Returns all entries in ascending key order
+    pub fn iter<'s, S: ReadKVStore + ?Sized>(
+        &self,
+        store: &'s S,
+    ) -> Box<dyn Iterator<Item = (K, V)> + 's>
+    where
+        K: 's,
+        V: 's,
+        C: 's,
+    {
+        self.iter_raw_prefix(store, self.prefix.to_vec())
+    }
+
+    /// Returns all entries, in ascending key order, whose key starts with the given partial key
+    pub fn prefix_iter<'s, P: KeyCodec, S: ReadKVStore + ?Sized>(
+        &self,
+        store: &'s S,
+        prefix: &P,
+    ) -> Box<dyn Iterator<Item = (K, V)> + 's>
+    where
+        K: 's,
+        V: 's,
+        C: 's,
+    {
+        self.iter_raw_prefix(store, self.prefix_key(prefix))
+    }
+
+    fn iter_raw_prefix<'s, S: ReadKVStore + ?Sized>(
+        &self,
+        store: &'s S,
+        raw_prefix: Vec<u8>,
+    ) -> Box<dyn Iterator<Item = (K, V)> + 's>
+    where
+        K: 's,
+        V: 's,
+        C: 's,
+    {
+        let prefix_len = self.prefix.len();
+
+        Box::new(store.prefix_iterator(&raw_prefix).map(move |(k, v)| {
+            (
+                K::decode(&k[prefix_len..]).expect("store should contain valid keys"),
+                C::decode(&v).expect("store should contain valid data"),
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use cosmwasm_std::Uint256;
+
+    use super::*;
+    use crate::{collections::StringCodec, store::MultiStore, types::AccAddress};
+
+    const BALANCES: Map<(AccAddress, String), Uint256, StringCodec> = Map::new(&[2]);
+
+    #[test]
+    fn map_set_get_remove_works() {
+        let mut store = MultiStore::new();
+        let mut sub_store = store.get_mutable_sub_store(vec![9]);
+        let key = (
+            AccAddress::try_from(vec![97, 98, 99, 100]).unwrap(),
+            "uatom".to_string(),
+        );
+
+        assert_eq!(BALANCES.get(&sub_store, &key), None);
+
+        BALANCES.set(&mut sub_store, &key, &Uint256::from(34u32));
+
+        assert_eq!(BALANCES.get(&sub_store, &key), Some(Uint256::from(34u32)));
+        assert_eq!(
+            store.get(&[9, 2, 4, 97, 98, 99, 100, 117, 97, 116, 111, 109]),
+            Some(&b"34".to_vec())
+        );
+
+        let mut sub_store = store.get_mutable_sub_store(vec![9]);
+        BALANCES.remove(&mut sub_store, &key);
+        assert!(!BALANCES.has(&sub_store, &key));
+    }
+
+    #[test]
+    fn map_prefix_iter_works() {
+        let mut store = MultiStore::new();
+        let mut sub_store = store.get_mutable_sub_store(vec![9]);
+        let addr_1 = AccAddress::try_from(vec![1]).unwrap();
+        let addr_2 = AccAddress::try_from(vec![2]).unwrap();
+
+        BALANCES.set(&mut sub_store, &(addr_1.clone(), "b".into()), &1u32.into());
+        BALANCES.set(&mut sub_store, &(addr_2.clone(), "a".into()), &2u32.into());
+        BALANCES.set(&mut sub_store, &(addr_1.clone(), "a".into()), &3u32.into());
+
+        let entries: Vec<((AccAddress, String), Uint256)> =
+            BALANCES.prefix_iter(&sub_store, &addr_1).collect();
+
+        assert_eq!(
+            entries,
+            vec![
+                ((addr_1.clone(), "a".into()), 3u32.into()),
+                ((addr_1, "b".into()), 1u32.into())
+            ]
+        );
+        assert_eq!(BALANCES.iter(&sub_store).count(), 3);
+    }
+}
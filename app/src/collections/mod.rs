@@ -0,0 +1,18 @@
+//! Typed state collections built on top of the sub stores, modelled on the cosmos SDK collections
+//! package. Modules declare their schema once as constants, e.g.
+//! `const BALANCES: Map<(AccAddress, String), Uint256, StringCodec> = Map::new(&[2]);`,
+//! and the collections take care of key construction and value encoding.
+
+mod codec;
+mod indexed_map;
+mod item;
+mod key_set;
+mod map;
+mod sequence;
+
+pub use codec::*;
+pub use indexed_map::*;
+pub use item::*;
+pub use key_set::*;
+pub use map::*;
+pub use sequence::*;
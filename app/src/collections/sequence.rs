@@ -0,0 +1,54 @@
+use crate::store::{ReadKVStore, WriteKVStore};
+
+use super::Item;
+
+/// A monotonically increasing u64 sequence, starting at zero. The value is protobuf encoded (as
+/// a `UInt64Value`) for compatibility with the cosmos SDK.
+pub struct Sequence {
+    item: Item<u64>,
+}
+
+impl Sequence {
+    pub const fn new(key: &'static [u8]) -> Self {
+        Sequence {
+            item: Item::new(key),
+        }
+    }
+
+    /// Returns the current value without incrementing it
+    pub fn peek<S: ReadKVStore + ?Sized>(&self, store: &S) -> u64 {
+        self.item.get(store).unwrap_or(0)
+    }
+
+    /// Returns the current value and increments the stored value
+    pub fn next<S: WriteKVStore + ?Sized>(&self, store: &mut S) -> u64 {
+        let value = self.peek(&*store);
+        self.item.set(store, &(value + 1));
+        value
+    }
+
+    pub fn set<S: WriteKVStore + ?Sized>(&self, store: &mut S, value: u64) {
+        self.item.set(store, &value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::store::MultiStore;
+
+    #[test]
+    fn sequence_works() {
+        let sequence = Sequence::new(&[5]);
+        let mut store = MultiStore::new();
+        let mut sub_store = store.get_mutable_sub_store(vec![1]);
+
+        assert_eq!(sequence.next(&mut sub_store), 0);
+        assert_eq!(sequence.next(&mut sub_store), 1);
+        assert_eq!(sequence.peek(&sub_store), 2);
+
+        sequence.set(&mut sub_store, 10);
+        assert_eq!(sequence.next(&mut sub_store), 10);
+    }
+}
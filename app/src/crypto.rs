@@ -1,95 +1,368 @@
-use bytes::Bytes;
-use ibc_proto::cosmos::tx::v1beta1::{SignDoc, Tx, TxRaw};
+use ibc_proto::{
+    cosmos::{
+        crypto::multisig::v1beta1::CompactBitArray,
+        tx::{
+            signing::v1beta1::SignMode,
+            v1beta1::{mode_info::Sum, ModeInfo},
+        },
+    },
+    google::protobuf::Any,
+};
 use prost::Message;
-use secp256k1::{ecdsa, hashes::sha256, PublicKey, Secp256k1};
-
-pub fn verify_signature(tx: Tx, tx_raw: TxRaw) -> bool {
-    let sign_bytes = SignDoc {
-        body_bytes: tx_raw.body_bytes,
-        auth_info_bytes: tx_raw.auth_info_bytes,
-        chain_id: "localnet".to_string(),
-        account_number: 0,
+use secp256k1::{
+    ecdsa,
+    hashes::{ripemd160, sha256, Hash},
+    PublicKey, Secp256k1,
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::{AppError, ERR_INVALID_PUB_KEY, ERR_UNAUTHORIZED},
+    types::AccAddress,
+};
+
+pub const SECP256K1_PUB_KEY_TYPE_URL: &str = "/cosmos.crypto.secp256k1.PubKey";
+pub const ED25519_PUB_KEY_TYPE_URL: &str = "/cosmos.crypto.ed25519.PubKey";
+pub const MULTISIG_PUB_KEY_TYPE_URL: &str = "/cosmos.crypto.multisig.LegacyAminoPubKey";
+
+// The amino prefixes of the registered public key types, used to derive multisig addresses
+const AMINO_PREFIX_SECP256K1: [u8; 4] = [0xeb, 0x5a, 0xe9, 0x87];
+const AMINO_PREFIX_ED25519: [u8; 4] = [0x16, 0x24, 0xde, 0x64];
+const AMINO_PREFIX_MULTISIG: [u8; 4] = [0x22, 0xc1, 0xf7, 0xe2];
+
+/// The most multisigs which can be nested in each other, the same as the cosmos SDK's
+/// `MaxUnpackAnyRecursionDepth`
+const MAX_MULTISIG_NESTING_DEPTH: usize = 10;
+
+/// The public key of an account which signs txs
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignerPubKey {
+    Secp256k1(PublicKey),
+    Ed25519(Vec<u8>),
+    Multisig {
+        threshold: u32,
+        public_keys: Vec<SignerPubKey>,
+    },
+}
+
+impl SignerPubKey {
+    pub fn from_any(any: &Any) -> Result<SignerPubKey, AppError> {
+        SignerPubKey::from_any_at_depth(any, 0)
+    }
+
+    /// Decodes a key which is nested in the given number of multisigs
+    fn from_any_at_depth(any: &Any, depth: usize) -> Result<SignerPubKey, AppError> {
+        let decode_err = |e: prost::DecodeError| ERR_INVALID_PUB_KEY.wrap(e.to_string());
+
+        match any.type_url.as_str() {
+            SECP256K1_PUB_KEY_TYPE_URL => {
+                let key = PubKey::decode(&any.value[..]).map_err(decode_err)?.key;
+                PublicKey::from_slice(&key)
+                    .map(SignerPubKey::Secp256k1)
+                    .map_err(|e| ERR_INVALID_PUB_KEY.wrap(e.to_string()))
+            }
+            ED25519_PUB_KEY_TYPE_URL => {
+                let key = PubKey::decode(&any.value[..]).map_err(decode_err)?.key;
+                if key.len() != 32 {
+                    return Err(ERR_INVALID_PUB_KEY.wrap("invalid ed25519 key length"));
+                }
+                Ok(SignerPubKey::Ed25519(key))
+            }
+            MULTISIG_PUB_KEY_TYPE_URL => {
+                if depth >= MAX_MULTISIG_NESTING_DEPTH {
+                    return Err(ERR_INVALID_PUB_KEY.wrap("multisig nested too deeply"));
+                }
+
+                let multisig = LegacyAminoPubKey::decode(&any.value[..]).map_err(decode_err)?;
+                let public_keys = multisig
+                    .public_keys
+                    .iter()
+                    .map(|any| SignerPubKey::from_any_at_depth(any, depth + 1))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if multisig.threshold == 0 || multisig.threshold as usize > public_keys.len() {
+                    return Err(ERR_INVALID_PUB_KEY.wrap("invalid multisig threshold"));
+                }
+
+                Ok(SignerPubKey::Multisig {
+                    threshold: multisig.threshold,
+                    public_keys,
+                })
+            }
+            _ => {
+                Err(ERR_INVALID_PUB_KEY
+                    .wrap(format!("unsupported public key type: {}", any.type_url)))
+            }
+        }
+    }
+
+    /// Returns the address of the key, derived as in the cosmos SDK
+    pub fn address(&self) -> AccAddress {
+        let address = match self {
+            SignerPubKey::Secp256k1(key) => {
+                let hash = sha256::Hash::hash(&key.serialize());
+                ripemd160::Hash::hash(&hash.into_inner())
+                    .into_inner()
+                    .to_vec()
+            }
+            SignerPubKey::Ed25519(key) => Sha256::digest(key)[..20].to_vec(),
+            SignerPubKey::Multisig { .. } => Sha256::digest(self.amino_bytes())[..20].to_vec(),
+        };
+
+        AccAddress::try_from(address).expect("20 byte addresses are valid")
+    }
+
+    /// Returns the number of keys, counting each key of a multisig
+    pub fn count_sub_keys(&self) -> usize {
+        match self {
+            SignerPubKey::Multisig { public_keys, .. } => {
+                public_keys.iter().map(SignerPubKey::count_sub_keys).sum()
+            }
+            _ => 1,
+        }
     }
-    .encode_to_vec();
 
-    let message = secp256k1::Message::from_hashed_data::<sha256::Hash>(&sign_bytes);
+    /// Verifies a signature over the sign bytes. Only `SIGN_MODE_DIRECT` is supported, for a
+    /// multisig each key which signed, as given by the bit array of the mode info, must have
+    /// signed in direct mode and at least the threshold number of keys must have signed.
+    pub fn verify(
+        &self,
+        sign_bytes: &[u8],
+        signature: &[u8],
+        mode_info: Option<&ModeInfo>,
+    ) -> Result<(), AppError> {
+        match (self, mode_info.and_then(|mode_info| mode_info.sum.as_ref())) {
+            (SignerPubKey::Secp256k1(key), Some(Sum::Single(single)))
+                if single.mode == SignMode::Direct as i32 =>
+            {
+                let message = secp256k1::Message::from_hashed_data::<sha256::Hash>(sign_bytes);
+                let signature = ecdsa::Signature::from_compact(signature)
+                    .map_err(|e| ERR_UNAUTHORIZED.wrap(format!("invalid signature: {}", e)))?;
 
-    let public = tx.auth_info.clone().unwrap().signer_infos[0]
-        .clone()
-        .public_key
-        .unwrap()
-        .value;
-    let public = PubKey::decode::<Bytes>(public.into()).unwrap();
-    let public_key = PublicKey::from_slice(&public.key).unwrap();
+                Secp256k1::verification_only()
+                    .verify_ecdsa(&message, &signature, key)
+                    .map_err(|_| ERR_UNAUTHORIZED.into())
+            }
+            (SignerPubKey::Ed25519(_), _) => {
+                Err(ERR_INVALID_PUB_KEY.wrap("ed25519 public keys are unsupported"))
+            }
+            (
+                SignerPubKey::Multisig {
+                    threshold,
+                    public_keys,
+                },
+                Some(Sum::Multi(multi)),
+            ) => {
+                let signatures = MultiSignature::decode(signature)
+                    .map_err(|e| ERR_UNAUTHORIZED.wrap(e.to_string()))?
+                    .signatures;
+                let signed = signed_indices(multi.bitarray.as_ref(), public_keys.len())?;
 
-    let sig = ecdsa::Signature::from_compact(&tx_raw.signatures[0]).unwrap();
+                if signed.len() != signatures.len() || signed.len() != multi.mode_infos.len() {
+                    return Err(ERR_UNAUTHORIZED.wrap("invalid multisig signature"));
+                }
+                if signed.len() < *threshold as usize {
+                    return Err(ERR_UNAUTHORIZED.wrap(format!(
+                        "not enough signatures; got {}, threshold {}",
+                        signed.len(),
+                        threshold
+                    )));
+                }
 
-    let secp = Secp256k1::verification_only();
+                for ((index, signature), mode_info) in
+                    signed.into_iter().zip(&signatures).zip(&multi.mode_infos)
+                {
+                    public_keys[index].verify(sign_bytes, signature, Some(mode_info))?;
+                }
 
-    assert!(secp.verify_ecdsa(&message, &sig, &public_key).is_ok());
+                Ok(())
+            }
+            _ => Err(ERR_UNAUTHORIZED.wrap("unsupported sign mode")),
+        }
+    }
+
+    /// Returns the amino encoding of the key, which for a multisig is hashed to give its address
+    fn amino_bytes(&self) -> Vec<u8> {
+        match self {
+            SignerPubKey::Secp256k1(key) => {
+                let key = key.serialize();
+                [&AMINO_PREFIX_SECP256K1[..], &[key.len() as u8], &key].concat()
+            }
+            SignerPubKey::Ed25519(key) => {
+                [&AMINO_PREFIX_ED25519[..], &[key.len() as u8], key].concat()
+            }
+            SignerPubKey::Multisig {
+                threshold,
+                public_keys,
+            } => {
+                let mut bytes = AMINO_PREFIX_MULTISIG.to_vec();
+
+                bytes.push(0x08);
+                prost::encoding::encode_varint(*threshold as u64, &mut bytes);
+
+                for public_key in public_keys {
+                    let key_bytes = public_key.amino_bytes();
+                    bytes.push(0x12);
+                    prost::encoding::encode_varint(key_bytes.len() as u64, &mut bytes);
+                    bytes.extend(key_bytes);
+                }
+
+                bytes
+            }
+        }
+    }
+}
 
-    let sig_res = secp.verify_ecdsa(&message, &sig, &public_key);
+/// Returns the indices of the keys which signed a multisig, given by the set bits of the bit
+/// array, which must have one bit per key
+pub fn signed_indices(
+    bitarray: Option<&CompactBitArray>,
+    num_keys: usize,
+) -> Result<Vec<usize>, AppError> {
+    let bitarray = bitarray.ok_or(ERR_UNAUTHORIZED.wrap("missing multisig bit array"))?;
 
-    if sig_res.is_ok() {
-        println!("Sig is good!!!!")
+    if bitarray.extra_bits_stored >= 8 {
+        return Err(ERR_UNAUTHORIZED.wrap(format!(
+            "invalid bit array extra bits: {}",
+            bitarray.extra_bits_stored
+        )));
     }
 
-    match sig_res {
-        Ok(_) => return true,
-        Err(_) => return false,
+    let size = match bitarray.extra_bits_stored {
+        0 => bitarray.elems.len() * 8,
+        extra => (bitarray.elems.len().saturating_sub(1)) * 8 + extra as usize,
+    };
+    // every bit must be in the elems, which can't have any spare bytes
+    if bitarray.elems.len() != size.div_ceil(8) {
+        return Err(ERR_UNAUTHORIZED.wrap("bit array size doesn't match its elems"));
+    }
+    if size != num_keys {
+        return Err(ERR_UNAUTHORIZED.wrap(format!(
+            "bit array size is incorrect, expecting: {}",
+            num_keys
+        )));
     }
+
+    Ok((0..size)
+        .filter(|i| bitarray.elems[i / 8] & (1 << (7 - i % 8)) != 0)
+        .collect())
 }
 
+/// The secp256k1 and ed25519 public keys share this message, it mirrors the cosmos SDK
+/// `cosmos.crypto.secp256k1.PubKey` and `cosmos.crypto.ed25519.PubKey` messages
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PubKey {
     #[prost(bytes = "vec", tag = "1")]
     pub key: ::prost::alloc::vec::Vec<u8>,
 }
 
+/// A multisig public key, this mirrors the cosmos SDK `cosmos.crypto.multisig.LegacyAminoPubKey`
+/// message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LegacyAminoPubKey {
+    #[prost(uint32, tag = "1")]
+    pub threshold: u32,
+    #[prost(message, repeated, tag = "2")]
+    pub public_keys: Vec<Any>,
+}
+
+/// The signatures of the keys which signed a multisig, this mirrors the cosmos SDK
+/// `cosmos.crypto.multisig.v1beta1.MultiSignature` message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MultiSignature {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub signatures: Vec<Vec<u8>>,
+}
+
 #[cfg(test)]
 mod tests {
 
-    // use ibc_proto::{
-    //     cosmos::{
-    //         auth,
-    //         tx::v1beta1::{AuthInfo, SignerInfo, TxBody},
-    //     },
-    //     google::protobuf::Any,
-    // };
-
-    // use super::*;
-
-    // #[test]
-    // fn verify_signature_test() {
-    //     let messages = vec![Any {
-    //         type_url: "/cosmos".to_string(),
-    //         value: vec![1, 23, 2],
-    //     }];
-
-    //     let body = TxBody {
-    //         messages,
-    //         memo: "".to_string(),
-    //         timeout_height: 0,
-    //         extension_options: vec![],
-    //         non_critical_extension_options: vec![],
-    //     };
-
-    //     let auth_info = AuthInfo {
-    //         signer_infos: vec![SignerInfo {
-    //             public_key: Some(Any {
-    //                 type_url: "".to_string(),
-    //                 value: vec![1, 23, 3],
-    //             }),
-    //             mode_info: None,
-    //             sequence: 0,
-    //         }],
-    //         fee: None,
-    //     };
-    //     let tx = Tx {
-    //         body: Some(body),
-    //         auth_info: Some(auth_info),
-    //         signatures: todo!(),
-    //     };
-    //     assert!(!verify_signature(tx))
-    // }
+    use super::*;
+
+    /// The public key of the README mnemonic's first account
+    const PUB_KEY: [u8; 33] = [
+        2, 245, 4, 176, 81, 219, 178, 190, 52, 157, 52, 166, 90, 30, 194, 89, 132, 89, 28, 108, 31,
+        225, 202, 81, 46, 210, 101, 105, 19, 184, 84, 10, 42,
+    ];
+
+    fn secp256k1_any(key: &[u8]) -> Any {
+        Any {
+            type_url: SECP256K1_PUB_KEY_TYPE_URL.into(),
+            value: PubKey { key: key.to_vec() }.encode_to_vec(),
+        }
+    }
+
+    #[test]
+    fn secp256k1_address_works() {
+        let pub_key = SignerPubKey::from_any(&secp256k1_any(&PUB_KEY)).unwrap();
+
+        assert_eq!(
+            pub_key.address().to_string(),
+            "cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux"
+        );
+        assert_eq!(pub_key.count_sub_keys(), 1);
+    }
+
+    #[test]
+    fn multisig_works() {
+        let any = Any {
+            type_url: MULTISIG_PUB_KEY_TYPE_URL.into(),
+            value: LegacyAminoPubKey {
+                threshold: 2,
+                public_keys: vec![secp256k1_any(&PUB_KEY); 3],
+            }
+            .encode_to_vec(),
+        };
+        assert_eq!(SignerPubKey::from_any(&any).unwrap().count_sub_keys(), 3);
+
+        // the threshold can't exceed the number of keys
+        let any = Any {
+            type_url: MULTISIG_PUB_KEY_TYPE_URL.into(),
+            value: LegacyAminoPubKey {
+                threshold: 4,
+                public_keys: vec![secp256k1_any(&PUB_KEY); 3],
+            }
+            .encode_to_vec(),
+        };
+        assert!(SignerPubKey::from_any(&any).is_err());
+    }
+
+    #[test]
+    fn multisig_nesting_depth_is_limited() {
+        let nest = |times| {
+            (0..times).fold(secp256k1_any(&PUB_KEY), |any, _| Any {
+                type_url: MULTISIG_PUB_KEY_TYPE_URL.into(),
+                value: LegacyAminoPubKey {
+                    threshold: 1,
+                    public_keys: vec![any],
+                }
+                .encode_to_vec(),
+            })
+        };
+
+        assert!(SignerPubKey::from_any(&nest(MAX_MULTISIG_NESTING_DEPTH)).is_ok());
+        let err = SignerPubKey::from_any(&nest(MAX_MULTISIG_NESTING_DEPTH + 1)).unwrap_err();
+        assert_eq!(err.code(), ERR_INVALID_PUB_KEY.code);
+    }
+
+    #[test]
+    fn signed_indices_works() {
+        let bitarray = CompactBitArray {
+            extra_bits_stored: 3,
+            elems: vec![0b1010_0000],
+        };
+        assert_eq!(signed_indices(Some(&bitarray), 3).unwrap(), vec![0, 2]);
+        assert!(signed_indices(Some(&bitarray), 4).is_err());
+        assert!(signed_indices(None, 3).is_err());
+
+        // the size of the bit array must match its elems
+        for (extra_bits_stored, elems) in [(3, vec![]), (8, vec![0])] {
+            let bitarray = CompactBitArray {
+                extra_bits_stored,
+                elems,
+            };
+            let err = signed_indices(Some(&bitarray), 3).unwrap_err();
+            assert_eq!(err.code(), ERR_UNAUTHORIZED.code);
+        }
+    }
 }
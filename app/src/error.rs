@@ -5,12 +5,143 @@ pub enum IAVLError {
     RotateError,
 }
 
+/// The codespace of the errors defined by the cosmos SDK itself
+pub const ROOT_CODESPACE: &str = "sdk";
+
+/// The codespace of errors which are not registered, e.g. panics
+pub const UNDEFINED_CODESPACE: &str = "undefined";
+
+/// An error registered with a codespace and code, as with the cosmos SDK `errors.Register`. The
+/// codespace and code are returned to Tendermint in the `code` and `codespace` response fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisteredError {
+    pub codespace: &'static str,
+    pub code: u32,
+    pub description: &'static str,
+}
+
+impl RegisteredError {
+    pub const fn register(codespace: &'static str, code: u32, description: &'static str) -> Self {
+        RegisteredError {
+            codespace,
+            code,
+            description,
+        }
+    }
+
+    /// Returns this error with additional context, as with the cosmos SDK `errors.Wrap`
+    pub fn wrap(self, context: impl Into<String>) -> AppError {
+        AppError::Registered(self, context.into())
+    }
+}
+
+impl From<RegisteredError> for AppError {
+    fn from(err: RegisteredError) -> AppError {
+        AppError::Registered(err, "".into())
+    }
+}
+
+// The same codes as the cosmos SDK `types/errors` package
+pub const ERR_INTERNAL: RegisteredError =
+    RegisteredError::register(UNDEFINED_CODESPACE, 1, "internal");
+pub const ERR_TX_DECODE: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 2, "tx parse error");
+pub const ERR_INVALID_SEQUENCE: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 3, "invalid sequence");
+pub const ERR_UNAUTHORIZED: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 4, "unauthorized");
+pub const ERR_INSUFFICIENT_FUNDS: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 5, "insufficient funds");
+pub const ERR_UNKNOWN_REQUEST: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 6, "unknown request");
+pub const ERR_INVALID_ADDRESS: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 7, "invalid address");
+pub const ERR_INVALID_PUB_KEY: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 8, "invalid pubkey");
+pub const ERR_UNKNOWN_ADDRESS: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 9, "unknown address");
+pub const ERR_INVALID_COINS: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 10, "invalid coins");
+pub const ERR_OUT_OF_GAS: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 11, "out of gas");
+pub const ERR_MEMO_TOO_LARGE: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 12, "memo too large");
+pub const ERR_INSUFFICIENT_FEE: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 13, "insufficient fee");
+pub const ERR_TOO_MANY_SIGNATURES: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 14, "maximum number of signatures exceeded");
+pub const ERR_NO_SIGNATURES: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 15, "no signatures supplied");
+pub const ERR_INVALID_REQUEST: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 18, "invalid request");
+pub const ERR_JSON_MARSHAL: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 19, "failed to marshal JSON bytes");
+pub const ERR_JSON_UNMARSHAL: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 20, "failed to unmarshal JSON bytes");
+pub const ERR_TX_TOO_LARGE: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 21, "tx too large");
+pub const ERR_KEY_NOT_FOUND: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 22, "key not found");
+pub const ERR_INVALID_CHAIN_ID: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 28, "invalid chain-id");
+pub const ERR_INVALID_TYPE: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 29, "invalid type");
+pub const ERR_TX_TIMEOUT_HEIGHT: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 30, "tx timeout height");
+pub const ERR_UNKNOWN_EXTENSION_OPTIONS: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 31, "unknown extension options");
+pub const ERR_WRONG_SEQUENCE: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 32, "incorrect account sequence");
+pub const ERR_LOGIC: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 35, "internal logic error");
+pub const ERR_NOT_FOUND: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 38, "not found");
+pub const ERR_INVALID_GAS_LIMIT: RegisteredError =
+    RegisteredError::register(ROOT_CODESPACE, 41, "invalid gas limit");
+pub const ERR_PANIC: RegisteredError =
+    RegisteredError::register(UNDEFINED_CODESPACE, 111222, "panic");
+
 #[derive(Debug, PartialEq)]
 pub enum AppError {
     Bech32(bech32::Error),
     InvalidAddress(String),
-    Send(String),
     AccountNotFound,
+    OutOfGas(String),
+    InvalidCoins(String),
+    /// A registered error along with its context, which may be empty
+    Registered(RegisteredError, String),
+}
+
+impl AppError {
+    /// Returns the registered error which gives the codespace and code of this error
+    pub fn registered(&self) -> RegisteredError {
+        match self {
+            AppError::Bech32(_) | AppError::InvalidAddress(_) => ERR_INVALID_ADDRESS,
+            AppError::AccountNotFound => ERR_UNKNOWN_ADDRESS,
+            AppError::OutOfGas(_) => ERR_OUT_OF_GAS,
+            AppError::InvalidCoins(_) => ERR_INVALID_COINS,
+            AppError::Registered(err, _) => *err,
+        }
+    }
+
+    pub fn codespace(&self) -> &'static str {
+        self.registered().codespace
+    }
+
+    pub fn code(&self) -> u32 {
+        self.registered().code
+    }
+
+    /// Returns this error with additional context, as with the cosmos SDK `errors.Wrap`
+    pub fn wrap(self, context: impl Into<String>) -> AppError {
+        let context = context.into();
+
+        match self {
+            AppError::Registered(err, inner) if inner.is_empty() => err.wrap(context),
+            AppError::Registered(err, inner) => err.wrap(format!("{}: {}", context, inner)),
+            err => err.registered().wrap(format!("{}: {}", context, err)),
+        }
+    }
 }
 
 impl Display for AppError {
@@ -18,8 +149,13 @@ impl Display for AppError {
         match self {
             AppError::Bech32(err) => err.fmt(f),
             AppError::InvalidAddress(msg) => write!(f, "Invalid address: {}", msg),
-            AppError::Send(msg) => write!(f, "Send error: {}", msg),
             AppError::AccountNotFound => write!(f, "Account does not exist"),
+            AppError::OutOfGas(descriptor) => write!(f, "out of gas in location: {}", descriptor),
+            AppError::InvalidCoins(msg) => write!(f, "Invalid coins: {}", msg),
+            AppError::Registered(err, context) if context.is_empty() => {
+                write!(f, "{}", err.description)
+            }
+            AppError::Registered(err, context) => write!(f, "{}: {}", context, err.description),
         }
     }
 }
@@ -31,3 +167,76 @@ impl From<bech32::Error> for AppError {
         AppError::Bech32(err)
     }
 }
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(std::io::Error),
+    UnknownFormat(u32),
+    InvalidMetadata(String),
+    ChunkHashMismatch(u32),
+    InvalidSnapshot(String),
+    NoRestoration,
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            SnapshotError::Io(err) => err.fmt(f),
+            SnapshotError::UnknownFormat(format) => {
+                write!(f, "Unknown snapshot format: {}", format)
+            }
+            SnapshotError::InvalidMetadata(msg) => write!(f, "Invalid snapshot metadata: {}", msg),
+            SnapshotError::ChunkHashMismatch(index) => {
+                write!(f, "Hash mismatch for snapshot chunk {}", index)
+            }
+            SnapshotError::InvalidSnapshot(msg) => write!(f, "Invalid snapshot: {}", msg),
+            SnapshotError::NoRestoration => write!(f, "No snapshot restoration in progress"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl From<std::io::Error> for SnapshotError {
+    fn from(err: std::io::Error) -> SnapshotError {
+        SnapshotError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn registered_error_wrapping_works() {
+        let err = ERR_INSUFFICIENT_FUNDS.wrap("0uatom is smaller than 10uatom");
+
+        assert_eq!(err.code(), 5);
+        assert_eq!(err.codespace(), "sdk");
+        assert_eq!(
+            err.to_string(),
+            "0uatom is smaller than 10uatom: insufficient funds"
+        );
+
+        let err = err.wrap("failed to execute message");
+        assert_eq!(err.code(), 5);
+        assert_eq!(
+            err.to_string(),
+            "failed to execute message: 0uatom is smaller than 10uatom: insufficient funds"
+        );
+
+        let err = AppError::from(ERR_UNAUTHORIZED).wrap("signature verification failed");
+        assert_eq!(
+            err.to_string(),
+            "signature verification failed: unauthorized"
+        );
+    }
+
+    #[test]
+    fn app_error_codes_work() {
+        assert_eq!(AppError::AccountNotFound.code(), 9);
+        assert_eq!(AppError::OutOfGas("ReadFlat".into()).code(), 11);
+        assert_eq!(ERR_PANIC.wrap("oops").codespace(), "undefined");
+    }
+}
@@ -3,7 +3,12 @@ use std::cmp;
 use integer_encoding::VarInt;
 use sha2::{Digest, Sha256};
 
-use crate::error::IAVLError;
+use crate::{
+    error::IAVLError,
+    ics23::{
+        CommitmentProof, ExistenceProof, HashOp, InnerOp, LeafOp, LengthOp, NonExistenceProof,
+    },
+};
 
 #[derive(Debug)]
 pub enum Node<'a> {
@@ -83,6 +88,27 @@ impl<'a> Node<'a> {
     }
 }
 
+impl<'a> LeafNode<'a> {
+    /// The ICS-23 op which hashes this leaf, matching `Node::serialize`
+    fn leaf_op(&self) -> LeafOp {
+        let height: i64 = 0;
+        let size: i64 = 1;
+        let version: i64 = self.version.into();
+
+        let mut prefix = height.encode_var_vec();
+        prefix.append(&mut size.encode_var_vec());
+        prefix.append(&mut version.encode_var_vec());
+
+        LeafOp {
+            hash: HashOp::Sha256 as i32,
+            prehash_key: HashOp::NoHash as i32,
+            prehash_value: HashOp::Sha256 as i32,
+            length: LengthOp::VarProto as i32,
+            prefix,
+        }
+    }
+}
+
 impl<'a> IAVLTree<'a> {
     pub fn new(key: &'a [u8], value: &'a [u8]) -> IAVLTree<'a> {
         IAVLTree {
@@ -95,6 +121,115 @@ impl<'a> IAVLTree<'a> {
         }
     }
 
+    /// Builds a tree containing the entries, or returns None if there are none
+    pub fn from_entries(entries: &'a [(Vec<u8>, Vec<u8>)]) -> Option<IAVLTree<'a>> {
+        let ((key, value), rest) = entries.split_first()?;
+        let tree = IAVLTree::new(key, value);
+
+        Some(
+            rest.iter()
+                .fold(tree, |tree, (key, value)| IAVLTree::set(tree, key, value)),
+        )
+    }
+
+    pub fn root_hash(&self) -> [u8; 32] {
+        self.root.hash()
+    }
+
+    /// Returns an ICS-23 proof of the existence of the key or, if the key isn't in the tree, of
+    /// its absence
+    pub fn commitment_proof(&self, key: &[u8]) -> CommitmentProof {
+        if let Some(exist) = self.existence_proof(key) {
+            return CommitmentProof {
+                exist: Some(exist),
+                nonexist: None,
+            };
+        }
+
+        let keys = self.keys();
+        let left = keys
+            .iter()
+            .rev()
+            .find(|k| **k < key)
+            .and_then(|k| self.existence_proof(k));
+        let right = keys
+            .iter()
+            .find(|k| **k > key)
+            .and_then(|k| self.existence_proof(k));
+
+        CommitmentProof {
+            exist: None,
+            nonexist: Some(NonExistenceProof {
+                key: key.to_vec(),
+                left,
+                right,
+            }),
+        }
+    }
+
+    /// Returns an ICS-23 proof of the existence of the key, or None if it isn't in the tree
+    pub fn existence_proof(&self, key: &[u8]) -> Option<ExistenceProof> {
+        let mut path = vec![];
+        let mut node = &self.root;
+
+        loop {
+            match node {
+                Node::Leaf(leaf) => {
+                    if leaf.key != key {
+                        return None;
+                    }
+
+                    // the path runs from the leaf up to the root
+                    path.reverse();
+                    return Some(ExistenceProof {
+                        key: key.to_vec(),
+                        value: leaf.value.to_vec(),
+                        leaf: Some(leaf.leaf_op()),
+                        path,
+                    });
+                }
+                Node::Inner(inner) => {
+                    // the serialized node ends with the length prefixed left and right hashes,
+                    // the hash of the child on the path is left out
+                    let serialized = node.serialize();
+                    let len = serialized.len();
+
+                    let (prefix, suffix) = if key < inner.key {
+                        node = &*inner.left_node;
+                        (&serialized[..len - 65], &serialized[len - 33..])
+                    } else {
+                        node = &*inner.right_node;
+                        (&serialized[..len - 32], &serialized[len..])
+                    };
+
+                    path.push(InnerOp {
+                        hash: HashOp::Sha256 as i32,
+                        prefix: prefix.to_vec(),
+                        suffix: suffix.to_vec(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Returns the keys in the tree in ascending order
+    fn keys(&self) -> Vec<&'a [u8]> {
+        let mut keys = vec![];
+        let mut stack = vec![&self.root];
+
+        while let Some(node) = stack.pop() {
+            match node {
+                Node::Leaf(leaf) => keys.push(leaf.key),
+                Node::Inner(inner) => {
+                    stack.push(&*inner.right_node);
+                    stack.push(&*inner.left_node);
+                }
+            }
+        }
+
+        keys
+    }
+
     pub fn set(tree: IAVLTree<'a>, key: &'a [u8], value: &'a [u8]) -> IAVLTree<'a> {
         IAVLTree {
             root: Self::recursive_set(tree.root, key, value, tree.version),
@@ -145,7 +280,7 @@ impl<'a> IAVLTree<'a> {
 
                         let right_hash = right_node.hash();
 
-                        let key = node.key;
+                        // an inner node's key is the smallest key in its right subtree
                         let left_node = Node::Leaf(node);
                         let left_hash = left_node.hash();
 
@@ -367,4 +502,39 @@ mod tests {
 
         assert_eq!(expected, tree.root.hash());
     }
+
+    #[test]
+    fn existence_proofs_work() {
+        let entries: Vec<(Vec<u8>, Vec<u8>)> =
+            (0..20u8).map(|i| (vec![i * 2], vec![i, i + 1])).collect();
+        let tree = IAVLTree::from_entries(&entries).unwrap();
+
+        for (key, value) in &entries {
+            let proof = tree.existence_proof(key).unwrap();
+            assert_eq!(&proof.value, value);
+            assert_eq!(proof.calculate_root().unwrap(), tree.root_hash());
+        }
+
+        assert!(tree.existence_proof(&[3]).is_none());
+    }
+
+    #[test]
+    fn non_existence_proofs_work() {
+        let entries: Vec<(Vec<u8>, Vec<u8>)> =
+            vec![(b"b".to_vec(), vec![1]), (b"d".to_vec(), vec![2])];
+        let tree = IAVLTree::from_entries(&entries).unwrap();
+
+        let nonexist = tree.commitment_proof(b"c").nonexist.unwrap();
+        assert_eq!(nonexist.left.unwrap().key, b"b");
+        assert_eq!(nonexist.right.unwrap().key, b"d");
+
+        let nonexist = tree.commitment_proof(b"a").nonexist.unwrap();
+        assert!(nonexist.left.is_none());
+        assert_eq!(
+            nonexist.right.unwrap().calculate_root().unwrap(),
+            tree.root_hash()
+        );
+
+        assert!(tree.commitment_proof(b"d").exist.is_some());
+    }
 }
@@ -0,0 +1,121 @@
+//! The ICS-23 commitment proof types used to prove the contents of the IAVL store to light
+//! clients and relayers, see https://github.com/cosmos/ics23
+
+use integer_encoding::VarInt;
+use prost::{Enumeration, Message};
+use sha2::{Digest, Sha256};
+
+/// The ABCI proof op type of an ICS-23 proof against an IAVL tree
+pub const PROOF_OP_IAVL_COMMITMENT: &str = "ics23:iavl";
+/// The ABCI proof op type of an ICS-23 proof of a store's root hash against the app hash
+pub const PROOF_OP_SIMPLE_MERKLE_COMMITMENT: &str = "ics23:simple";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+#[repr(i32)]
+pub enum HashOp {
+    NoHash = 0,
+    Sha256 = 1,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+#[repr(i32)]
+pub enum LengthOp {
+    NoPrefix = 0,
+    VarProto = 1,
+}
+
+/// Either an existence or a non-existence proof. In the ICS-23 schema these two fields form a
+/// oneof, only one of them is ever set so the encoding is the same.
+#[derive(Clone, PartialEq, Message)]
+pub struct CommitmentProof {
+    #[prost(message, optional, tag = "1")]
+    pub exist: Option<ExistenceProof>,
+    #[prost(message, optional, tag = "2")]
+    pub nonexist: Option<NonExistenceProof>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ExistenceProof {
+    #[prost(bytes, tag = "1")]
+    pub key: Vec<u8>,
+    #[prost(bytes, tag = "2")]
+    pub value: Vec<u8>,
+    #[prost(message, optional, tag = "3")]
+    pub leaf: Option<LeafOp>,
+    /// The inner ops from the leaf up to the root
+    #[prost(message, repeated, tag = "4")]
+    pub path: Vec<InnerOp>,
+}
+
+/// Proves a key is absent by proving the existence of its neighbours, either of which may be
+/// missing if the key is beyond the first or last key in the tree
+#[derive(Clone, PartialEq, Message)]
+pub struct NonExistenceProof {
+    #[prost(bytes, tag = "1")]
+    pub key: Vec<u8>,
+    #[prost(message, optional, tag = "2")]
+    pub left: Option<ExistenceProof>,
+    #[prost(message, optional, tag = "3")]
+    pub right: Option<ExistenceProof>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct LeafOp {
+    #[prost(enumeration = "HashOp", tag = "1")]
+    pub hash: i32,
+    #[prost(enumeration = "HashOp", tag = "2")]
+    pub prehash_key: i32,
+    #[prost(enumeration = "HashOp", tag = "3")]
+    pub prehash_value: i32,
+    #[prost(enumeration = "LengthOp", tag = "4")]
+    pub length: i32,
+    #[prost(bytes, tag = "5")]
+    pub prefix: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct InnerOp {
+    #[prost(enumeration = "HashOp", tag = "1")]
+    pub hash: i32,
+    #[prost(bytes, tag = "2")]
+    pub prefix: Vec<u8>,
+    #[prost(bytes, tag = "3")]
+    pub suffix: Vec<u8>,
+}
+
+impl ExistenceProof {
+    /// Returns the root hash implied by this proof, or None if the proof is malformed
+    pub fn calculate_root(&self) -> Option<Vec<u8>> {
+        let leaf = self.leaf.as_ref()?;
+
+        let key = prepare_leaf_data(leaf.prehash_key, leaf.length, &self.key)?;
+        let value = prepare_leaf_data(leaf.prehash_value, leaf.length, &self.value)?;
+
+        let mut hash = do_hash(leaf.hash, &[&leaf.prefix, &key, &value].concat())?;
+        for inner in &self.path {
+            hash = do_hash(inner.hash, &[&inner.prefix, &hash, &inner.suffix].concat())?;
+        }
+
+        Some(hash)
+    }
+}
+
+fn do_hash(op: i32, data: &[u8]) -> Option<Vec<u8>> {
+    match HashOp::from_i32(op)? {
+        HashOp::NoHash => Some(data.to_vec()),
+        HashOp::Sha256 => Some(Sha256::digest(data).to_vec()),
+    }
+}
+
+fn prepare_leaf_data(prehash: i32, length: i32, data: &[u8]) -> Option<Vec<u8>> {
+    let mut hashed = do_hash(prehash, data)?;
+
+    match LengthOp::from_i32(length)? {
+        LengthOp::NoPrefix => Some(hashed),
+        LengthOp::VarProto => {
+            let mut prepared = hashed.len().encode_var_vec();
+            prepared.append(&mut hashed);
+            Some(prepared)
+        }
+    }
+}
@@ -1,15 +1,24 @@
 //! In-memory key/value store application for Tendermint.
 
+use std::{path::PathBuf, sync::Arc};
+
 use baseapp::BaseApp;
+use snapshots::SnapshotManager;
+use streaming::FileStreamingService;
 use structopt::StructOpt;
 use tendermint_abci::ServerBuilder;
 use tracing_subscriber::filter::LevelFilter;
+use types::parse_dec_coins;
 
 mod baseapp;
+mod collections;
 mod crypto;
 mod error;
 mod iavl;
+mod ics23;
+mod snapshots;
 mod store;
+mod streaming;
 mod types;
 mod x;
 
@@ -35,6 +44,34 @@ struct Opt {
     /// Suppress all output logging (overrides --verbose).
     #[structopt(short, long)]
     quiet: bool,
+
+    /// Stream state changes, one file per ABCI message, into this directory. Streaming is
+    /// disabled if not set.
+    #[structopt(long, parse(from_os_str))]
+    streaming_dir: Option<PathBuf>,
+
+    /// Prefix for the names of the files written by the state streamer.
+    #[structopt(long, default_value = "")]
+    streaming_prefix: String,
+
+    /// Store state sync snapshots in this directory. State sync snapshots are neither served
+    /// nor restored if not set.
+    #[structopt(long, parse(from_os_str))]
+    snapshot_dir: Option<PathBuf>,
+
+    /// Snapshot the state every this many blocks, zero disables snapshot creation.
+    #[structopt(long, default_value = "0")]
+    snapshot_interval: u64,
+
+    /// Number of recent snapshots to keep, zero keeps all of them.
+    #[structopt(long, default_value = "2")]
+    snapshot_keep_recent: u32,
+
+    /// The minimum gas prices a tx must pay to be accepted into the mempool, e.g.
+    /// "0.025uatom,0.1stake". A tx's fee must cover the price for its gas limit in at least one
+    /// of the denoms.
+    #[structopt(long, default_value = "")]
+    min_gas_prices: String,
 }
 
 fn main() {
@@ -49,7 +86,26 @@ fn main() {
 
     tracing_subscriber::fmt().with_max_level(log_level).init(); //TODO: fix this
 
-    let app = BaseApp::new();
+    let min_gas_prices =
+        parse_dec_coins(&opt.min_gas_prices).expect("Failed to parse the minimum gas prices");
+    let mut app = BaseApp::new().with_min_gas_prices(min_gas_prices);
+
+    if let Some(streaming_dir) = opt.streaming_dir {
+        let streamer = FileStreamingService::new(streaming_dir, opt.streaming_prefix)
+            .expect("Failed to create the streaming directory");
+        app = app.with_listener(Arc::new(streamer));
+    }
+
+    if let Some(snapshot_dir) = opt.snapshot_dir {
+        let snapshot_manager = SnapshotManager::new(
+            snapshot_dir,
+            opt.snapshot_interval,
+            opt.snapshot_keep_recent,
+        )
+        .expect("Failed to create the snapshot directory");
+        app = app.with_snapshot_manager(snapshot_manager);
+    }
+
     let server = ServerBuilder::new(opt.read_buf_size)
         .bind(format!("{}:{}", opt.host, opt.port), app)
         .unwrap();
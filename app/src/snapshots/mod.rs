@@ -0,0 +1,306 @@
+use std::{
+    io,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use prost::Message;
+use sha2::{Digest, Sha256};
+use tendermint_proto::abci::Snapshot;
+use tracing::{error, info};
+
+use crate::{error::SnapshotError, store::MultiStore};
+
+mod store;
+
+pub use store::*;
+
+/// The only snapshot format, a stream of length prefixed `SnapshotItem`s holding every entry
+/// of the multistore in key order
+pub const SNAPSHOT_FORMAT: u32 = 1;
+
+/// Same as the cosmos SDK default chunk size
+const CHUNK_SIZE: usize = 10_000_000;
+
+/// Stored in `Snapshot.metadata`, so that each chunk can be verified as it's received
+#[derive(Clone, PartialEq, Message)]
+pub struct SnapshotMetadata {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    pub chunk_hashes: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct SnapshotItem {
+    #[prost(bytes = "vec", tag = "1")]
+    pub key: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub value: Vec<u8>,
+}
+
+/// Creates snapshots of the multistore, serves them to Tendermint and restores the multistore
+/// from snapshot chunks received from peers.
+///
+/// The snapshot hash is the sha256 hash of the full (unchunked) snapshot, the hash of each chunk
+/// is held in the snapshot metadata.
+#[derive(Debug)]
+pub struct SnapshotManager {
+    store: SnapshotStore,
+    interval: u64,
+    keep_recent: u32,
+    chunk_size: usize,
+    restoration: Mutex<Option<Restoration>>,
+}
+
+#[derive(Debug)]
+struct Restoration {
+    snapshot: Snapshot,
+    chunk_hashes: Vec<Vec<u8>>,
+    chunks: Vec<Vec<u8>>,
+}
+
+impl SnapshotManager {
+    /// Snapshots are taken every `interval` blocks (never if zero) and only the `keep_recent`
+    /// most recent snapshots are kept
+    pub fn new(dir: PathBuf, interval: u64, keep_recent: u32) -> io::Result<Self> {
+        Ok(SnapshotManager {
+            store: SnapshotStore::new(dir)?,
+            interval,
+            keep_recent,
+            chunk_size: CHUNK_SIZE,
+            restoration: Mutex::new(None),
+        })
+    }
+
+    /// Snapshots the committed multistore, in the background, if the height is a multiple of the
+    /// snapshot interval
+    pub fn snapshot_if_due(self: &Arc<Self>, height: u64, multi_store: Arc<MultiStore>) {
+        if self.interval == 0 || height % self.interval != 0 {
+            return;
+        }
+
+        let manager = self.clone();
+        thread::spawn(move || {
+            match manager.create(height, &multi_store) {
+                Ok(snapshot) => info!(
+                    "Created snapshot at height {} with {} chunks",
+                    height, snapshot.chunks
+                ),
+                Err(e) => {
+                    error!("Failed to create snapshot at height {}: {}", height, e);
+                    return;
+                }
+            }
+
+            if let Err(e) = manager.store.prune(manager.keep_recent) {
+                error!("Failed to prune snapshots: {}", e);
+            }
+        });
+    }
+
+    pub fn create(&self, height: u64, multi_store: &MultiStore) -> io::Result<Snapshot> {
+        let mut stream = vec![];
+        for (key, value) in multi_store.prefix_iterator(vec![]) {
+            let item = SnapshotItem {
+                key: key.clone(),
+                value: value.clone(),
+            };
+            stream.append(&mut item.encode_length_delimited_to_vec());
+        }
+
+        let chunks: Vec<Vec<u8>> = if stream.is_empty() {
+            vec![vec![]]
+        } else {
+            stream.chunks(self.chunk_size).map(<[u8]>::to_vec).collect()
+        };
+
+        let metadata = SnapshotMetadata {
+            chunk_hashes: chunks
+                .iter()
+                .map(|chunk| Sha256::digest(chunk).to_vec())
+                .collect(),
+        };
+
+        let snapshot = Snapshot {
+            height,
+            format: SNAPSHOT_FORMAT,
+            chunks: chunks.len() as u32,
+            hash: Sha256::digest(&stream).to_vec().into(),
+            metadata: metadata.encode_to_vec().into(),
+        };
+
+        self.store.save(&snapshot, &chunks)?;
+
+        Ok(snapshot)
+    }
+
+    pub fn list(&self) -> io::Result<Vec<Snapshot>> {
+        self.store.list()
+    }
+
+    pub fn load_chunk(&self, height: u64, format: u32, chunk: u32) -> io::Result<Option<Vec<u8>>> {
+        self.store.load_chunk(height, format, chunk)
+    }
+
+    /// Starts restoring from the given snapshot, abandoning any restoration in progress
+    pub fn offer(&self, snapshot: Snapshot) -> Result<(), SnapshotError> {
+        if snapshot.format != SNAPSHOT_FORMAT {
+            return Err(SnapshotError::UnknownFormat(snapshot.format));
+        }
+
+        let metadata = SnapshotMetadata::decode(&snapshot.metadata[..])
+            .map_err(|e| SnapshotError::InvalidMetadata(e.to_string()))?;
+
+        if snapshot.chunks == 0 || metadata.chunk_hashes.len() != snapshot.chunks as usize {
+            return Err(SnapshotError::InvalidMetadata(format!(
+                "snapshot has {} chunks but {} chunk hashes",
+                snapshot.chunks,
+                metadata.chunk_hashes.len()
+            )));
+        }
+
+        *self.restoration.lock().expect("Mutex will not be poisoned") = Some(Restoration {
+            snapshot,
+            chunk_hashes: metadata.chunk_hashes,
+            chunks: vec![],
+        });
+
+        Ok(())
+    }
+
+    /// Applies the next chunk of the snapshot being restored. Once the final chunk has been
+    /// applied the snapshot height and restored multistore are returned.
+    pub fn apply_chunk(
+        &self,
+        index: u32,
+        chunk: Vec<u8>,
+    ) -> Result<Option<(u64, MultiStore)>, SnapshotError> {
+        let mut guard = self.restoration.lock().expect("Mutex will not be poisoned");
+        let restoration = guard.as_mut().ok_or(SnapshotError::NoRestoration)?;
+
+        if index as usize != restoration.chunks.len() {
+            return Err(SnapshotError::InvalidSnapshot(format!(
+                "expected chunk {}, got chunk {}",
+                restoration.chunks.len(),
+                index
+            )));
+        }
+
+        if Sha256::digest(&chunk)[..] != restoration.chunk_hashes[index as usize][..] {
+            return Err(SnapshotError::ChunkHashMismatch(index));
+        }
+
+        restoration.chunks.push(chunk);
+
+        if restoration.chunks.len() < restoration.chunk_hashes.len() {
+            return Ok(None);
+        }
+
+        let restoration = guard.take().expect("restoration is in progress");
+        let stream = restoration.chunks.concat();
+
+        if Sha256::digest(&stream)[..] != restoration.snapshot.hash[..] {
+            return Err(SnapshotError::InvalidSnapshot(
+                "snapshot hash does not match".into(),
+            ));
+        }
+
+        let mut multi_store = MultiStore::new();
+        let mut buf = &stream[..];
+        while !buf.is_empty() {
+            let item = SnapshotItem::decode_length_delimited(&mut buf)
+                .map_err(|e| SnapshotError::InvalidSnapshot(e.to_string()))?;
+            multi_store.set(item.key, item.value);
+        }
+
+        Ok(Some((restoration.snapshot.height, multi_store)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "gaia-rs-snapshot-{}-test-{}",
+            name,
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn snapshot_restore_works() {
+        let mut multi_store = MultiStore::new();
+        multi_store.set(b"bank1".to_vec(), vec![1; 20]);
+        multi_store.set(b"acc1".to_vec(), vec![2; 20]);
+
+        let dir = test_dir("source");
+        let mut source = SnapshotManager::new(dir.clone(), 10, 2).unwrap();
+        source.chunk_size = 16;
+        let snapshot = source.create(10, &multi_store).unwrap();
+
+        assert!(snapshot.chunks > 1);
+        assert_eq!(source.list().unwrap(), vec![snapshot.clone()]);
+
+        let target_dir = test_dir("target");
+        let target = SnapshotManager::new(target_dir.clone(), 10, 2).unwrap();
+        target.offer(snapshot.clone()).unwrap();
+
+        let mut restored = None;
+        for index in 0..snapshot.chunks {
+            let chunk = source.load_chunk(10, SNAPSHOT_FORMAT, index).unwrap().unwrap();
+            restored = target.apply_chunk(index, chunk).unwrap();
+        }
+
+        let (height, restored) = restored.unwrap();
+        assert_eq!(height, 10);
+        assert_eq!(
+            restored.prefix_iterator(vec![]).collect::<Vec<_>>(),
+            multi_store.prefix_iterator(vec![]).collect::<Vec<_>>()
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+        std::fs::remove_dir_all(target_dir).unwrap();
+    }
+
+    #[test]
+    fn apply_chunk_rejects_bad_chunk() {
+        let mut multi_store = MultiStore::new();
+        multi_store.set(b"bank1".to_vec(), vec![1]);
+
+        let dir = test_dir("bad-chunk");
+        let manager = SnapshotManager::new(dir.clone(), 10, 2).unwrap();
+        let snapshot = manager.create(10, &multi_store).unwrap();
+
+        manager.offer(snapshot).unwrap();
+        let err = manager.apply_chunk(0, vec![1, 2, 3]).unwrap_err();
+        assert!(matches!(err, SnapshotError::ChunkHashMismatch(0)));
+
+        // the chunk can be refetched and applied again
+        let chunk = manager.load_chunk(10, SNAPSHOT_FORMAT, 0).unwrap().unwrap();
+        assert!(manager.apply_chunk(0, chunk).unwrap().is_some());
+
+        let err = manager.apply_chunk(0, vec![]).unwrap_err();
+        assert!(matches!(err, SnapshotError::NoRestoration));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn offer_rejects_unknown_format() {
+        let dir = test_dir("format");
+        let manager = SnapshotManager::new(dir.clone(), 10, 2).unwrap();
+
+        let err = manager
+            .offer(Snapshot {
+                format: 2,
+                ..Default::default()
+            })
+            .unwrap_err();
+        assert!(matches!(err, SnapshotError::UnknownFormat(2)));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use prost::Message;
+use tendermint_proto::abci::Snapshot;
+
+const METADATA_FILE: &str = "metadata";
+
+/// Stores snapshots on local disk. Each snapshot has its own directory `{height}/{format}`
+/// containing one file per chunk, named by the chunk index, and a `metadata` file holding the
+/// protobuf encoded `Snapshot`. The metadata file is written last so that incomplete snapshots
+/// are never listed.
+#[derive(Debug)]
+pub struct SnapshotStore {
+    dir: PathBuf,
+}
+
+impl SnapshotStore {
+    pub fn new(dir: PathBuf) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(SnapshotStore { dir })
+    }
+
+    fn snapshot_dir(&self, height: u64, format: u32) -> PathBuf {
+        self.dir.join(height.to_string()).join(format.to_string())
+    }
+
+    pub fn save(&self, snapshot: &Snapshot, chunks: &[Vec<u8>]) -> io::Result<()> {
+        let dir = self.snapshot_dir(snapshot.height, snapshot.format);
+        fs::create_dir_all(&dir)?;
+
+        for (index, chunk) in chunks.iter().enumerate() {
+            fs::write(dir.join(index.to_string()), chunk)?;
+        }
+
+        fs::write(dir.join(METADATA_FILE), snapshot.encode_to_vec())
+    }
+
+    pub fn get(&self, height: u64, format: u32) -> io::Result<Option<Snapshot>> {
+        let metadata = match fs::read(self.snapshot_dir(height, format).join(METADATA_FILE)) {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        Snapshot::decode(&metadata[..])
+            .map(Some)
+            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+    }
+
+    /// Returns all complete snapshots, most recent first
+    pub fn list(&self) -> io::Result<Vec<Snapshot>> {
+        let mut snapshots = vec![];
+
+        for height in parse_dir_names::<u64>(&self.dir)? {
+            for format in parse_dir_names::<u32>(&self.dir.join(height.to_string()))? {
+                if let Some(snapshot) = self.get(height, format)? {
+                    snapshots.push(snapshot);
+                }
+            }
+        }
+
+        snapshots.sort_by(|a, b| (b.height, b.format).cmp(&(a.height, a.format)));
+        Ok(snapshots)
+    }
+
+    pub fn load_chunk(&self, height: u64, format: u32, chunk: u32) -> io::Result<Option<Vec<u8>>> {
+        // only serve chunks of complete snapshots
+        if self.get(height, format)?.is_none() {
+            return Ok(None);
+        }
+
+        match fs::read(self.snapshot_dir(height, format).join(chunk.to_string())) {
+            Ok(chunk) => Ok(Some(chunk)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn delete(&self, height: u64, format: u32) -> io::Result<()> {
+        fs::remove_dir_all(self.snapshot_dir(height, format))?;
+
+        // remove the height directory once it holds no more formats
+        let height_dir = self.dir.join(height.to_string());
+        if fs::read_dir(&height_dir)?.next().is_none() {
+            fs::remove_dir(height_dir)?;
+        }
+
+        Ok(())
+    }
+
+    /// Deletes all but the `retain` most recent snapshots, returning the number deleted. As in the
+    /// cosmos SDK, zero retains every snapshot.
+    pub fn prune(&self, retain: u32) -> io::Result<usize> {
+        if retain == 0 {
+            return Ok(0);
+        }
+
+        let stale: Vec<Snapshot> = self.list()?.into_iter().skip(retain as usize).collect();
+
+        for snapshot in &stale {
+            self.delete(snapshot.height, snapshot.format)?;
+        }
+
+        Ok(stale.len())
+    }
+}
+
+/// Returns the names of the sub directories of dir which parse as T, other entries are ignored
+fn parse_dir_names<T: std::str::FromStr>(dir: &Path) -> io::Result<Vec<T>> {
+    let mut names = vec![];
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        if let Some(name) = entry.file_name().to_str().and_then(|name| name.parse().ok()) {
+            names.push(name);
+        }
+    }
+
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn snapshot_store_list_and_prune_works() {
+        let dir = std::env::temp_dir().join(format!(
+            "gaia-rs-snapshot-store-test-{}",
+            std::process::id()
+        ));
+        let store = SnapshotStore::new(dir.clone()).unwrap();
+
+        for height in [5, 15, 10] {
+            let snapshot = Snapshot {
+                height,
+                format: 1,
+                chunks: 1,
+                hash: vec![height as u8].into(),
+                metadata: Default::default(),
+            };
+            store.save(&snapshot, &[vec![height as u8]]).unwrap();
+        }
+
+        let heights: Vec<u64> = store.list().unwrap().iter().map(|s| s.height).collect();
+        assert_eq!(heights, vec![15, 10, 5]);
+        assert_eq!(store.load_chunk(10, 1, 0).unwrap(), Some(vec![10]));
+        assert_eq!(store.load_chunk(10, 1, 1).unwrap(), None);
+
+        assert_eq!(store.prune(0).unwrap(), 0);
+        assert_eq!(store.list().unwrap().len(), 3);
+
+        assert_eq!(store.prune(2).unwrap(), 1);
+
+        let heights: Vec<u64> = store.list().unwrap().iter().map(|s| s.height).collect();
+        assert_eq!(heights, vec![15, 10]);
+        assert_eq!(store.load_chunk(5, 1, 0).unwrap(), None);
+        assert!(!dir.join("5").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,181 @@
+use integer_encoding::VarInt;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    iavl::IAVLTree,
+    ics23::{CommitmentProof, ExistenceProof, HashOp, InnerOp, LeafOp, LengthOp},
+};
+
+use super::MultiStore;
+
+/// The root hashes of the stores of a committed version of the multistore. As in the cosmos
+/// SDK's rootmulti store, the app hash is the root of a simple merkle tree of the store root
+/// hashes, keyed by store key, and each store root is the root of an IAVL tree of its entries.
+#[derive(Debug, Clone)]
+pub struct CommitInfo {
+    /// The store keys and root hashes, in ascending store key order. An empty store's root hash
+    /// is empty.
+    store_hashes: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl CommitInfo {
+    pub fn new(multi_store: &MultiStore, store_keys: &[&[u8]]) -> Self {
+        let mut store_hashes: Vec<_> = store_keys
+            .iter()
+            .map(|store_key| {
+                let entries: Vec<(Vec<u8>, Vec<u8>)> = multi_store
+                    .get_immutable_sub_store(store_key.to_vec())
+                    .prefix_iter(&[])
+                    .collect();
+                let hash = IAVLTree::from_entries(&entries)
+                    .map_or(vec![], |tree| tree.root_hash().to_vec());
+
+                (store_key.to_vec(), hash)
+            })
+            .collect();
+        store_hashes.sort();
+
+        CommitInfo { store_hashes }
+    }
+
+    /// Returns the app hash
+    pub fn hash(&self) -> Vec<u8> {
+        simple_merkle_root(&self.leaves())
+    }
+
+    /// Returns an ICS-23 proof of the root hash of the store against the app hash, or None if
+    /// there's no store with the key
+    pub fn proof(&self, store_key: &[u8]) -> Option<CommitmentProof> {
+        let index = self
+            .store_hashes
+            .iter()
+            .position(|(key, _)| key == store_key)?;
+        let (key, hash) = &self.store_hashes[index];
+
+        Some(CommitmentProof {
+            exist: Some(ExistenceProof {
+                key: key.clone(),
+                value: hash.clone(),
+                leaf: Some(LeafOp {
+                    hash: HashOp::Sha256 as i32,
+                    prehash_key: HashOp::NoHash as i32,
+                    prehash_value: HashOp::Sha256 as i32,
+                    length: LengthOp::VarProto as i32,
+                    prefix: vec![LEAF_PREFIX],
+                }),
+                path: simple_merkle_path(&self.leaves(), index),
+            }),
+            nonexist: None,
+        })
+    }
+
+    /// The leaves are the length prefixed store key and hash of the store root hash, the same as
+    /// the ICS-23 leaf op of `proof`
+    fn leaves(&self) -> Vec<Vec<u8>> {
+        self.store_hashes
+            .iter()
+            .map(|(key, hash)| [encode_bytes(key), encode_bytes(&Sha256::digest(hash))].concat())
+            .collect()
+    }
+}
+
+// The domain separation of leaves and inner nodes in Tendermint's simple merkle trees
+const LEAF_PREFIX: u8 = 0;
+const INNER_PREFIX: u8 = 1;
+
+/// Returns the root of Tendermint's simple merkle tree of the leaves, which splits the leaves
+/// at the largest power of two less than their number
+fn simple_merkle_root(leaves: &[Vec<u8>]) -> Vec<u8> {
+    match leaves {
+        [] => Sha256::digest(b"").to_vec(),
+        [leaf] => Sha256::digest([&[LEAF_PREFIX][..], &leaf[..]].concat()).to_vec(),
+        _ => {
+            let (left, right) = leaves.split_at(split_point(leaves.len()));
+            let mut inner = vec![INNER_PREFIX];
+            inner.extend(simple_merkle_root(left));
+            inner.extend(simple_merkle_root(right));
+            Sha256::digest(inner).to_vec()
+        }
+    }
+}
+
+/// Returns the inner ops from the leaf at the index up to the root
+fn simple_merkle_path(leaves: &[Vec<u8>], index: usize) -> Vec<InnerOp> {
+    if leaves.len() <= 1 {
+        return vec![];
+    }
+
+    let split = split_point(leaves.len());
+    let (left, right) = leaves.split_at(split);
+    let (mut path, op) = if index < split {
+        let op = InnerOp {
+            hash: HashOp::Sha256 as i32,
+            prefix: vec![INNER_PREFIX],
+            suffix: simple_merkle_root(right),
+        };
+        (simple_merkle_path(left, index), op)
+    } else {
+        let op = InnerOp {
+            hash: HashOp::Sha256 as i32,
+            prefix: [vec![INNER_PREFIX], simple_merkle_root(left)].concat(),
+            suffix: vec![],
+        };
+        (simple_merkle_path(right, index - split), op)
+    };
+    path.push(op);
+
+    path
+}
+
+/// Returns the largest power of two less than n, which must be at least 2
+fn split_point(n: usize) -> usize {
+    let power = n.next_power_of_two();
+    if power == n {
+        n / 2
+    } else {
+        power / 2
+    }
+}
+
+fn encode_bytes(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = bytes.len().encode_var_vec();
+    encoded.extend(bytes);
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn store_proofs_work() {
+        let mut multi_store = MultiStore::new();
+        multi_store.set(b"bank1".to_vec(), vec![1]);
+        multi_store.set(b"acc1".to_vec(), vec![2]);
+        multi_store.set(b"acc2".to_vec(), vec![3]);
+        let store_keys: [&[u8]; 3] = [b"bank", b"acc", b"staking"];
+        let commit_info = CommitInfo::new(&multi_store, &store_keys);
+
+        for store_key in store_keys {
+            let proof = commit_info.proof(store_key).unwrap().exist.unwrap();
+            assert_eq!(proof.key, store_key);
+            assert_eq!(proof.calculate_root().unwrap(), commit_info.hash());
+        }
+        assert!(commit_info.proof(b"foo").is_none());
+
+        // the empty store's root is empty
+        let proof = commit_info.proof(b"staking").unwrap().exist.unwrap();
+        assert!(proof.value.is_empty());
+    }
+
+    #[test]
+    fn split_point_works() {
+        assert_eq!(split_point(2), 1);
+        assert_eq!(split_point(3), 2);
+        assert_eq!(split_point(4), 2);
+        assert_eq!(split_point(7), 4);
+        assert_eq!(split_point(8), 4);
+        assert_eq!(split_point(9), 8);
+    }
+}
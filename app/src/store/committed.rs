@@ -0,0 +1,91 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use tendermint_proto::google::protobuf::Timestamp;
+
+use super::{CommitInfo, MultiStore};
+
+/// The multistore as it was committed at a height, with the time of the block at that height
+/// and the store root hashes which the app hash commits to
+#[derive(Debug, Clone)]
+pub struct CommittedVersion {
+    pub multi_store: Arc<MultiStore>,
+    pub block_time: Timestamp,
+    pub commit_info: CommitInfo,
+}
+
+/// Immutable copies of the multistore as it was at each of the most recently committed heights.
+/// Queries run against these copies so that they never contend with the deliver state. The
+/// copies are cheap as each shares the entries it didn't change with the other versions.
+#[derive(Debug)]
+pub struct CommittedVersions {
+    versions: BTreeMap<u32, CommittedVersion>,
+    keep_recent: u32,
+}
+
+impl CommittedVersions {
+    /// Keeps the `keep_recent` most recent versions, the latest version is always kept
+    pub fn new(keep_recent: u32) -> Self {
+        CommittedVersions {
+            versions: BTreeMap::new(),
+            keep_recent,
+        }
+    }
+
+    /// Adds the version committed at the given height, pruning the oldest versions
+    pub fn commit(&mut self, height: u32, version: CommittedVersion) {
+        self.versions.insert(height, version);
+
+        while self.versions.len() > self.keep_recent.max(1) as usize {
+            let oldest = *self.versions.keys().next().expect("versions is not empty");
+            self.versions.remove(&oldest);
+        }
+    }
+
+    /// Returns the height and the latest version
+    pub fn latest(&self) -> Option<(u32, CommittedVersion)> {
+        self.versions
+            .iter()
+            .next_back()
+            .map(|(height, version)| (*height, version.clone()))
+    }
+
+    pub fn get(&self, height: u32) -> Option<CommittedVersion> {
+        self.versions.get(&height).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn committed_versions_pruning_works() {
+        let mut versions = CommittedVersions::new(2);
+
+        for height in 1..=3 {
+            let mut multi_store = MultiStore::new();
+            multi_store.set(vec![1], vec![height as u8]);
+            versions.commit(
+                height,
+                CommittedVersion {
+                    commit_info: CommitInfo::new(&multi_store, &[]),
+                    multi_store: Arc::new(multi_store),
+                    block_time: Timestamp {
+                        seconds: height.into(),
+                        nanos: 0,
+                    },
+                },
+            );
+        }
+
+        assert!(versions.get(1).is_none());
+        let version = versions.get(2).unwrap();
+        assert_eq!(version.multi_store.get(&[1]), Some(&vec![2]));
+        assert_eq!(version.block_time.seconds, 2);
+
+        let (height, latest) = versions.latest().unwrap();
+        assert_eq!(height, 3);
+        assert_eq!(latest.multi_store.get(&[1]), Some(&vec![3]));
+    }
+}
@@ -0,0 +1,229 @@
+use crate::types::{Gas, GasMeter};
+
+use super::{MutableSubStore, ReadKVStore, WriteKVStore};
+
+const GAS_HAS_DESC: &str = "Has";
+const GAS_READ_COST_FLAT_DESC: &str = "ReadFlat";
+const GAS_READ_PER_BYTE_DESC: &str = "ReadPerByte";
+const GAS_WRITE_COST_FLAT_DESC: &str = "WriteFlat";
+const GAS_WRITE_PER_BYTE_DESC: &str = "WritePerByte";
+const GAS_VALUE_PER_BYTE_DESC: &str = "ValuePerByte";
+const GAS_ITER_NEXT_COST_FLAT_DESC: &str = "IterNextFlat";
+const GAS_DELETE_DESC: &str = "Delete";
+
+/// Gas costs charged by a GasKVStore for each store operation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GasConfig {
+    pub has_cost: Gas,
+    pub delete_cost: Gas,
+    pub read_cost_flat: Gas,
+    pub read_cost_per_byte: Gas,
+    pub write_cost_flat: Gas,
+    pub write_cost_per_byte: Gas,
+    pub iter_next_cost_flat: Gas,
+}
+
+/// The cosmos SDK default gas config for KV stores
+pub const KV_GAS_CONFIG: GasConfig = GasConfig {
+    has_cost: 1000,
+    delete_cost: 1000,
+    read_cost_flat: 1000,
+    read_cost_per_byte: 3,
+    write_cost_flat: 2000,
+    write_cost_per_byte: 30,
+    iter_next_cost_flat: 30,
+};
+
+/// Wraps a MutableSubStore and charges gas to a GasMeter for every operation
+pub struct GasKVStore<'a> {
+    parent: MutableSubStore<'a>,
+    gas_meter: &'a dyn GasMeter,
+    gas_config: GasConfig,
+}
+
+impl<'a> GasKVStore<'a> {
+    pub fn new(
+        parent: MutableSubStore<'a>,
+        gas_meter: &'a dyn GasMeter,
+        gas_config: GasConfig,
+    ) -> Self {
+        GasKVStore {
+            parent,
+            gas_meter,
+            gas_config,
+        }
+    }
+
+    pub fn get(&self, k: &[u8]) -> Option<&Vec<u8>> {
+        self.gas_meter
+            .consume_gas(self.gas_config.read_cost_flat, GAS_READ_COST_FLAT_DESC);
+
+        let value = self.parent.get(k);
+
+        self.gas_meter.consume_gas(
+            self.gas_config.read_cost_per_byte * k.len() as Gas,
+            GAS_READ_PER_BYTE_DESC,
+        );
+        self.gas_meter.consume_gas(
+            self.gas_config.read_cost_per_byte * value.map_or(0, |v| v.len()) as Gas,
+            GAS_READ_PER_BYTE_DESC,
+        );
+
+        value
+    }
+
+    pub fn has(&self, k: &[u8]) -> bool {
+        self.gas_meter
+            .consume_gas(self.gas_config.has_cost, GAS_HAS_DESC);
+        self.parent.get(k).is_some()
+    }
+
+    pub fn set(&mut self, k: Vec<u8>, v: Vec<u8>) -> Option<Vec<u8>> {
+        self.gas_meter
+            .consume_gas(self.gas_config.write_cost_flat, GAS_WRITE_COST_FLAT_DESC);
+        self.gas_meter.consume_gas(
+            self.gas_config.write_cost_per_byte * k.len() as Gas,
+            GAS_WRITE_PER_BYTE_DESC,
+        );
+        self.gas_meter.consume_gas(
+            self.gas_config.write_cost_per_byte * v.len() as Gas,
+            GAS_WRITE_PER_BYTE_DESC,
+        );
+
+        self.parent.set(k, v)
+    }
+
+    pub fn delete(&mut self, k: &[u8]) -> Option<Vec<u8>> {
+        self.gas_meter
+            .consume_gas(self.gas_config.delete_cost, GAS_DELETE_DESC);
+        self.parent.delete(k)
+    }
+
+    /// Returns an iterator over all entries in this store. Gas is charged in the same way as the
+    /// cosmos SDK gas iterator: the first entry is charged on creation and the current entry is
+    /// charged again (together with the flat cost) each time the iterator is advanced.
+    pub fn iter(&self) -> GasIterator<'_> {
+        self.prefix_iter(&[])
+    }
+
+    /// Returns a gas metered iterator over all entries in this store whose key starts with the
+    /// given prefix
+    pub fn prefix_iter(&self, prefix: &[u8]) -> GasIterator<'_> {
+        let mut parent = self.parent.prefix_iter(prefix).peekable();
+
+        consume_seek_gas(self.gas_meter, &self.gas_config, parent.peek());
+
+        GasIterator {
+            parent: Box::new(parent),
+            gas_meter: self.gas_meter,
+            gas_config: self.gas_config,
+            current: None,
+        }
+    }
+}
+
+impl<'a> ReadKVStore for GasKVStore<'a> {
+    fn get(&self, k: &[u8]) -> Option<Vec<u8>> {
+        GasKVStore::get(self, k).cloned()
+    }
+
+    fn prefix_iterator<'b>(
+        &'b self,
+        prefix: &[u8],
+    ) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'b> {
+        Box::new(self.prefix_iter(prefix))
+    }
+}
+
+impl<'a> WriteKVStore for GasKVStore<'a> {
+    fn set(&mut self, k: Vec<u8>, v: Vec<u8>) {
+        GasKVStore::set(self, k, v);
+    }
+
+    fn delete(&mut self, k: &[u8]) {
+        GasKVStore::delete(self, k);
+    }
+}
+
+pub struct GasIterator<'a> {
+    parent: Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a>,
+    gas_meter: &'a dyn GasMeter,
+    gas_config: GasConfig,
+    current: Option<(Vec<u8>, Vec<u8>)>,
+}
+
+impl<'a> Iterator for GasIterator<'a> {
+    type Item = (Vec<u8>, Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Moving past an entry charges for that entry, there's nothing to move past on the
+        // first call since the first entry was charged when the iterator was created
+        if let Some(current) = &self.current {
+            consume_seek_gas(self.gas_meter, &self.gas_config, Some(current));
+        }
+
+        self.current = self.parent.next();
+        self.current.clone()
+    }
+}
+
+fn consume_seek_gas(
+    gas_meter: &dyn GasMeter,
+    gas_config: &GasConfig,
+    entry: Option<&(Vec<u8>, Vec<u8>)>,
+) {
+    if let Some((key, value)) = entry {
+        gas_meter.consume_gas(
+            gas_config.read_cost_per_byte * key.len() as Gas,
+            GAS_VALUE_PER_BYTE_DESC,
+        );
+        gas_meter.consume_gas(
+            gas_config.read_cost_per_byte * value.len() as Gas,
+            GAS_VALUE_PER_BYTE_DESC,
+        );
+    }
+    gas_meter.consume_gas(gas_config.iter_next_cost_flat, GAS_ITER_NEXT_COST_FLAT_DESC);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::{store::MultiStore, types::InfiniteGasMeter};
+
+    #[test]
+    fn gas_kv_store_set_get_works() {
+        let mut store = MultiStore::new();
+        let meter = InfiniteGasMeter::new();
+        let mut gas_store =
+            GasKVStore::new(store.get_mutable_sub_store(vec![1]), &meter, KV_GAS_CONFIG);
+
+        gas_store.set(vec![1, 2], vec![3, 4, 5]);
+        assert_eq!(meter.gas_consumed(), 2000 + 30 * 2 + 30 * 3);
+
+        let value = gas_store.get(&[1, 2]).cloned();
+        assert_eq!(value, Some(vec![3, 4, 5]));
+        assert_eq!(meter.gas_consumed(), 2150 + 1000 + 3 * 2 + 3 * 3);
+
+        gas_store.delete(&[1, 2]);
+        assert_eq!(meter.gas_consumed(), 3165 + 1000);
+    }
+
+    #[test]
+    fn gas_kv_store_iterator_works() {
+        let mut store = MultiStore::new();
+        store.set(vec![1, 1], vec![1]);
+        store.set(vec![1, 2], vec![2]);
+
+        let meter = InfiniteGasMeter::new();
+        let gas_store =
+            GasKVStore::new(store.get_mutable_sub_store(vec![1]), &meter, KV_GAS_CONFIG);
+
+        let items: Vec<(Vec<u8>, Vec<u8>)> = gas_store.iter().collect();
+
+        assert_eq!(items, vec![(vec![1], vec![1]), (vec![2], vec![2])]);
+        // the first entry is charged on creation and each entry is charged again as the iterator
+        // moves past it, along with the flat cost each time
+        assert_eq!(meter.gas_consumed(), 3 * 30 + 6 + 6 + 6);
+    }
+}
@@ -0,0 +1,15 @@
+/// A single write or delete, this mirrors the cosmos SDK `cosmos.base.store.v1beta1.StoreKVPair`
+/// message so that the encoding is the same as the SDK's
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StoreKVPair {
+    /// the store key of the store which was written to
+    #[prost(string, tag = "1")]
+    pub store_key: ::prost::alloc::string::String,
+    /// true indicates a delete operation, false indicates a set operation
+    #[prost(bool, tag = "2")]
+    pub delete: bool,
+    #[prost(bytes = "vec", tag = "3")]
+    pub key: ::prost::alloc::vec::Vec<u8>,
+    #[prost(bytes = "vec", tag = "4")]
+    pub value: ::prost::alloc::vec::Vec<u8>,
+}
@@ -0,0 +1,324 @@
+use im::OrdMap;
+
+mod commit_info;
+mod committed;
+mod gas;
+mod listen;
+
+pub use commit_info::*;
+pub use committed::*;
+pub use gas::*;
+pub use listen::*;
+
+#[derive(Debug, Clone)]
+pub struct MultiStore {
+    /// A persistent map, so that the many copies of the store (the committed versions, the
+    /// check state and the branches of each tx) share their unchanged entries
+    core: OrdMap<Vec<u8>, Vec<u8>>,
+    listening: Vec<Vec<u8>>,
+    change_set: Vec<StoreKVPair>,
+}
+
+impl MultiStore {
+    pub fn new() -> Self {
+        MultiStore {
+            core: OrdMap::new(),
+            listening: vec![],
+            change_set: vec![],
+        }
+    }
+
+    pub fn get(&self, k: &[u8]) -> Option<&Vec<u8>> {
+        self.core.get(k)
+    }
+
+    pub fn set(&mut self, k: Vec<u8>, v: Vec<u8>) -> Option<Vec<u8>> {
+        self.record_write(&k, Some(&v));
+        self.core.insert(k, v)
+    }
+
+    pub fn delete(&mut self, k: &[u8]) -> Option<Vec<u8>> {
+        self.record_write(k, None);
+        self.core.remove(k)
+    }
+
+    /// Records all subsequent writes and deletes to the store with the given store key (the
+    /// prefix of a module's sub store) in the change set
+    pub fn add_listening_store_key(&mut self, store_key: Vec<u8>) {
+        if !self.listening.contains(&store_key) {
+            self.listening.push(store_key);
+        }
+    }
+
+    /// Returns, and clears, the writes recorded since the change set was last taken. Since the
+    /// change set is cloned along with the store, the writes made to a discarded branch of the
+    /// store are discarded with it.
+    pub fn take_change_set(&mut self) -> Vec<StoreKVPair> {
+        std::mem::take(&mut self.change_set)
+    }
+
+    fn record_write(&mut self, k: &[u8], v: Option<&Vec<u8>>) {
+        if let Some(store_key) = self.listening.iter().find(|store_key| k.starts_with(store_key)) {
+            self.change_set.push(StoreKVPair {
+                store_key: String::from_utf8_lossy(store_key).into(),
+                delete: v.is_none(),
+                key: k[store_key.len()..].to_vec(),
+                value: v.cloned().unwrap_or_default(),
+            });
+        }
+    }
+
+    /// Returns an iterator, in ascending key order, over all entries whose key starts with the prefix
+    pub fn prefix_iterator(
+        &self,
+        prefix: Vec<u8>,
+    ) -> impl Iterator<Item = (&Vec<u8>, &Vec<u8>)> + '_ {
+        self.core
+            .range(prefix.clone()..)
+            .take_while(move |(k, _)| k.starts_with(&prefix))
+    }
+
+    pub fn get_immutable_sub_store(&self, prefix: Vec<u8>) -> ImmutableSubStore {
+        ImmutableSubStore {
+            store: self,
+            prefix,
+        }
+    }
+
+    pub fn get_mutable_sub_store(&mut self, prefix: Vec<u8>) -> MutableSubStore {
+        MutableSubStore {
+            store: self,
+            prefix,
+        }
+    }
+}
+
+/// Wraps an immutable reference to a MultiStore with a prefix
+pub struct ImmutableSubStore<'a> {
+    store: &'a MultiStore,
+    prefix: Vec<u8>,
+}
+
+impl<'a> ImmutableSubStore<'a> {
+    pub fn get(&self, k: &[u8]) -> Option<&Vec<u8>> {
+        let mut full_key = self.prefix.clone();
+        full_key.extend(k);
+        self.store.get(&full_key)
+    }
+
+    pub fn get_sub_store(&self, mut prefix: Vec<u8>) -> ImmutableSubStore {
+        let mut full_prefix = self.prefix.clone();
+        full_prefix.append(&mut prefix);
+
+        ImmutableSubStore {
+            store: self.store,
+            prefix: full_prefix,
+        }
+    }
+
+    /// Returns an iterator, in ascending key order, over all entries in this sub store whose key
+    /// starts with the given prefix. Keys are returned with the sub store prefix removed.
+    pub fn prefix_iter(&self, prefix: &[u8]) -> impl Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a {
+        let mut full_prefix = self.prefix.clone();
+        full_prefix.extend(prefix);
+        let prefix_len = self.prefix.len();
+        self.store
+            .prefix_iterator(full_prefix)
+            .map(move |(k, v)| (k[prefix_len..].to_vec(), v.to_owned()))
+    }
+}
+
+/// Wraps an mutable reference to a MultiStore with a prefix
+pub struct MutableSubStore<'a> {
+    store: &'a mut MultiStore,
+    prefix: Vec<u8>,
+}
+
+impl<'a> MutableSubStore<'a> {
+    pub fn get(&self, k: &[u8]) -> Option<&Vec<u8>> {
+        let mut full_key = self.prefix.clone();
+        full_key.extend(k);
+        self.store.get(&full_key)
+    }
+
+    pub fn set(&mut self, k: Vec<u8>, v: Vec<u8>) -> Option<Vec<u8>> {
+        let full_key = self.get_full_key(&k);
+        self.store.set(full_key, v)
+    }
+
+    pub fn delete(&mut self, k: &[u8]) -> Option<Vec<u8>> {
+        let full_key = self.get_full_key(k);
+        self.store.delete(&full_key)
+    }
+
+    pub fn get_sub_store(&mut self, mut prefix: Vec<u8>) -> MutableSubStore {
+        let mut full_prefix = self.prefix.clone();
+        full_prefix.append(&mut prefix);
+
+        MutableSubStore {
+            store: self.store,
+            prefix: full_prefix,
+        }
+    }
+
+    /// Returns an iterator, in ascending key order, over all entries in this sub store. Keys
+    /// are returned with the sub store prefix removed.
+    pub fn iter(&self) -> impl Iterator<Item = (Vec<u8>, Vec<u8>)> + '_ {
+        self.prefix_iter(&[])
+    }
+
+    /// Returns an iterator, in ascending key order, over all entries in this sub store whose key
+    /// starts with the given prefix. Keys are returned with the sub store prefix removed.
+    pub fn prefix_iter(&self, prefix: &[u8]) -> impl Iterator<Item = (Vec<u8>, Vec<u8>)> + '_ {
+        let full_prefix = self.get_full_key(prefix);
+        let prefix_len = self.prefix.len();
+        self.store
+            .prefix_iterator(full_prefix)
+            .map(move |(k, v)| (k[prefix_len..].to_vec(), v.to_owned()))
+    }
+
+    fn get_full_key(&self, k: &[u8]) -> Vec<u8> {
+        let mut full_key = self.prefix.clone();
+        full_key.extend(k);
+        return full_key;
+    }
+
+    pub fn _get_prefix(&self) -> Vec<u8> {
+        return self.prefix.clone();
+    }
+}
+
+impl<'a> IntoIterator for ImmutableSubStore<'a> {
+    type Item = (Vec<u8>, Vec<u8>);
+    type IntoIter = Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        return Box::new(self.prefix_iter(&[]));
+    }
+}
+
+/// Read access to a key value store, implemented by all the sub store types so that typed
+/// collections can be used with any of them
+pub trait ReadKVStore {
+    fn get(&self, k: &[u8]) -> Option<Vec<u8>>;
+
+    /// Returns all entries, in ascending key order, whose key starts with the given prefix
+    fn prefix_iterator<'b>(
+        &'b self,
+        prefix: &[u8],
+    ) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'b>;
+}
+
+/// Write access to a key value store
+pub trait WriteKVStore: ReadKVStore {
+    fn set(&mut self, k: Vec<u8>, v: Vec<u8>);
+
+    fn delete(&mut self, k: &[u8]);
+}
+
+impl<'a> ReadKVStore for ImmutableSubStore<'a> {
+    fn get(&self, k: &[u8]) -> Option<Vec<u8>> {
+        ImmutableSubStore::get(self, k).cloned()
+    }
+
+    fn prefix_iterator<'b>(
+        &'b self,
+        prefix: &[u8],
+    ) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'b> {
+        Box::new(self.prefix_iter(prefix))
+    }
+}
+
+impl<'a> ReadKVStore for MutableSubStore<'a> {
+    fn get(&self, k: &[u8]) -> Option<Vec<u8>> {
+        MutableSubStore::get(self, k).cloned()
+    }
+
+    fn prefix_iterator<'b>(
+        &'b self,
+        prefix: &[u8],
+    ) -> Box<dyn Iterator<Item = (Vec<u8>, Vec<u8>)> + 'b> {
+        Box::new(self.prefix_iter(prefix))
+    }
+}
+
+impl<'a> WriteKVStore for MutableSubStore<'a> {
+    fn set(&mut self, k: Vec<u8>, v: Vec<u8>) {
+        MutableSubStore::set(self, k, v);
+    }
+
+    fn delete(&mut self, k: &[u8]) {
+        MutableSubStore::delete(self, k);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn sub_store_iterator_works() {
+        let mut store = MultiStore::new();
+        store.set(vec![0, 1], vec![1]);
+        store.set(vec![1, 3], vec![2]);
+
+        let sub_store = store.get_immutable_sub_store(vec![1]);
+
+        for (k, v) in sub_store {
+            assert_eq!(k, vec![3]);
+            assert_eq!(v, vec![2]);
+        }
+    }
+
+    #[test]
+    fn sub_store_iterator_is_ordered() {
+        let mut store = MultiStore::new();
+        store.set(vec![1, 9], vec![3]);
+        store.set(vec![2, 0], vec![4]);
+        store.set(vec![1, 2], vec![1]);
+        store.set(vec![1, 5], vec![2]);
+        store.set(vec![0, 5], vec![0]);
+
+        let sub_store = store.get_immutable_sub_store(vec![1]);
+        let items: Vec<(Vec<u8>, Vec<u8>)> = sub_store.into_iter().collect();
+
+        assert_eq!(
+            items,
+            vec![(vec![2], vec![1]), (vec![5], vec![2]), (vec![9], vec![3])]
+        );
+    }
+
+    #[test]
+    fn change_set_works() {
+        let mut store = MultiStore::new();
+        store.add_listening_store_key(b"bank".to_vec());
+
+        store.set(b"bank1".to_vec(), vec![1]);
+        store.set(b"acc1".to_vec(), vec![2]);
+
+        let mut branch = store.clone();
+        branch.get_mutable_sub_store(b"bank".to_vec()).delete(b"1");
+
+        assert_eq!(
+            store.take_change_set(),
+            vec![StoreKVPair {
+                store_key: "bank".into(),
+                delete: false,
+                key: b"1".to_vec(),
+                value: vec![1],
+            }]
+        );
+        assert_eq!(store.take_change_set(), vec![]);
+    }
+
+    #[test]
+    fn mutable_sub_store_delete_works() {
+        let mut store = MultiStore::new();
+        let mut sub_store = store.get_mutable_sub_store(vec![1]);
+        sub_store.set(vec![2], vec![3]);
+        assert_eq!(sub_store.delete(&[2]), Some(vec![3]));
+        assert_eq!(sub_store.get(&[2]), None);
+        assert_eq!(store.get(&[1, 2]), None);
+    }
+}
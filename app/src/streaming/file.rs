@@ -0,0 +1,185 @@
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use prost::Message;
+use tendermint_proto::abci::{
+    RequestBeginBlock, RequestCommit, RequestDeliverTx, RequestEndBlock, ResponseBeginBlock,
+    ResponseCommit, ResponseDeliverTx, ResponseEndBlock,
+};
+
+use crate::store::StoreKVPair;
+
+use super::ABCIListener;
+
+/// Writes each ABCI message to its own file in the write directory, in the same format as the
+/// cosmos SDK file streaming service. Each file contains the length prefixed (uvarint) request,
+/// followed by the length prefixed `StoreKVPair`s for each state change, followed by the length
+/// prefixed response. The files are named:
+///
+/// - `{prefix}-block-{N}-begin`
+/// - `{prefix}-block-{N}-tx-{M}`
+/// - `{prefix}-block-{N}-end`
+/// - `{prefix}-block-{N}-commit`
+///
+/// where the prefix, and the hyphen following it, are omitted if the prefix is empty.
+#[derive(Debug)]
+pub struct FileStreamingService {
+    write_dir: PathBuf,
+    file_prefix: String,
+    block: Mutex<BlockProgress>,
+}
+
+#[derive(Debug, Default)]
+struct BlockProgress {
+    height: i64,
+    tx_index: u64,
+}
+
+impl FileStreamingService {
+    pub fn new(write_dir: PathBuf, file_prefix: String) -> io::Result<Self> {
+        fs::create_dir_all(&write_dir)?;
+
+        Ok(FileStreamingService {
+            write_dir,
+            file_prefix,
+            block: Mutex::new(BlockProgress::default()),
+        })
+    }
+
+    fn file_path(&self, name: String) -> PathBuf {
+        if self.file_prefix.is_empty() {
+            self.write_dir.join(name)
+        } else {
+            self.write_dir
+                .join(format!("{}-{}", self.file_prefix, name))
+        }
+    }
+
+    fn write_file(
+        &self,
+        name: String,
+        req: &impl Message,
+        change_set: &[StoreKVPair],
+        res: &impl Message,
+    ) -> io::Result<()> {
+        let mut file = File::create(self.file_path(name))?;
+
+        file.write_all(&req.encode_length_delimited_to_vec())?;
+        for pair in change_set {
+            file.write_all(&pair.encode_length_delimited_to_vec())?;
+        }
+        file.write_all(&res.encode_length_delimited_to_vec())?;
+
+        file.sync_all()
+    }
+}
+
+impl ABCIListener for FileStreamingService {
+    fn listen_begin_block(
+        &self,
+        req: &RequestBeginBlock,
+        res: &ResponseBeginBlock,
+        change_set: &[StoreKVPair],
+    ) -> io::Result<()> {
+        let height = req.header.as_ref().map_or(0, |header| header.height);
+        *self.block.lock().expect("Mutex will not be poisoned") = BlockProgress {
+            height,
+            tx_index: 0,
+        };
+
+        self.write_file(format!("block-{}-begin", height), req, change_set, res)
+    }
+
+    fn listen_deliver_tx(
+        &self,
+        req: &RequestDeliverTx,
+        res: &ResponseDeliverTx,
+        change_set: &[StoreKVPair],
+    ) -> io::Result<()> {
+        let mut block = self.block.lock().expect("Mutex will not be poisoned");
+        let name = format!("block-{}-tx-{}", block.height, block.tx_index);
+        block.tx_index += 1;
+        drop(block);
+
+        self.write_file(name, req, change_set, res)
+    }
+
+    fn listen_end_block(
+        &self,
+        req: &RequestEndBlock,
+        res: &ResponseEndBlock,
+        change_set: &[StoreKVPair],
+    ) -> io::Result<()> {
+        self.write_file(format!("block-{}-end", req.height), req, change_set, res)
+    }
+
+    fn listen_commit(&self, res: &ResponseCommit, change_set: &[StoreKVPair]) -> io::Result<()> {
+        let height = self.block.lock().expect("Mutex will not be poisoned").height;
+
+        self.write_file(
+            format!("block-{}-commit", height),
+            &RequestCommit {},
+            change_set,
+            res,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use tendermint_proto::types::Header;
+
+    use super::*;
+
+    #[test]
+    fn file_streaming_service_works() {
+        let write_dir = std::env::temp_dir().join(format!(
+            "gaia-rs-file-streaming-test-{}",
+            std::process::id()
+        ));
+        let service = FileStreamingService::new(write_dir.clone(), "gaia".into()).unwrap();
+
+        let req = RequestBeginBlock {
+            header: Some(Header {
+                height: 7,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        service
+            .listen_begin_block(&req, &ResponseBeginBlock::default(), &[])
+            .unwrap();
+
+        let pair = StoreKVPair {
+            store_key: "bank".into(),
+            delete: false,
+            key: vec![1, 2],
+            value: vec![3],
+        };
+        let req = RequestDeliverTx {
+            tx: vec![9, 9].into(),
+        };
+        service
+            .listen_deliver_tx(&req, &ResponseDeliverTx::default(), &[pair.clone()])
+            .unwrap();
+
+        let contents = fs::read(write_dir.join("gaia-block-7-tx-0")).unwrap();
+        let mut buf = &contents[..];
+
+        assert_eq!(RequestDeliverTx::decode_length_delimited(&mut buf).unwrap(), req);
+        assert_eq!(StoreKVPair::decode_length_delimited(&mut buf).unwrap(), pair);
+        assert_eq!(
+            ResponseDeliverTx::decode_length_delimited(&mut buf).unwrap(),
+            ResponseDeliverTx::default()
+        );
+        assert!(buf.is_empty());
+        assert!(write_dir.join("gaia-block-7-begin").exists());
+
+        fs::remove_dir_all(write_dir).unwrap();
+    }
+}
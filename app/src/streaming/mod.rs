@@ -0,0 +1,42 @@
+//! State streaming as described in ADR-038: every state change, along with the ABCI request and
+//! response which caused it, is passed to the registered listeners.
+
+use std::{fmt::Debug, io};
+
+use tendermint_proto::abci::{
+    RequestBeginBlock, RequestDeliverTx, RequestEndBlock, ResponseBeginBlock, ResponseCommit,
+    ResponseDeliverTx, ResponseEndBlock,
+};
+
+use crate::store::StoreKVPair;
+
+mod file;
+
+pub use file::*;
+
+/// Receives the ABCI request and response of every state changing ABCI message along with the
+/// writes to listened stores made while processing the message
+pub trait ABCIListener: Debug + Send + Sync {
+    fn listen_begin_block(
+        &self,
+        req: &RequestBeginBlock,
+        res: &ResponseBeginBlock,
+        change_set: &[StoreKVPair],
+    ) -> io::Result<()>;
+
+    fn listen_deliver_tx(
+        &self,
+        req: &RequestDeliverTx,
+        res: &ResponseDeliverTx,
+        change_set: &[StoreKVPair],
+    ) -> io::Result<()>;
+
+    fn listen_end_block(
+        &self,
+        req: &RequestEndBlock,
+        res: &ResponseEndBlock,
+        change_set: &[StoreKVPair],
+    ) -> io::Result<()>;
+
+    fn listen_commit(&self, res: &ResponseCommit, change_set: &[StoreKVPair]) -> io::Result<()>;
+}
@@ -0,0 +1,55 @@
+use ibc_proto::google::protobuf::Any;
+use tendermint_proto::abci::Event;
+
+/// The gas wanted and used by a tx, this mirrors the cosmos SDK
+/// `cosmos.base.abci.v1beta1.GasInfo` message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GasInfo {
+    #[prost(uint64, tag = "1")]
+    pub gas_wanted: u64,
+    #[prost(uint64, tag = "2")]
+    pub gas_used: u64,
+}
+
+/// The result of executing a tx's messages, this mirrors the cosmos SDK
+/// `cosmos.base.abci.v1beta1.Result` message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TxResult {
+    #[prost(bytes = "vec", tag = "1")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+    #[prost(string, tag = "2")]
+    pub log: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "3")]
+    pub events: ::prost::alloc::vec::Vec<Event>,
+}
+
+/// The response to a simulated tx, this mirrors the cosmos SDK
+/// `cosmos.base.abci.v1beta1.SimulationResponse` message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SimulationResponse {
+    #[prost(message, optional, tag = "1")]
+    pub gas_info: ::core::option::Option<GasInfo>,
+    #[prost(message, optional, tag = "2")]
+    pub result: ::core::option::Option<TxResult>,
+}
+
+/// The type and encoded response of a message, this mirrors the cosmos SDK
+/// `cosmos.base.abci.v1beta1.MsgData` message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MsgData {
+    #[prost(string, tag = "1")]
+    pub msg_type: ::prost::alloc::string::String,
+    #[prost(bytes = "vec", tag = "2")]
+    pub data: ::prost::alloc::vec::Vec<u8>,
+}
+
+/// The responses of a tx's messages, in message order, this mirrors the cosmos SDK
+/// `cosmos.base.abci.v1beta1.TxMsgData` message. The deprecated `data` field is filled as it is
+/// by SDK v0.45, for clients which don't read `msg_responses`.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TxMsgData {
+    #[prost(message, repeated, tag = "1")]
+    pub data: ::prost::alloc::vec::Vec<MsgData>,
+    #[prost(message, repeated, tag = "2")]
+    pub msg_responses: ::prost::alloc::vec::Vec<Any>,
+}
@@ -1,46 +1,81 @@
+use std::fmt::{self, Display};
+
 use crate::error::AppError;
-use bech32::{self, FromBase32, Variant};
+use bech32::{self, FromBase32, ToBase32, Variant};
+use sha2::{Digest, Sha256};
 
 //TODO: finish testing
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct AccAddress(Vec<u8>);
 
+/// The operator address of a validator, which has the same bytes as the operator's account
+/// address
+#[derive(Debug, PartialEq, Clone)]
+pub struct ValAddress(Vec<u8>);
+
+/// The consensus address of a validator, derived from its consensus public key
+#[derive(Debug, PartialEq, Clone)]
+pub struct ConsAddress(Vec<u8>);
+
 const MAX_ADDR_LEN: u8 = 255;
 const BECH32_MAIN_PREFIX: &str = "cosmos";
-const BECH32_PREFIX_ACC_ADDR: &str = BECH32_MAIN_PREFIX;
+pub const BECH32_PREFIX_ACC_ADDR: &str = BECH32_MAIN_PREFIX;
+pub const BECH32_PREFIX_VAL_ADDR: &str = "cosmosvaloper";
+pub const BECH32_PREFIX_CONS_ADDR: &str = "cosmosvalcons";
+
+/// Decodes a bech32 address with the given prefix
+fn decode_bech32(address: &str, prefix: &str) -> Result<Vec<u8>, AppError> {
+    let (hrp, data, variant) = bech32::decode(address)?;
+
+    if hrp != prefix {
+        return Err(AppError::InvalidAddress(
+            format!(
+                "Address has wrong prefix, expected {}, found {}",
+                prefix, hrp
+            )
+            .into(),
+        ));
+    };
+
+    if let Variant::Bech32m = variant {
+        return Err(AppError::InvalidAddress(
+            "Incorrect variant, expected Bech32, found Bech32m".into(),
+        ));
+    }
 
-impl AccAddress {
-    pub fn from_bech32(address: &str) -> Result<Self, AppError> {
-        let (hrp, data, variant) = bech32::decode(address)?;
+    // It's unclear whether the conversion from base32 can ever fail. Since this method
+    // returns a Result there's no harm in returning an error here.
+    let address = Vec::<u8>::from_base32(&data)?;
+    check_len(&address)?;
 
-        if hrp != BECH32_PREFIX_ACC_ADDR {
-            return Err(AppError::InvalidAddress(
-                format!(
-                    "Address has wrong prefix, expected {}, found {}",
-                    BECH32_PREFIX_ACC_ADDR, hrp
-                )
-                .into(),
-            ));
-        };
+    Ok(address)
+}
 
-        if let Variant::Bech32m = variant {
-            return Err(AppError::InvalidAddress(
-                "Incorrect variant, expected Bech32, found Bech32m".into(),
-            ));
-        }
+fn encode_bech32(prefix: &str, address: &[u8], f: &mut fmt::Formatter) -> fmt::Result {
+    let address = bech32::encode(prefix, address.to_base32(), Variant::Bech32)
+        .expect("the prefix is a valid human readable part");
+    write!(f, "{}", address)
+}
 
-        // It's unclear whether the conversion from base32 can ever fail. Since this method
-        // returns a Result there's no harm in returning an error here.
-        let address = Vec::<u8>::from_base32(&data)?;
+fn check_len(v: &[u8]) -> Result<(), AppError> {
+    if v.len() > MAX_ADDR_LEN.into() {
+        return Err(AppError::InvalidAddress(
+            format!("Decoded address has length greater than {}", MAX_ADDR_LEN).into(),
+        ));
+    }
+    Ok(())
+}
 
-        if address.len() > MAX_ADDR_LEN.into() {
-            return Err(AppError::InvalidAddress(
-                format!("Decoded address has length greater than {}", MAX_ADDR_LEN).into(),
-            ));
-        }
+impl AccAddress {
+    pub fn from_bech32(address: &str) -> Result<Self, AppError> {
+        decode_bech32(address, BECH32_PREFIX_ACC_ADDR).map(Self)
+    }
 
-        return Ok(Self(address));
+    /// Returns the address of a module account, which as in the cosmos SDK is the first 20 bytes
+    /// of the SHA-256 hash of the module name
+    pub fn new_module_address(name: &str) -> Self {
+        AccAddress(Sha256::digest(name.as_bytes())[..20].to_vec())
     }
 
     pub fn len(&self) -> u8 {
@@ -48,15 +83,17 @@ impl AccAddress {
     }
 }
 
+impl Display for AccAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        encode_bech32(BECH32_PREFIX_ACC_ADDR, &self.0, f)
+    }
+}
+
 impl TryFrom<Vec<u8>> for AccAddress {
     type Error = AppError;
 
     fn try_from(v: Vec<u8>) -> Result<AccAddress, AppError> {
-        if v.len() > MAX_ADDR_LEN.into() {
-            return Err(AppError::InvalidAddress(
-                format!("Decoded address has length greater than {}", MAX_ADDR_LEN).into(),
-            ));
-        }
+        check_len(&v)?;
         Ok(AccAddress(v))
     }
 }
@@ -67,11 +104,83 @@ impl From<AccAddress> for Vec<u8> {
     }
 }
 
+impl From<ValAddress> for AccAddress {
+    fn from(v: ValAddress) -> AccAddress {
+        AccAddress(v.0)
+    }
+}
+
+impl ValAddress {
+    pub fn from_bech32(address: &str) -> Result<Self, AppError> {
+        decode_bech32(address, BECH32_PREFIX_VAL_ADDR).map(Self)
+    }
+
+    pub fn len(&self) -> u8 {
+        self.0.len().try_into().expect("MAX_ADDR_LEN is also a u8")
+    }
+}
+
+impl Display for ValAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        encode_bech32(BECH32_PREFIX_VAL_ADDR, &self.0, f)
+    }
+}
+
+impl TryFrom<Vec<u8>> for ValAddress {
+    type Error = AppError;
+
+    fn try_from(v: Vec<u8>) -> Result<ValAddress, AppError> {
+        check_len(&v)?;
+        Ok(ValAddress(v))
+    }
+}
+
+impl From<ValAddress> for Vec<u8> {
+    fn from(v: ValAddress) -> Vec<u8> {
+        v.0
+    }
+}
+
+impl From<AccAddress> for ValAddress {
+    fn from(v: AccAddress) -> ValAddress {
+        ValAddress(v.0)
+    }
+}
+
+impl ConsAddress {
+    pub fn from_bech32(address: &str) -> Result<Self, AppError> {
+        decode_bech32(address, BECH32_PREFIX_CONS_ADDR).map(Self)
+    }
+
+    pub fn len(&self) -> u8 {
+        self.0.len().try_into().expect("MAX_ADDR_LEN is also a u8")
+    }
+}
+
+impl Display for ConsAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        encode_bech32(BECH32_PREFIX_CONS_ADDR, &self.0, f)
+    }
+}
+
+impl TryFrom<Vec<u8>> for ConsAddress {
+    type Error = AppError;
+
+    fn try_from(v: Vec<u8>) -> Result<ConsAddress, AppError> {
+        check_len(&v)?;
+        Ok(ConsAddress(v))
+    }
+}
+
+impl From<ConsAddress> for Vec<u8> {
+    fn from(v: ConsAddress) -> Vec<u8> {
+        v.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
-    use bech32::ToBase32;
-
     use super::*;
 
     #[test]
@@ -85,6 +194,38 @@ mod tests {
         assert_eq!(expected_address, address);
     }
 
+    #[test]
+    fn module_address_works() {
+        let address = AccAddress::new_module_address("fee_collector");
+
+        assert_eq!(
+            address.to_string(),
+            "cosmos17xpfvakm2amg962yls6f84z3kell8c5lserqta"
+        );
+        assert_eq!(
+            AccAddress::from_bech32(&address.to_string()).unwrap(),
+            address
+        );
+    }
+
+    #[test]
+    fn val_address_works() {
+        let address =
+            AccAddress::from_bech32("cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux").unwrap();
+        let val_address = ValAddress::from(address.clone());
+
+        assert_eq!(
+            val_address.to_string(),
+            "cosmosvaloper1syavy2npfyt9tcncdtsdzf7kny9lh777yfrfs4"
+        );
+        assert_eq!(
+            ValAddress::from_bech32(&val_address.to_string()).unwrap(),
+            val_address
+        );
+        assert_eq!(AccAddress::from(val_address), address);
+        assert!(ValAddress::from_bech32("cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux").is_err());
+    }
+
     #[test]
     fn from_bech32_failure_checksum() {
         let input_address = vec![0x00, 0x01, 0x02];
@@ -0,0 +1,393 @@
+use std::str::FromStr;
+
+use cosmwasm_std::Uint256;
+use ibc_proto::cosmos::base::v1beta1::{Coin, DecCoin as ProtoDecCoin};
+use math::Decimal256;
+
+use crate::error::AppError;
+
+/// A coin with a decimal amount, e.g. a gas price
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecCoin {
+    pub denom: String,
+    pub amount: Decimal256,
+}
+
+impl FromStr for DecCoin {
+    type Err = AppError;
+
+    /// Parses a coin in the format used by the cosmos SDK, e.g. "0.025uatom"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let denom_start = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .ok_or(AppError::InvalidCoins(format!("missing denom: {}", s)))?;
+        let (amount, denom) = s.split_at(denom_start);
+
+        validate_denom(denom)?;
+
+        Ok(DecCoin {
+            denom: denom.into(),
+            amount: Decimal256::from_str(amount)
+                .map_err(|_| AppError::InvalidCoins(format!("invalid amount: {}", s)))?,
+        })
+    }
+}
+
+impl DecCoin {
+    /// Decodes a `cosmos.base.v1beta1.DecCoin`, whose amount is an integer number of 10^-18 units
+    pub fn from_proto(coin: ProtoDecCoin) -> Result<DecCoin, AppError> {
+        let amount = Decimal256::from_cosmos_proto_string(&coin.amount)
+            .map_err(|_| AppError::InvalidCoins(format!("invalid amount: {}", coin.amount)))?;
+
+        Ok(DecCoin {
+            denom: coin.denom,
+            amount,
+        })
+    }
+
+    pub fn to_proto(&self) -> ProtoDecCoin {
+        ProtoDecCoin {
+            denom: self.denom.clone(),
+            amount: self.amount.to_cosmos_proto_string(),
+        }
+    }
+}
+
+/// Formats coins as in the cosmos SDK, e.g. "10stake,34uatom"
+pub fn coins_to_string(coins: &[Coin]) -> String {
+    coins
+        .iter()
+        .map(|coin| format!("{}{}", coin.amount, coin.denom))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Returns the amount of the denom in the coins, which is zero if the denom is missing
+pub fn amount_of(coins: &[Coin], denom: &str) -> Uint256 {
+    coins
+        .iter()
+        .find(|coin| coin.denom == denom)
+        .map_or(Uint256::zero(), |coin| coin.amount)
+}
+
+/// Returns the sum of two sorted sets of coins, sorted by denom
+pub fn add_coins(a: &[Coin], b: &[Coin]) -> Vec<Coin> {
+    let mut sum: Vec<Coin> = a.to_vec();
+
+    for coin in b {
+        match sum.iter_mut().find(|sum_coin| sum_coin.denom == coin.denom) {
+            Some(sum_coin) => sum_coin.amount += coin.amount,
+            None => sum.push(coin.clone()),
+        }
+    }
+
+    sum.retain(|coin| !coin.amount.is_zero());
+    sum.sort_by(|a, b| a.denom.cmp(&b.denom));
+    sum
+}
+
+/// Returns the coins of a less the coins of b, where the amount of any denom which would be
+/// negative is zero. Zero amounts are removed.
+pub fn sub_coins_saturating(a: &[Coin], b: &[Coin]) -> Vec<Coin> {
+    a.iter()
+        .map(|coin| Coin {
+            denom: coin.denom.clone(),
+            amount: coin.amount.saturating_sub(amount_of(b, &coin.denom)),
+        })
+        .filter(|coin| !coin.amount.is_zero())
+        .collect()
+}
+
+/// Formats dec coins as in the cosmos SDK, which gives all 18 decimal places, e.g.
+/// "0.500000000000000000uatom"
+pub fn dec_coins_to_string(coins: &[DecCoin]) -> String {
+    let fractional = Decimal256::one().atomics();
+
+    coins
+        .iter()
+        .map(|coin| {
+            let atomics = coin.amount.atomics();
+            format!(
+                "{}.{:0>18}{}",
+                atomics / fractional,
+                (atomics % fractional).to_string(),
+                coin.denom
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Returns the coins as dec coins
+pub fn dec_coins_from_coins(coins: &[Coin]) -> Vec<DecCoin> {
+    coins
+        .iter()
+        .map(|coin| DecCoin {
+            denom: coin.denom.clone(),
+            amount: Decimal256::from_ratio(coin.amount, 1u8),
+        })
+        .collect()
+}
+
+/// Returns the amount of the denom in the dec coins, which is zero if the denom is missing
+pub fn amount_of_dec(coins: &[DecCoin], denom: &str) -> Decimal256 {
+    coins
+        .iter()
+        .find(|coin| coin.denom == denom)
+        .map_or(Decimal256::zero(), |coin| coin.amount)
+}
+
+/// Returns the sum of two sorted sets of dec coins, sorted by denom. Zero amounts are removed.
+pub fn add_dec_coins(a: &[DecCoin], b: &[DecCoin]) -> Vec<DecCoin> {
+    let mut sum: Vec<DecCoin> = a.to_vec();
+
+    for coin in b {
+        match sum.iter_mut().find(|sum_coin| sum_coin.denom == coin.denom) {
+            Some(sum_coin) => sum_coin.amount += coin.amount,
+            None => sum.push(coin.clone()),
+        }
+    }
+
+    sum.retain(|coin| !coin.amount.is_zero());
+    sum.sort_by(|a, b| a.denom.cmp(&b.denom));
+    sum
+}
+
+/// Returns the dec coins of a less the dec coins of b. As with the cosmos SDK `DecCoins.Sub` this
+/// panics if the amount of any denom would be negative. Zero amounts are removed.
+pub fn sub_dec_coins(a: &[DecCoin], b: &[DecCoin]) -> Vec<DecCoin> {
+    for coin in b {
+        if amount_of_dec(a, &coin.denom) < coin.amount {
+            panic!("negative coin amount")
+        }
+    }
+
+    a.iter()
+        .map(|coin| DecCoin {
+            denom: coin.denom.clone(),
+            amount: coin.amount - amount_of_dec(b, &coin.denom),
+        })
+        .filter(|coin| !coin.amount.is_zero())
+        .collect()
+}
+
+/// Returns the smaller amount of each denom of a in the two sets of dec coins, as with the cosmos
+/// SDK `DecCoins.Intersect`. Zero amounts are removed.
+pub fn intersect_dec_coins(a: &[DecCoin], b: &[DecCoin]) -> Vec<DecCoin> {
+    a.iter()
+        .map(|coin| DecCoin {
+            denom: coin.denom.clone(),
+            amount: coin.amount.min(amount_of_dec(b, &coin.denom)),
+        })
+        .filter(|coin| !coin.amount.is_zero())
+        .collect()
+}
+
+/// Splits the dec coins into whole coins and the remaining fractions, as with the cosmos SDK
+/// `DecCoins.TruncateDecimal`. Zero amounts are removed from both.
+pub fn truncate_dec_coins(coins: &[DecCoin]) -> (Vec<Coin>, Vec<DecCoin>) {
+    let mut truncated = vec![];
+    let mut change = vec![];
+
+    for coin in coins {
+        let amount = coin.amount * Uint256::one();
+        if !amount.is_zero() {
+            truncated.push(Coin {
+                denom: coin.denom.clone(),
+                amount,
+            });
+        }
+
+        let remainder = coin.amount - coin.amount.floor();
+        if !remainder.is_zero() {
+            change.push(DecCoin {
+                denom: coin.denom.clone(),
+                amount: remainder,
+            });
+        }
+    }
+
+    (truncated, change)
+}
+
+/// Parses a comma separated list of coins, e.g. "0.025uatom,0.1stake". The coins are returned
+/// sorted by denom and duplicate denoms are rejected.
+pub fn parse_dec_coins(s: &str) -> Result<Vec<DecCoin>, AppError> {
+    if s.trim().is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut coins = s
+        .split(',')
+        .map(DecCoin::from_str)
+        .collect::<Result<Vec<_>, _>>()?;
+    coins.sort_by(|a, b| a.denom.cmp(&b.denom));
+
+    if coins.windows(2).any(|pair| pair[0].denom == pair[1].denom) {
+        return Err(AppError::InvalidCoins(format!("duplicate denom: {}", s)));
+    }
+
+    Ok(coins)
+}
+
+/// Checks that the coins are valid as in the cosmos SDK `Coins.Validate`, they must be sorted by
+/// denom without duplicates, have valid denoms and have positive amounts
+pub fn validate_coins(coins: &[Coin]) -> Result<(), AppError> {
+    for coin in coins {
+        validate_denom(&coin.denom)?;
+
+        if coin.amount.is_zero() {
+            return Err(AppError::InvalidCoins(format!(
+                "coin {}{} amount is not positive",
+                coin.amount, coin.denom
+            )));
+        }
+    }
+
+    if coins.windows(2).any(|pair| pair[0].denom >= pair[1].denom) {
+        return Err(AppError::InvalidCoins(format!(
+            "coins are not sorted or contain duplicates: {}",
+            coins_to_string(coins)
+        )));
+    }
+
+    Ok(())
+}
+
+/// Denoms must match the cosmos SDK regex `[a-zA-Z][a-zA-Z0-9/:._-]{2,127}`
+fn validate_denom(denom: &str) -> Result<(), AppError> {
+    let mut chars = denom.chars();
+    let valid = denom.len() >= 3
+        && denom.len() <= 128
+        && chars.next().map_or(false, |c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c));
+
+    if valid {
+        Ok(())
+    } else {
+        Err(AppError::InvalidCoins(format!("invalid denom: {}", denom)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_dec_coins_works() {
+        let coins = parse_dec_coins("0.1stake,0.025uatom").unwrap();
+
+        assert_eq!(
+            coins,
+            vec![
+                DecCoin {
+                    denom: "stake".into(),
+                    amount: Decimal256::permille(100),
+                },
+                DecCoin {
+                    denom: "uatom".into(),
+                    amount: Decimal256::from_str("0.025").unwrap(),
+                },
+            ]
+        );
+        assert_eq!(parse_dec_coins("").unwrap(), vec![]);
+    }
+
+    #[test]
+    fn validate_coins_works() {
+        let coin = |amount: u32, denom: &str| Coin {
+            denom: denom.into(),
+            amount: amount.into(),
+        };
+
+        assert!(validate_coins(&[]).is_ok());
+        assert!(validate_coins(&[coin(1, "stake"), coin(2, "uatom")]).is_ok());
+        assert!(validate_coins(&[coin(2, "uatom"), coin(1, "stake")]).is_err());
+        assert!(validate_coins(&[coin(1, "uatom"), coin(2, "uatom")]).is_err());
+        assert!(validate_coins(&[coin(0, "uatom")]).is_err());
+        assert!(validate_coins(&[coin(1, "u")]).is_err());
+    }
+
+    #[test]
+    fn coin_arithmetic_works() {
+        let coin = |amount: u32, denom: &str| Coin {
+            denom: denom.into(),
+            amount: amount.into(),
+        };
+        let a = vec![coin(10, "stake"), coin(5, "uatom")];
+        let b = vec![coin(3, "stake"), coin(7, "uatom"), coin(1, "uosmo")];
+
+        assert_eq!(amount_of(&a, "uatom"), Uint256::from(5u32));
+        assert_eq!(amount_of(&a, "uosmo"), Uint256::zero());
+        assert_eq!(
+            add_coins(&a, &b),
+            vec![coin(13, "stake"), coin(12, "uatom"), coin(1, "uosmo")]
+        );
+        assert_eq!(sub_coins_saturating(&a, &b), vec![coin(7, "stake")]);
+        assert_eq!(
+            sub_coins_saturating(&b, &a),
+            vec![coin(2, "uatom"), coin(1, "uosmo")]
+        );
+    }
+
+    #[test]
+    fn dec_coin_arithmetic_works() {
+        let dec_coin = |amount: &str, denom: &str| DecCoin {
+            denom: denom.into(),
+            amount: Decimal256::from_str(amount).unwrap(),
+        };
+        let a = vec![dec_coin("10.5", "stake"), dec_coin("5", "uatom")];
+        let b = vec![dec_coin("3.25", "stake"), dec_coin("5", "uatom")];
+
+        assert_eq!(
+            add_dec_coins(&a, &b),
+            vec![dec_coin("13.75", "stake"), dec_coin("10", "uatom")]
+        );
+        assert_eq!(sub_dec_coins(&a, &b), vec![dec_coin("7.25", "stake")]);
+        assert_eq!(intersect_dec_coins(&a, &b), b);
+        assert_eq!(
+            intersect_dec_coins(&a, &[dec_coin("1", "uatom")]),
+            vec![dec_coin("1", "uatom")]
+        );
+
+        let (coins, change) =
+            truncate_dec_coins(&[dec_coin("0.5", "stake"), dec_coin("3.25", "uatom")]);
+        assert_eq!(
+            coins,
+            vec![Coin {
+                denom: "uatom".into(),
+                amount: 3u32.into(),
+            }]
+        );
+        assert_eq!(
+            change,
+            vec![dec_coin("0.5", "stake"), dec_coin("0.25", "uatom")]
+        );
+
+        assert_eq!(
+            dec_coins_to_string(&a),
+            "10.500000000000000000stake,5.000000000000000000uatom"
+        );
+        assert_eq!(DecCoin::from_proto(a[0].to_proto()).unwrap(), a[0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "negative coin amount")]
+    fn sub_dec_coins_panics_on_negative() {
+        let dec_coin = |amount: u32, denom: &str| DecCoin {
+            denom: denom.into(),
+            amount: Decimal256::from_ratio(amount, 1u8),
+        };
+
+        sub_dec_coins(&[dec_coin(1, "stake")], &[dec_coin(2, "stake")]);
+    }
+
+    #[test]
+    fn parse_dec_coins_failure() {
+        assert!(parse_dec_coins("0.025").is_err());
+        assert!(parse_dec_coins("1ab").is_err());
+        assert!(parse_dec_coins("1uatom,2uatom").is_err());
+    }
+}
@@ -1,12 +1,119 @@
-use crate::store::MultiStore;
+use std::sync::Arc;
+
+use tendermint_proto::{
+    abci::{ConsensusParams, Evidence, VoteInfo},
+    google::protobuf::Timestamp,
+    types::Header,
+};
+
+use crate::{
+    baseapp::PARAMS_STORE_PREFIX,
+    collections::Item,
+    store::{GasKVStore, MultiStore, KV_GAS_CONFIG},
+};
+
+use super::{DecCoin, EventManager, GasMeter, InfiniteGasMeter};
+
+const CONSENSUS_PARAMS_KEY: [u8; 1] = [0x00];
+
+/// The consensus params given by init_chain are kept in the state, rather than in memory, so that
+/// a node restored from a snapshot has them too
+const CONSENSUS_PARAMS: Item<ConsensusParams> = Item::new(&CONSENSUS_PARAMS_KEY);
 
 pub struct Context {
     pub multi_store: MultiStore,
+    header: Header,
+    header_hash: Vec<u8>,
+    chain_id: String,
+    tx_bytes: Vec<u8>,
+    vote_info: Vec<VoteInfo>,
+    byzantine_validators: Vec<Evidence>,
+    gas_meter: Arc<dyn GasMeter>,
+    block_gas_meter: Arc<dyn GasMeter>,
+    check_tx: bool,
+    recheck_tx: bool,
+    min_gas_prices: Vec<DecCoin>,
+    priority: i64,
+    event_manager: EventManager,
 }
 
 impl Context {
     pub fn new(multi_store: MultiStore) -> Self {
-        Context { multi_store }
+        Context {
+            multi_store,
+            header: Header::default(),
+            header_hash: vec![],
+            chain_id: "".into(),
+            tx_bytes: vec![],
+            vote_info: vec![],
+            byzantine_validators: vec![],
+            gas_meter: Arc::new(InfiniteGasMeter::new()),
+            block_gas_meter: Arc::new(InfiniteGasMeter::new()),
+            check_tx: false,
+            recheck_tx: false,
+            min_gas_prices: vec![],
+            priority: 0,
+            event_manager: EventManager::new(),
+        }
+    }
+
+    pub fn with_header(mut self, header: Header) -> Self {
+        self.header = header;
+        self
+    }
+
+    pub fn with_header_hash(mut self, header_hash: Vec<u8>) -> Self {
+        self.header_hash = header_hash;
+        self
+    }
+
+    pub fn with_chain_id(mut self, chain_id: String) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    pub fn with_tx_bytes(mut self, tx_bytes: Vec<u8>) -> Self {
+        self.tx_bytes = tx_bytes;
+        self
+    }
+
+    pub fn with_vote_info(mut self, vote_info: Vec<VoteInfo>) -> Self {
+        self.vote_info = vote_info;
+        self
+    }
+
+    pub fn with_byzantine_validators(mut self, byzantine_validators: Vec<Evidence>) -> Self {
+        self.byzantine_validators = byzantine_validators;
+        self
+    }
+
+    pub fn with_check_tx(mut self, check_tx: bool) -> Self {
+        self.check_tx = check_tx;
+        self
+    }
+
+    /// Recheck implies check, so setting recheck also sets check
+    pub fn with_recheck_tx(mut self, recheck_tx: bool) -> Self {
+        if recheck_tx {
+            self.check_tx = true;
+        }
+        self.recheck_tx = recheck_tx;
+        self
+    }
+
+    pub fn with_min_gas_prices(mut self, min_gas_prices: Vec<DecCoin>) -> Self {
+        self.min_gas_prices = min_gas_prices;
+        self
+    }
+
+    pub fn with_gas_meter(mut self, gas_meter: Arc<dyn GasMeter>) -> Self {
+        self.gas_meter = gas_meter;
+        self
+    }
+
+    pub fn with_block_gas_meter(mut self, block_gas_meter: Arc<dyn GasMeter>) -> Self {
+        self.block_gas_meter = block_gas_meter;
+        self
     }
 
     pub fn get_multi_store(&self) -> &MultiStore {
@@ -16,22 +123,134 @@ impl Context {
     pub fn get_mutable_store(&mut self) -> &mut MultiStore {
         return &mut self.multi_store;
     }
+
+    /// Returns a sub store with the given prefix which charges gas to the context gas meter
+    pub fn get_kv_store(&mut self, prefix: Vec<u8>) -> GasKVStore {
+        GasKVStore::new(
+            self.multi_store.get_mutable_sub_store(prefix),
+            self.gas_meter.as_ref(),
+            KV_GAS_CONFIG,
+        )
+    }
+
+    pub fn gas_meter(&self) -> &dyn GasMeter {
+        self.gas_meter.as_ref()
+    }
+
+    pub fn block_gas_meter(&self) -> &dyn GasMeter {
+        self.block_gas_meter.as_ref()
+    }
+
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    pub fn header_hash(&self) -> &[u8] {
+        &self.header_hash
+    }
+
+    pub fn chain_id(&self) -> &str {
+        &self.chain_id
+    }
+
+    pub fn block_height(&self) -> i64 {
+        self.header.height
+    }
+
+    pub fn block_time(&self) -> Timestamp {
+        self.header.time.clone().unwrap_or_default()
+    }
+
+    pub fn tx_bytes(&self) -> &[u8] {
+        &self.tx_bytes
+    }
+
+    pub fn vote_info(&self) -> &[VoteInfo] {
+        &self.vote_info
+    }
+
+    /// Returns the evidence of misbehaviour Tendermint included in the block
+    pub fn byzantine_validators(&self) -> &[Evidence] {
+        &self.byzantine_validators
+    }
+
+    pub fn is_check_tx(&self) -> bool {
+        self.check_tx
+    }
+
+    pub fn is_recheck_tx(&self) -> bool {
+        self.recheck_tx
+    }
+
+    pub fn min_gas_prices(&self) -> &[DecCoin] {
+        &self.min_gas_prices
+    }
+
+    /// Returns the mempool priority of the tx, as set by the ante handler
+    pub fn priority(&self) -> i64 {
+        self.priority
+    }
+
+    pub fn set_priority(&mut self, priority: i64) {
+        self.priority = priority;
+    }
+
+    /// Returns the consensus params set by init_chain. Every node must check the same params so,
+    /// as with a missing module param, missing consensus params are a fatal error.
+    pub fn consensus_params(&self) -> ConsensusParams {
+        read_consensus_params(&self.multi_store)
+    }
+
+    pub fn set_consensus_params(&mut self, consensus_params: &ConsensusParams) {
+        let mut params_store = self.get_kv_store(PARAMS_STORE_PREFIX.into());
+        CONSENSUS_PARAMS.set(&mut params_store, consensus_params);
+    }
+
+    pub fn event_manager(&mut self) -> &mut EventManager {
+        &mut self.event_manager
+    }
 }
 
-// type Context struct {
-// 	ctx           context.Context
-// 	ms            MultiStore
-// 	header        tmproto.Header
-// 	headerHash    tmbytes.HexBytes
-// 	chainID       string
-// 	txBytes       []byte
-// 	logger        log.Logger
-// 	voteInfo      []abci.VoteInfo
-// 	gasMeter      GasMeter
-// 	blockGasMeter GasMeter
-// 	checkTx       bool
-// 	recheckTx     bool // if recheckTx == true, then checkTx must also be true
-// 	minGasPrice   DecCoins
-// 	consParams    *abci.ConsensusParams
-// 	eventManager  *EventManager
-// }
+/// Returns the consensus params stored in the multistore by init_chain
+pub fn read_consensus_params(multi_store: &MultiStore) -> ConsensusParams {
+    let params_store = multi_store.get_immutable_sub_store(PARAMS_STORE_PREFIX.into());
+    CONSENSUS_PARAMS
+        .get(&params_store)
+        .expect("consensus params are set by init_chain")
+}
+
+/// A read only context for serving queries against a committed version of the store
+pub struct QueryContext<'a> {
+    multi_store: &'a MultiStore,
+    height: u32,
+    block_time: Timestamp,
+}
+
+impl<'a> QueryContext<'a> {
+    pub fn new(multi_store: &'a MultiStore, height: u32) -> Self {
+        QueryContext {
+            multi_store,
+            height,
+            block_time: Timestamp::default(),
+        }
+    }
+
+    /// Sets the block time seen by the query, which for historical queries is the time of the
+    /// block at their version
+    pub fn with_block_time(mut self, block_time: Timestamp) -> Self {
+        self.block_time = block_time;
+        self
+    }
+
+    pub fn get_multi_store(&self) -> &MultiStore {
+        self.multi_store
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn block_time(&self) -> Timestamp {
+        self.block_time.clone()
+    }
+}
@@ -0,0 +1,120 @@
+use serde::Serialize;
+use tendermint_proto::abci::{Event, EventAttribute};
+
+pub const EVENT_TYPE_MESSAGE: &str = "message";
+pub const EVENT_TYPE_TX: &str = "tx";
+pub const ATTRIBUTE_KEY_ACTION: &str = "action";
+pub const ATTRIBUTE_KEY_MODULE: &str = "module";
+pub const ATTRIBUTE_KEY_SENDER: &str = "sender";
+pub const ATTRIBUTE_KEY_AMOUNT: &str = "amount";
+pub const ATTRIBUTE_KEY_FEE: &str = "fee";
+pub const ATTRIBUTE_KEY_FEE_PAYER: &str = "fee_payer";
+pub const ATTRIBUTE_KEY_ACCOUNT_SEQUENCE: &str = "acc_seq";
+
+/// Returns an event with indexed attributes
+pub fn new_event(event_type: &str, attributes: Vec<(&str, String)>) -> Event {
+    Event {
+        r#type: event_type.into(),
+        attributes: attributes
+            .into_iter()
+            .map(|(key, value)| EventAttribute {
+                key: key.to_string().into(),
+                value: value.into(),
+                index: true,
+            })
+            .collect(),
+    }
+}
+
+/// A protobuf message emitted as an event. As in the cosmos SDK the event type is the fully
+/// qualified message name and there is one attribute per field, whose value is the JSON encoding
+/// of the field. Attributes are ordered by field name.
+pub trait TypedEvent: Serialize {
+    const EVENT_TYPE: &'static str;
+}
+
+/// Collects the events emitted while executing a transaction or block
+#[derive(Debug, Clone, Default)]
+pub struct EventManager {
+    events: Vec<Event>,
+}
+
+impl EventManager {
+    pub fn new() -> Self {
+        EventManager { events: vec![] }
+    }
+
+    pub fn emit_event(&mut self, event: Event) {
+        self.events.push(event);
+    }
+
+    pub fn emit_events(&mut self, events: Vec<Event>) {
+        self.events.extend(events);
+    }
+
+    pub fn emit_typed_event<T: TypedEvent>(&mut self, event: &T) {
+        let value = serde_json::to_value(event).expect("serialization of events won't fail");
+
+        let attributes = match value {
+            serde_json::Value::Object(fields) => fields
+                .into_iter()
+                .map(|(key, value)| EventAttribute {
+                    key: key.into(),
+                    value: value.to_string().into(),
+                    index: true,
+                })
+                .collect(),
+            _ => panic!("typed events must serialize to a JSON object"),
+        };
+
+        self.emit_event(Event {
+            r#type: T::EVENT_TYPE.into(),
+            attributes,
+        });
+    }
+
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Returns, and clears, the events emitted so far
+    pub fn take_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[derive(Serialize)]
+    struct EventUnjail {
+        validator: String,
+        power: u64,
+    }
+
+    impl TypedEvent for EventUnjail {
+        const EVENT_TYPE: &'static str = "test.v1.EventUnjail";
+    }
+
+    #[test]
+    fn emit_typed_event_works() {
+        let mut event_manager = EventManager::new();
+        event_manager.emit_typed_event(&EventUnjail {
+            validator: "val".into(),
+            power: 5,
+        });
+
+        let events = event_manager.take_events();
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].r#type, "test.v1.EventUnjail");
+        // attributes are ordered by key
+        assert_eq!(events[0].attributes[0].key, b"power".to_vec());
+        assert_eq!(events[0].attributes[0].value, b"5".to_vec());
+        assert_eq!(events[0].attributes[1].key, b"validator".to_vec());
+        assert_eq!(events[0].attributes[1].value, b"\"val\"".to_vec());
+        assert!(event_manager.events().is_empty());
+    }
+}
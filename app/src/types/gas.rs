@@ -0,0 +1,215 @@
+use std::{
+    fmt::Debug,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+pub type Gas = u64;
+
+/// Panic payload used when a gas meter's limit is exceeded. It is a panic (rather than a returned
+/// error) so that gas can be charged deep inside store operations, as in the cosmos SDK. Callers
+/// which execute transactions must catch it and convert it into an error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorOutOfGas {
+    pub descriptor: String,
+}
+
+/// Panic payload used when consuming gas would overflow a u64.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorGasOverflow {
+    pub descriptor: String,
+}
+
+pub trait GasMeter: Debug + Send + Sync {
+    fn gas_consumed(&self) -> Gas;
+    fn gas_consumed_to_limit(&self) -> Gas;
+    fn gas_remaining(&self) -> Gas;
+    fn limit(&self) -> Gas;
+    /// Panics with an `ErrorOutOfGas` if the limit is exceeded, or an `ErrorGasOverflow` if the
+    /// total overflows.
+    fn consume_gas(&self, amount: Gas, descriptor: &str);
+    /// Panics if more gas is refunded than has been consumed.
+    fn refund_gas(&self, amount: Gas, descriptor: &str);
+    fn is_past_limit(&self) -> bool;
+    fn is_out_of_gas(&self) -> bool;
+}
+
+/// A gas meter which panics once the consumed gas exceeds its limit
+#[derive(Debug)]
+pub struct BasicGasMeter {
+    limit: Gas,
+    consumed: AtomicU64,
+}
+
+impl BasicGasMeter {
+    pub fn new(limit: Gas) -> Self {
+        BasicGasMeter {
+            limit,
+            consumed: AtomicU64::new(0),
+        }
+    }
+}
+
+impl GasMeter for BasicGasMeter {
+    fn gas_consumed(&self) -> Gas {
+        self.consumed.load(Ordering::Relaxed)
+    }
+
+    fn gas_consumed_to_limit(&self) -> Gas {
+        if self.is_past_limit() {
+            return self.limit;
+        }
+        self.gas_consumed()
+    }
+
+    fn gas_remaining(&self) -> Gas {
+        self.limit.saturating_sub(self.gas_consumed())
+    }
+
+    fn limit(&self) -> Gas {
+        self.limit
+    }
+
+    fn consume_gas(&self, amount: Gas, descriptor: &str) {
+        let consumed = match self.gas_consumed().checked_add(amount) {
+            Some(consumed) => consumed,
+            None => {
+                self.consumed.store(Gas::MAX, Ordering::Relaxed);
+                std::panic::panic_any(ErrorGasOverflow {
+                    descriptor: descriptor.into(),
+                });
+            }
+        };
+
+        self.consumed.store(consumed, Ordering::Relaxed);
+
+        if consumed > self.limit {
+            std::panic::panic_any(ErrorOutOfGas {
+                descriptor: descriptor.into(),
+            });
+        }
+    }
+
+    fn refund_gas(&self, amount: Gas, descriptor: &str) {
+        let consumed = self.gas_consumed();
+        if consumed < amount {
+            panic!("{}: refunded more gas than consumed", descriptor);
+        }
+        self.consumed.store(consumed - amount, Ordering::Relaxed);
+    }
+
+    fn is_past_limit(&self) -> bool {
+        self.gas_consumed() > self.limit
+    }
+
+    fn is_out_of_gas(&self) -> bool {
+        self.gas_consumed() >= self.limit
+    }
+}
+
+/// A gas meter which tracks consumption but never runs out
+#[derive(Debug)]
+pub struct InfiniteGasMeter {
+    consumed: AtomicU64,
+}
+
+impl InfiniteGasMeter {
+    pub fn new() -> Self {
+        InfiniteGasMeter {
+            consumed: AtomicU64::new(0),
+        }
+    }
+}
+
+impl GasMeter for InfiniteGasMeter {
+    fn gas_consumed(&self) -> Gas {
+        self.consumed.load(Ordering::Relaxed)
+    }
+
+    fn gas_consumed_to_limit(&self) -> Gas {
+        self.gas_consumed()
+    }
+
+    fn gas_remaining(&self) -> Gas {
+        Gas::MAX
+    }
+
+    fn limit(&self) -> Gas {
+        Gas::MAX
+    }
+
+    fn consume_gas(&self, amount: Gas, descriptor: &str) {
+        match self.gas_consumed().checked_add(amount) {
+            Some(consumed) => self.consumed.store(consumed, Ordering::Relaxed),
+            None => std::panic::panic_any(ErrorGasOverflow {
+                descriptor: descriptor.into(),
+            }),
+        }
+    }
+
+    fn refund_gas(&self, amount: Gas, descriptor: &str) {
+        let consumed = self.gas_consumed();
+        if consumed < amount {
+            panic!("{}: refunded more gas than consumed", descriptor);
+        }
+        self.consumed.store(consumed - amount, Ordering::Relaxed);
+    }
+
+    fn is_past_limit(&self) -> bool {
+        false
+    }
+
+    fn is_out_of_gas(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::panic;
+
+    use super::*;
+
+    #[test]
+    fn basic_gas_meter_works() {
+        let meter = BasicGasMeter::new(100);
+        meter.consume_gas(40, "first");
+        meter.consume_gas(60, "second");
+
+        assert_eq!(meter.gas_consumed(), 100);
+        assert_eq!(meter.gas_remaining(), 0);
+        assert!(meter.is_out_of_gas());
+        assert!(!meter.is_past_limit());
+
+        meter.refund_gas(10, "refund");
+        assert_eq!(meter.gas_consumed(), 90);
+    }
+
+    #[test]
+    fn basic_gas_meter_out_of_gas() {
+        let meter = BasicGasMeter::new(100);
+
+        let err = panic::catch_unwind(|| meter.consume_gas(101, "WriteFlat")).unwrap_err();
+
+        assert_eq!(
+            err.downcast_ref::<ErrorOutOfGas>(),
+            Some(&ErrorOutOfGas {
+                descriptor: "WriteFlat".into()
+            })
+        );
+        assert_eq!(meter.gas_consumed(), 101);
+        assert_eq!(meter.gas_consumed_to_limit(), 100);
+        assert!(meter.is_past_limit());
+    }
+
+    #[test]
+    fn infinite_gas_meter_overflow() {
+        let meter = InfiniteGasMeter::new();
+        meter.consume_gas(Gas::MAX, "first");
+
+        let err = panic::catch_unwind(|| meter.consume_gas(1, "second")).unwrap_err();
+
+        assert!(err.downcast_ref::<ErrorGasOverflow>().is_some());
+        assert!(!meter.is_out_of_gas());
+    }
+}
@@ -1,5 +1,15 @@
+mod abci;
 mod address;
+mod coin;
 mod context;
+mod events;
+mod gas;
+mod pagination;
 
+pub use abci::*;
 pub use address::*;
+pub use coin::*;
 pub use context::*;
+pub use events::*;
+pub use gas::*;
+pub use pagination::*;
@@ -0,0 +1,154 @@
+use ibc_proto::cosmos::base::query::v1beta1::{PageRequest, PageResponse};
+
+use crate::error::{AppError, ERR_INVALID_REQUEST};
+
+/// The number of items in a page when the request doesn't give a limit, as in the cosmos SDK
+pub const DEFAULT_PAGE_LIMIT: u64 = 100;
+
+/// Returns the page of items given by the request along with the page response, as with the
+/// cosmos SDK `query.Paginate`. The items must be in ascending order of their keys, which are
+/// used as the `key` and `next_key` of key based pagination. As in the SDK the total is only
+/// counted for offset based pagination, and is counted by default when no limit is given.
+pub fn paginate<T>(
+    items: impl Iterator<Item = (Vec<u8>, T)>,
+    req: Option<PageRequest>,
+) -> Result<(Vec<T>, Option<PageResponse>), AppError> {
+    let mut req = req.unwrap_or_default();
+
+    if req.offset > 0 && !req.key.is_empty() {
+        return Err(ERR_INVALID_REQUEST.wrap("either offset or key is expected, got both"));
+    }
+
+    if req.limit == 0 {
+        req.limit = DEFAULT_PAGE_LIMIT;
+        req.count_total = true;
+    }
+
+    let res = if req.reverse {
+        let mut items: Vec<_> = items.collect();
+        items.reverse();
+        page(items.into_iter(), &req)
+    } else {
+        page(items, &req)
+    };
+
+    Ok(res)
+}
+
+fn page<T>(
+    items: impl Iterator<Item = (Vec<u8>, T)>,
+    req: &PageRequest,
+) -> (Vec<T>, Option<PageResponse>) {
+    let mut page = vec![];
+    let mut next_key = vec![];
+
+    if !req.key.is_empty() {
+        let items = items.skip_while(|(key, _)| {
+            if req.reverse {
+                key > &req.key
+            } else {
+                key < &req.key
+            }
+        });
+
+        for (key, item) in items {
+            if page.len() as u64 == req.limit {
+                next_key = key;
+                break;
+            }
+            page.push(item);
+        }
+
+        return (page, Some(PageResponse { next_key, total: 0 }));
+    }
+
+    let end = req.offset.saturating_add(req.limit);
+    let mut total = 0;
+
+    for (i, (key, item)) in (0u64..).zip(items) {
+        if i >= end {
+            if i == end {
+                next_key = key;
+            }
+            if !req.count_total {
+                break;
+            }
+        } else if i >= req.offset {
+            page.push(item);
+        }
+        total = i + 1;
+    }
+
+    let total = if req.count_total { total } else { 0 };
+    (page, Some(PageResponse { next_key, total }))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn items() -> impl Iterator<Item = (Vec<u8>, u8)> {
+        (0..10).map(|i| (vec![i], i))
+    }
+
+    #[test]
+    fn paginate_by_offset_works() {
+        let req = PageRequest {
+            offset: 2,
+            limit: 3,
+            count_total: true,
+            ..Default::default()
+        };
+        let (page, res) = paginate(items(), Some(req)).unwrap();
+
+        assert_eq!(page, vec![2, 3, 4]);
+        assert_eq!(
+            res,
+            Some(PageResponse {
+                next_key: vec![5],
+                total: 10
+            })
+        );
+
+        // all items are returned by default
+        let (page, res) = paginate(items(), None).unwrap();
+        assert_eq!(page.len(), 10);
+        assert_eq!(
+            res,
+            Some(PageResponse {
+                next_key: vec![],
+                total: 10
+            })
+        );
+    }
+
+    #[test]
+    fn paginate_by_key_works() {
+        let req = PageRequest {
+            key: vec![5],
+            limit: 3,
+            ..Default::default()
+        };
+        let (page, res) = paginate(items(), Some(req.clone())).unwrap();
+
+        assert_eq!(page, vec![5, 6, 7]);
+        assert_eq!(res.unwrap().next_key, vec![8]);
+
+        let req = PageRequest {
+            reverse: true,
+            ..req
+        };
+        let (page, res) = paginate(items(), Some(req)).unwrap();
+
+        assert_eq!(page, vec![5, 4, 3]);
+        assert_eq!(res.unwrap().next_key, vec![2]);
+
+        let req = PageRequest {
+            key: vec![5],
+            offset: 1,
+            ..Default::default()
+        };
+        assert!(paginate(items(), Some(req)).is_err());
+    }
+}
@@ -0,0 +1,489 @@
+use cosmwasm_std::Uint256;
+use ibc_proto::cosmos::{
+    auth::v1beta1::Params,
+    base::v1beta1::Coin,
+    tx::v1beta1::{mode_info::Sum, Fee, ModeInfo, SignDoc, SignerInfo, Tx, TxRaw},
+};
+use math::Decimal256;
+use prost::Message;
+
+use crate::{
+    crypto::{signed_indices, SignerPubKey},
+    error::{
+        AppError, ERR_INSUFFICIENT_FEE, ERR_INVALID_GAS_LIMIT, ERR_INVALID_PUB_KEY,
+        ERR_INVALID_REQUEST, ERR_MEMO_TOO_LARGE, ERR_NO_SIGNATURES, ERR_TOO_MANY_SIGNATURES,
+        ERR_TX_TIMEOUT_HEIGHT, ERR_UNAUTHORIZED, ERR_UNKNOWN_ADDRESS,
+        ERR_UNKNOWN_EXTENSION_OPTIONS, ERR_WRONG_SEQUENCE,
+    },
+    types::{
+        coins_to_string, new_event, validate_coins, AccAddress, Context, GasMeter,
+        ATTRIBUTE_KEY_ACCOUNT_SEQUENCE, ATTRIBUTE_KEY_FEE, ATTRIBUTE_KEY_FEE_PAYER, EVENT_TYPE_TX,
+    },
+    x::bank::Bank,
+};
+
+use super::{Auth, FEE_COLLECTOR_NAME};
+
+/// The maximum gas limit of a tx, as in the cosmos SDK this is the largest gas which can be
+/// represented as an i64
+const MAX_GAS_WANTED: u64 = i64::MAX as u64;
+
+/// The length of a compact secp256k1 signature
+const SECP256K1_SIGNATURE_LENGTH: usize = 64;
+
+/// Runs the checks, and state changes, which precede the execution of a tx's messages. When
+/// simulating, e.g. to estimate gas, the tx may not have been signed yet so signatures aren't
+/// verified. When rechecking the mempool after a commit the signatures have already been
+/// verified, so only the checks which depend on the state, e.g. fee payment and sequences, are
+/// rerun against the updated check state.
+pub fn ante_handler(
+    ctx: &mut Context,
+    tx: &Tx,
+    tx_raw: &TxRaw,
+    signers: &[AccAddress],
+    simulate: bool,
+) -> Result<(), AppError> {
+    let fee = tx
+        .auth_info
+        .as_ref()
+        .and_then(|auth_info| auth_info.fee.clone())
+        .unwrap_or_default();
+
+    let body = tx.body.clone().unwrap_or_default();
+    let params = Auth::get_params(ctx);
+
+    // as in the cosmos SDK the gas limit isn't enforced on genesis txs
+    if !simulate && ctx.block_height() > 0 && fee.gas_limit == 0 {
+        return Err(ERR_INVALID_GAS_LIMIT.wrap("must provide positive gas"));
+    }
+
+    // no extension options are supported, so any critical option is unknown
+    if !body.extension_options.is_empty() {
+        return Err(ERR_UNKNOWN_EXTENSION_OPTIONS.into());
+    }
+
+    if !ctx.is_recheck_tx() {
+        validate_basic(tx, tx_raw, signers, &fee)?;
+    }
+
+    if body.timeout_height > 0 && ctx.block_height() as u64 > body.timeout_height {
+        return Err(ERR_TX_TIMEOUT_HEIGHT.wrap(format!(
+            "block height: {}, timeout height: {}",
+            ctx.block_height(),
+            body.timeout_height
+        )));
+    }
+
+    // as in the cosmos SDK the memo is limited in bytes, despite the param's name
+    let memo_length = body.memo.len() as u64;
+    if memo_length > params.max_memo_characters {
+        return Err(ERR_MEMO_TOO_LARGE.wrap(format!(
+            "maximum number of characters is {} but received {} characters",
+            params.max_memo_characters, memo_length
+        )));
+    }
+
+    consume_tx_size_gas(ctx, tx_raw, params.tx_size_cost_per_byte, simulate);
+
+    if ctx.is_check_tx() && !simulate {
+        check_min_gas_prices(ctx, &fee)?;
+    }
+    ctx.set_priority(get_tx_priority(&fee));
+
+    deduct_fee(ctx, &fee, signers)?;
+
+    let signer_infos = tx
+        .auth_info
+        .as_ref()
+        .map(|auth_info| auth_info.signer_infos.as_slice())
+        .unwrap_or_default();
+
+    let pub_keys = set_pub_keys(ctx, signers, signer_infos, simulate)?;
+
+    let sig_count: usize = pub_keys
+        .iter()
+        .map(|pub_key| pub_key.as_ref().map_or(1, SignerPubKey::count_sub_keys))
+        .sum();
+    if sig_count as u64 > params.tx_sig_limit {
+        return Err(ERR_TOO_MANY_SIGNATURES.wrap(format!(
+            "signatures: {}, limit: {}",
+            sig_count, params.tx_sig_limit
+        )));
+    }
+
+    for (pub_key, signer_info) in pub_keys.iter().zip(signer_infos) {
+        consume_sig_gas(
+            ctx.gas_meter(),
+            pub_key.as_ref(),
+            signer_info.mode_info.as_ref(),
+            &params,
+        )?;
+    }
+
+    verify_signatures(ctx, tx_raw, signers, signer_infos, &pub_keys, simulate)?;
+
+    for signer in signers {
+        let mut account = Auth::get_account(ctx, signer).expect("signer accounts exist");
+        account.sequence += 1;
+        Auth::set_account(ctx, account);
+    }
+
+    Ok(())
+}
+
+/// Runs the stateless checks of the tx, as with the cosmos SDK `Tx.ValidateBasic`
+fn validate_basic(
+    tx: &Tx,
+    tx_raw: &TxRaw,
+    signers: &[AccAddress],
+    fee: &Fee,
+) -> Result<(), AppError> {
+    if fee.gas_limit > MAX_GAS_WANTED {
+        return Err(ERR_INVALID_REQUEST.wrap(format!(
+            "invalid gas supplied; {} > {}",
+            fee.gas_limit, MAX_GAS_WANTED
+        )));
+    }
+
+    let signatures = &tx_raw.signatures;
+    if signatures.is_empty() {
+        return Err(ERR_NO_SIGNATURES.into());
+    }
+
+    if signatures.len() != signers.len() {
+        return Err(ERR_UNAUTHORIZED.wrap(format!(
+            "wrong number of signers; expected {}, got {}",
+            signers.len(),
+            signatures.len()
+        )));
+    }
+
+    let signer_infos = tx
+        .auth_info
+        .as_ref()
+        .map_or(0, |auth_info| auth_info.signer_infos.len());
+    if signatures.len() != signer_infos {
+        return Err(ERR_UNAUTHORIZED.wrap(format!(
+            "invalid number of signer infos; expected {}, got {}",
+            signatures.len(),
+            signer_infos
+        )));
+    }
+
+    Ok(())
+}
+
+/// Charges gas for the size of the tx. When simulating an unsigned tx, empty signatures are
+/// charged as if they were secp256k1 signatures so that the estimate covers the signed tx.
+fn consume_tx_size_gas(ctx: &Context, tx_raw: &TxRaw, cost_per_byte: u64, simulate: bool) {
+    let mut tx_size = ctx.tx_bytes().len() as u64;
+
+    if simulate {
+        let empty_signatures = tx_raw
+            .signatures
+            .iter()
+            .filter(|sig| sig.is_empty())
+            .count();
+        tx_size += (empty_signatures * SECP256K1_SIGNATURE_LENGTH) as u64;
+    }
+
+    ctx.gas_meter()
+        .consume_gas(tx_size * cost_per_byte, "txSize");
+}
+
+/// Checks that the fee covers the node's minimum gas prices, if any, for the tx's gas limit.
+/// This is a local check, applied only to txs entering the mempool.
+fn check_min_gas_prices(ctx: &Context, fee: &Fee) -> Result<(), AppError> {
+    let gas = Decimal256::from_ratio(fee.gas_limit, 1u8);
+
+    let required_fees: Vec<Coin> = ctx
+        .min_gas_prices()
+        .iter()
+        .map(|price| Coin {
+            denom: price.denom.clone(),
+            amount: (price.amount * gas).ceil() * Uint256::one(),
+        })
+        .filter(|coin| !coin.amount.is_zero())
+        .collect();
+
+    if required_fees.is_empty() {
+        return Ok(());
+    }
+
+    // as with the cosmos SDK `IsAnyGTE`, paying the required fee in any one denom is enough
+    let sufficient = required_fees.iter().any(|required| {
+        fee.amount.iter().any(|coin| {
+            coin.denom == required.denom && !coin.amount.is_zero() && coin.amount >= required.amount
+        })
+    });
+
+    if sufficient {
+        Ok(())
+    } else {
+        Err(ERR_INSUFFICIENT_FEE.wrap(format!(
+            "insufficient fees; got: {} required: {}",
+            coins_to_string(&fee.amount),
+            coins_to_string(&required_fees)
+        )))
+    }
+}
+
+/// Returns the mempool priority of a tx, which as with the cosmos SDK `TxFeeChecker` is its
+/// lowest gas price, in whole units, across the denoms of its fee. As in the SDK a zero priority
+/// is replaced by the priority of the next denom.
+fn get_tx_priority(fee: &Fee) -> i64 {
+    if fee.gas_limit == 0 {
+        return 0;
+    }
+
+    let max_priority = Uint256::from(i64::MAX as u64);
+
+    fee.amount
+        .iter()
+        .map(|coin| {
+            let gas_price = (coin.amount / Uint256::from(fee.gas_limit)).min(max_priority);
+            gas_price
+                .to_string()
+                .parse::<i64>()
+                .expect("gas price is at most i64::MAX")
+        })
+        .fold(0, |priority, p| {
+            if priority == 0 || p < priority {
+                p
+            } else {
+                priority
+            }
+        })
+}
+
+/// Transfers the fee from the fee payer, which is the explicit payer if set or else the first
+/// signer, to the fee collector module account
+fn deduct_fee(ctx: &mut Context, fee: &Fee, signers: &[AccAddress]) -> Result<(), AppError> {
+    if !fee.granter.is_empty() {
+        return Err(ERR_INVALID_REQUEST.wrap("fee grants are not enabled"));
+    }
+
+    let payer = if fee.payer.is_empty() {
+        signers
+            .first()
+            .cloned()
+            .ok_or(ERR_UNKNOWN_ADDRESS.wrap("tx has no signers"))?
+    } else {
+        AccAddress::from_bech32(&fee.payer)?
+    };
+
+    if !Auth::has_account(ctx, &payer) {
+        return Err(
+            ERR_UNKNOWN_ADDRESS.wrap(format!("fee payer address: {} does not exist", payer))
+        );
+    }
+
+    if fee.amount.iter().any(|coin| !coin.amount.is_zero()) {
+        validate_coins(&fee.amount).map_err(|_| {
+            ERR_INSUFFICIENT_FEE.wrap(format!(
+                "invalid fee amount: {}",
+                coins_to_string(&fee.amount)
+            ))
+        })?;
+
+        Bank::send_coins_from_account_to_module(ctx, &payer, FEE_COLLECTOR_NAME, &fee.amount)?;
+    }
+
+    ctx.event_manager().emit_event(new_event(
+        EVENT_TYPE_TX,
+        vec![
+            (ATTRIBUTE_KEY_FEE, coins_to_string(&fee.amount)),
+            (ATTRIBUTE_KEY_FEE_PAYER, payer.to_string()),
+        ],
+    ));
+
+    Ok(())
+}
+
+/// Sets the public key of each signer's account, from the signer's signer info, if the account
+/// doesn't have one yet. Returns the public keys of the signers' accounts, a key is only missing
+/// when simulating a tx whose signer infos don't include the key.
+fn set_pub_keys(
+    ctx: &mut Context,
+    signers: &[AccAddress],
+    signer_infos: &[SignerInfo],
+    simulate: bool,
+) -> Result<Vec<Option<SignerPubKey>>, AppError> {
+    let mut pub_keys = vec![];
+    let mut events = vec![];
+
+    for (i, (signer, signer_info)) in signers.iter().zip(signer_infos).enumerate() {
+        let mut account = Auth::get_account(ctx, signer).ok_or_else(|| {
+            ERR_UNKNOWN_ADDRESS.wrap(format!("account {} does not exist", signer))
+        })?;
+
+        if let Some(public_key) = &signer_info.public_key {
+            if &SignerPubKey::from_any(public_key)?.address() != signer {
+                return Err(ERR_INVALID_PUB_KEY.wrap(format!(
+                    "pubKey does not match signer address {} with signer index: {}",
+                    signer, i
+                )));
+            }
+
+            if account.pub_key.is_none() {
+                account.pub_key = Some(public_key.clone());
+                Auth::set_account(ctx, account.clone());
+            }
+        }
+
+        let pub_key = match &account.pub_key {
+            Some(pub_key) => Some(SignerPubKey::from_any(pub_key)?),
+            None if simulate => None,
+            None => return Err(ERR_INVALID_PUB_KEY.wrap("pubkey on account is not set")),
+        };
+        pub_keys.push(pub_key);
+
+        events.push(new_event(
+            EVENT_TYPE_TX,
+            vec![(
+                ATTRIBUTE_KEY_ACCOUNT_SEQUENCE,
+                format!("{}/{}", signer, signer_info.sequence),
+            )],
+        ));
+    }
+
+    ctx.event_manager().emit_events(events);
+
+    Ok(pub_keys)
+}
+
+/// Charges the gas cost of verifying a signature, as with the cosmos SDK
+/// `DefaultSigVerificationGasConsumer`. A missing key, when simulating, is charged as a
+/// secp256k1 key. Each key of a multisig which signed is charged separately.
+fn consume_sig_gas(
+    gas_meter: &dyn GasMeter,
+    pub_key: Option<&SignerPubKey>,
+    mode_info: Option<&ModeInfo>,
+    params: &Params,
+) -> Result<(), AppError> {
+    match pub_key {
+        None | Some(SignerPubKey::Secp256k1(_)) => {
+            gas_meter.consume_gas(params.sig_verify_cost_secp256k1, "ante verify: secp256k1");
+            Ok(())
+        }
+        Some(SignerPubKey::Ed25519(_)) => {
+            gas_meter.consume_gas(params.sig_verify_cost_ed25519, "ante verify: ed25519");
+            Err(ERR_INVALID_PUB_KEY.wrap("ED25519 public keys are unsupported"))
+        }
+        Some(SignerPubKey::Multisig { public_keys, .. }) => {
+            let multi = match mode_info.and_then(|mode_info| mode_info.sum.as_ref()) {
+                Some(Sum::Multi(multi)) => multi,
+                _ => return Err(ERR_UNAUTHORIZED.wrap("expected multisig mode info")),
+            };
+
+            let signed = signed_indices(multi.bitarray.as_ref(), public_keys.len())?;
+            for (i, index) in signed.into_iter().enumerate() {
+                consume_sig_gas(
+                    gas_meter,
+                    Some(&public_keys[index]),
+                    multi.mode_infos.get(i),
+                    params,
+                )?;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Checks that each signer info's sequence is its account's sequence, which prevents replaying
+/// a tx, and verifies the signatures. Signatures aren't verified when simulating or rechecking.
+fn verify_signatures(
+    ctx: &mut Context,
+    tx_raw: &TxRaw,
+    signers: &[AccAddress],
+    signer_infos: &[SignerInfo],
+    pub_keys: &[Option<SignerPubKey>],
+    simulate: bool,
+) -> Result<(), AppError> {
+    for (i, (signer, signer_info)) in signers.iter().zip(signer_infos).enumerate() {
+        let account = Auth::get_account(ctx, signer).expect("signer accounts exist");
+
+        if signer_info.sequence != account.sequence {
+            return Err(ERR_WRONG_SEQUENCE.wrap(format!(
+                "account sequence mismatch, expected {}, got {}",
+                account.sequence, signer_info.sequence
+            )));
+        }
+
+        if simulate || ctx.is_recheck_tx() {
+            continue;
+        }
+
+        let pub_key = pub_keys[i]
+            .as_ref()
+            .expect("keys are only missing when simulating");
+        let sign_bytes = SignDoc {
+            body_bytes: tx_raw.body_bytes.clone(),
+            auth_info_bytes: tx_raw.auth_info_bytes.clone(),
+            chain_id: ctx.chain_id().to_string(),
+            account_number: account.account_number,
+        }
+        .encode_to_vec();
+
+        pub_key
+            .verify(
+                &sign_bytes,
+                &tx_raw.signatures[i],
+                signer_info.mode_info.as_ref(),
+            )
+            .map_err(|e| {
+                e.wrap(format!(
+                    "signature verification failed; please verify account number ({}) and chain-id ({})",
+                    account.account_number,
+                    ctx.chain_id()
+                ))
+            })?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn get_tx_priority_works() {
+        let fee = |amounts: Vec<(u64, &str)>, gas_limit| Fee {
+            amount: amounts
+                .into_iter()
+                .map(|(amount, denom)| Coin {
+                    denom: denom.into(),
+                    amount: amount.into(),
+                })
+                .collect(),
+            gas_limit,
+            ..Default::default()
+        };
+
+        assert_eq!(get_tx_priority(&fee(vec![(1000, "uatom")], 100)), 10);
+        // the lowest gas price across the fee denoms
+        assert_eq!(
+            get_tx_priority(&fee(vec![(1000, "stake"), (500, "uatom")], 100)),
+            5
+        );
+        assert_eq!(get_tx_priority(&fee(vec![(99, "uatom")], 100)), 0);
+        // a zero priority is replaced by the next denom's, but a later zero is still the lowest
+        assert_eq!(
+            get_tx_priority(&fee(vec![(99, "stake"), (1000, "uatom")], 100)),
+            10
+        );
+        assert_eq!(
+            get_tx_priority(&fee(vec![(1000, "stake"), (99, "uatom")], 100)),
+            0
+        );
+        assert_eq!(get_tx_priority(&fee(vec![], 100)), 0);
+        assert_eq!(get_tx_priority(&fee(vec![(1000, "uatom")], 0)), 0);
+        assert_eq!(
+            get_tx_priority(&fee(vec![(u64::MAX, "uatom")], 1)),
+            i64::MAX
+        );
+    }
+}
@@ -0,0 +1,786 @@
+use ibc_proto::{
+    cosmos::auth::v1beta1::{
+        BaseAccount, ModuleAccount, Params, QueryAccountRequest, QueryAccountResponse,
+        QueryAccountsRequest, QueryAccountsResponse, QueryParamsRequest, QueryParamsResponse,
+    },
+    google::protobuf::Any,
+};
+use prost::Message;
+use serde::Deserialize;
+
+use crate::{
+    baseapp::{QueryRouter, AUTH_STORE_PREFIX},
+    collections::{Item, Map, Sequence},
+    error::{
+        AppError, ERR_INVALID_REQUEST, ERR_INVALID_TYPE, ERR_JSON_MARSHAL, ERR_JSON_UNMARSHAL,
+        ERR_NOT_FOUND, ERR_UNKNOWN_REQUEST,
+    },
+    store::ReadKVStore,
+    types::{paginate, AccAddress, Context, QueryContext, BECH32_PREFIX_ACC_ADDR},
+    x::{
+        distribution, staking,
+        vesting::{
+            Vesting, VestingAccount, CONTINUOUS_VESTING_ACCOUNT_TYPE_URL,
+            DELAYED_VESTING_ACCOUNT_TYPE_URL,
+        },
+    },
+};
+
+mod ante;
+
+pub use ante::*;
+
+pub const MODULE_NAME: &str = "auth";
+
+/// The name of the module account which collects tx fees
+pub const FEE_COLLECTOR_NAME: &str = "fee_collector";
+
+// The permissions of module accounts
+pub const PERMISSION_BURNER: &str = "burner";
+pub const PERMISSION_STAKING: &str = "staking";
+
+/// The module accounts and their permissions, as with the `maccPerms` of a cosmos SDK app
+pub const MODULE_ACCOUNT_PERMISSIONS: &[(&str, &[&str])] = &[
+    (FEE_COLLECTOR_NAME, &[]),
+    (distribution::MODULE_NAME, &[]),
+    (
+        staking::BONDED_POOL_NAME,
+        &[PERMISSION_BURNER, PERMISSION_STAKING],
+    ),
+    (
+        staking::NOT_BONDED_POOL_NAME,
+        &[PERMISSION_BURNER, PERMISSION_STAKING],
+    ),
+];
+
+pub const QUERY_ACCOUNT: &str = "account";
+
+const BASE_ACCOUNT_TYPE_URL: &str = "/cosmos.auth.v1beta1.BaseAccount";
+const MODULE_ACCOUNT_TYPE_URL: &str = "/cosmos.auth.v1beta1.ModuleAccount";
+
+// The default params, the same as the cosmos SDK
+pub const DEFAULT_MAX_MEMO_CHARACTERS: u64 = 256;
+pub const DEFAULT_TX_SIG_LIMIT: u64 = 7;
+pub const DEFAULT_TX_SIZE_COST_PER_BYTE: u64 = 10;
+pub const DEFAULT_SIG_VERIFY_COST_ED25519: u64 = 590;
+pub const DEFAULT_SIG_VERIFY_COST_SECP256K1: u64 = 1000;
+
+const PARAMS_KEY: [u8; 1] = [0];
+const ACCOUNT_STORE_PREFIX: [u8; 1] = [1];
+const GLOBAL_ACCOUNT_NUMBER_KEY: [u8; 19] = [
+    103, 108, 111, 098, 097, 108, 065, 099, 099, 111, 117, 110, 116, 078, 117, 109, 098, 101, 114,
+]; // "globalAccountNumber"
+
+const PARAMS: Item<Params> = Item::new(&PARAMS_KEY);
+const ACCOUNTS: Map<AccAddress, BaseAccount> = Map::new(&ACCOUNT_STORE_PREFIX);
+// NOTE: The next available account number is what's stored in the KV store
+const GLOBAL_ACCOUNT_NUMBER: Sequence = Sequence::new(&GLOBAL_ACCOUNT_NUMBER_KEY);
+
+/// The accounts are `Any`s as in the cosmos SDK, each a base, module or vesting account
+pub struct GenesisState {
+    pub params: Params,
+    pub accounts: Vec<Any>,
+}
+
+/// Returns a genesis account for the address, which is a base account without a pubkey
+pub fn genesis_account(address: &AccAddress) -> Any {
+    Any {
+        type_url: BASE_ACCOUNT_TYPE_URL.into(),
+        value: BaseAccount {
+            address: address.to_string(),
+            ..Default::default()
+        }
+        .encode_to_vec(),
+    }
+}
+
+/// Returns the default params, which are the same as the cosmos SDK's
+pub fn default_params() -> Params {
+    Params {
+        max_memo_characters: DEFAULT_MAX_MEMO_CHARACTERS,
+        tx_sig_limit: DEFAULT_TX_SIG_LIMIT,
+        tx_size_cost_per_byte: DEFAULT_TX_SIZE_COST_PER_BYTE,
+        sig_verify_cost_ed25519: DEFAULT_SIG_VERIFY_COST_ED25519,
+        sig_verify_cost_secp256k1: DEFAULT_SIG_VERIFY_COST_SECP256K1,
+    }
+}
+
+/// Checks that every param is positive, as in the cosmos SDK
+fn validate_params(params: &Params) -> Result<(), String> {
+    let checks = [
+        ("max memo characters", params.max_memo_characters),
+        ("tx signature limit", params.tx_sig_limit),
+        ("tx size cost per byte", params.tx_size_cost_per_byte),
+        (
+            "ED25519 signature verification cost",
+            params.sig_verify_cost_ed25519,
+        ),
+        (
+            "SECP256K1 signature verification cost",
+            params.sig_verify_cost_secp256k1,
+        ),
+    ];
+
+    match checks.iter().find(|(_, value)| *value == 0) {
+        Some((name, value)) => Err(format!("invalid {}: {}", name, value)),
+        None => Ok(()),
+    }
+}
+
+/// The JSON params of the legacy account query
+#[derive(Deserialize)]
+struct QueryAccountParams {
+    address: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Auth {}
+
+impl Auth {
+    pub fn register_queries(router: QueryRouter) -> QueryRouter {
+        router
+            .register("/cosmos.auth.v1beta1.Query/Account", Auth::query_account)
+            .register("/cosmos.auth.v1beta1.Query/Accounts", Auth::query_accounts)
+            .register("/cosmos.auth.v1beta1.Query/Params", Auth::query_params)
+            .register(
+                "/cosmos.auth.v1beta1.Query/ModuleAccountByName",
+                Auth::query_module_account_by_name,
+            )
+            .register(
+                "/cosmos.auth.v1beta1.Query/Bech32Prefix",
+                Auth::bech32_prefix,
+            )
+            .register(
+                "/cosmos.auth.v1beta1.Query/AddressBytesToString",
+                Auth::address_bytes_to_string,
+            )
+            .register_legacy(MODULE_NAME, Auth::legacy_query)
+    }
+
+    /// Handles the legacy `custom/auth/account` query, returning the account in the amino JSON
+    /// format
+    pub fn legacy_query(
+        ctx: &QueryContext,
+        path: &[&str],
+        data: &[u8],
+    ) -> Result<Vec<u8>, AppError> {
+        if path.first() != Some(&QUERY_ACCOUNT) {
+            return Err(ERR_UNKNOWN_REQUEST.wrap(format!(
+                "unknown {} query endpoint: {}",
+                MODULE_NAME,
+                path.join("/")
+            )));
+        }
+
+        let params: QueryAccountParams =
+            serde_json::from_slice(data).map_err(|e| ERR_JSON_UNMARSHAL.wrap(e.to_string()))?;
+        let res = Auth::query_account(
+            ctx,
+            QueryAccountRequest {
+                address: params.address,
+            },
+        )?;
+
+        let account = res
+            .account
+            .and_then(|any| account_from_any(&any).ok())
+            .map(|(account, _)| account)
+            .unwrap_or_default();
+        let res = serde_json::json!({
+            "type": "cosmos-sdk/BaseAccount",
+            "value": {
+                "address": account.address,
+                "account_number": account.account_number.to_string(),
+                "sequence": account.sequence.to_string(),
+            },
+        });
+
+        serde_json::to_vec(&res).map_err(|e| ERR_JSON_MARSHAL.wrap(e.to_string()))
+    }
+
+    pub fn get_params(ctx: &mut Context) -> Params {
+        let auth_store = ctx.get_kv_store(AUTH_STORE_PREFIX.into());
+        read_params(&auth_store)
+    }
+
+    /// Sets the params and stores the genesis accounts, in account number order, followed by any
+    /// module accounts which aren't in the genesis. As in the cosmos SDK each genesis account is
+    /// given the next account number, keeping its sequence, pubkey and vesting state. Panics if
+    /// the params or accounts are invalid.
+    pub fn init_genesis(ctx: &mut Context, genesis: GenesisState) {
+        if let Err(e) = validate_params(&genesis.params) {
+            panic!("invalid auth genesis params: {}", e)
+        }
+
+        let mut auth_store = ctx.get_kv_store(AUTH_STORE_PREFIX.into());
+        PARAMS.set(&mut auth_store, &genesis.params);
+
+        let mut accounts: Vec<_> = genesis
+            .accounts
+            .iter()
+            .map(|any| {
+                account_from_any(any)
+                    .unwrap_or_else(|e| panic!("invalid auth genesis account: {}", e))
+            })
+            .collect();
+        accounts.sort_by_key(|(account, _)| account.account_number);
+
+        for (account, vesting_account) in accounts {
+            let address = AccAddress::from_bech32(&account.address)
+                .unwrap_or_else(|e| panic!("invalid auth genesis account: {}", e));
+            if let Some(vesting_account) = vesting_account {
+                Vesting::set_vesting_account(ctx, &address, &vesting_account);
+            }
+
+            let account = BaseAccount {
+                account_number: Auth::get_next_account_number(ctx),
+                ..account
+            };
+            Auth::set_account(ctx, account);
+        }
+
+        for (name, _) in MODULE_ACCOUNT_PERMISSIONS {
+            let address = AccAddress::new_module_address(name);
+            if !Auth::has_account(ctx, &address) {
+                let account = Auth::new_account_with_address(ctx, &address);
+                Auth::set_account(ctx, account);
+            }
+        }
+    }
+
+    /// Returns the params and all the accounts, including module and vesting accounts, in account
+    /// number order so that importing the genesis state gives the accounts the same numbers
+    pub fn export_genesis(ctx: &Context) -> GenesisState {
+        let auth_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(AUTH_STORE_PREFIX.into());
+
+        let mut accounts: Vec<BaseAccount> = ACCOUNTS
+            .iter(&auth_store)
+            .map(|(_, account)| account)
+            .collect();
+        accounts.sort_by_key(|account| account.account_number);
+
+        GenesisState {
+            params: read_params(&auth_store),
+            accounts: accounts
+                .into_iter()
+                .map(|account| account_to_any(&auth_store, account))
+                .collect(),
+        }
+    }
+
+    pub fn query_account(
+        ctx: &QueryContext,
+        req: QueryAccountRequest,
+    ) -> Result<QueryAccountResponse, AppError> {
+        let address = AccAddress::from_bech32(&req.address)?;
+        let auth_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(AUTH_STORE_PREFIX.into());
+        let account = ACCOUNTS.get(&auth_store, &address);
+
+        match account {
+            Some(account) => Ok(QueryAccountResponse {
+                account: Some(account_to_any(&auth_store, account)),
+            }),
+            None => Err(ERR_NOT_FOUND.wrap(format!("account {} not found", req.address))),
+        }
+    }
+
+    pub fn query_accounts(
+        ctx: &QueryContext,
+        req: QueryAccountsRequest,
+    ) -> Result<QueryAccountsResponse, AppError> {
+        let auth_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(AUTH_STORE_PREFIX.into());
+
+        let accounts = ACCOUNTS.iter(&auth_store).map(|(address, account)| {
+            (ACCOUNTS.key(&address), account_to_any(&auth_store, account))
+        });
+        let (accounts, pagination) = paginate(accounts, req.pagination)?;
+
+        Ok(QueryAccountsResponse {
+            accounts,
+            pagination,
+        })
+    }
+
+    pub fn query_params(
+        ctx: &QueryContext,
+        _req: QueryParamsRequest,
+    ) -> Result<QueryParamsResponse, AppError> {
+        let auth_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(AUTH_STORE_PREFIX.into());
+
+        Ok(QueryParamsResponse {
+            params: Some(read_params(&auth_store)),
+        })
+    }
+
+    pub fn query_module_account_by_name(
+        ctx: &QueryContext,
+        req: QueryModuleAccountByNameRequest,
+    ) -> Result<QueryModuleAccountByNameResponse, AppError> {
+        if req.name.is_empty() {
+            return Err(ERR_INVALID_REQUEST.wrap("module name is empty"));
+        }
+
+        let auth_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(AUTH_STORE_PREFIX.into());
+        let address = AccAddress::new_module_address(&req.name);
+
+        match ACCOUNTS.get(&auth_store, &address) {
+            Some(account) if module_account(&account.address).is_some() => {
+                Ok(QueryModuleAccountByNameResponse {
+                    account: Some(account_to_any(&auth_store, account)),
+                })
+            }
+            _ => Err(ERR_NOT_FOUND.wrap(format!("account {} not found", req.name))),
+        }
+    }
+
+    pub fn bech32_prefix(
+        _ctx: &QueryContext,
+        _req: Bech32PrefixRequest,
+    ) -> Result<Bech32PrefixResponse, AppError> {
+        Ok(Bech32PrefixResponse {
+            bech32_prefix: BECH32_PREFIX_ACC_ADDR.into(),
+        })
+    }
+
+    pub fn address_bytes_to_string(
+        _ctx: &QueryContext,
+        req: AddressBytesToStringRequest,
+    ) -> Result<AddressBytesToStringResponse, AppError> {
+        if req.address_bytes.is_empty() {
+            return Err(ERR_INVALID_REQUEST.wrap("empty address bytes is not allowed"));
+        }
+
+        Ok(AddressBytesToStringResponse {
+            address_string: AccAddress::try_from(req.address_bytes)?.to_string(),
+        })
+    }
+
+    fn get_next_account_number(ctx: &mut Context) -> u64 {
+        let mut auth_store = ctx.get_kv_store(AUTH_STORE_PREFIX.into());
+        GLOBAL_ACCOUNT_NUMBER.next(&mut auth_store)
+    }
+
+    /// Returns a new account with the next account number, the account isn't stored
+    pub fn new_account_with_address(ctx: &mut Context, addr: &AccAddress) -> BaseAccount {
+        BaseAccount {
+            address: addr.to_string(),
+            pub_key: None,
+            account_number: Auth::get_next_account_number(ctx),
+            sequence: 0,
+        }
+    }
+
+    /// Returns the account of the address, if it exists
+    pub fn get_account(ctx: &mut Context, addr: &AccAddress) -> Option<BaseAccount> {
+        let auth_store = ctx.get_kv_store(AUTH_STORE_PREFIX.into());
+        ACCOUNTS.get(&auth_store, addr)
+    }
+
+    /// Returns whether the address has an account, charging the flat has cost as the cosmos SDK
+    /// does
+    pub fn has_account(ctx: &mut Context, addr: &AccAddress) -> bool {
+        let auth_store = ctx.get_kv_store(AUTH_STORE_PREFIX.into());
+        auth_store.has(&ACCOUNTS.key(addr))
+    }
+
+    /// Stores the account under its address, which must be a valid address
+    pub fn set_account(ctx: &mut Context, acct: BaseAccount) {
+        let addr = AccAddress::from_bech32(&acct.address).expect("account addresses are valid");
+        let mut auth_store = ctx.get_kv_store(AUTH_STORE_PREFIX.into());
+        ACCOUNTS.set(&mut auth_store, &addr, &acct);
+    }
+}
+
+fn read_params<S: ReadKVStore + ?Sized>(auth_store: &S) -> Params {
+    PARAMS
+        .get(auth_store)
+        .expect("auth params are set in init_genesis")
+}
+
+/// Returns the module name and permissions of a module account's address
+fn module_account(address: &str) -> Option<&'static (&'static str, &'static [&'static str])> {
+    MODULE_ACCOUNT_PERMISSIONS
+        .iter()
+        .find(|(name, _)| AccAddress::new_module_address(name).to_string() == address)
+}
+
+/// Returns the account as an `Any`, which holds a `ModuleAccount` for module accounts and a
+/// vesting account for vesting accounts
+fn account_to_any<S: ReadKVStore + ?Sized>(auth_store: &S, account: BaseAccount) -> Any {
+    let address = AccAddress::from_bech32(&account.address).expect("account addresses are valid");
+    if let Some(vesting_account) = Vesting::read_vesting_account(auth_store, &address) {
+        return vesting_account.to_any(Some(account));
+    }
+
+    match module_account(&account.address) {
+        Some((name, permissions)) => Any {
+            type_url: MODULE_ACCOUNT_TYPE_URL.into(),
+            value: ModuleAccount {
+                base_account: Some(account),
+                name: name.to_string(),
+                permissions: permissions.iter().map(|p| p.to_string()).collect(),
+            }
+            .encode_to_vec(),
+        },
+        None => Any {
+            type_url: BASE_ACCOUNT_TYPE_URL.into(),
+            value: account.encode_to_vec(),
+        },
+    }
+}
+
+/// Decodes a base, module or vesting account, returning the base account along with the vesting
+/// state of a vesting account
+fn account_from_any(any: &Any) -> Result<(BaseAccount, Option<VestingAccount>), AppError> {
+    let decode_err = |e: prost::DecodeError| ERR_INVALID_REQUEST.wrap(e.to_string());
+
+    match any.type_url.as_str() {
+        BASE_ACCOUNT_TYPE_URL => Ok((
+            BaseAccount::decode(&any.value[..]).map_err(decode_err)?,
+            None,
+        )),
+        MODULE_ACCOUNT_TYPE_URL => {
+            let account = ModuleAccount::decode(&any.value[..]).map_err(decode_err)?;
+            Ok((account.base_account.unwrap_or_default(), None))
+        }
+        CONTINUOUS_VESTING_ACCOUNT_TYPE_URL | DELAYED_VESTING_ACCOUNT_TYPE_URL => {
+            let (vesting_account, account) = VestingAccount::from_any(any)?;
+            Ok((account.unwrap_or_default(), Some(vesting_account)))
+        }
+        _ => Err(ERR_INVALID_TYPE.wrap(format!("unknown account type: {}", any.type_url))),
+    }
+}
+
+/// This mirrors the cosmos SDK `cosmos.auth.v1beta1.QueryModuleAccountByNameRequest` message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryModuleAccountByNameRequest {
+    #[prost(string, tag = "1")]
+    pub name: String,
+}
+
+/// This mirrors the cosmos SDK `cosmos.auth.v1beta1.QueryModuleAccountByNameResponse` message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryModuleAccountByNameResponse {
+    #[prost(message, optional, tag = "1")]
+    pub account: Option<Any>,
+}
+
+/// This mirrors the cosmos SDK `cosmos.auth.v1beta1.Bech32PrefixRequest` message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Bech32PrefixRequest {}
+
+/// This mirrors the cosmos SDK `cosmos.auth.v1beta1.Bech32PrefixResponse` message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Bech32PrefixResponse {
+    #[prost(string, tag = "1")]
+    pub bech32_prefix: String,
+}
+
+/// This mirrors the cosmos SDK `cosmos.auth.v1beta1.AddressBytesToStringRequest` message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AddressBytesToStringRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub address_bytes: Vec<u8>,
+}
+
+/// This mirrors the cosmos SDK `cosmos.auth.v1beta1.AddressBytesToStringResponse` message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AddressBytesToStringResponse {
+    #[prost(string, tag = "1")]
+    pub address_string: String,
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+
+    use ibc_proto::cosmos::base::v1beta1::Coin;
+
+    use super::*;
+    use crate::{
+        store::{MultiStore, KV_GAS_CONFIG},
+        types::{GasMeter, InfiniteGasMeter},
+        x::vesting::VestingSchedule,
+    };
+
+    #[test]
+    fn address_store_key_works() {
+        let expected = vec![1, 97, 98, 99, 100];
+        let acc_address = AccAddress::try_from(vec![97, 98, 99, 100]).unwrap();
+        let res = ACCOUNTS.key(&acc_address);
+
+        assert_eq!(expected, res);
+    }
+
+    #[test]
+    fn query_account_on_unseen_account_works() {
+        let req = QueryAccountRequest {
+            address: "cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux".into(),
+        };
+
+        let store = MultiStore::new();
+        let ctx = QueryContext::new(&store, 0);
+        let err = Auth::query_account(&ctx, req).unwrap_err();
+
+        assert_eq!(err.code(), ERR_NOT_FOUND.code);
+        assert_eq!(
+            err.to_string(),
+            "account cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux not found: not found"
+        );
+    }
+
+    #[test]
+    fn account_and_params_reads_charge_gas() {
+        let mut ctx = Context::new(MultiStore::new());
+        PARAMS.set(
+            &mut ctx.get_kv_store(AUTH_STORE_PREFIX.into()),
+            &default_params(),
+        );
+        let gas_meter = Arc::new(InfiniteGasMeter::new());
+        let mut ctx = ctx.with_gas_meter(gas_meter.clone());
+        let addr =
+            AccAddress::from_bech32("cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux").unwrap();
+
+        assert!(!Auth::has_account(&mut ctx, &addr));
+        assert_eq!(gas_meter.gas_consumed(), KV_GAS_CONFIG.has_cost);
+
+        Auth::get_params(&mut ctx);
+        assert!(gas_meter.gas_consumed() > KV_GAS_CONFIG.has_cost + KV_GAS_CONFIG.read_cost_flat);
+    }
+
+    #[test]
+    fn get_next_account_number_init_works() {
+        let expected = 0;
+        let store = MultiStore::new();
+        let mut ctx = Context::new(store);
+        let acct_num = Auth::get_next_account_number(&mut ctx);
+
+        assert_eq!(expected, acct_num);
+    }
+
+    #[test]
+    fn get_next_account_number_works() {
+        let expected = 5038438478387;
+        let mut store = MultiStore::new();
+        let mut auth_store = store.get_mutable_sub_store(AUTH_STORE_PREFIX.into());
+
+        auth_store.set(
+            GLOBAL_ACCOUNT_NUMBER_KEY.clone().into(),
+            expected.encode_to_vec(),
+        );
+
+        let mut ctx = Context::new(store);
+        let acct_num = Auth::get_next_account_number(&mut ctx);
+
+        assert_eq!(expected, acct_num);
+
+        // check account number is being incremented
+        let acct_num = Auth::get_next_account_number(&mut ctx);
+        assert_eq!(expected + 1, acct_num);
+    }
+
+    /// Returns a context with ADDRESS as the only genesis account
+    fn genesis_context() -> Context {
+        let mut ctx = Context::new(MultiStore::new());
+        Auth::init_genesis(
+            &mut ctx,
+            GenesisState {
+                params: Params {
+                    tx_sig_limit: 3,
+                    ..default_params()
+                },
+                accounts: vec![genesis_account(
+                    &AccAddress::from_bech32("cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux")
+                        .unwrap(),
+                )],
+            },
+        );
+        ctx
+    }
+
+    #[test]
+    fn genesis_works() {
+        let mut ctx = genesis_context();
+        let genesis = Auth::export_genesis(&ctx);
+
+        assert_eq!(Auth::get_params(&mut ctx).tx_sig_limit, 3);
+        assert_eq!(genesis.params, Auth::get_params(&mut ctx));
+        // the genesis account is followed by the module accounts created by init_genesis
+        let types: Vec<&str> = genesis
+            .accounts
+            .iter()
+            .map(|any| any.type_url.as_str())
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                BASE_ACCOUNT_TYPE_URL,
+                MODULE_ACCOUNT_TYPE_URL,
+                MODULE_ACCOUNT_TYPE_URL,
+                MODULE_ACCOUNT_TYPE_URL,
+                MODULE_ACCOUNT_TYPE_URL
+            ]
+        );
+
+        let ctx = QueryContext::new(ctx.get_multi_store(), 0);
+        let res = Auth::query_params(&ctx, QueryParamsRequest {}).unwrap();
+        assert_eq!(res.params, Some(genesis.params));
+    }
+
+    #[test]
+    fn genesis_round_trip_works() {
+        let mut ctx = genesis_context();
+
+        // an account with a pubkey and sequence, and a vesting account
+        let address =
+            AccAddress::from_bech32("cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux").unwrap();
+        let account = BaseAccount {
+            pub_key: Some(Any {
+                type_url: "/cosmos.crypto.secp256k1.PubKey".into(),
+                value: vec![1, 2, 3],
+            }),
+            sequence: 4,
+            ..Auth::get_account(&mut ctx, &address).unwrap()
+        };
+        Auth::set_account(&mut ctx, account);
+
+        let vesting_address =
+            AccAddress::from_bech32("cosmos1qyqszqgpqyqszqgpqyqszqgpqyqszqgpjnp7du").unwrap();
+        let account = Auth::new_account_with_address(&mut ctx, &vesting_address);
+        Auth::set_account(&mut ctx, account);
+        Vesting::set_vesting_account(
+            &mut ctx,
+            &vesting_address,
+            &VestingAccount {
+                original_vesting: vec![Coin {
+                    denom: "uatom".into(),
+                    amount: 100u32.into(),
+                }],
+                delegated_free: vec![],
+                delegated_vesting: vec![],
+                end_time: 200,
+                schedule: VestingSchedule::Delayed,
+            },
+        );
+
+        let genesis = Auth::export_genesis(&ctx);
+        assert_eq!(genesis.accounts.len(), 6);
+        assert_eq!(
+            genesis.accounts[5].type_url,
+            DELAYED_VESTING_ACCOUNT_TYPE_URL
+        );
+
+        let mut imported = Context::new(MultiStore::new());
+        Auth::init_genesis(
+            &mut imported,
+            GenesisState {
+                params: genesis.params.clone(),
+                accounts: genesis.accounts.clone(),
+            },
+        );
+
+        let exported = Auth::export_genesis(&imported);
+        assert_eq!(exported.params, genesis.params);
+        assert_eq!(exported.accounts, genesis.accounts);
+        assert_eq!(
+            Auth::get_account(&mut imported, &address),
+            Auth::get_account(&mut ctx, &address)
+        );
+        assert_eq!(
+            Vesting::get_vesting_account(&mut imported, &vesting_address),
+            Vesting::get_vesting_account(&mut ctx, &vesting_address)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid auth genesis params: invalid tx signature limit: 0")]
+    fn init_genesis_validates_params() {
+        Auth::init_genesis(
+            &mut Context::new(MultiStore::new()),
+            GenesisState {
+                params: Params {
+                    tx_sig_limit: 0,
+                    ..default_params()
+                },
+                accounts: vec![],
+            },
+        );
+    }
+
+    #[test]
+    fn query_accounts_works() {
+        let ctx = genesis_context();
+        let ctx = QueryContext::new(ctx.get_multi_store(), 0);
+
+        let res = Auth::query_accounts(&ctx, QueryAccountsRequest { pagination: None }).unwrap();
+        assert_eq!(res.accounts.len(), 5);
+        assert_eq!(res.pagination.unwrap().total, 5);
+
+        let res = Auth::query_module_account_by_name(
+            &ctx,
+            QueryModuleAccountByNameRequest {
+                name: FEE_COLLECTOR_NAME.into(),
+            },
+        )
+        .unwrap();
+        let account = res.account.unwrap();
+        assert_eq!(account.type_url, "/cosmos.auth.v1beta1.ModuleAccount");
+        let account = ModuleAccount::decode(&account.value[..]).unwrap();
+        assert_eq!(account.name, FEE_COLLECTOR_NAME);
+        assert_eq!(
+            account.base_account.unwrap().address,
+            "cosmos17xpfvakm2amg962yls6f84z3kell8c5lserqta"
+        );
+
+        let err = Auth::query_module_account_by_name(
+            &ctx,
+            QueryModuleAccountByNameRequest {
+                name: "unknown".into(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "account unknown not found: not found");
+    }
+
+    #[test]
+    fn address_queries_work() {
+        let store = MultiStore::new();
+        let ctx = QueryContext::new(&store, 0);
+
+        let res = Auth::bech32_prefix(&ctx, Bech32PrefixRequest {}).unwrap();
+        assert_eq!(res.bech32_prefix, "cosmos");
+
+        let address =
+            AccAddress::from_bech32("cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux").unwrap();
+        let res = Auth::address_bytes_to_string(
+            &ctx,
+            AddressBytesToStringRequest {
+                address_bytes: address.into(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.address_string,
+            "cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux"
+        );
+
+        let res = Auth::address_bytes_to_string(
+            &ctx,
+            AddressBytesToStringRequest {
+                address_bytes: vec![],
+            },
+        );
+        assert!(res.is_err());
+    }
+}
@@ -1,23 +1,63 @@
-use std::str::FromStr;
-
 use cosmwasm_std::Uint256;
 use ibc_proto::cosmos::{
     bank::v1beta1::{
-        MsgSend, QueryAllBalancesRequest, QueryAllBalancesResponse, QueryBalanceRequest,
+        MsgSend, MsgSendResponse, QueryAllBalancesRequest, QueryAllBalancesResponse, QueryBalanceRequest,
         QueryBalanceResponse,
     },
-    base::v1beta1::Coin,
+    base::{
+        query::v1beta1::{PageRequest, PageResponse},
+        v1beta1::Coin,
+    },
 };
 
+use serde::Deserialize;
+
 use crate::{
-    baseapp::BANK_STORE_PREFIX,
-    error::AppError,
-    store::MutableSubStore,
-    types::{AccAddress, Context},
+    baseapp::{QueryRouter, AUTH_STORE_PREFIX, BANK_STORE_PREFIX},
+    collections::{Map, StringCodec},
+    error::{
+        AppError, RegisteredError, ERR_INSUFFICIENT_FUNDS, ERR_INVALID_COINS, ERR_JSON_MARSHAL,
+        ERR_JSON_UNMARSHAL, ERR_UNAUTHORIZED, ERR_UNKNOWN_REQUEST,
+    },
+    types::{
+        amount_of, coins_to_string, new_event, paginate, sub_coins_saturating, validate_coins,
+        AccAddress, Context, QueryContext, ATTRIBUTE_KEY_AMOUNT, ATTRIBUTE_KEY_MODULE,
+        ATTRIBUTE_KEY_SENDER, EVENT_TYPE_MESSAGE,
+    },
+    x::{
+        auth::{Auth, MODULE_ACCOUNT_PERMISSIONS, PERMISSION_BURNER},
+        distribution,
+        vesting::Vesting,
+    },
 };
 
+pub const MODULE_NAME: &str = "bank";
+
+pub const EVENT_TYPE_TRANSFER: &str = "transfer";
+pub const EVENT_TYPE_COIN_SPENT: &str = "coin_spent";
+pub const EVENT_TYPE_COIN_RECEIVED: &str = "coin_received";
+pub const EVENT_TYPE_BURN: &str = "burn";
+
+pub const ATTRIBUTE_KEY_RECIPIENT: &str = "recipient";
+pub const ATTRIBUTE_KEY_SPENDER: &str = "spender";
+pub const ATTRIBUTE_KEY_RECEIVER: &str = "receiver";
+pub const ATTRIBUTE_KEY_BURNER: &str = "burner";
+
+pub const ERR_NO_INPUTS: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 2, "no inputs to send transaction");
+pub const ERR_NO_OUTPUTS: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 3, "no outputs to send transaction");
+pub const ERR_SEND_DISABLED: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 5, "send transactions are disabled");
+
+pub const QUERY_BALANCE: &str = "balance";
+pub const QUERY_ALL_BALANCES: &str = "all_balances";
+
 const ADDRESS_BALANCES_STORE_PREFIX: [u8; 1] = [2];
 
+const BALANCES: Map<(AccAddress, String), Uint256, StringCodec> =
+    Map::new(&ADDRESS_BALANCES_STORE_PREFIX);
+
 #[derive(Debug, Clone)]
 pub struct Bank {}
 
@@ -30,27 +70,96 @@ pub struct Balance {
     pub coins: Vec<Coin>,
 }
 
+/// The JSON params of the legacy balance queries, the denom is ignored by `all_balances`
+#[derive(Deserialize)]
+struct QueryBalanceParams {
+    address: String,
+    #[serde(default)]
+    denom: String,
+}
+
 impl Bank {
+    pub fn register_queries(router: QueryRouter) -> QueryRouter {
+        router
+            .register("/cosmos.bank.v1beta1.Query/Balance", Bank::query_balance)
+            .register(
+                "/cosmos.bank.v1beta1.Query/AllBalances",
+                Bank::query_all_balances,
+            )
+            .register(
+                "/cosmos.bank.v1beta1.Query/SpendableBalances",
+                Bank::query_spendable_balances,
+            )
+            .register_legacy(MODULE_NAME, Bank::legacy_query)
+    }
+
+    /// Handles the legacy `custom/bank/balance` and `custom/bank/all_balances` queries
+    pub fn legacy_query(
+        ctx: &QueryContext,
+        path: &[&str],
+        data: &[u8],
+    ) -> Result<Vec<u8>, AppError> {
+        let params = || -> Result<QueryBalanceParams, AppError> {
+            serde_json::from_slice(data).map_err(|e| ERR_JSON_UNMARSHAL.wrap(e.to_string()))
+        };
+
+        let res = match path.first() {
+            Some(&QUERY_BALANCE) => {
+                let params = params()?;
+                let res = Bank::query_balance(
+                    ctx,
+                    QueryBalanceRequest {
+                        address: params.address,
+                        denom: params.denom.clone(),
+                    },
+                )?;
+
+                // as in the cosmos SDK, a missing balance is a zero balance
+                let balance = res.balance.unwrap_or(Coin {
+                    denom: params.denom,
+                    amount: Uint256::zero(),
+                });
+                coin_to_json(&balance)
+            }
+            Some(&QUERY_ALL_BALANCES) => {
+                let res = Bank::query_all_balances(
+                    ctx,
+                    QueryAllBalancesRequest {
+                        address: params()?.address,
+                        pagination: None,
+                    },
+                )?;
+
+                res.balances.iter().map(coin_to_json).collect()
+            }
+            _ => {
+                return Err(ERR_UNKNOWN_REQUEST.wrap(format!(
+                    "unknown {} query endpoint: {}",
+                    MODULE_NAME,
+                    path.join("/")
+                )))
+            }
+        };
+
+        serde_json::to_vec(&res).map_err(|e| ERR_JSON_MARSHAL.wrap(e.to_string()))
+    }
+
     pub fn init_genesis(ctx: &mut Context, genesis: GenesisState) {
-        let mut bank_store = ctx
-            .get_mutable_store()
-            .get_mutable_sub_store(BANK_STORE_PREFIX.into());
+        let mut bank_store = ctx.get_kv_store(BANK_STORE_PREFIX.into());
 
         for balance in genesis.balances {
-            let prefix = create_denom_balance_prefix(balance.address);
-            let mut denom_balance_store = bank_store.get_sub_store(prefix);
-
             for coin in balance.coins {
-                denom_balance_store.set(
-                    coin.denom.as_bytes().to_vec(),
-                    coin.amount.to_string().into(),
+                BALANCES.set(
+                    &mut bank_store,
+                    &(balance.address.clone(), coin.denom),
+                    &coin.amount,
                 );
             }
         }
     }
 
     pub fn query_balance(
-        ctx: &Context,
+        ctx: &QueryContext,
         req: QueryBalanceRequest,
     ) -> Result<QueryBalanceResponse, AppError> {
         let address = AccAddress::from_bech32(&req.address)?;
@@ -58,19 +167,13 @@ impl Bank {
         let bank_store = ctx
             .get_multi_store()
             .get_immutable_sub_store(BANK_STORE_PREFIX.into());
-        let prefix = create_denom_balance_prefix(address);
-
-        let account_store = bank_store.get_sub_store(prefix);
-        let bal = account_store.get(req.denom.as_bytes());
+        let bal = BALANCES.get(&bank_store, &(address, req.denom.clone()));
 
         match bal {
             Some(amount) => Ok(QueryBalanceResponse {
                 balance: Some(Coin {
                     denom: req.denom,
-                    amount: Uint256::from_str(
-                        &String::from_utf8(amount.to_owned()).expect("Should be valid Uint256"),
-                    )
-                    .expect("Should be valid utf8"),
+                    amount,
                 }),
             }),
             None => Ok(QueryBalanceResponse { balance: None }),
@@ -78,7 +181,7 @@ impl Bank {
     }
 
     pub fn query_all_balances(
-        ctx: &Context,
+        ctx: &QueryContext,
         req: QueryAllBalancesRequest,
     ) -> Result<QueryAllBalancesResponse, AppError> {
         let address = AccAddress::from_bech32(&req.address)?;
@@ -86,20 +189,11 @@ impl Bank {
         let bank_store = ctx
             .get_multi_store()
             .get_immutable_sub_store(BANK_STORE_PREFIX.into());
-        let prefix = create_denom_balance_prefix(address);
-        let account_store = bank_store.get_sub_store(prefix);
 
-        let mut balances = vec![];
-
-        for (denom, amount) in account_store {
-            let denom = String::from_utf8(denom).expect("Should be valid utf8");
-            let amount =
-                Uint256::from_str(&String::from_utf8(amount).expect("Should be valid Uint256"))
-                    .expect("Should be valid utf8");
-
-            let coin = Coin { denom, amount };
-            balances.push(coin);
-        }
+        let balances = BALANCES
+            .prefix_iter(&bank_store, &address)
+            .map(|((_, denom), amount)| Coin { denom, amount })
+            .collect();
 
         return Ok(QueryAllBalancesResponse {
             balances,
@@ -107,84 +201,374 @@ impl Bank {
         });
     }
 
-    pub fn send_coins(ctx: &mut Context, msg: MsgSend) -> Result<(), AppError> {
-        let mut bank_store = ctx
-            .get_mutable_store()
-            .get_mutable_sub_store(BANK_STORE_PREFIX.into());
+    /// Returns the balances of the address less the coins locked by vesting, as in the cosmos
+    /// SDK each balance is returned, even when none of it is spendable
+    pub fn query_spendable_balances(
+        ctx: &QueryContext,
+        req: QuerySpendableBalancesRequest,
+    ) -> Result<QuerySpendableBalancesResponse, AppError> {
+        let address = AccAddress::from_bech32(&req.address)?;
 
-        let from_address = AccAddress::from_bech32(&msg.from_address)?;
+        let auth_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(AUTH_STORE_PREFIX.into());
+        let locked = Vesting::read_vesting_account(&auth_store, &address)
+            .map(|account| account.locked_coins(ctx.block_time().seconds))
+            .unwrap_or_default();
 
+        let bank_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(BANK_STORE_PREFIX.into());
+        let balances = BALANCES
+            .prefix_iter(&bank_store, &address)
+            .map(|(key, amount)| {
+                let coin = Coin {
+                    amount: amount.saturating_sub(amount_of(&locked, &key.1)),
+                    denom: key.1.clone(),
+                };
+                (BALANCES.key(&key), coin)
+            });
+        let (balances, pagination) = paginate(balances, req.pagination)?;
+
+        Ok(QuerySpendableBalancesResponse {
+            balances,
+            pagination,
+        })
+    }
+
+    /// Handles MsgSend
+    pub fn send(ctx: &mut Context, msg: MsgSend) -> Result<MsgSendResponse, AppError> {
+        let from_address = AccAddress::from_bech32(&msg.from_address)?;
         let to_address = AccAddress::from_bech32(&msg.to_address)?;
 
-        for send_coin in msg.amount {
-            let mut from_account_store =
-                Bank::get_address_balances_store(&mut bank_store, &from_address);
-            let from_balance = from_account_store
-                .get(send_coin.denom.as_bytes())
-                .ok_or(AppError::Send("Insufficient funds".into()))?;
+        if Bank::blocked_addr(&to_address) {
+            return Err(ERR_UNAUTHORIZED.wrap(format!(
+                "{} is not allowed to receive funds",
+                to_address
+            )));
+        }
+
+        Bank::send_coins(ctx, &from_address, &to_address, &msg.amount)?;
 
-            let from_balance = Uint256::from_str(
-                &String::from_utf8(from_balance.to_owned()).expect("Should be valid Uint256"),
-            )
-            .expect("Should be valid utf8");
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_MESSAGE,
+            vec![(ATTRIBUTE_KEY_MODULE, MODULE_NAME.into())],
+        ));
+
+        Ok(MsgSendResponse {})
+    }
+
+    /// Transfers coins from an account to a module account, e.g. to pay fees
+    pub fn send_coins_from_account_to_module(
+        ctx: &mut Context,
+        from_address: &AccAddress,
+        module_name: &str,
+        amount: &[Coin],
+    ) -> Result<(), AppError> {
+        let module_address = AccAddress::new_module_address(module_name);
+        Bank::send_coins(ctx, from_address, &module_address, amount)
+    }
+
+    /// Transfers coins from a module account to an account, which may not be a blocked address,
+    /// e.g. to pay out rewards
+    pub fn send_coins_from_module_to_account(
+        ctx: &mut Context,
+        module_name: &str,
+        to_address: &AccAddress,
+        amount: &[Coin],
+    ) -> Result<(), AppError> {
+        if Bank::blocked_addr(to_address) {
+            return Err(ERR_UNAUTHORIZED.wrap(format!(
+                "{} is not allowed to receive funds",
+                to_address
+            )));
+        }
+
+        let module_address = AccAddress::new_module_address(module_name);
+        Bank::send_coins(ctx, &module_address, to_address, amount)
+    }
+
+    /// Transfers coins from one module account to another, e.g. between the staking pools
+    pub fn send_coins_from_module_to_module(
+        ctx: &mut Context,
+        sender_module: &str,
+        recipient_module: &str,
+        amount: &[Coin],
+    ) -> Result<(), AppError> {
+        let sender = AccAddress::new_module_address(sender_module);
+        let recipient = AccAddress::new_module_address(recipient_module);
+        Bank::send_coins(ctx, &sender, &recipient, amount)
+    }
+
+    /// Transfers coins from a module account to an account for an undelegation, as with the
+    /// cosmos SDK `UndelegateCoinsFromModuleToAccount`. The undelegation is tracked by the
+    /// delegator's vesting account.
+    pub fn undelegate_coins_from_module_to_account(
+        ctx: &mut Context,
+        module_name: &str,
+        delegator: &AccAddress,
+        amount: &[Coin],
+    ) -> Result<(), AppError> {
+        validate_coins(amount).map_err(|_| ERR_INVALID_COINS.wrap(coins_to_string(amount)))?;
+
+        let module_address = AccAddress::new_module_address(module_name);
+        Bank::sub_unlocked_coins(ctx, &module_address, amount)?;
+
+        if let Some(mut vesting_account) = Vesting::get_vesting_account(ctx, delegator) {
+            vesting_account.track_undelegation(amount);
+            Vesting::set_vesting_account(ctx, delegator, &vesting_account);
+        }
 
-            if from_balance < send_coin.amount {
-                return Err(AppError::Send("Insufficient funds".into()));
+        Bank::add_coins(ctx, delegator, amount);
+
+        Ok(())
+    }
+
+    /// Transfers coins from an account to a module account for a delegation, as with the cosmos
+    /// SDK `DelegateCoinsFromAccountToModule`. Unlike a send, coins which are locked by vesting
+    /// may be delegated, the delegation is tracked by the delegator's vesting account.
+    pub fn delegate_coins_from_account_to_module(
+        ctx: &mut Context,
+        delegator: &AccAddress,
+        module_name: &str,
+        amount: &[Coin],
+    ) -> Result<(), AppError> {
+        validate_coins(amount).map_err(|_| ERR_INVALID_COINS.wrap(coins_to_string(amount)))?;
+
+        let mut bank_store = ctx.get_kv_store(BANK_STORE_PREFIX.into());
+
+        for coin in amount {
+            let key = (delegator.clone(), coin.denom.clone());
+            let balance = BALANCES.get(&bank_store, &key).unwrap_or(Uint256::zero());
+
+            if balance < coin.amount {
+                return Err(ERR_INSUFFICIENT_FUNDS.wrap(format!(
+                    "failed to delegate; {}{} is smaller than {}{}",
+                    balance, coin.denom, coin.amount, coin.denom
+                )));
             }
 
-            from_account_store.set(
-                send_coin.denom.clone().into(),
-                (from_balance - send_coin.amount).to_string().into(),
-            );
+            BALANCES.set(&mut bank_store, &key, &(balance - coin.amount));
+        }
 
-            let mut to_account_store =
-                Bank::get_address_balances_store(&mut bank_store, &to_address);
-            let to_balance = to_account_store.get(send_coin.denom.as_bytes());
-            let to_balance = match to_balance {
-                Some(to_balance) => Uint256::from_str(
-                    &String::from_utf8(to_balance.to_owned()).expect("Should be valid Uint256"),
-                )
-                .expect("Should be valid utf8"),
-                None => Uint256::zero(),
-            };
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_COIN_SPENT,
+            vec![
+                (ATTRIBUTE_KEY_SPENDER, delegator.to_string()),
+                (ATTRIBUTE_KEY_AMOUNT, coins_to_string(amount)),
+            ],
+        ));
+
+        if let Some(mut vesting_account) = Vesting::get_vesting_account(ctx, delegator) {
+            vesting_account.track_delegation(ctx.block_time().seconds, amount);
+            Vesting::set_vesting_account(ctx, delegator, &vesting_account);
+        }
 
-            to_account_store.set(
-                send_coin.denom.into(),
-                (to_balance + send_coin.amount).to_string().into(),
-            );
+        let module_address = AccAddress::new_module_address(module_name);
+        Bank::add_coins(ctx, &module_address, amount);
+
+        Ok(())
+    }
+
+    /// Removes coins from a module account, which must have the burner permission, e.g. when
+    /// a validator is slashed
+    pub fn burn_coins(
+        ctx: &mut Context,
+        module_name: &str,
+        amount: &[Coin],
+    ) -> Result<(), AppError> {
+        let has_permission = MODULE_ACCOUNT_PERMISSIONS
+            .iter()
+            .any(|(name, permissions)| {
+                *name == module_name && permissions.contains(&PERMISSION_BURNER)
+            });
+        if !has_permission {
+            panic!(
+                "module account {} does not have permissions to burn tokens",
+                module_name
+            )
+        }
+
+        validate_coins(amount).map_err(|_| ERR_INVALID_COINS.wrap(coins_to_string(amount)))?;
+
+        let module_address = AccAddress::new_module_address(module_name);
+        Bank::sub_unlocked_coins(ctx, &module_address, amount)?;
+
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_BURN,
+            vec![
+                (ATTRIBUTE_KEY_BURNER, module_address.to_string()),
+                (ATTRIBUTE_KEY_AMOUNT, coins_to_string(amount)),
+            ],
+        ));
+
+        Ok(())
+    }
+
+    /// Returns whether the address may not receive funds, as in gaia these are the module
+    /// accounts other than the distribution module's, which receives the community pool funding
+    pub fn blocked_addr(address: &AccAddress) -> bool {
+        MODULE_ACCOUNT_PERMISSIONS
+            .iter()
+            .filter(|(name, _)| *name != distribution::MODULE_NAME)
+            .any(|(name, _)| &AccAddress::new_module_address(name) == address)
+    }
+
+    /// Returns all the balances of the address, sorted by denom
+    pub fn get_all_balances(ctx: &mut Context, address: &AccAddress) -> Vec<Coin> {
+        let bank_store = ctx.get_kv_store(BANK_STORE_PREFIX.into());
+        BALANCES
+            .prefix_iter(&bank_store, address)
+            .map(|((_, denom), amount)| Coin { denom, amount })
+            .collect()
+    }
+
+    /// Returns the balances of the address less the coins locked by vesting
+    pub fn spendable_coins(ctx: &mut Context, address: &AccAddress) -> Vec<Coin> {
+        let locked = Bank::locked_coins(ctx, address);
+        let balances = Bank::get_all_balances(ctx, address);
+
+        sub_coins_saturating(&balances, &locked)
+    }
+
+    pub fn send_coins(
+        ctx: &mut Context,
+        from_address: &AccAddress,
+        to_address: &AccAddress,
+        amount: &[Coin],
+    ) -> Result<(), AppError> {
+        Bank::sub_unlocked_coins(ctx, from_address, amount)?;
+        Bank::add_coins(ctx, to_address, amount);
+
+        if !Auth::has_account(ctx, to_address) {
+            let account = Auth::new_account_with_address(ctx, to_address);
+            Auth::set_account(ctx, account);
         }
 
-        //TODO:
-        // Create account if recipient does not exist
+        let amount = coins_to_string(amount);
+        let from_address = from_address.to_string();
+        let to_address = to_address.to_string();
+
+        // same events, in the same order, as the cosmos SDK bank keeper
+        ctx.event_manager().emit_events(vec![
+            new_event(
+                EVENT_TYPE_TRANSFER,
+                vec![
+                    (ATTRIBUTE_KEY_RECIPIENT, to_address),
+                    (ATTRIBUTE_KEY_SENDER, from_address.clone()),
+                    (ATTRIBUTE_KEY_AMOUNT, amount),
+                ],
+            ),
+            new_event(
+                EVENT_TYPE_MESSAGE,
+                vec![(ATTRIBUTE_KEY_SENDER, from_address)],
+            ),
+        ]);
 
         return Ok(());
     }
 
-    fn get_address_balances_store<'a>(
-        bank_store: &'a mut MutableSubStore,
+    /// Returns the coins of the address which are locked by vesting at the block time
+    fn locked_coins(ctx: &mut Context, address: &AccAddress) -> Vec<Coin> {
+        let block_time = ctx.block_time().seconds;
+        Vesting::get_vesting_account(ctx, address)
+            .map(|account| account.locked_coins(block_time))
+            .unwrap_or_default()
+    }
+
+    /// Subtracts the coins from the balance of the address, failing if they aren't spendable
+    fn sub_unlocked_coins(
+        ctx: &mut Context,
         address: &AccAddress,
-    ) -> MutableSubStore<'a> {
-        let prefix = create_denom_balance_prefix(address.to_owned());
-        bank_store.get_sub_store(prefix)
+        amount: &[Coin],
+    ) -> Result<(), AppError> {
+        let locked = Bank::locked_coins(ctx, address);
+        let mut bank_store = ctx.get_kv_store(BANK_STORE_PREFIX.into());
+
+        for coin in amount {
+            let key = (address.clone(), coin.denom.clone());
+            let balance = BALANCES.get(&bank_store, &key).unwrap_or(Uint256::zero());
+            let spendable = balance.saturating_sub(amount_of(&locked, &coin.denom));
+
+            if spendable < coin.amount {
+                return Err(ERR_INSUFFICIENT_FUNDS.wrap(format!(
+                    "{}{} is smaller than {}{}",
+                    spendable, coin.denom, coin.amount, coin.denom
+                )));
+            }
+
+            BALANCES.set(&mut bank_store, &key, &(balance - coin.amount));
+        }
+
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_COIN_SPENT,
+            vec![
+                (ATTRIBUTE_KEY_SPENDER, address.to_string()),
+                (ATTRIBUTE_KEY_AMOUNT, coins_to_string(amount)),
+            ],
+        ));
+
+        Ok(())
+    }
+
+    /// Adds the coins to the balance of the address
+    fn add_coins(ctx: &mut Context, address: &AccAddress, amount: &[Coin]) {
+        let mut bank_store = ctx.get_kv_store(BANK_STORE_PREFIX.into());
+
+        for coin in amount {
+            let key = (address.clone(), coin.denom.clone());
+            let balance = BALANCES.get(&bank_store, &key).unwrap_or(Uint256::zero());
+            BALANCES.set(&mut bank_store, &key, &(balance + coin.amount));
+        }
+
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_COIN_RECEIVED,
+            vec![
+                (ATTRIBUTE_KEY_RECEIVER, address.to_string()),
+                (ATTRIBUTE_KEY_AMOUNT, coins_to_string(amount)),
+            ],
+        ));
     }
 }
 
-fn create_denom_balance_prefix(addr: AccAddress) -> Vec<u8> {
-    let addr_len = addr.len();
-    let mut addr: Vec<u8> = addr.into();
-    let mut prefix = Vec::new();
+/// This mirrors the cosmos SDK `cosmos.bank.v1beta1.QuerySpendableBalancesRequest` message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QuerySpendableBalancesRequest {
+    #[prost(string, tag = "1")]
+    pub address: String,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: Option<PageRequest>,
+}
 
-    prefix.extend(ADDRESS_BALANCES_STORE_PREFIX);
-    prefix.push(addr_len);
-    prefix.append(&mut addr);
+/// This mirrors the cosmos SDK `cosmos.bank.v1beta1.QuerySpendableBalancesResponse` message
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QuerySpendableBalancesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub balances: Vec<Coin>,
+    #[prost(message, optional, tag = "2")]
+    pub pagination: Option<PageResponse>,
+}
 
-    return prefix;
+fn coin_to_json(coin: &Coin) -> serde_json::Value {
+    serde_json::json!({
+        "denom": coin.denom,
+        "amount": coin.amount.to_string(),
+    })
 }
 
 #[cfg(test)]
 mod tests {
 
-    use crate::store::MultiStore;
+    use std::{str::FromStr, sync::Arc};
+
+    use ibc_proto::cosmos::vesting::v1beta1::MsgCreateVestingAccount;
+    use tendermint_proto::google::protobuf::Timestamp;
+
+    use crate::{
+        store::MultiStore,
+        types::{GasMeter, InfiniteGasMeter},
+        x::staking,
+    };
 
     use super::*;
 
@@ -192,7 +576,7 @@ mod tests {
     fn create_account_balances_prefix_works() {
         let expected = vec![2, 4, 97, 98, 99, 100];
         let acc_address = AccAddress::try_from(vec![97, 98, 99, 100]).unwrap();
-        let res = create_denom_balance_prefix(acc_address);
+        let res = BALANCES.prefix_key(&acc_address);
 
         assert_eq!(expected, res);
     }
@@ -219,7 +603,8 @@ mod tests {
             denom: "coinA".to_string(),
         };
 
-        let res = Bank::query_balance(&ctx, req).unwrap();
+        let query_ctx = QueryContext::new(ctx.get_multi_store(), 0);
+        let res = Bank::query_balance(&query_ctx, req).unwrap();
 
         let expected_res = QueryBalanceResponse {
             balance: Some(Coin {
@@ -253,7 +638,8 @@ mod tests {
         let mut ctx = Context::new(store);
         Bank::init_genesis(&mut ctx, genesis);
 
-        let res = Bank::query_all_balances(&ctx, req).unwrap();
+        let query_ctx = QueryContext::new(ctx.get_multi_store(), 0);
+        let res = Bank::query_all_balances(&query_ctx, req).unwrap();
 
         let expected_res = QueryAllBalancesResponse {
             balances: vec![Coin {
@@ -265,4 +651,263 @@ mod tests {
 
         assert_eq!(expected_res, res);
     }
+
+    #[test]
+    fn legacy_query_works() {
+        let address = "cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux";
+        let mut ctx = Context::new(MultiStore::new());
+        Bank::init_genesis(
+            &mut ctx,
+            GenesisState {
+                balances: vec![Balance {
+                    address: AccAddress::from_bech32(address).unwrap(),
+                    coins: vec![Coin {
+                        denom: "uatom".into(),
+                        amount: Uint256::from(34u32),
+                    }],
+                }],
+            },
+        );
+        let query_ctx = QueryContext::new(ctx.get_multi_store(), 0);
+
+        let params = format!(r#"{{"address":"{}","denom":"uatom"}}"#, address);
+        let res = Bank::legacy_query(&query_ctx, &["balance"], params.as_bytes()).unwrap();
+        assert_eq!(res, br#"{"amount":"34","denom":"uatom"}"#);
+
+        let params = format!(r#"{{"address":"{}","denom":"stake"}}"#, address);
+        let res = Bank::legacy_query(&query_ctx, &["balance"], params.as_bytes()).unwrap();
+        assert_eq!(res, br#"{"amount":"0","denom":"stake"}"#);
+
+        let params = format!(r#"{{"address":"{}"}}"#, address);
+        let res = Bank::legacy_query(&query_ctx, &["all_balances"], params.as_bytes()).unwrap();
+        assert_eq!(res, br#"[{"amount":"34","denom":"uatom"}]"#);
+
+        let err = Bank::legacy_query(&query_ctx, &["balance"], b"{").unwrap_err();
+        assert_eq!(err.code(), 20);
+
+        let err = Bank::legacy_query(&query_ctx, &["supply"], &[]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unknown bank query endpoint: supply: unknown request"
+        );
+    }
+
+    #[test]
+    fn send_emits_events() {
+        let from = "cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux";
+        let to = "cosmos1qyqszqgpqyqszqgpqyqszqgpqyqszqgpjnp7du";
+        let genesis = GenesisState {
+            balances: vec![Balance {
+                address: AccAddress::from_bech32(from).unwrap(),
+                coins: vec![Coin {
+                    denom: "uatom".into(),
+                    amount: Uint256::from(34u32),
+                }],
+            }],
+        };
+
+        let mut ctx = Context::new(MultiStore::new());
+        Bank::init_genesis(&mut ctx, genesis);
+
+        let msg = MsgSend {
+            from_address: from.into(),
+            to_address: to.into(),
+            amount: vec![Coin {
+                denom: "uatom".into(),
+                amount: Uint256::from(10u32),
+            }],
+        };
+        Bank::send(&mut ctx, msg).unwrap();
+
+        let events = ctx.event_manager().take_events();
+        let types: Vec<&str> = events.iter().map(|e| e.r#type.as_str()).collect();
+
+        assert_eq!(
+            types,
+            vec!["coin_spent", "coin_received", "transfer", "message", "message"]
+        );
+        assert_eq!(
+            events[2],
+            new_event(
+                "transfer",
+                vec![
+                    ("recipient", to.into()),
+                    ("sender", from.into()),
+                    ("amount", "10uatom".into())
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn send_insufficient_funds() {
+        let mut ctx = Context::new(MultiStore::new());
+
+        let msg = MsgSend {
+            from_address: "cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux".into(),
+            to_address: "cosmos1qyqszqgpqyqszqgpqyqszqgpqyqszqgpjnp7du".into(),
+            amount: vec![Coin {
+                denom: "uatom".into(),
+                amount: Uint256::from(10u32),
+            }],
+        };
+        let err = Bank::send(&mut ctx, msg).unwrap_err();
+
+        assert_eq!(err.code(), 5);
+        assert_eq!(
+            err.to_string(),
+            "0uatom is smaller than 10uatom: insufficient funds"
+        );
+    }
+
+    #[test]
+    fn send_to_blocked_address_fails() {
+        let from = "cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux";
+        let mut ctx = Context::new(MultiStore::new());
+        Bank::init_genesis(
+            &mut ctx,
+            GenesisState {
+                balances: vec![Balance {
+                    address: AccAddress::from_bech32(from).unwrap(),
+                    coins: vec![Coin {
+                        denom: "uatom".into(),
+                        amount: Uint256::from(34u32),
+                    }],
+                }],
+            },
+        );
+
+        let pool = AccAddress::new_module_address(staking::BONDED_POOL_NAME);
+        let msg = MsgSend {
+            from_address: from.into(),
+            to_address: pool.to_string(),
+            amount: vec![Coin {
+                denom: "uatom".into(),
+                amount: Uint256::from(10u32),
+            }],
+        };
+        let err = Bank::send(&mut ctx, msg).unwrap_err();
+
+        assert_eq!(err.code(), ERR_UNAUTHORIZED.code);
+        assert_eq!(
+            err.to_string(),
+            format!("{} is not allowed to receive funds: unauthorized", pool)
+        );
+        assert_eq!(Bank::get_all_balances(&mut ctx, &pool), vec![]);
+    }
+
+    #[test]
+    fn send_to_new_account_charges_gas() {
+        let from =
+            AccAddress::from_bech32("cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux").unwrap();
+        let to = AccAddress::from_bech32("cosmos1qyqszqgpqyqszqgpqyqszqgpqyqszqgpjnp7du").unwrap();
+        let funded_context = || {
+            let mut ctx = Context::new(MultiStore::new());
+            Bank::init_genesis(
+                &mut ctx,
+                GenesisState {
+                    balances: vec![Balance {
+                        address: from.clone(),
+                        coins: vec![Coin {
+                            denom: "uatom".into(),
+                            amount: Uint256::from(34u32),
+                        }],
+                    }],
+                },
+            );
+            ctx
+        };
+        let gas_of = |ctx: Context, f: &dyn Fn(&mut Context)| {
+            let gas_meter = Arc::new(InfiniteGasMeter::new());
+            let mut ctx = ctx.with_gas_meter(gas_meter.clone());
+            f(&mut ctx);
+            gas_meter.gas_consumed()
+        };
+        let send = |ctx: &mut Context| {
+            let msg = MsgSend {
+                from_address: from.to_string(),
+                to_address: to.to_string(),
+                amount: vec![Coin {
+                    denom: "uatom".into(),
+                    amount: Uint256::from(10u32),
+                }],
+            };
+            Bank::send(ctx, msg).unwrap();
+        };
+        let create_account = |ctx: &mut Context| {
+            let account = Auth::new_account_with_address(ctx, &to);
+            Auth::set_account(ctx, account);
+        };
+
+        let mut ctx = funded_context();
+        create_account(&mut ctx);
+        let existing_account_gas = gas_of(ctx, &send);
+
+        // the account check is charged either way, a new account is also charged for its creation
+        assert_eq!(
+            gas_of(funded_context(), &send),
+            existing_account_gas + gas_of(funded_context(), &create_account)
+        );
+    }
+
+    #[test]
+    fn query_spendable_balances_works() {
+        let from =
+            AccAddress::from_bech32("cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux").unwrap();
+        let to = "cosmos1qyqszqgpqyqszqgpqyqszqgpqyqszqgpjnp7du";
+        let mut ctx = Context::new(MultiStore::new());
+        Bank::init_genesis(
+            &mut ctx,
+            GenesisState {
+                balances: vec![Balance {
+                    address: from,
+                    coins: vec![Coin {
+                        denom: "uatom".into(),
+                        amount: Uint256::from(34u32),
+                    }],
+                }],
+            },
+        );
+        Vesting::create_vesting_account(
+            &mut ctx,
+            MsgCreateVestingAccount {
+                from_address: "cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux".into(),
+                to_address: to.into(),
+                amount: vec![Coin {
+                    denom: "uatom".into(),
+                    amount: Uint256::from(10u32),
+                }],
+                end_time: 100,
+                delayed: true,
+            },
+        )
+        .unwrap();
+        let query = |seconds| {
+            let query_ctx = QueryContext::new(ctx.get_multi_store(), 0)
+                .with_block_time(Timestamp { seconds, nanos: 0 });
+            let req = QuerySpendableBalancesRequest {
+                address: to.into(),
+                pagination: None,
+            };
+            Bank::query_spendable_balances(&query_ctx, req)
+                .unwrap()
+                .balances
+        };
+
+        // the balance is returned even though none of it can be spent
+        assert_eq!(
+            query(99),
+            vec![Coin {
+                denom: "uatom".into(),
+                amount: Uint256::zero(),
+            }]
+        );
+        assert_eq!(
+            query(100),
+            vec![Coin {
+                denom: "uatom".into(),
+                amount: Uint256::from(10u32),
+            }]
+        );
+    }
 }
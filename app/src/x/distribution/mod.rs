@@ -0,0 +1,1952 @@
+use ibc_proto::cosmos::{
+    base::v1beta1::{Coin, DecCoin as ProtoDecCoin},
+    distribution::v1beta1::{
+        DelegationDelegatorReward, DelegatorStartingInfo, FeePool, MsgFundCommunityPool,
+        MsgFundCommunityPoolResponse, MsgSetWithdrawAddress, MsgSetWithdrawAddressResponse,
+        MsgWithdrawDelegatorReward, MsgWithdrawDelegatorRewardResponse,
+        MsgWithdrawValidatorCommission, MsgWithdrawValidatorCommissionResponse, Params,
+        QueryCommunityPoolRequest, QueryCommunityPoolResponse, QueryDelegationRewardsRequest,
+        QueryDelegationRewardsResponse, QueryDelegationTotalRewardsRequest,
+        QueryDelegationTotalRewardsResponse, QueryDelegatorValidatorsRequest,
+        QueryDelegatorValidatorsResponse, QueryDelegatorWithdrawAddressRequest,
+        QueryDelegatorWithdrawAddressResponse, QueryParamsRequest, QueryParamsResponse,
+        QueryValidatorCommissionRequest, QueryValidatorCommissionResponse,
+        QueryValidatorOutstandingRewardsRequest, QueryValidatorOutstandingRewardsResponse,
+        QueryValidatorSlashesRequest, QueryValidatorSlashesResponse,
+        ValidatorAccumulatedCommission, ValidatorCurrentRewards, ValidatorHistoricalRewards,
+        ValidatorOutstandingRewards, ValidatorSlashEvent,
+    },
+};
+use math::Decimal256;
+use tendermint_proto::abci::VoteInfo;
+use tracing::error;
+
+use crate::{
+    baseapp::{QueryRouter, DISTRIBUTION_STORE_PREFIX, STAKING_STORE_PREFIX},
+    collections::{Item, KeyValueCodec, Map},
+    error::{AppError, RegisteredError, ERR_INVALID_REQUEST, ERR_UNAUTHORIZED},
+    store::{ReadKVStore, WriteKVStore},
+    types::{
+        add_dec_coins, coins_to_string, dec_coins_from_coins, dec_coins_to_string,
+        intersect_dec_coins, new_event, paginate, sub_dec_coins, truncate_dec_coins, AccAddress,
+        ConsAddress, Context, DecCoin, QueryContext, ValAddress, ATTRIBUTE_KEY_AMOUNT,
+        ATTRIBUTE_KEY_MODULE, ATTRIBUTE_KEY_SENDER, EVENT_TYPE_MESSAGE,
+    },
+    x::{
+        auth::{FEE_COLLECTOR_NAME, MODULE_ACCOUNT_PERMISSIONS},
+        bank::Bank,
+        staking::{Delegation, Staking, Validator},
+    },
+};
+
+mod msgs;
+
+pub use msgs::*;
+
+pub const MODULE_NAME: &str = "distribution";
+
+pub const EVENT_TYPE_SET_WITHDRAW_ADDRESS: &str = "set_withdraw_address";
+pub const EVENT_TYPE_REWARDS: &str = "rewards";
+pub const EVENT_TYPE_COMMISSION: &str = "commission";
+pub const EVENT_TYPE_WITHDRAW_REWARDS: &str = "withdraw_rewards";
+pub const EVENT_TYPE_WITHDRAW_COMMISSION: &str = "withdraw_commission";
+pub const EVENT_TYPE_PROPOSER_REWARD: &str = "proposer_reward";
+
+pub const ATTRIBUTE_KEY_WITHDRAW_ADDRESS: &str = "withdraw_address";
+pub const ATTRIBUTE_KEY_VALIDATOR: &str = "validator";
+
+// The same codes as the cosmos SDK distribution module
+pub const ERR_EMPTY_DELEGATOR_ADDR: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 2, "delegator address is empty");
+pub const ERR_EMPTY_WITHDRAW_ADDR: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 3, "withdraw address is empty");
+pub const ERR_EMPTY_VALIDATOR_ADDR: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 4, "validator address is empty");
+pub const ERR_EMPTY_DELEGATION_DIST_INFO: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 5, "no delegation distribution info");
+pub const ERR_NO_VALIDATOR_DIST_INFO: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 6, "no validator distribution info");
+pub const ERR_NO_VALIDATOR_COMMISSION: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 7, "no validator commission to withdraw");
+pub const ERR_SET_WITHDRAW_ADDR_DISABLED: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 8, "set withdraw address disabled");
+pub const ERR_NO_VALIDATOR_EXISTS: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 12, "validator does not exist");
+pub const ERR_NO_DELEGATION_EXISTS: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 13, "delegation does not exist");
+
+const FEE_POOL_KEY: [u8; 1] = [0x00];
+const PREVIOUS_PROPOSER_KEY: [u8; 1] = [0x01];
+const VALIDATOR_OUTSTANDING_REWARDS_STORE_PREFIX: [u8; 1] = [0x02];
+const DELEGATOR_WITHDRAW_ADDR_STORE_PREFIX: [u8; 1] = [0x03];
+const DELEGATOR_STARTING_INFO_STORE_PREFIX: [u8; 1] = [0x04];
+const VALIDATOR_HISTORICAL_REWARDS_STORE_PREFIX: [u8; 1] = [0x05];
+const VALIDATOR_CURRENT_REWARDS_STORE_PREFIX: [u8; 1] = [0x06];
+const VALIDATOR_ACCUMULATED_COMMISSION_STORE_PREFIX: [u8; 1] = [0x07];
+const VALIDATOR_SLASH_EVENT_STORE_PREFIX: [u8; 1] = [0x08];
+const PARAMS_KEY: [u8; 1] = [0x09];
+
+const FEE_POOL: Item<FeePool> = Item::new(&FEE_POOL_KEY);
+const PREVIOUS_PROPOSER: Item<ConsAddress, KeyValueCodec<ConsAddress>> =
+    Item::new(&PREVIOUS_PROPOSER_KEY);
+const VALIDATOR_OUTSTANDING_REWARDS: Map<ValAddress, ValidatorOutstandingRewards> =
+    Map::new(&VALIDATOR_OUTSTANDING_REWARDS_STORE_PREFIX);
+const DELEGATOR_WITHDRAW_ADDRS: Map<AccAddress, AccAddress, KeyValueCodec<AccAddress>> =
+    Map::new(&DELEGATOR_WITHDRAW_ADDR_STORE_PREFIX);
+const DELEGATOR_STARTING_INFOS: Map<(ValAddress, AccAddress), DelegatorStartingInfo> =
+    Map::new(&DELEGATOR_STARTING_INFO_STORE_PREFIX);
+/// The cumulative reward ratio of each ended period of a validator. A period is kept while it's
+/// referenced by a delegation's starting info, a slash event or the next period.
+const VALIDATOR_HISTORICAL_REWARDS: Map<(ValAddress, u64), ValidatorHistoricalRewards> =
+    Map::new(&VALIDATOR_HISTORICAL_REWARDS_STORE_PREFIX);
+const VALIDATOR_CURRENT_REWARDS: Map<ValAddress, ValidatorCurrentRewards> =
+    Map::new(&VALIDATOR_CURRENT_REWARDS_STORE_PREFIX);
+const VALIDATOR_ACCUMULATED_COMMISSIONS: Map<ValAddress, ValidatorAccumulatedCommission> =
+    Map::new(&VALIDATOR_ACCUMULATED_COMMISSION_STORE_PREFIX);
+/// The slash events of each validator, keyed by the height and the period the slash ended
+const VALIDATOR_SLASH_EVENTS: Map<(ValAddress, (u64, u64)), ValidatorSlashEvent> =
+    Map::new(&VALIDATOR_SLASH_EVENT_STORE_PREFIX);
+const PARAMS: Item<Params> = Item::new(&PARAMS_KEY);
+
+#[derive(Debug, Clone)]
+pub struct Distribution {}
+
+pub struct GenesisState {
+    pub params: Params,
+}
+
+/// Returns the default params, which are the same as the cosmos SDK's
+pub fn default_params() -> Params {
+    Params {
+        community_tax: Decimal256::percent(2).to_cosmos_proto_string(),
+        base_proposer_reward: Decimal256::percent(1).to_cosmos_proto_string(),
+        bonus_proposer_reward: Decimal256::percent(4).to_cosmos_proto_string(),
+        withdraw_addr_enabled: true,
+    }
+}
+
+fn validate_params(params: &Params) -> Result<(), String> {
+    let decs = [
+        ("community tax", &params.community_tax),
+        ("base proposer reward", &params.base_proposer_reward),
+        ("bonus proposer reward", &params.bonus_proposer_reward),
+    ];
+
+    let mut sum = Decimal256::zero();
+    for (name, s) in decs {
+        let dec = Decimal256::from_cosmos_proto_string(s)
+            .map_err(|_| format!("invalid {}: {}", name, s))?;
+
+        if dec > Decimal256::one() {
+            return Err(format!(
+                "{} should be non-negative and less than one: {}",
+                name, dec
+            ));
+        }
+
+        sum += dec;
+    }
+
+    if sum > Decimal256::one() {
+        return Err(format!(
+            "sum of base, bonus proposer rewards, and community tax cannot be greater than one: {}",
+            sum
+        ));
+    }
+
+    Ok(())
+}
+
+impl Distribution {
+    pub fn register_queries(router: QueryRouter) -> QueryRouter {
+        router
+            .register(
+                "/cosmos.distribution.v1beta1.Query/Params",
+                Distribution::query_params,
+            )
+            .register(
+                "/cosmos.distribution.v1beta1.Query/ValidatorOutstandingRewards",
+                Distribution::query_validator_outstanding_rewards,
+            )
+            .register(
+                "/cosmos.distribution.v1beta1.Query/ValidatorCommission",
+                Distribution::query_validator_commission,
+            )
+            .register(
+                "/cosmos.distribution.v1beta1.Query/ValidatorSlashes",
+                Distribution::query_validator_slashes,
+            )
+            .register(
+                "/cosmos.distribution.v1beta1.Query/DelegationRewards",
+                Distribution::query_delegation_rewards,
+            )
+            .register(
+                "/cosmos.distribution.v1beta1.Query/DelegationTotalRewards",
+                Distribution::query_delegation_total_rewards,
+            )
+            .register(
+                "/cosmos.distribution.v1beta1.Query/DelegatorValidators",
+                Distribution::query_delegator_validators,
+            )
+            .register(
+                "/cosmos.distribution.v1beta1.Query/DelegatorWithdrawAddress",
+                Distribution::query_delegator_withdraw_address,
+            )
+            .register(
+                "/cosmos.distribution.v1beta1.Query/CommunityPool",
+                Distribution::query_community_pool,
+            )
+    }
+
+    /// Sets the params with an empty community pool. As in the cosmos SDK default genesis the
+    /// previous proposer is the empty address, so the fees of the first block after genesis
+    /// don't give a proposer reward.
+    pub fn init_genesis(ctx: &mut Context, genesis: GenesisState) {
+        if let Err(e) = validate_params(&genesis.params) {
+            panic!("invalid distribution genesis params: {}", e)
+        }
+
+        let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+        PARAMS.set(&mut distribution_store, &genesis.params);
+        FEE_POOL.set(&mut distribution_store, &FeePool::default());
+        PREVIOUS_PROPOSER.set(
+            &mut distribution_store,
+            &ConsAddress::try_from(vec![]).expect("the empty address is a valid address"),
+        );
+    }
+
+    pub fn query_params(
+        ctx: &QueryContext,
+        _req: QueryParamsRequest,
+    ) -> Result<QueryParamsResponse, AppError> {
+        let distribution_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(DISTRIBUTION_STORE_PREFIX.into());
+
+        Ok(QueryParamsResponse {
+            params: Some(read_params(&distribution_store)),
+        })
+    }
+
+    pub fn query_validator_outstanding_rewards(
+        ctx: &QueryContext,
+        req: QueryValidatorOutstandingRewardsRequest,
+    ) -> Result<QueryValidatorOutstandingRewardsResponse, AppError> {
+        let validator_address = parse_query_validator_address(&req.validator_address)?;
+
+        let distribution_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(DISTRIBUTION_STORE_PREFIX.into());
+        let rewards = read_outstanding_rewards(&distribution_store, &validator_address);
+
+        Ok(QueryValidatorOutstandingRewardsResponse {
+            rewards: Some(ValidatorOutstandingRewards {
+                rewards: dec_coins_to_proto(&rewards),
+            }),
+        })
+    }
+
+    pub fn query_validator_commission(
+        ctx: &QueryContext,
+        req: QueryValidatorCommissionRequest,
+    ) -> Result<QueryValidatorCommissionResponse, AppError> {
+        let validator_address = parse_query_validator_address(&req.validator_address)?;
+
+        let distribution_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(DISTRIBUTION_STORE_PREFIX.into());
+        let commission = read_accumulated_commission(&distribution_store, &validator_address);
+
+        Ok(QueryValidatorCommissionResponse {
+            commission: Some(ValidatorAccumulatedCommission {
+                commission: dec_coins_to_proto(&commission),
+            }),
+        })
+    }
+
+    /// Returns the slash events of the validator between the starting and ending heights,
+    /// inclusive
+    pub fn query_validator_slashes(
+        ctx: &QueryContext,
+        req: QueryValidatorSlashesRequest,
+    ) -> Result<QueryValidatorSlashesResponse, AppError> {
+        let validator_address = parse_query_validator_address(&req.validator_address)?;
+
+        if req.ending_height < req.starting_height {
+            return Err(ERR_INVALID_REQUEST.wrap(format!(
+                "starting height greater than ending height ({} > {})",
+                req.starting_height, req.ending_height
+            )));
+        }
+
+        let distribution_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(DISTRIBUTION_STORE_PREFIX.into());
+
+        let slashes = VALIDATOR_SLASH_EVENTS
+            .prefix_iter(&distribution_store, &validator_address)
+            .filter(|((_, (height, _)), _)| {
+                *height >= req.starting_height && *height <= req.ending_height
+            })
+            .map(|(key, event)| (VALIDATOR_SLASH_EVENTS.key(&key), event));
+        let (slashes, pagination) = paginate(slashes, req.pagination)?;
+
+        Ok(QueryValidatorSlashesResponse {
+            slashes,
+            pagination,
+        })
+    }
+
+    /// Returns the rewards the delegation would withdraw now, without ending the validator's
+    /// current period
+    pub fn query_delegation_rewards(
+        ctx: &QueryContext,
+        req: QueryDelegationRewardsRequest,
+    ) -> Result<QueryDelegationRewardsResponse, AppError> {
+        let delegator_address = parse_query_delegator_address(&req.delegator_address)?;
+        let validator_address = parse_query_validator_address(&req.validator_address)?;
+
+        let staking_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(STAKING_STORE_PREFIX.into());
+        let validator = Staking::read_validator(&staking_store, &validator_address)
+            .ok_or_else(|| ERR_NO_VALIDATOR_EXISTS.wrap(req.validator_address.clone()))?;
+        let delegation =
+            Staking::read_delegation(&staking_store, &delegator_address, &validator_address)
+                .ok_or(AppError::from(ERR_NO_DELEGATION_EXISTS))?;
+
+        let distribution_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(DISTRIBUTION_STORE_PREFIX.into());
+        let rewards = calculate_delegation_rewards(
+            &distribution_store,
+            &validator,
+            &delegation,
+            &current_reward_ratio(&distribution_store, &validator),
+            ctx.height().into(),
+        );
+
+        Ok(QueryDelegationRewardsResponse {
+            rewards: dec_coins_to_proto(&rewards),
+        })
+    }
+
+    pub fn query_delegation_total_rewards(
+        ctx: &QueryContext,
+        req: QueryDelegationTotalRewardsRequest,
+    ) -> Result<QueryDelegationTotalRewardsResponse, AppError> {
+        let delegator_address = parse_query_delegator_address(&req.delegator_address)?;
+
+        let staking_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(STAKING_STORE_PREFIX.into());
+        let distribution_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(DISTRIBUTION_STORE_PREFIX.into());
+
+        let mut rewards = vec![];
+        let mut total = vec![];
+        for delegation in Staking::read_delegator_delegations(&staking_store, &delegator_address) {
+            let validator = Staking::read_validator(&staking_store, &delegation.validator_address)
+                .expect("delegations are to existing validators");
+
+            let reward = calculate_delegation_rewards(
+                &distribution_store,
+                &validator,
+                &delegation,
+                &current_reward_ratio(&distribution_store, &validator),
+                ctx.height().into(),
+            );
+            total = add_dec_coins(&total, &reward);
+
+            rewards.push(DelegationDelegatorReward {
+                validator_address: validator.operator_address.to_string(),
+                reward: dec_coins_to_proto(&reward),
+            });
+        }
+
+        Ok(QueryDelegationTotalRewardsResponse {
+            rewards,
+            total: dec_coins_to_proto(&total),
+        })
+    }
+
+    pub fn query_delegator_validators(
+        ctx: &QueryContext,
+        req: QueryDelegatorValidatorsRequest,
+    ) -> Result<QueryDelegatorValidatorsResponse, AppError> {
+        let delegator_address = parse_query_delegator_address(&req.delegator_address)?;
+
+        let staking_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(STAKING_STORE_PREFIX.into());
+
+        let validators = Staking::read_delegator_delegations(&staking_store, &delegator_address)
+            .into_iter()
+            .map(|delegation| delegation.validator_address.to_string())
+            .collect();
+
+        Ok(QueryDelegatorValidatorsResponse { validators })
+    }
+
+    pub fn query_delegator_withdraw_address(
+        ctx: &QueryContext,
+        req: QueryDelegatorWithdrawAddressRequest,
+    ) -> Result<QueryDelegatorWithdrawAddressResponse, AppError> {
+        let delegator_address = parse_query_delegator_address(&req.delegator_address)?;
+
+        let distribution_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(DISTRIBUTION_STORE_PREFIX.into());
+
+        Ok(QueryDelegatorWithdrawAddressResponse {
+            withdraw_address: read_withdraw_addr(&distribution_store, &delegator_address)
+                .to_string(),
+        })
+    }
+
+    pub fn query_community_pool(
+        ctx: &QueryContext,
+        _req: QueryCommunityPoolRequest,
+    ) -> Result<QueryCommunityPoolResponse, AppError> {
+        let distribution_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(DISTRIBUTION_STORE_PREFIX.into());
+
+        Ok(QueryCommunityPoolResponse {
+            pool: dec_coins_to_proto(&read_community_pool(&distribution_store)),
+        })
+    }
+
+    /// Handles MsgSetWithdrawAddress, which sets the address the delegator's rewards and, for
+    /// an operator, their commission are sent to
+    pub fn set_withdraw_address_msg(
+        ctx: &mut Context,
+        msg: MsgSetWithdrawAddress,
+    ) -> Result<MsgSetWithdrawAddressResponse, AppError> {
+        let delegator_address = AccAddress::from_bech32(&msg.delegator_address)?;
+        let withdraw_address = AccAddress::from_bech32(&msg.withdraw_address)?;
+
+        Distribution::set_withdraw_addr(ctx, &delegator_address, &withdraw_address)?;
+
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_MESSAGE,
+            vec![
+                (ATTRIBUTE_KEY_MODULE, MODULE_NAME.into()),
+                (ATTRIBUTE_KEY_SENDER, msg.delegator_address),
+            ],
+        ));
+
+        Ok(MsgSetWithdrawAddressResponse {})
+    }
+
+    /// Handles MsgWithdrawDelegatorReward
+    pub fn withdraw_delegator_reward_msg(
+        ctx: &mut Context,
+        msg: MsgWithdrawDelegatorReward,
+    ) -> Result<MsgWithdrawDelegatorRewardResponse, AppError> {
+        let delegator_address = AccAddress::from_bech32(&msg.delegator_address)?;
+        let validator_address = ValAddress::from_bech32(&msg.validator_address)?;
+
+        Distribution::withdraw_delegation_rewards(ctx, &delegator_address, &validator_address)?;
+
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_MESSAGE,
+            vec![
+                (ATTRIBUTE_KEY_MODULE, MODULE_NAME.into()),
+                (ATTRIBUTE_KEY_SENDER, msg.delegator_address),
+            ],
+        ));
+
+        Ok(MsgWithdrawDelegatorRewardResponse {})
+    }
+
+    /// Handles MsgWithdrawValidatorCommission
+    pub fn withdraw_validator_commission_msg(
+        ctx: &mut Context,
+        msg: MsgWithdrawValidatorCommission,
+    ) -> Result<MsgWithdrawValidatorCommissionResponse, AppError> {
+        let validator_address = ValAddress::from_bech32(&msg.validator_address)?;
+
+        Distribution::withdraw_validator_commission(ctx, &validator_address)?;
+
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_MESSAGE,
+            vec![
+                (ATTRIBUTE_KEY_MODULE, MODULE_NAME.into()),
+                (ATTRIBUTE_KEY_SENDER, msg.validator_address),
+            ],
+        ));
+
+        Ok(MsgWithdrawValidatorCommissionResponse {})
+    }
+
+    /// Handles MsgFundCommunityPool
+    pub fn fund_community_pool_msg(
+        ctx: &mut Context,
+        msg: MsgFundCommunityPool,
+    ) -> Result<MsgFundCommunityPoolResponse, AppError> {
+        let depositor = AccAddress::from_bech32(&msg.depositor)?;
+
+        Distribution::fund_community_pool(ctx, &msg.amount, &depositor)?;
+
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_MESSAGE,
+            vec![
+                (ATTRIBUTE_KEY_MODULE, MODULE_NAME.into()),
+                (ATTRIBUTE_KEY_SENDER, msg.depositor),
+            ],
+        ));
+
+        Ok(MsgFundCommunityPoolResponse {})
+    }
+
+    /// Distributes the fees collected in the previous block to the validators which voted on
+    /// it, then records the block's proposer so that it's rewarded in the next block. As with
+    /// the cosmos SDK distribution `BeginBlocker`.
+    pub fn begin_block(ctx: &mut Context) {
+        let votes = ctx.vote_info().to_vec();
+
+        let mut total_power = 0;
+        let mut signed_power = 0;
+        for vote in &votes {
+            let power = vote
+                .validator
+                .as_ref()
+                .map_or(0, |validator| validator.power);
+            total_power += power;
+            if vote.signed_last_block {
+                signed_power += power;
+            }
+        }
+
+        // the votes in the first block are for the genesis state
+        if ctx.block_height() > 1 {
+            let distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+            let previous_proposer = PREVIOUS_PROPOSER
+                .get(&distribution_store)
+                .expect("previous proposer not set");
+            drop(distribution_store);
+
+            Distribution::allocate_tokens(
+                ctx,
+                signed_power,
+                total_power,
+                &previous_proposer,
+                &votes,
+            );
+        }
+
+        let proposer = ConsAddress::try_from(ctx.header().proposer_address.to_vec())
+            .expect("Tendermint gives 20 byte validator addresses");
+        let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+        PREVIOUS_PROPOSER.set(&mut distribution_store, &proposer);
+    }
+
+    /// Sets the address the delegator's rewards are sent to, which may not be a module account
+    pub fn set_withdraw_addr(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        withdraw_address: &AccAddress,
+    ) -> Result<(), AppError> {
+        // as in gaia any module account is blocked, including the distribution module's
+        let blocked = MODULE_ACCOUNT_PERMISSIONS
+            .iter()
+            .any(|(name, _)| &AccAddress::new_module_address(name) == withdraw_address);
+        if blocked {
+            return Err(ERR_UNAUTHORIZED.wrap(format!(
+                "{} is not allowed to receive external funds",
+                withdraw_address
+            )));
+        }
+
+        let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+        if !read_params(&distribution_store).withdraw_addr_enabled {
+            return Err(ERR_SET_WITHDRAW_ADDR_DISABLED.into());
+        }
+
+        DELEGATOR_WITHDRAW_ADDRS.set(&mut distribution_store, delegator_address, withdraw_address);
+        drop(distribution_store);
+
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_SET_WITHDRAW_ADDRESS,
+            vec![(ATTRIBUTE_KEY_WITHDRAW_ADDRESS, withdraw_address.to_string())],
+        ));
+
+        Ok(())
+    }
+
+    pub fn get_withdraw_addr(ctx: &mut Context, delegator_address: &AccAddress) -> AccAddress {
+        let distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+        read_withdraw_addr(&distribution_store, delegator_address)
+    }
+
+    /// Sends the delegation's rewards to the delegator's withdraw address and starts a new
+    /// period for the delegation, returning the rewards
+    pub fn withdraw_delegation_rewards(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        validator_address: &ValAddress,
+    ) -> Result<Vec<Coin>, AppError> {
+        let validator = Staking::get_validator(ctx, validator_address)
+            .ok_or(AppError::from(ERR_NO_VALIDATOR_DIST_INFO))?;
+        let delegation = Staking::get_delegation(ctx, delegator_address, validator_address)
+            .ok_or(AppError::from(ERR_EMPTY_DELEGATION_DIST_INFO))?;
+
+        let rewards = Distribution::withdraw_rewards(ctx, &validator, &delegation)?;
+
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_WITHDRAW_REWARDS,
+            vec![
+                (ATTRIBUTE_KEY_AMOUNT, coins_to_string(&rewards)),
+                (ATTRIBUTE_KEY_VALIDATOR, validator_address.to_string()),
+            ],
+        ));
+
+        Distribution::initialize_delegation(ctx, delegator_address, validator_address);
+
+        Ok(rewards)
+    }
+
+    /// Sends the whole coins of the validator's accumulated commission to the operator's
+    /// withdraw address, the fractions remain as commission
+    pub fn withdraw_validator_commission(
+        ctx: &mut Context,
+        validator_address: &ValAddress,
+    ) -> Result<Vec<Coin>, AppError> {
+        let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+
+        let commission = read_accumulated_commission(&distribution_store, validator_address);
+        if commission.is_empty() {
+            return Err(ERR_NO_VALIDATOR_COMMISSION.into());
+        }
+
+        let (coins, remainder) = truncate_dec_coins(&commission);
+        set_accumulated_commission(&mut distribution_store, validator_address, &remainder);
+
+        let outstanding = read_outstanding_rewards(&distribution_store, validator_address);
+        set_outstanding_rewards(
+            &mut distribution_store,
+            validator_address,
+            &sub_dec_coins(&outstanding, &dec_coins_from_coins(&coins)),
+        );
+        drop(distribution_store);
+
+        if !coins.is_empty() {
+            let withdraw_address =
+                Distribution::get_withdraw_addr(ctx, &AccAddress::from(validator_address.clone()));
+            Bank::send_coins_from_module_to_account(ctx, MODULE_NAME, &withdraw_address, &coins)?;
+        }
+
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_WITHDRAW_COMMISSION,
+            vec![(ATTRIBUTE_KEY_AMOUNT, coins_to_string(&coins))],
+        ));
+
+        Ok(coins)
+    }
+
+    /// Moves coins from the depositor to the community pool
+    pub fn fund_community_pool(
+        ctx: &mut Context,
+        amount: &[Coin],
+        depositor: &AccAddress,
+    ) -> Result<(), AppError> {
+        Bank::send_coins_from_account_to_module(ctx, depositor, MODULE_NAME, amount)?;
+
+        let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+        add_to_community_pool(&mut distribution_store, &dec_coins_from_coins(amount));
+
+        Ok(())
+    }
+
+    /// Sets up the reward tracking of a new validator, as with the cosmos SDK distribution
+    /// `AfterValidatorCreated` hook
+    pub fn after_validator_created(ctx: &mut Context, validator_address: &ValAddress) {
+        let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+
+        // the period before the first period, whose ratio is zero, is referenced by the first
+        VALIDATOR_HISTORICAL_REWARDS.set(
+            &mut distribution_store,
+            &(validator_address.clone(), 0),
+            &ValidatorHistoricalRewards {
+                cumulative_reward_ratio: vec![],
+                reference_count: 1,
+            },
+        );
+        VALIDATOR_CURRENT_REWARDS.set(
+            &mut distribution_store,
+            validator_address,
+            &ValidatorCurrentRewards {
+                rewards: vec![],
+                period: 1,
+            },
+        );
+        set_accumulated_commission(&mut distribution_store, validator_address, &[]);
+        set_outstanding_rewards(&mut distribution_store, validator_address, &[]);
+    }
+
+    /// Ends the validator's current period so that a new delegation starts in the next one, as
+    /// with the cosmos SDK distribution `BeforeDelegationCreated` hook
+    pub fn before_delegation_created(ctx: &mut Context, validator_address: &ValAddress) {
+        let validator =
+            Staking::get_validator(ctx, validator_address).expect("the validator exists");
+        Distribution::increment_validator_period(ctx, &validator);
+    }
+
+    /// Withdraws the delegation's rewards before its shares change, as with the cosmos SDK
+    /// distribution `BeforeDelegationSharesModified` hook
+    pub fn before_delegation_shares_modified(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        validator_address: &ValAddress,
+    ) {
+        let validator =
+            Staking::get_validator(ctx, validator_address).expect("the validator exists");
+        let delegation = Staking::get_delegation(ctx, delegator_address, validator_address)
+            .expect("the delegation exists");
+
+        if let Err(e) = Distribution::withdraw_rewards(ctx, &validator, &delegation) {
+            panic!("{}", e)
+        }
+    }
+
+    /// Starts tracking the rewards of the delegation's new shares, as with the cosmos SDK
+    /// distribution `AfterDelegationModified` hook
+    pub fn after_delegation_modified(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        validator_address: &ValAddress,
+    ) {
+        Distribution::initialize_delegation(ctx, delegator_address, validator_address);
+    }
+
+    /// Records the fraction of the validator's tokens being slashed, which ends its current
+    /// period, as with the cosmos SDK distribution `BeforeValidatorSlashed` hook
+    pub fn before_validator_slashed(
+        ctx: &mut Context,
+        validator: &Validator,
+        fraction: Decimal256,
+    ) {
+        if fraction > Decimal256::one() {
+            panic!(
+                "fraction must be >=0 and <=1, current fraction: {}",
+                fraction
+            )
+        }
+
+        let period = Distribution::increment_validator_period(ctx, validator);
+        let height = ctx.block_height() as u64;
+
+        let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+        let address = &validator.operator_address;
+        increment_reference_count(&mut distribution_store, address, period);
+        VALIDATOR_SLASH_EVENTS.set(
+            &mut distribution_store,
+            &(address.clone(), (height, period)),
+            &ValidatorSlashEvent {
+                validator_period: period,
+                fraction: fraction.to_cosmos_proto_string(),
+            },
+        );
+    }
+
+    /// Pays out the commission of a removed validator and clears its records, what's left of
+    /// its outstanding rewards is dust which goes to the community pool. As with the cosmos SDK
+    /// distribution `AfterValidatorRemoved` hook.
+    pub fn after_validator_removed(ctx: &mut Context, validator_address: &ValAddress) {
+        let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+        let mut outstanding = read_outstanding_rewards(&distribution_store, validator_address);
+        let commission = read_accumulated_commission(&distribution_store, validator_address);
+
+        let mut coins = vec![];
+        if !commission.is_empty() {
+            outstanding = sub_dec_coins(&outstanding, &commission);
+
+            let (truncated, remainder) = truncate_dec_coins(&commission);
+            add_to_community_pool(&mut distribution_store, &remainder);
+            coins = truncated;
+        }
+        drop(distribution_store);
+
+        if !coins.is_empty() {
+            let withdraw_address =
+                Distribution::get_withdraw_addr(ctx, &AccAddress::from(validator_address.clone()));
+            if let Err(e) =
+                Bank::send_coins_from_module_to_account(ctx, MODULE_NAME, &withdraw_address, &coins)
+            {
+                panic!("{}", e)
+            }
+        }
+
+        let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+        add_to_community_pool(&mut distribution_store, &outstanding);
+
+        VALIDATOR_OUTSTANDING_REWARDS.remove(&mut distribution_store, validator_address);
+        VALIDATOR_ACCUMULATED_COMMISSIONS.remove(&mut distribution_store, validator_address);
+        VALIDATOR_CURRENT_REWARDS.remove(&mut distribution_store, validator_address);
+
+        let slash_events: Vec<_> = VALIDATOR_SLASH_EVENTS
+            .prefix_iter(&distribution_store, validator_address)
+            .map(|(key, _)| key)
+            .collect();
+        for key in slash_events {
+            VALIDATOR_SLASH_EVENTS.remove(&mut distribution_store, &key);
+        }
+
+        let historical_rewards: Vec<_> = VALIDATOR_HISTORICAL_REWARDS
+            .prefix_iter(&distribution_store, validator_address)
+            .map(|(key, _)| key)
+            .collect();
+        for key in historical_rewards {
+            VALIDATOR_HISTORICAL_REWARDS.remove(&mut distribution_store, &key);
+        }
+    }
+
+    /// Moves the fees collected in the previous block to the distribution module and allocates
+    /// them to the previous proposer, the validators which voted and the community pool. As
+    /// with the cosmos SDK distribution `AllocateTokens`.
+    fn allocate_tokens(
+        ctx: &mut Context,
+        signed_power: i64,
+        total_power: i64,
+        previous_proposer: &ConsAddress,
+        votes: &[VoteInfo],
+    ) {
+        let fees = Bank::get_all_balances(ctx, &AccAddress::new_module_address(FEE_COLLECTOR_NAME));
+        Bank::send_coins_from_module_to_module(ctx, FEE_COLLECTOR_NAME, MODULE_NAME, &fees)
+            .expect("the fee collector holds the fees");
+        let fees = dec_coins_from_coins(&fees);
+
+        if total_power == 0 {
+            let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+            add_to_community_pool(&mut distribution_store, &fees);
+            return;
+        }
+
+        let params = Distribution::get_params(ctx);
+        let community_tax = parse_dec(&params.community_tax);
+        let base_proposer_reward = parse_dec(&params.base_proposer_reward);
+        let bonus_proposer_reward = parse_dec(&params.bonus_proposer_reward);
+
+        // the proposer's reward grows with the power which signed the block it proposed
+        let signed_fraction = dec_from_power(signed_power).div_rounded(dec_from_power(total_power));
+        let proposer_multiplier = base_proposer_reward + bonus_proposer_reward * signed_fraction;
+        let proposer_reward = mul_dec_truncate(&fees, proposer_multiplier);
+
+        let mut remaining = fees.clone();
+        match Staking::get_validator_by_cons_addr(ctx, previous_proposer) {
+            Some(proposer) => {
+                ctx.event_manager().emit_event(new_event(
+                    EVENT_TYPE_PROPOSER_REWARD,
+                    vec![
+                        (ATTRIBUTE_KEY_AMOUNT, dec_coins_to_string(&proposer_reward)),
+                        (
+                            ATTRIBUTE_KEY_VALIDATOR,
+                            proposer.operator_address.to_string(),
+                        ),
+                    ],
+                ));
+
+                Distribution::allocate_tokens_to_validator(ctx, &proposer, &proposer_reward);
+                remaining = sub_dec_coins(&remaining, &proposer_reward);
+            }
+            // the proposer may have unbonded completely within a single block
+            None => error!(
+                "attempt to allocate proposer rewards to unknown proposer {}",
+                previous_proposer
+            ),
+        }
+
+        let vote_multiplier = Decimal256::one() - proposer_multiplier - community_tax;
+        let voter_rewards = mul_dec_truncate(&fees, vote_multiplier);
+
+        for vote in votes {
+            let vote_validator = vote.validator.clone().unwrap_or_default();
+            let cons_address = ConsAddress::try_from(vote_validator.address.to_vec())
+                .expect("Tendermint gives 20 byte validator addresses");
+            // the validators of a chain started without gentxs, such as the README's single
+            // node chain, are only known to Tendermint, their rewards go to the community pool
+            let validator = match Staking::get_validator_by_cons_addr(ctx, &cons_address) {
+                Some(validator) => validator,
+                None => continue,
+            };
+
+            let power_fraction = dec_from_power(vote_validator.power) / dec_from_power(total_power);
+            let reward = mul_dec_truncate(&voter_rewards, power_fraction);
+
+            Distribution::allocate_tokens_to_validator(ctx, &validator, &reward);
+            remaining = sub_dec_coins(&remaining, &reward);
+        }
+
+        let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+        add_to_community_pool(&mut distribution_store, &remaining);
+    }
+
+    /// Splits the tokens between the validator's commission and its delegators' rewards
+    fn allocate_tokens_to_validator(ctx: &mut Context, validator: &Validator, tokens: &[DecCoin]) {
+        let address = &validator.operator_address;
+        let commission = mul_dec(tokens, validator.commission.rate);
+        let shared = sub_dec_coins(tokens, &commission);
+
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_COMMISSION,
+            vec![
+                (ATTRIBUTE_KEY_AMOUNT, dec_coins_to_string(&commission)),
+                (ATTRIBUTE_KEY_VALIDATOR, address.to_string()),
+            ],
+        ));
+
+        let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+        let accumulated = read_accumulated_commission(&distribution_store, address);
+        set_accumulated_commission(
+            &mut distribution_store,
+            address,
+            &add_dec_coins(&accumulated, &commission),
+        );
+
+        let mut current = read_current_rewards(&distribution_store, address);
+        current.rewards = dec_coins_to_proto(&add_dec_coins(
+            &dec_coins_from_proto(current.rewards),
+            &shared,
+        ));
+        VALIDATOR_CURRENT_REWARDS.set(&mut distribution_store, address, &current);
+        drop(distribution_store);
+
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_REWARDS,
+            vec![
+                (ATTRIBUTE_KEY_AMOUNT, dec_coins_to_string(tokens)),
+                (ATTRIBUTE_KEY_VALIDATOR, address.to_string()),
+            ],
+        ));
+
+        let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+        let outstanding = read_outstanding_rewards(&distribution_store, address);
+        set_outstanding_rewards(
+            &mut distribution_store,
+            address,
+            &add_dec_coins(&outstanding, tokens),
+        );
+    }
+
+    /// Ends the validator's current period, storing the cumulative reward ratio it ended with,
+    /// and returns the period which ended. The rewards of a validator without tokens can't be
+    /// shared out so they go to the community pool.
+    fn increment_validator_period(ctx: &mut Context, validator: &Validator) -> u64 {
+        let address = &validator.operator_address;
+        let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+        let current = read_current_rewards(&distribution_store, address);
+
+        if validator.tokens.is_zero() {
+            let rewards = dec_coins_from_proto(current.rewards.clone());
+            add_to_community_pool(&mut distribution_store, &rewards);
+
+            let outstanding = read_outstanding_rewards(&distribution_store, address);
+            set_outstanding_rewards(
+                &mut distribution_store,
+                address,
+                &sub_dec_coins(&outstanding, &rewards),
+            );
+        }
+
+        let ratio = current_reward_ratio(&distribution_store, validator);
+        decrement_reference_count(&mut distribution_store, address, current.period - 1);
+
+        VALIDATOR_HISTORICAL_REWARDS.set(
+            &mut distribution_store,
+            &(address.clone(), current.period),
+            &ValidatorHistoricalRewards {
+                cumulative_reward_ratio: dec_coins_to_proto(&ratio),
+                reference_count: 1,
+            },
+        );
+        VALIDATOR_CURRENT_REWARDS.set(
+            &mut distribution_store,
+            address,
+            &ValidatorCurrentRewards {
+                rewards: vec![],
+                period: current.period + 1,
+            },
+        );
+
+        current.period
+    }
+
+    /// Records the stake of the delegation and the period it starts after, which the
+    /// delegation's rewards are later calculated from
+    fn initialize_delegation(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        validator_address: &ValAddress,
+    ) {
+        let validator =
+            Staking::get_validator(ctx, validator_address).expect("the validator exists");
+        let delegation = Staking::get_delegation(ctx, delegator_address, validator_address)
+            .expect("the delegation exists");
+        let height = ctx.block_height() as u64;
+
+        let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+
+        // the validator's period has already been incremented, the delegation starts after the
+        // period which just ended
+        let previous_period =
+            read_current_rewards(&distribution_store, validator_address).period - 1;
+        increment_reference_count(&mut distribution_store, validator_address, previous_period);
+
+        // truncated so that more rewards than are owed can't be withdrawn
+        let stake = validator.dec_tokens_from_shares_truncated(delegation.shares);
+
+        DELEGATOR_STARTING_INFOS.set(
+            &mut distribution_store,
+            &(validator_address.clone(), delegator_address.clone()),
+            &DelegatorStartingInfo {
+                previous_period,
+                stake: stake.to_cosmos_proto_string(),
+                height,
+            },
+        );
+    }
+
+    /// Ends the validator's period and sends the delegation's rewards to the delegator's
+    /// withdraw address. The fractions of the rewards which can't be sent go to the community
+    /// pool. The delegation's starting info is removed, it must be initialized again.
+    fn withdraw_rewards(
+        ctx: &mut Context,
+        validator: &Validator,
+        delegation: &Delegation,
+    ) -> Result<Vec<Coin>, AppError> {
+        let validator_address = &validator.operator_address;
+        let starting_info_key = (
+            validator_address.clone(),
+            delegation.delegator_address.clone(),
+        );
+
+        let distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+        let starting_info = DELEGATOR_STARTING_INFOS
+            .get(&distribution_store, &starting_info_key)
+            .ok_or(AppError::from(ERR_EMPTY_DELEGATION_DIST_INFO))?;
+        drop(distribution_store);
+
+        let ending_period = Distribution::increment_validator_period(ctx, validator);
+        let height = ctx.block_height() as u64;
+
+        let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+        let ending_ratio = dec_coins_from_proto(
+            read_historical_rewards(&distribution_store, validator_address, ending_period)
+                .cumulative_reward_ratio,
+        );
+        let raw_rewards = calculate_delegation_rewards(
+            &distribution_store,
+            validator,
+            delegation,
+            &ending_ratio,
+            height,
+        );
+
+        // the rewards may exceed the outstanding rewards in the last digits due to the order of
+        // the operations
+        let outstanding = read_outstanding_rewards(&distribution_store, validator_address);
+        let rewards = intersect_dec_coins(&raw_rewards, &outstanding);
+        let (coins, remainder) = truncate_dec_coins(&rewards);
+        drop(distribution_store);
+
+        if !coins.is_empty() {
+            let withdraw_address =
+                Distribution::get_withdraw_addr(ctx, &delegation.delegator_address);
+            Bank::send_coins_from_module_to_account(ctx, MODULE_NAME, &withdraw_address, &coins)?;
+        }
+
+        let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+        set_outstanding_rewards(
+            &mut distribution_store,
+            validator_address,
+            &sub_dec_coins(&outstanding, &rewards),
+        );
+        add_to_community_pool(&mut distribution_store, &remainder);
+
+        decrement_reference_count(
+            &mut distribution_store,
+            validator_address,
+            starting_info.previous_period,
+        );
+        DELEGATOR_STARTING_INFOS.remove(&mut distribution_store, &starting_info_key);
+
+        Ok(coins)
+    }
+
+    fn get_params(ctx: &Context) -> Params {
+        let distribution_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(DISTRIBUTION_STORE_PREFIX.into());
+        read_params(&distribution_store)
+    }
+}
+
+/// Returns the rewards of the delegation from its starting period up to the given ending
+/// ratio. Each slash of the validator since the delegation started splits the calculation,
+/// as the stake is reduced by the slash. As with the cosmos SDK `CalculateDelegationRewards`.
+fn calculate_delegation_rewards<S: ReadKVStore + ?Sized>(
+    distribution_store: &S,
+    validator: &Validator,
+    delegation: &Delegation,
+    ending_ratio: &[DecCoin],
+    height: u64,
+) -> Vec<DecCoin> {
+    let address = &validator.operator_address;
+    let starting_info = DELEGATOR_STARTING_INFOS
+        .get(
+            distribution_store,
+            &(address.clone(), delegation.delegator_address.clone()),
+        )
+        .expect("delegations have starting info");
+
+    // the delegation started at this height so it has no rewards yet
+    if starting_info.height == height {
+        return vec![];
+    }
+
+    let mut rewards = vec![];
+    let mut starting_period = starting_info.previous_period;
+    let mut stake = parse_dec(&starting_info.stake);
+
+    let slash_events = VALIDATOR_SLASH_EVENTS
+        .prefix_iter(distribution_store, address)
+        .filter(|((_, (slash_height, _)), _)| {
+            *slash_height >= starting_info.height && *slash_height <= height
+        });
+    for (_, event) in slash_events {
+        if event.validator_period > starting_period {
+            let event_ratio = dec_coins_from_proto(
+                read_historical_rewards(distribution_store, address, event.validator_period)
+                    .cumulative_reward_ratio,
+            );
+            rewards = add_dec_coins(
+                &rewards,
+                &rewards_between(
+                    distribution_store,
+                    address,
+                    starting_period,
+                    &event_ratio,
+                    stake,
+                ),
+            );
+
+            // truncated so that more rewards than are owed can't be withdrawn
+            stake = stake * (Decimal256::one() - parse_dec(&event.fraction));
+            starting_period = event.validator_period;
+        }
+    }
+
+    // the stake calculated through the slashes is rounded differently to the current stake, so
+    // it may be slightly greater
+    let current_stake = validator.dec_tokens_from_shares(delegation.shares);
+    if stake > current_stake {
+        let margin_of_error = Decimal256::raw(3);
+        if stake <= current_stake + margin_of_error {
+            stake = current_stake;
+        } else {
+            panic!(
+                "calculated final stake for delegator {} greater than current stake\n\tfinal stake:\t{}\n\tcurrent stake:\t{}",
+                delegation.delegator_address, stake, current_stake
+            )
+        }
+    }
+
+    add_dec_coins(
+        &rewards,
+        &rewards_between(
+            distribution_store,
+            address,
+            starting_period,
+            ending_ratio,
+            stake,
+        ),
+    )
+}
+
+/// Returns the rewards of the stake between the starting period and the ending ratio
+fn rewards_between<S: ReadKVStore + ?Sized>(
+    distribution_store: &S,
+    validator_address: &ValAddress,
+    starting_period: u64,
+    ending_ratio: &[DecCoin],
+    stake: Decimal256,
+) -> Vec<DecCoin> {
+    let starting_ratio = dec_coins_from_proto(
+        read_historical_rewards(distribution_store, validator_address, starting_period)
+            .cumulative_reward_ratio,
+    );
+
+    mul_dec_truncate(&sub_dec_coins(ending_ratio, &starting_ratio), stake)
+}
+
+/// Returns the cumulative reward ratio the validator's current period would end with
+fn current_reward_ratio<S: ReadKVStore + ?Sized>(
+    distribution_store: &S,
+    validator: &Validator,
+) -> Vec<DecCoin> {
+    let address = &validator.operator_address;
+    let current = read_current_rewards(distribution_store, address);
+    let previous = dec_coins_from_proto(
+        read_historical_rewards(distribution_store, address, current.period - 1)
+            .cumulative_reward_ratio,
+    );
+
+    // a validator without tokens has no ratio, its rewards go to the community pool
+    if validator.tokens.is_zero() {
+        return previous;
+    }
+
+    // truncated so that more rewards than are owed can't be withdrawn
+    let increase = quo_dec_truncate(
+        &dec_coins_from_proto(current.rewards),
+        Decimal256::from_ratio(validator.tokens, 1u8),
+    );
+
+    add_dec_coins(&previous, &increase)
+}
+
+fn increment_reference_count<S: WriteKVStore + ?Sized>(
+    distribution_store: &mut S,
+    validator_address: &ValAddress,
+    period: u64,
+) {
+    let key = (validator_address.clone(), period);
+    let mut historical = read_historical_rewards(distribution_store, validator_address, period);
+
+    historical.reference_count += 1;
+    if historical.reference_count > 2 {
+        panic!("reference count should never exceed 2")
+    }
+
+    VALIDATOR_HISTORICAL_REWARDS.set(distribution_store, &key, &historical);
+}
+
+/// Decrements the reference count of the period, which is removed once it's no longer
+/// referenced
+fn decrement_reference_count<S: WriteKVStore + ?Sized>(
+    distribution_store: &mut S,
+    validator_address: &ValAddress,
+    period: u64,
+) {
+    let key = (validator_address.clone(), period);
+    let mut historical = read_historical_rewards(distribution_store, validator_address, period);
+
+    if historical.reference_count == 0 {
+        panic!("cannot set negative reference count")
+    }
+
+    historical.reference_count -= 1;
+    if historical.reference_count == 0 {
+        VALIDATOR_HISTORICAL_REWARDS.remove(distribution_store, &key);
+    } else {
+        VALIDATOR_HISTORICAL_REWARDS.set(distribution_store, &key, &historical);
+    }
+}
+
+fn read_params<S: ReadKVStore + ?Sized>(distribution_store: &S) -> Params {
+    PARAMS
+        .get(distribution_store)
+        .expect("distribution params are set in init_genesis")
+}
+
+fn read_community_pool<S: ReadKVStore + ?Sized>(distribution_store: &S) -> Vec<DecCoin> {
+    FEE_POOL
+        .get(distribution_store)
+        .map(|pool| dec_coins_from_proto(pool.community_pool))
+        .unwrap_or_default()
+}
+
+fn add_to_community_pool<S: WriteKVStore + ?Sized>(distribution_store: &mut S, coins: &[DecCoin]) {
+    let community_pool = add_dec_coins(&read_community_pool(distribution_store), coins);
+    FEE_POOL.set(
+        distribution_store,
+        &FeePool {
+            community_pool: dec_coins_to_proto(&community_pool),
+        },
+    );
+}
+
+/// Returns the address the delegator's rewards are sent to, which is the delegator unless
+/// they've set a withdraw address
+fn read_withdraw_addr<S: ReadKVStore + ?Sized>(
+    distribution_store: &S,
+    delegator_address: &AccAddress,
+) -> AccAddress {
+    DELEGATOR_WITHDRAW_ADDRS
+        .get(distribution_store, delegator_address)
+        .unwrap_or_else(|| delegator_address.clone())
+}
+
+fn read_outstanding_rewards<S: ReadKVStore + ?Sized>(
+    distribution_store: &S,
+    validator_address: &ValAddress,
+) -> Vec<DecCoin> {
+    VALIDATOR_OUTSTANDING_REWARDS
+        .get(distribution_store, validator_address)
+        .map(|outstanding| dec_coins_from_proto(outstanding.rewards))
+        .unwrap_or_default()
+}
+
+fn set_outstanding_rewards<S: WriteKVStore + ?Sized>(
+    distribution_store: &mut S,
+    validator_address: &ValAddress,
+    rewards: &[DecCoin],
+) {
+    VALIDATOR_OUTSTANDING_REWARDS.set(
+        distribution_store,
+        validator_address,
+        &ValidatorOutstandingRewards {
+            rewards: dec_coins_to_proto(rewards),
+        },
+    );
+}
+
+fn read_accumulated_commission<S: ReadKVStore + ?Sized>(
+    distribution_store: &S,
+    validator_address: &ValAddress,
+) -> Vec<DecCoin> {
+    VALIDATOR_ACCUMULATED_COMMISSIONS
+        .get(distribution_store, validator_address)
+        .map(|commission| dec_coins_from_proto(commission.commission))
+        .unwrap_or_default()
+}
+
+fn set_accumulated_commission<S: WriteKVStore + ?Sized>(
+    distribution_store: &mut S,
+    validator_address: &ValAddress,
+    commission: &[DecCoin],
+) {
+    VALIDATOR_ACCUMULATED_COMMISSIONS.set(
+        distribution_store,
+        validator_address,
+        &ValidatorAccumulatedCommission {
+            commission: dec_coins_to_proto(commission),
+        },
+    );
+}
+
+fn read_current_rewards<S: ReadKVStore + ?Sized>(
+    distribution_store: &S,
+    validator_address: &ValAddress,
+) -> ValidatorCurrentRewards {
+    VALIDATOR_CURRENT_REWARDS
+        .get(distribution_store, validator_address)
+        .expect("current rewards are set when the validator is created")
+}
+
+fn read_historical_rewards<S: ReadKVStore + ?Sized>(
+    distribution_store: &S,
+    validator_address: &ValAddress,
+    period: u64,
+) -> ValidatorHistoricalRewards {
+    VALIDATOR_HISTORICAL_REWARDS
+        .get(distribution_store, &(validator_address.clone(), period))
+        .expect("historical rewards are kept while they're referenced")
+}
+
+/// Multiplies each coin by the decimal, rounding half to even as with the cosmos SDK
+/// `DecCoins.MulDec`. Zero amounts are removed.
+fn mul_dec(coins: &[DecCoin], dec: Decimal256) -> Vec<DecCoin> {
+    map_amounts(coins, |amount| amount.mul_rounded(dec))
+}
+
+/// Multiplies each coin by the decimal, rounding down as with the cosmos SDK
+/// `DecCoins.MulDecTruncate`. Zero amounts are removed.
+fn mul_dec_truncate(coins: &[DecCoin], dec: Decimal256) -> Vec<DecCoin> {
+    map_amounts(coins, |amount| amount * dec)
+}
+
+/// Divides each coin by the decimal, rounding down as with the cosmos SDK
+/// `DecCoins.QuoDecTruncate`. Zero amounts are removed.
+fn quo_dec_truncate(coins: &[DecCoin], dec: Decimal256) -> Vec<DecCoin> {
+    map_amounts(coins, |amount| amount / dec)
+}
+
+fn map_amounts(coins: &[DecCoin], f: impl Fn(Decimal256) -> Decimal256) -> Vec<DecCoin> {
+    coins
+        .iter()
+        .map(|coin| DecCoin {
+            denom: coin.denom.clone(),
+            amount: f(coin.amount),
+        })
+        .filter(|coin| !coin.amount.is_zero())
+        .collect()
+}
+
+fn dec_coins_from_proto(coins: Vec<ProtoDecCoin>) -> Vec<DecCoin> {
+    coins
+        .into_iter()
+        .map(|coin| DecCoin::from_proto(coin).expect("store should contain valid data"))
+        .collect()
+}
+
+fn dec_coins_to_proto(coins: &[DecCoin]) -> Vec<ProtoDecCoin> {
+    coins.iter().map(DecCoin::to_proto).collect()
+}
+
+fn dec_from_power(power: i64) -> Decimal256 {
+    Decimal256::from_ratio(power.unsigned_abs(), 1u8)
+}
+
+/// Parses a decimal written by this module, e.g. a param or a stake
+fn parse_dec(s: &str) -> Decimal256 {
+    Decimal256::from_cosmos_proto_string(s).expect("distribution decimals are valid")
+}
+
+fn parse_query_delegator_address(address: &str) -> Result<AccAddress, AppError> {
+    if address.is_empty() {
+        return Err(ERR_INVALID_REQUEST.wrap("empty delegator address"));
+    }
+
+    AccAddress::from_bech32(address)
+}
+
+fn parse_query_validator_address(address: &str) -> Result<ValAddress, AppError> {
+    if address.is_empty() {
+        return Err(ERR_INVALID_REQUEST.wrap("empty validator address"));
+    }
+
+    ValAddress::from_bech32(address)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use cosmwasm_std::Uint256;
+    use tendermint_proto::{
+        abci::{Validator as VoteValidator, VoteInfo},
+        types::Header,
+    };
+
+    use super::*;
+    use crate::{
+        error::{ERR_INSUFFICIENT_FUNDS, ERR_INVALID_COINS},
+        store::ImmutableSubStore,
+        types::parse_dec_coins,
+        x::{
+            bank::{Balance, GenesisState as BankGenesisState},
+            staking::{
+                self,
+                testing::{bond_validators, context_with_accounts, uatom},
+            },
+        },
+    };
+
+    fn dec_coins(s: &str) -> Vec<DecCoin> {
+        parse_dec_coins(s).unwrap()
+    }
+
+    fn account(byte: u8) -> AccAddress {
+        AccAddress::try_from(vec![byte; 20]).unwrap()
+    }
+
+    /// Returns a context with three bonded validators of 1 power and 10% commission, each
+    /// operated by an account which is their only delegator. The fee collector holds 100uatom.
+    fn context_with_validators() -> (Context, Vec<ValAddress>) {
+        let accounts: Vec<_> = (1..=3).map(account).collect();
+        let mut ctx = context_with_accounts(&accounts, staking::DEFAULT_MAX_VALIDATORS);
+        Bank::init_genesis(
+            &mut ctx,
+            BankGenesisState {
+                balances: vec![
+                    Balance {
+                        address: AccAddress::new_module_address(FEE_COLLECTOR_NAME),
+                        coins: vec![uatom(100)],
+                    },
+                    Balance {
+                        address: account(9),
+                        coins: vec![uatom(1_000)],
+                    },
+                ],
+            },
+        );
+        Distribution::init_genesis(
+            &mut ctx,
+            GenesisState {
+                params: default_params(),
+            },
+        );
+
+        let validators = bond_validators(&mut ctx, &accounts, 1_000_000);
+
+        (ctx, validators)
+    }
+
+    fn cons_address(ctx: &mut Context, validator_address: &ValAddress) -> ConsAddress {
+        Staking::get_validator(ctx, validator_address)
+            .unwrap()
+            .cons_address()
+    }
+
+    /// Runs begin_block at the height with signed votes of 1 power from all the validators
+    fn begin_block_at(
+        ctx: Context,
+        height: i64,
+        proposer: &ConsAddress,
+        voters: &[ConsAddress],
+    ) -> Context {
+        let mut ctx = ctx
+            .with_header(Header {
+                height,
+                proposer_address: Vec::from(proposer.clone()).into(),
+                ..Default::default()
+            })
+            .with_vote_info(
+                voters
+                    .iter()
+                    .map(|cons_address| VoteInfo {
+                        validator: Some(VoteValidator {
+                            address: Vec::from(cons_address.clone()).into(),
+                            power: 1,
+                        }),
+                        signed_last_block: true,
+                    })
+                    .collect(),
+            );
+        Distribution::begin_block(&mut ctx);
+        ctx
+    }
+
+    /// Returns the context after the fees have been allocated in block 2, the first validator
+    /// proposed block 1
+    fn context_with_rewards() -> (Context, Vec<ValAddress>) {
+        let (mut ctx, validators) = context_with_validators();
+        let voters: Vec<_> = validators
+            .iter()
+            .map(|validator_address| cons_address(&mut ctx, validator_address))
+            .collect();
+
+        let ctx = begin_block_at(ctx, 1, &voters[0], &voters);
+        let ctx = begin_block_at(ctx, 2, &voters[1], &voters);
+
+        (ctx, validators)
+    }
+
+    fn distribution_store(ctx: &Context) -> ImmutableSubStore<'_> {
+        ctx.get_multi_store()
+            .get_immutable_sub_store(DISTRIBUTION_STORE_PREFIX.into())
+    }
+
+    #[test]
+    fn begin_block_allocates_fees() {
+        let (mut ctx, validators) = context_with_validators();
+        let voters: Vec<_> = validators
+            .iter()
+            .map(|validator_address| cons_address(&mut ctx, validator_address))
+            .collect();
+
+        // the votes of the first block are for the genesis state
+        let mut ctx = begin_block_at(ctx, 1, &voters[0], &voters);
+        assert_eq!(
+            Bank::get_all_balances(
+                &mut ctx,
+                &AccAddress::new_module_address(FEE_COLLECTOR_NAME)
+            ),
+            vec![uatom(100)]
+        );
+
+        ctx.event_manager().take_events();
+        let mut ctx = begin_block_at(ctx, 2, &voters[1], &voters);
+        let events = ctx.event_manager().take_events();
+        assert!(events.contains(&new_event(
+            EVENT_TYPE_PROPOSER_REWARD,
+            vec![
+                (ATTRIBUTE_KEY_AMOUNT, "5.000000000000000000uatom".into()),
+                (ATTRIBUTE_KEY_VALIDATOR, validators[0].to_string()),
+            ],
+        )));
+
+        assert!(Bank::get_all_balances(
+            &mut ctx,
+            &AccAddress::new_module_address(FEE_COLLECTOR_NAME)
+        )
+        .is_empty());
+        assert_eq!(
+            Bank::get_all_balances(&mut ctx, &AccAddress::new_module_address(MODULE_NAME)),
+            vec![uatom(100)]
+        );
+
+        // the proposer gets 5% as all the power signed, the community 2% and the validators
+        // share the rest by power, truncated to the last decimal place as in the cosmos SDK
+        let store = distribution_store(&ctx);
+        assert_eq!(
+            read_outstanding_rewards(&store, &validators[0]),
+            dec_coins("35.999999999999999969uatom")
+        );
+        assert_eq!(
+            read_outstanding_rewards(&store, &validators[1]),
+            dec_coins("30.999999999999999969uatom")
+        );
+        assert_eq!(
+            read_community_pool(&store),
+            dec_coins("2.000000000000000093uatom")
+        );
+
+        // the commission is rounded rather than truncated
+        assert_eq!(
+            read_accumulated_commission(&store, &validators[0]),
+            dec_coins("3.599999999999999997uatom")
+        );
+        assert_eq!(
+            read_accumulated_commission(&store, &validators[1]),
+            dec_coins("3.099999999999999997uatom")
+        );
+        assert_eq!(
+            dec_coins_from_proto(read_current_rewards(&store, &validators[1]).rewards),
+            dec_coins("27.899999999999999972uatom")
+        );
+    }
+
+    #[test]
+    fn unknown_voter_rewards_go_to_community_pool() {
+        let (mut ctx, validators) = context_with_validators();
+        let voters = vec![
+            cons_address(&mut ctx, &validators[0]),
+            ConsAddress::try_from(vec![9; 20]).unwrap(),
+        ];
+
+        let ctx = begin_block_at(ctx, 1, &voters[0], &voters);
+        let ctx = begin_block_at(ctx, 2, &voters[1], &voters);
+
+        let store = distribution_store(&ctx);
+        assert_eq!(
+            read_outstanding_rewards(&store, &validators[0]),
+            dec_coins("51.5uatom")
+        );
+        assert_eq!(read_community_pool(&store), dec_coins("48.5uatom"));
+    }
+
+    #[test]
+    fn withdraw_rewards_and_commission_works() {
+        let (ctx, validators) = context_with_rewards();
+        let mut ctx = ctx.with_header(Header {
+            height: 3,
+            ..Default::default()
+        });
+        let delegator = account(2);
+
+        // the reward ratio is truncated so some of the rewards are left outstanding
+        let query_ctx = QueryContext::new(ctx.get_multi_store(), 3);
+        let res = Distribution::query_delegation_rewards(
+            &query_ctx,
+            QueryDelegationRewardsRequest {
+                delegator_address: delegator.to_string(),
+                validator_address: validators[1].to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.rewards,
+            dec_coins_to_proto(&dec_coins("27.899999999999000000uatom"))
+        );
+
+        let msg = MsgWithdrawDelegatorReward {
+            delegator_address: delegator.to_string(),
+            validator_address: validators[1].to_string(),
+        };
+        validate_withdraw_delegator_reward(&msg).unwrap();
+        ctx.event_manager().take_events();
+        Distribution::withdraw_delegator_reward_msg(&mut ctx, msg).unwrap();
+        let events = ctx.event_manager().take_events();
+        assert!(events.contains(&new_event(
+            EVENT_TYPE_WITHDRAW_REWARDS,
+            vec![
+                (ATTRIBUTE_KEY_AMOUNT, "27uatom".into()),
+                (ATTRIBUTE_KEY_VALIDATOR, validators[1].to_string()),
+            ],
+        )));
+        assert_eq!(
+            Bank::get_all_balances(&mut ctx, &delegator),
+            vec![uatom(9_000_027)]
+        );
+
+        // the fractions of the withdrawn rewards go to the community pool
+        let store = distribution_store(&ctx);
+        assert_eq!(
+            read_community_pool(&store),
+            dec_coins("2.899999999999000093uatom")
+        );
+        assert_eq!(
+            read_outstanding_rewards(&store, &validators[1]),
+            dec_coins("3.100000000000999969uatom")
+        );
+        drop(store);
+
+        // only the whole coins of the commission are withdrawn
+        let msg = MsgWithdrawValidatorCommission {
+            validator_address: validators[1].to_string(),
+        };
+        validate_withdraw_validator_commission(&msg).unwrap();
+        Distribution::withdraw_validator_commission_msg(&mut ctx, msg).unwrap();
+        assert_eq!(
+            Bank::get_all_balances(&mut ctx, &delegator),
+            vec![uatom(9_000_030)]
+        );
+
+        let store = distribution_store(&ctx);
+        assert_eq!(
+            read_accumulated_commission(&store, &validators[1]),
+            dec_coins("0.099999999999999997uatom")
+        );
+        assert_eq!(
+            read_outstanding_rewards(&store, &validators[1]),
+            dec_coins("0.100000000000999969uatom")
+        );
+        drop(store);
+
+        let err =
+            Distribution::withdraw_validator_commission(&mut ctx, &ValAddress::from(account(9)))
+                .unwrap_err();
+        assert_eq!(err.code(), ERR_NO_VALIDATOR_COMMISSION.code);
+
+        let err = Distribution::withdraw_delegation_rewards(&mut ctx, &account(9), &validators[1])
+            .unwrap_err();
+        assert_eq!(err.code(), ERR_EMPTY_DELEGATION_DIST_INFO.code);
+    }
+
+    #[test]
+    fn set_withdraw_address_works() {
+        let (ctx, validators) = context_with_rewards();
+        let mut ctx = ctx.with_header(Header {
+            height: 3,
+            ..Default::default()
+        });
+        let delegator = account(2);
+        let withdraw_address = account(8);
+
+        // module accounts can't be withdrawn to
+        let err = Distribution::set_withdraw_addr(
+            &mut ctx,
+            &delegator,
+            &AccAddress::new_module_address(MODULE_NAME),
+        )
+        .unwrap_err();
+        assert_eq!(err.code(), ERR_UNAUTHORIZED.code);
+
+        let msg = MsgSetWithdrawAddress {
+            delegator_address: delegator.to_string(),
+            withdraw_address: withdraw_address.to_string(),
+        };
+        validate_set_withdraw_address(&msg).unwrap();
+        Distribution::set_withdraw_address_msg(&mut ctx, msg).unwrap();
+
+        let query_ctx = QueryContext::new(ctx.get_multi_store(), 0);
+        let res = Distribution::query_delegator_withdraw_address(
+            &query_ctx,
+            QueryDelegatorWithdrawAddressRequest {
+                delegator_address: delegator.to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(res.withdraw_address, withdraw_address.to_string());
+
+        // both the rewards and the commission go to the withdraw address
+        Distribution::withdraw_delegation_rewards(&mut ctx, &delegator, &validators[1]).unwrap();
+        Distribution::withdraw_validator_commission(&mut ctx, &validators[1]).unwrap();
+        assert_eq!(
+            Bank::get_all_balances(&mut ctx, &withdraw_address),
+            vec![uatom(30)]
+        );
+        assert_eq!(
+            Bank::get_all_balances(&mut ctx, &delegator),
+            vec![uatom(9_000_000)]
+        );
+
+        let mut distribution_store = ctx.get_kv_store(DISTRIBUTION_STORE_PREFIX.into());
+        PARAMS.set(
+            &mut distribution_store,
+            &Params {
+                withdraw_addr_enabled: false,
+                ..default_params()
+            },
+        );
+        drop(distribution_store);
+        let err = Distribution::set_withdraw_addr(&mut ctx, &delegator, &delegator).unwrap_err();
+        assert_eq!(err.code(), ERR_SET_WITHDRAW_ADDR_DISABLED.code);
+    }
+
+    #[test]
+    fn fund_community_pool_works() {
+        let (mut ctx, _) = context_with_validators();
+
+        let msg = MsgFundCommunityPool {
+            amount: vec![uatom(600)],
+            depositor: account(9).to_string(),
+        };
+        validate_fund_community_pool(&msg).unwrap();
+        Distribution::fund_community_pool_msg(&mut ctx, msg.clone()).unwrap();
+
+        let err = Distribution::fund_community_pool_msg(&mut ctx, msg).unwrap_err();
+        assert_eq!(err.code(), ERR_INSUFFICIENT_FUNDS.code);
+
+        assert_eq!(
+            Bank::get_all_balances(&mut ctx, &AccAddress::new_module_address(MODULE_NAME)),
+            vec![uatom(600)]
+        );
+        let query_ctx = QueryContext::new(ctx.get_multi_store(), 0);
+        let res =
+            Distribution::query_community_pool(&query_ctx, QueryCommunityPoolRequest {}).unwrap();
+        assert_eq!(res.pool, dec_coins_to_proto(&dec_coins("600uatom")));
+    }
+
+    #[test]
+    fn slash_reduces_delegation_rewards() {
+        let (mut ctx, validators) = context_with_validators();
+        let cons_address = cons_address(&mut ctx, &validators[0]);
+
+        let mut ctx = ctx.with_header(Header {
+            height: 1,
+            ..Default::default()
+        });
+        Staking::slash(
+            &mut ctx,
+            &cons_address,
+            1,
+            1,
+            Decimal256::from_ratio(1u8, 2u8),
+        );
+        let validator = Staking::get_validator(&mut ctx, &validators[0]).unwrap();
+        assert_eq!(validator.tokens, Uint256::from(500_000u32));
+
+        let mut ctx = ctx.with_header(Header {
+            height: 2,
+            ..Default::default()
+        });
+        Bank::send_coins_from_module_to_module(
+            &mut ctx,
+            FEE_COLLECTOR_NAME,
+            MODULE_NAME,
+            &[uatom(100)],
+        )
+        .unwrap();
+        Distribution::allocate_tokens_to_validator(&mut ctx, &validator, &dec_coins("100uatom"));
+
+        // the delegation's stake is halved from the slash, so it gets all the rewards after the
+        // commission rather than twice as much
+        let mut ctx = ctx.with_header(Header {
+            height: 3,
+            ..Default::default()
+        });
+        let rewards =
+            Distribution::withdraw_delegation_rewards(&mut ctx, &account(1), &validators[0])
+                .unwrap();
+        assert_eq!(rewards, vec![uatom(90)]);
+
+        let query_ctx = QueryContext::new(ctx.get_multi_store(), 0);
+        let query_slashes = |starting_height, ending_height| {
+            Distribution::query_validator_slashes(
+                &query_ctx,
+                QueryValidatorSlashesRequest {
+                    validator_address: validators[0].to_string(),
+                    starting_height,
+                    ending_height,
+                    pagination: None,
+                },
+            )
+        };
+
+        let res = query_slashes(0, 10).unwrap();
+        assert_eq!(
+            res.slashes,
+            vec![ValidatorSlashEvent {
+                validator_period: 2,
+                fraction: Decimal256::from_ratio(1u8, 2u8).to_cosmos_proto_string(),
+            }]
+        );
+        assert!(query_slashes(2, 10).unwrap().slashes.is_empty());
+
+        let err = query_slashes(2, 1).unwrap_err();
+        assert_eq!(err.code(), ERR_INVALID_REQUEST.code);
+    }
+
+    #[test]
+    fn query_delegation_total_rewards_works() {
+        let (ctx, validators) = context_with_rewards();
+        let delegator = account(1);
+
+        let query_ctx = QueryContext::new(ctx.get_multi_store(), 3);
+        let res = Distribution::query_delegation_total_rewards(
+            &query_ctx,
+            QueryDelegationTotalRewardsRequest {
+                delegator_address: delegator.to_string(),
+            },
+        )
+        .unwrap();
+        let reward = dec_coins_to_proto(&dec_coins("32.399999999999000000uatom"));
+        assert_eq!(
+            res.rewards,
+            vec![DelegationDelegatorReward {
+                validator_address: validators[0].to_string(),
+                reward: reward.clone(),
+            }]
+        );
+        assert_eq!(res.total, reward);
+
+        let res = Distribution::query_delegator_validators(
+            &query_ctx,
+            QueryDelegatorValidatorsRequest {
+                delegator_address: delegator.to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(res.validators, vec![validators[0].to_string()]);
+
+        let err = Distribution::query_delegation_rewards(
+            &query_ctx,
+            QueryDelegationRewardsRequest {
+                delegator_address: delegator.to_string(),
+                validator_address: validators[1].to_string(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.code(), ERR_NO_DELEGATION_EXISTS.code);
+
+        let err = Distribution::query_delegator_validators(
+            &query_ctx,
+            QueryDelegatorValidatorsRequest {
+                delegator_address: "".into(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.code(), ERR_INVALID_REQUEST.code);
+    }
+
+    #[test]
+    fn validate_msgs_works() {
+        let err = validate_withdraw_delegator_reward(&MsgWithdrawDelegatorReward {
+            delegator_address: "".into(),
+            validator_address: ValAddress::from(account(1)).to_string(),
+        })
+        .unwrap_err();
+        assert_eq!(err.code(), ERR_EMPTY_DELEGATOR_ADDR.code);
+
+        let err = validate_withdraw_validator_commission(&MsgWithdrawValidatorCommission {
+            validator_address: "".into(),
+        })
+        .unwrap_err();
+        assert_eq!(err.code(), ERR_EMPTY_VALIDATOR_ADDR.code);
+
+        let err = validate_set_withdraw_address(&MsgSetWithdrawAddress {
+            delegator_address: account(1).to_string(),
+            withdraw_address: "".into(),
+        })
+        .unwrap_err();
+        assert_eq!(err.code(), ERR_EMPTY_WITHDRAW_ADDR.code);
+
+        let err = validate_fund_community_pool(&MsgFundCommunityPool {
+            amount: vec![uatom(0)],
+            depositor: account(1).to_string(),
+        })
+        .unwrap_err();
+        assert_eq!(err.code(), ERR_INVALID_COINS.code);
+
+        // unlike a send the amount may be empty
+        validate_fund_community_pool(&MsgFundCommunityPool {
+            amount: vec![],
+            depositor: account(1).to_string(),
+        })
+        .unwrap();
+    }
+}
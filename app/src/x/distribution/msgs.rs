@@ -0,0 +1,73 @@
+use ibc_proto::cosmos::distribution::v1beta1::{
+    MsgFundCommunityPool, MsgSetWithdrawAddress, MsgWithdrawDelegatorReward,
+    MsgWithdrawValidatorCommission,
+};
+
+use crate::{
+    error::{AppError, ERR_INVALID_ADDRESS, ERR_INVALID_COINS},
+    types::{coins_to_string, validate_coins, AccAddress, ValAddress},
+};
+
+use super::{ERR_EMPTY_DELEGATOR_ADDR, ERR_EMPTY_VALIDATOR_ADDR, ERR_EMPTY_WITHDRAW_ADDR};
+
+/// The stateless checks of MsgSetWithdrawAddress, as with the cosmos SDK `ValidateBasic`
+pub fn validate_set_withdraw_address(msg: &MsgSetWithdrawAddress) -> Result<(), AppError> {
+    parse_delegator_address(&msg.delegator_address)?;
+
+    if msg.withdraw_address.is_empty() {
+        return Err(ERR_EMPTY_WITHDRAW_ADDR.into());
+    }
+    AccAddress::from_bech32(&msg.withdraw_address)?;
+
+    Ok(())
+}
+
+/// The stateless checks of MsgWithdrawDelegatorReward
+pub fn validate_withdraw_delegator_reward(
+    msg: &MsgWithdrawDelegatorReward,
+) -> Result<(), AppError> {
+    parse_delegator_address(&msg.delegator_address)?;
+    parse_validator_address(&msg.validator_address)?;
+
+    Ok(())
+}
+
+/// The stateless checks of MsgWithdrawValidatorCommission
+pub fn validate_withdraw_validator_commission(
+    msg: &MsgWithdrawValidatorCommission,
+) -> Result<(), AppError> {
+    parse_validator_address(&msg.validator_address)?;
+
+    Ok(())
+}
+
+/// The stateless checks of MsgFundCommunityPool, the amount must be valid coins although unlike
+/// a send it may be empty
+pub fn validate_fund_community_pool(msg: &MsgFundCommunityPool) -> Result<(), AppError> {
+    if validate_coins(&msg.amount).is_err() {
+        return Err(ERR_INVALID_COINS.wrap(coins_to_string(&msg.amount)));
+    }
+
+    if msg.depositor.is_empty() {
+        return Err(ERR_INVALID_ADDRESS.wrap(msg.depositor.clone()));
+    }
+    AccAddress::from_bech32(&msg.depositor)?;
+
+    Ok(())
+}
+
+fn parse_delegator_address(address: &str) -> Result<AccAddress, AppError> {
+    if address.is_empty() {
+        return Err(ERR_EMPTY_DELEGATOR_ADDR.into());
+    }
+
+    AccAddress::from_bech32(address)
+}
+
+fn parse_validator_address(address: &str) -> Result<ValAddress, AppError> {
+    if address.is_empty() {
+        return Err(ERR_EMPTY_VALIDATOR_ADDR.into());
+    }
+
+    ValAddress::from_bech32(address)
+}
@@ -0,0 +1,391 @@
+use ibc_proto::{
+    cosmos::{
+        evidence::v1beta1::{
+            Equivocation, QueryAllEvidenceRequest, QueryAllEvidenceResponse, QueryEvidenceRequest,
+            QueryEvidenceResponse,
+        },
+        staking::v1beta1::BondStatus,
+    },
+    google::protobuf::Any,
+};
+use prost::Message;
+use sha2::{Digest, Sha256};
+use tendermint_proto::{
+    abci::{Evidence as AbciEvidence, EvidenceType},
+    google::protobuf::Timestamp,
+};
+use tracing::info;
+
+use crate::{
+    baseapp::{QueryRouter, EVIDENCE_STORE_PREFIX},
+    collections::Map,
+    error::{AppError, ERR_INVALID_REQUEST, ERR_NOT_FOUND},
+    types::{paginate, ConsAddress, Context, QueryContext},
+    x::{
+        slashing::Slashing,
+        staking::{self, Staking},
+    },
+};
+
+pub const MODULE_NAME: &str = "evidence";
+
+pub const EQUIVOCATION_TYPE_URL: &str = "/cosmos.evidence.v1beta1.Equivocation";
+
+/// The time until which double signing validators are jailed, which is the end of time so that
+/// they can never be unjailed, as in the cosmos SDK `DoubleSignJailEndTime`
+pub const DOUBLE_SIGN_JAIL_END_TIME: Timestamp = Timestamp {
+    seconds: 253402300799,
+    nanos: 0,
+};
+
+const EVIDENCE_STORE_KEY_PREFIX: [u8; 1] = [0x00];
+
+/// The handled evidence keyed by its hash
+const EVIDENCE: Map<Vec<u8>, Equivocation> = Map::new(&EVIDENCE_STORE_KEY_PREFIX);
+
+#[derive(Debug, Clone)]
+pub struct Evidence {}
+
+impl Evidence {
+    pub fn register_queries(router: QueryRouter) -> QueryRouter {
+        router
+            .register(
+                "/cosmos.evidence.v1beta1.Query/Evidence",
+                Evidence::query_evidence,
+            )
+            .register(
+                "/cosmos.evidence.v1beta1.Query/AllEvidence",
+                Evidence::query_all_evidence,
+            )
+    }
+
+    pub fn query_evidence(
+        ctx: &QueryContext,
+        req: QueryEvidenceRequest,
+    ) -> Result<QueryEvidenceResponse, AppError> {
+        if req.evidence_hash.is_empty() {
+            return Err(ERR_INVALID_REQUEST.wrap("invalid hash"));
+        }
+
+        let evidence_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(EVIDENCE_STORE_PREFIX.into());
+
+        match EVIDENCE.get(&evidence_store, &req.evidence_hash) {
+            Some(evidence) => Ok(QueryEvidenceResponse {
+                evidence: Some(equivocation_to_any(&evidence)),
+            }),
+            None => Err(ERR_NOT_FOUND.wrap(format!(
+                "evidence {} not found",
+                hex_upper(&req.evidence_hash)
+            ))),
+        }
+    }
+
+    pub fn query_all_evidence(
+        ctx: &QueryContext,
+        req: QueryAllEvidenceRequest,
+    ) -> Result<QueryAllEvidenceResponse, AppError> {
+        let evidence_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(EVIDENCE_STORE_PREFIX.into());
+
+        let evidence = EVIDENCE
+            .iter(&evidence_store)
+            .map(|(hash, evidence)| (EVIDENCE.key(&hash), equivocation_to_any(&evidence)));
+        let (evidence, pagination) = paginate(evidence, req.pagination)?;
+
+        Ok(QueryAllEvidenceResponse {
+            evidence,
+            pagination,
+        })
+    }
+
+    /// Handles the duplicate vote evidence Tendermint included in the block, other evidence
+    /// types are ignored. As with the cosmos SDK evidence `BeginBlocker`.
+    pub fn begin_block(ctx: &mut Context) {
+        let byzantine_validators = ctx.byzantine_validators().to_vec();
+
+        for evidence in byzantine_validators {
+            match EvidenceType::from_i32(evidence.r#type) {
+                Some(EvidenceType::DuplicateVote) => {
+                    let equivocation = from_abci_evidence(evidence);
+                    Evidence::handle_equivocation_evidence(ctx, equivocation);
+                }
+                _ => info!("ignored unknown evidence type: {}", evidence.r#type),
+            }
+        }
+    }
+
+    /// Slashes, jails and tombstones the validator which double signed, unless the evidence is
+    /// too old or the validator was already punished, and stores the evidence. As with the
+    /// cosmos SDK `HandleEquivocationEvidence`.
+    pub fn handle_equivocation_evidence(ctx: &mut Context, evidence: Equivocation) {
+        let cons_address = ConsAddress::from_bech32(&evidence.consensus_address)
+            .expect("equivocation addresses are valid");
+
+        // evidence from validators we've never seen can't be handled
+        let validator = match Staking::get_validator_by_cons_addr(ctx, &cons_address) {
+            Some(validator) if validator.status != BondStatus::Unbonded => validator,
+            _ => return,
+        };
+
+        let infraction_time = evidence.time.clone().unwrap_or_default();
+        let evidence_params = ctx
+            .consensus_params()
+            .evidence
+            .expect("the evidence consensus params are set by init_chain");
+        let age_duration = duration_since(&infraction_time, &ctx.block_time());
+        let age_blocks = ctx.block_height() - evidence.height;
+        let max_age_duration = evidence_params.max_age_duration.unwrap_or_default();
+
+        // the evidence is stale if it's too old in both time and blocks
+        if age_duration > (max_age_duration.seconds, max_age_duration.nanos)
+            && age_blocks > evidence_params.max_age_num_blocks
+        {
+            info!(
+                "ignored equivocation; evidence too old: validator {}, infraction height {}",
+                cons_address, evidence.height
+            );
+            return;
+        }
+
+        if Slashing::get_signing_info(ctx, &cons_address).is_none() {
+            panic!(
+                "expected signing info for validator {} but not found",
+                cons_address
+            )
+        }
+
+        if Slashing::is_tombstoned(ctx, &cons_address) {
+            info!(
+                "ignored equivocation; validator already tombstoned: validator {}, infraction height {}",
+                cons_address, evidence.height
+            );
+            return;
+        }
+
+        // the validator signed with the power it had when the validator set was last updated,
+        // this can be negative for evidence from the genesis block which is fine as it's only
+        // used to filter unbonding delegations and redelegations
+        let distribution_height = evidence.height - staking::VALIDATOR_UPDATE_DELAY;
+
+        Slashing::slash(
+            ctx,
+            &cons_address,
+            Slashing::slash_fraction_double_sign(ctx),
+            evidence.power,
+            distribution_height,
+        );
+
+        if !validator.jailed {
+            Slashing::jail(ctx, &cons_address);
+        }
+
+        Slashing::jail_until(ctx, &cons_address, DOUBLE_SIGN_JAIL_END_TIME);
+        Slashing::tombstone(ctx, &cons_address);
+
+        let mut evidence_store = ctx.get_kv_store(EVIDENCE_STORE_PREFIX.into());
+        EVIDENCE.set(&mut evidence_store, &evidence_hash(&evidence), &evidence);
+    }
+}
+
+/// Converts Tendermint duplicate vote evidence to an equivocation
+fn from_abci_evidence(evidence: AbciEvidence) -> Equivocation {
+    let validator = evidence.validator.unwrap_or_default();
+    let cons_address = ConsAddress::try_from(validator.address.to_vec())
+        .expect("Tendermint gives 20 byte validator addresses");
+
+    Equivocation {
+        height: evidence.height,
+        time: evidence.time,
+        power: validator.power,
+        consensus_address: cons_address.to_string(),
+    }
+}
+
+/// Returns the hash of the evidence, which as in the cosmos SDK is the sha256 hash of its proto
+/// encoding
+pub fn evidence_hash(evidence: &Equivocation) -> Vec<u8> {
+    Sha256::digest(evidence.encode_to_vec()).to_vec()
+}
+
+fn equivocation_to_any(evidence: &Equivocation) -> Any {
+    Any {
+        type_url: EQUIVOCATION_TYPE_URL.into(),
+        value: evidence.encode_to_vec(),
+    }
+}
+
+/// Returns the time from a to b as seconds and nanos
+fn duration_since(a: &Timestamp, b: &Timestamp) -> (i64, i32) {
+    let mut seconds = b.seconds - a.seconds;
+    let mut nanos = b.nanos - a.nanos;
+    if nanos < 0 {
+        seconds -= 1;
+        nanos += 1_000_000_000;
+    }
+
+    (seconds, nanos)
+}
+
+fn hex_upper(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use cosmwasm_std::Uint256;
+    use tendermint_proto::{
+        abci::{ConsensusParams, Validator as AbciValidator},
+        google::protobuf::Duration,
+        types::{EvidenceParams, Header},
+    };
+
+    use super::*;
+    use crate::{
+        types::{AccAddress, ValAddress},
+        x::{
+            slashing::{self, GenesisState as SlashingGenesisState},
+            staking::testing::{bond_validators, consensus_params, context_with_accounts},
+        },
+    };
+
+    const FROM: &str = "cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux";
+
+    /// Returns a context where FROM has a bonded validator with 2 power and evidence expires
+    /// after 100 blocks and 1000 seconds
+    fn context_with_validator() -> (Context, ValAddress) {
+        let from = AccAddress::from_bech32(FROM).unwrap();
+        let mut ctx = context_with_accounts(&[from.clone()], staking::DEFAULT_MAX_VALIDATORS);
+        ctx.set_consensus_params(&ConsensusParams {
+            evidence: Some(EvidenceParams {
+                max_age_num_blocks: 100,
+                max_age_duration: Some(Duration {
+                    seconds: 1000,
+                    nanos: 0,
+                }),
+                max_bytes: 0,
+            }),
+            ..consensus_params()
+        });
+        Slashing::init_genesis(
+            &mut ctx,
+            SlashingGenesisState {
+                params: slashing::default_params(),
+            },
+        );
+
+        let validator_address = bond_validators(&mut ctx, &[from], 2_000_000).remove(0);
+
+        (ctx, validator_address)
+    }
+
+    /// Runs begin_block at the height, at 10 seconds per block, with duplicate vote evidence
+    /// against the validator from the infraction height
+    fn begin_block_at(
+        ctx: Context,
+        height: i64,
+        cons_address: &ConsAddress,
+        infraction_height: i64,
+    ) -> Context {
+        let mut ctx = ctx
+            .with_header(Header {
+                height,
+                time: Some(Timestamp {
+                    seconds: height * 10,
+                    nanos: 0,
+                }),
+                ..Default::default()
+            })
+            .with_byzantine_validators(vec![AbciEvidence {
+                r#type: EvidenceType::DuplicateVote as i32,
+                validator: Some(AbciValidator {
+                    address: Vec::from(cons_address.clone()).into(),
+                    power: 2,
+                }),
+                height: infraction_height,
+                time: Some(Timestamp {
+                    seconds: infraction_height * 10,
+                    nanos: 0,
+                }),
+                total_voting_power: 2,
+            }]);
+        Evidence::begin_block(&mut ctx);
+        ctx
+    }
+
+    #[test]
+    fn double_sign_slashes_and_tombstones_validator() {
+        let (mut ctx, validator_address) = context_with_validator();
+        let cons_address = Staking::get_validator(&mut ctx, &validator_address)
+            .unwrap()
+            .cons_address();
+
+        let mut ctx = begin_block_at(ctx, 10, &cons_address, 5);
+
+        // five percent of the validator's tokens are burned
+        let validator = Staking::get_validator(&mut ctx, &validator_address).unwrap();
+        assert!(validator.jailed);
+        assert_eq!(validator.tokens, Uint256::from(1_900_000u32));
+
+        let info = Slashing::get_signing_info(&mut ctx, &cons_address).unwrap();
+        assert!(info.tombstoned);
+        assert_eq!(info.jailed_until, Some(DOUBLE_SIGN_JAIL_END_TIME));
+
+        let query_ctx = QueryContext::new(ctx.get_multi_store(), 0);
+        let res =
+            Evidence::query_all_evidence(&query_ctx, QueryAllEvidenceRequest::default()).unwrap();
+        let evidence = Equivocation {
+            height: 5,
+            time: Some(Timestamp {
+                seconds: 50,
+                nanos: 0,
+            }),
+            power: 2,
+            consensus_address: cons_address.to_string(),
+        };
+        assert_eq!(res.evidence, vec![equivocation_to_any(&evidence)]);
+
+        let res = Evidence::query_evidence(
+            &query_ctx,
+            QueryEvidenceRequest {
+                evidence_hash: evidence_hash(&evidence),
+            },
+        )
+        .unwrap();
+        assert_eq!(res.evidence, Some(equivocation_to_any(&evidence)));
+
+        let err = Evidence::query_evidence(
+            &query_ctx,
+            QueryEvidenceRequest {
+                evidence_hash: vec![0xAB, 0x01],
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.code(), ERR_NOT_FOUND.code);
+
+        // a tombstoned validator isn't punished again
+        let mut ctx = begin_block_at(ctx, 11, &cons_address, 6);
+        let validator = Staking::get_validator(&mut ctx, &validator_address).unwrap();
+        assert_eq!(validator.tokens, Uint256::from(1_900_000u32));
+    }
+
+    #[test]
+    fn old_evidence_is_ignored() {
+        let (mut ctx, validator_address) = context_with_validator();
+        let cons_address = Staking::get_validator(&mut ctx, &validator_address)
+            .unwrap()
+            .cons_address();
+
+        // evidence is only stale once it's too old in both blocks and time
+        let mut ctx = begin_block_at(ctx, 106, &cons_address, 5);
+        let validator = Staking::get_validator(&mut ctx, &validator_address).unwrap();
+        assert!(!validator.jailed);
+
+        let mut ctx = begin_block_at(ctx, 106, &cons_address, 6);
+        let validator = Staking::get_validator(&mut ctx, &validator_address).unwrap();
+        assert!(validator.jailed);
+    }
+}
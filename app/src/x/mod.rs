@@ -1,2 +1,7 @@
 pub mod auth;
 pub mod bank;
+pub mod distribution;
+pub mod evidence;
+pub mod slashing;
+pub mod staking;
+pub mod vesting;
@@ -0,0 +1,773 @@
+use cosmwasm_std::{Uint128, Uint256};
+use ibc_proto::cosmos::slashing::v1beta1::{
+    MsgUnjail, MsgUnjailResponse, Params, QueryParamsRequest, QueryParamsResponse,
+    QuerySigningInfoRequest, QuerySigningInfoResponse, QuerySigningInfosRequest,
+    QuerySigningInfosResponse, ValidatorSigningInfo,
+};
+use math::Decimal256;
+use tendermint_proto::google::protobuf::{Duration, Timestamp};
+
+use crate::{
+    baseapp::{QueryRouter, SLASHING_STORE_PREFIX},
+    collections::{Item, KeySet, Map},
+    error::{AppError, RegisteredError, ERR_INVALID_REQUEST, ERR_NOT_FOUND},
+    store::ReadKVStore,
+    types::{
+        new_event, paginate, AccAddress, ConsAddress, Context, QueryContext, ValAddress,
+        ATTRIBUTE_KEY_MODULE, ATTRIBUTE_KEY_SENDER, EVENT_TYPE_MESSAGE,
+    },
+    x::staking::{self, Staking},
+};
+
+pub const MODULE_NAME: &str = "slashing";
+
+pub const EVENT_TYPE_SLASH: &str = "slash";
+pub const EVENT_TYPE_LIVENESS: &str = "liveness";
+
+pub const ATTRIBUTE_KEY_ADDRESS: &str = "address";
+pub const ATTRIBUTE_KEY_HEIGHT: &str = "height";
+pub const ATTRIBUTE_KEY_POWER: &str = "power";
+pub const ATTRIBUTE_KEY_REASON: &str = "reason";
+pub const ATTRIBUTE_KEY_JAILED: &str = "jailed";
+pub const ATTRIBUTE_KEY_MISSED_BLOCKS: &str = "missed_blocks";
+
+pub const ATTRIBUTE_VALUE_MISSING_SIGNATURE: &str = "missing_signature";
+pub const ATTRIBUTE_VALUE_DOUBLE_SIGN: &str = "double_sign";
+
+// The same codes as the cosmos SDK slashing module
+pub const ERR_NO_VALIDATOR_FOR_ADDRESS: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    2,
+    "address is not associated with any known validator",
+);
+pub const ERR_BAD_VALIDATOR_ADDR: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 3, "validator does not exist for that address");
+pub const ERR_VALIDATOR_JAILED: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 4, "validator still jailed; cannot be unjailed");
+pub const ERR_VALIDATOR_NOT_JAILED: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 5, "validator not jailed; cannot be unjailed");
+pub const ERR_MISSING_SELF_DELEGATION: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    6,
+    "validator has no self-delegation; cannot be unjailed",
+);
+pub const ERR_SELF_DELEGATION_TOO_LOW_TO_UNJAIL: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    7,
+    "validator's self delegation less than minimum; cannot be unjailed",
+);
+
+pub const DEFAULT_SIGNED_BLOCKS_WINDOW: i64 = 100;
+/// In seconds
+pub const DEFAULT_DOWNTIME_JAIL_DURATION: i64 = 60 * 10;
+
+const VALIDATOR_SIGNING_INFO_STORE_PREFIX: [u8; 1] = [0x01];
+const VALIDATOR_MISSED_BLOCK_BIT_ARRAY_STORE_PREFIX: [u8; 1] = [0x02];
+const PARAMS_KEY: [u8; 1] = [0x11];
+
+const PARAMS: Item<Params> = Item::new(&PARAMS_KEY);
+const VALIDATOR_SIGNING_INFOS: Map<ConsAddress, ValidatorSigningInfo> =
+    Map::new(&VALIDATOR_SIGNING_INFO_STORE_PREFIX);
+/// The blocks in the signed blocks window which each validator missed, keyed by their index in
+/// the window. Blocks which were signed aren't stored.
+const VALIDATOR_MISSED_BLOCKS: KeySet<(ConsAddress, i64)> =
+    KeySet::new(&VALIDATOR_MISSED_BLOCK_BIT_ARRAY_STORE_PREFIX);
+
+#[derive(Debug, Clone)]
+pub struct Slashing {}
+
+pub struct GenesisState {
+    pub params: Params,
+}
+
+/// Returns the default params, which are the same as the cosmos SDK's
+pub fn default_params() -> Params {
+    Params {
+        signed_blocks_window: DEFAULT_SIGNED_BLOCKS_WINDOW,
+        min_signed_per_window: dec_to_bytes(Decimal256::percent(50)),
+        downtime_jail_duration: Some(Duration {
+            seconds: DEFAULT_DOWNTIME_JAIL_DURATION,
+            nanos: 0,
+        }),
+        slash_fraction_double_sign: dec_to_bytes(Decimal256::from_ratio(1u8, 20u8)),
+        slash_fraction_downtime: dec_to_bytes(Decimal256::from_ratio(1u8, 100u8)),
+    }
+}
+
+fn validate_params(params: &Params) -> Result<(), String> {
+    if params.signed_blocks_window <= 0 {
+        return Err(format!(
+            "signed blocks window must be positive: {}",
+            params.signed_blocks_window
+        ));
+    }
+
+    let downtime_jail_duration = params.downtime_jail_duration.clone().unwrap_or_default();
+    if downtime_jail_duration.seconds <= 0 && downtime_jail_duration.nanos <= 0 {
+        return Err(format!(
+            "downtime jail duration must be positive: {}s",
+            downtime_jail_duration.seconds
+        ));
+    }
+
+    let decs = [
+        ("min signed per window", &params.min_signed_per_window),
+        (
+            "double sign slash fraction",
+            &params.slash_fraction_double_sign,
+        ),
+        ("downtime slash fraction", &params.slash_fraction_downtime),
+    ];
+    for (name, bytes) in decs {
+        let dec = decode_dec_bytes(bytes).ok_or_else(|| format!("invalid {}", name))?;
+
+        if dec > Decimal256::one() {
+            return Err(format!("{} too large: {}", name, dec));
+        }
+    }
+
+    Ok(())
+}
+
+impl Slashing {
+    pub fn register_queries(router: QueryRouter) -> QueryRouter {
+        router
+            .register(
+                "/cosmos.slashing.v1beta1.Query/Params",
+                Slashing::query_params,
+            )
+            .register(
+                "/cosmos.slashing.v1beta1.Query/SigningInfo",
+                Slashing::query_signing_info,
+            )
+            .register(
+                "/cosmos.slashing.v1beta1.Query/SigningInfos",
+                Slashing::query_signing_infos,
+            )
+    }
+
+    pub fn init_genesis(ctx: &mut Context, genesis: GenesisState) {
+        if let Err(e) = validate_params(&genesis.params) {
+            panic!("invalid slashing genesis params: {}", e)
+        }
+
+        let mut slashing_store = ctx.get_kv_store(SLASHING_STORE_PREFIX.into());
+        PARAMS.set(&mut slashing_store, &genesis.params);
+    }
+
+    pub fn get_params(ctx: &Context) -> Params {
+        let slashing_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(SLASHING_STORE_PREFIX.into());
+        read_params(&slashing_store)
+    }
+
+    pub fn query_params(
+        ctx: &QueryContext,
+        _req: QueryParamsRequest,
+    ) -> Result<QueryParamsResponse, AppError> {
+        let slashing_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(SLASHING_STORE_PREFIX.into());
+
+        Ok(QueryParamsResponse {
+            params: Some(read_params(&slashing_store)),
+        })
+    }
+
+    pub fn query_signing_info(
+        ctx: &QueryContext,
+        req: QuerySigningInfoRequest,
+    ) -> Result<QuerySigningInfoResponse, AppError> {
+        if req.cons_address.is_empty() {
+            return Err(ERR_INVALID_REQUEST.wrap("empty request"));
+        }
+        let cons_address = ConsAddress::from_bech32(&req.cons_address)?;
+
+        let slashing_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(SLASHING_STORE_PREFIX.into());
+
+        match VALIDATOR_SIGNING_INFOS.get(&slashing_store, &cons_address) {
+            Some(info) => Ok(QuerySigningInfoResponse {
+                val_signing_info: Some(info),
+            }),
+            None => Err(ERR_NOT_FOUND.wrap(format!(
+                "SigningInfo not found for validator {}",
+                req.cons_address
+            ))),
+        }
+    }
+
+    pub fn query_signing_infos(
+        ctx: &QueryContext,
+        req: QuerySigningInfosRequest,
+    ) -> Result<QuerySigningInfosResponse, AppError> {
+        let slashing_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(SLASHING_STORE_PREFIX.into());
+
+        let infos = VALIDATOR_SIGNING_INFOS
+            .iter(&slashing_store)
+            .map(|(address, info)| (VALIDATOR_SIGNING_INFOS.key(&address), info));
+        let (info, pagination) = paginate(infos, req.pagination)?;
+
+        Ok(QuerySigningInfosResponse { info, pagination })
+    }
+
+    /// Handles MsgUnjail, which returns a jailed validator to the power index once its jail
+    /// time has passed if it still has enough self delegation
+    pub fn unjail_msg(ctx: &mut Context, msg: MsgUnjail) -> Result<MsgUnjailResponse, AppError> {
+        let validator_address = ValAddress::from_bech32(&msg.validator_addr)?;
+
+        let validator = Staking::get_validator(ctx, &validator_address)
+            .ok_or(AppError::from(ERR_NO_VALIDATOR_FOR_ADDRESS))?;
+
+        let self_delegation = Staking::get_delegation(
+            ctx,
+            &AccAddress::from(validator_address.clone()),
+            &validator_address,
+        )
+        .ok_or(AppError::from(ERR_MISSING_SELF_DELEGATION))?;
+
+        let tokens = validator.tokens_from_shares(self_delegation.shares);
+        if tokens < validator.min_self_delegation {
+            return Err(ERR_SELF_DELEGATION_TOO_LOW_TO_UNJAIL.wrap(format!(
+                "{} less than {}",
+                tokens, validator.min_self_delegation
+            )));
+        }
+
+        if !validator.jailed {
+            return Err(ERR_VALIDATOR_NOT_JAILED.into());
+        }
+
+        let cons_address = validator.cons_address();
+        if let Some(info) = Slashing::get_signing_info(ctx, &cons_address) {
+            let jailed_until = info.jailed_until.unwrap_or_default();
+            if info.tombstoned || staking::is_after(&jailed_until, &ctx.block_time()) {
+                return Err(ERR_VALIDATOR_JAILED.into());
+            }
+        }
+
+        Staking::unjail(ctx, &cons_address);
+
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_MESSAGE,
+            vec![
+                (ATTRIBUTE_KEY_MODULE, MODULE_NAME.into()),
+                (ATTRIBUTE_KEY_SENDER, msg.validator_addr),
+            ],
+        ));
+
+        Ok(MsgUnjailResponse {})
+    }
+
+    /// Tracks whether each validator in the last commit signed the block, jailing and slashing
+    /// those which missed too many blocks of the signed blocks window. As with the cosmos SDK
+    /// slashing `BeginBlocker`.
+    pub fn begin_block(ctx: &mut Context) {
+        let votes = ctx.vote_info().to_vec();
+
+        for vote in votes {
+            let validator = vote.validator.unwrap_or_default();
+            let cons_address = ConsAddress::try_from(validator.address.to_vec())
+                .expect("Tendermint gives 20 byte validator addresses");
+
+            // the validators of a chain started without gentxs, such as the README's single
+            // node chain, are only known to Tendermint so they have no signing info
+            if Slashing::get_signing_info(ctx, &cons_address).is_none() {
+                continue;
+            }
+
+            Slashing::handle_validator_signature(
+                ctx,
+                cons_address,
+                validator.power,
+                vote.signed_last_block,
+            );
+        }
+    }
+
+    /// Creates the signing info of a validator when it's first bonded, as with the cosmos SDK
+    /// slashing `AfterValidatorBonded` hook
+    pub fn after_validator_bonded(ctx: &mut Context, cons_address: &ConsAddress) {
+        if Slashing::get_signing_info(ctx, cons_address).is_some() {
+            return;
+        }
+
+        let info = ValidatorSigningInfo {
+            address: cons_address.to_string(),
+            start_height: ctx.block_height(),
+            index_offset: 0,
+            jailed_until: Some(Timestamp::default()),
+            tombstoned: false,
+            missed_blocks_counter: 0,
+        };
+        Slashing::set_signing_info(ctx, cons_address, &info);
+    }
+
+    /// Slashes the validator for double signing, as with the cosmos SDK slashing keeper's `Slash`
+    pub fn slash(
+        ctx: &mut Context,
+        cons_address: &ConsAddress,
+        fraction: Decimal256,
+        power: i64,
+        distribution_height: i64,
+    ) {
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_SLASH,
+            vec![
+                (ATTRIBUTE_KEY_ADDRESS, cons_address.to_string()),
+                (ATTRIBUTE_KEY_POWER, power.to_string()),
+                (ATTRIBUTE_KEY_REASON, ATTRIBUTE_VALUE_DOUBLE_SIGN.into()),
+            ],
+        ));
+
+        Staking::slash(ctx, cons_address, distribution_height, power, fraction);
+    }
+
+    pub fn jail(ctx: &mut Context, cons_address: &ConsAddress) {
+        Staking::jail(ctx, cons_address);
+
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_SLASH,
+            vec![(ATTRIBUTE_KEY_JAILED, cons_address.to_string())],
+        ));
+    }
+
+    /// Sets the time until which the validator can't be unjailed
+    pub fn jail_until(ctx: &mut Context, cons_address: &ConsAddress, jailed_until: Timestamp) {
+        let mut info = Slashing::must_get_signing_info(ctx, cons_address);
+        info.jailed_until = Some(jailed_until);
+        Slashing::set_signing_info(ctx, cons_address, &info);
+    }
+
+    /// Permanently prevents the validator from being unjailed, e.g. after double signing
+    pub fn tombstone(ctx: &mut Context, cons_address: &ConsAddress) {
+        let mut info = Slashing::must_get_signing_info(ctx, cons_address);
+        if info.tombstoned {
+            panic!("cannot tombstone validator that is already tombstoned")
+        }
+
+        info.tombstoned = true;
+        Slashing::set_signing_info(ctx, cons_address, &info);
+    }
+
+    pub fn is_tombstoned(ctx: &mut Context, cons_address: &ConsAddress) -> bool {
+        Slashing::get_signing_info(ctx, cons_address).map_or(false, |info| info.tombstoned)
+    }
+
+    pub fn slash_fraction_double_sign(ctx: &Context) -> Decimal256 {
+        parse_dec_bytes(&Slashing::get_params(ctx).slash_fraction_double_sign)
+    }
+
+    pub fn get_signing_info(
+        ctx: &mut Context,
+        cons_address: &ConsAddress,
+    ) -> Option<ValidatorSigningInfo> {
+        let slashing_store = ctx.get_kv_store(SLASHING_STORE_PREFIX.into());
+        VALIDATOR_SIGNING_INFOS.get(&slashing_store, cons_address)
+    }
+
+    pub fn set_signing_info(
+        ctx: &mut Context,
+        cons_address: &ConsAddress,
+        info: &ValidatorSigningInfo,
+    ) {
+        let mut slashing_store = ctx.get_kv_store(SLASHING_STORE_PREFIX.into());
+        VALIDATOR_SIGNING_INFOS.set(&mut slashing_store, cons_address, info);
+    }
+
+    fn must_get_signing_info(
+        ctx: &mut Context,
+        cons_address: &ConsAddress,
+    ) -> ValidatorSigningInfo {
+        Slashing::get_signing_info(ctx, cons_address).unwrap_or_else(|| {
+            panic!(
+                "expected signing info for validator {} but not found",
+                cons_address
+            )
+        })
+    }
+
+    /// Records whether the validator signed the last block in its missed block bit array, and
+    /// jails and slashes it if it has missed too many blocks
+    fn handle_validator_signature(
+        ctx: &mut Context,
+        cons_address: ConsAddress,
+        power: i64,
+        signed: bool,
+    ) {
+        let params = Slashing::get_params(ctx);
+        let height = ctx.block_height();
+
+        let mut info = Slashing::must_get_signing_info(ctx, &cons_address);
+
+        // the index of the block in the window, which wraps around
+        let index = info.index_offset % params.signed_blocks_window;
+        info.index_offset += 1;
+
+        let mut slashing_store = ctx.get_kv_store(SLASHING_STORE_PREFIX.into());
+        let key = (cons_address.clone(), index);
+        let previous_missed = VALIDATOR_MISSED_BLOCKS.has(&slashing_store, &key);
+        let missed = !signed;
+        match (previous_missed, missed) {
+            (false, true) => {
+                VALIDATOR_MISSED_BLOCKS.insert(&mut slashing_store, &key);
+                info.missed_blocks_counter += 1;
+            }
+            (true, false) => {
+                VALIDATOR_MISSED_BLOCKS.remove(&mut slashing_store, &key);
+                info.missed_blocks_counter -= 1;
+            }
+            // the bit array is unchanged
+            _ => {}
+        }
+        drop(slashing_store);
+
+        if missed {
+            ctx.event_manager().emit_event(new_event(
+                EVENT_TYPE_LIVENESS,
+                vec![
+                    (ATTRIBUTE_KEY_ADDRESS, cons_address.to_string()),
+                    (
+                        ATTRIBUTE_KEY_MISSED_BLOCKS,
+                        info.missed_blocks_counter.to_string(),
+                    ),
+                    (ATTRIBUTE_KEY_HEIGHT, height.to_string()),
+                ],
+            ));
+        }
+
+        let min_height = info.start_height + params.signed_blocks_window;
+        let max_missed = params.signed_blocks_window - min_signed_per_window(&params);
+
+        // the validator is only punished once it's been bonded for a whole window
+        if height > min_height && info.missed_blocks_counter > max_missed {
+            match Staking::get_validator_by_cons_addr(ctx, &cons_address) {
+                Some(validator) if !validator.jailed => {
+                    // the validator signed the blocks it missed with the power it had at the
+                    // height the validator set was last updated
+                    let distribution_height = height - staking::VALIDATOR_UPDATE_DELAY - 1;
+
+                    ctx.event_manager().emit_event(new_event(
+                        EVENT_TYPE_SLASH,
+                        vec![
+                            (ATTRIBUTE_KEY_ADDRESS, cons_address.to_string()),
+                            (ATTRIBUTE_KEY_POWER, power.to_string()),
+                            (
+                                ATTRIBUTE_KEY_REASON,
+                                ATTRIBUTE_VALUE_MISSING_SIGNATURE.into(),
+                            ),
+                            (ATTRIBUTE_KEY_JAILED, cons_address.to_string()),
+                        ],
+                    ));
+
+                    Staking::slash(
+                        ctx,
+                        &cons_address,
+                        distribution_height,
+                        power,
+                        parse_dec_bytes(&params.slash_fraction_downtime),
+                    );
+                    Staking::jail(ctx, &cons_address);
+
+                    info.jailed_until = Some(staking::add_duration(
+                        &ctx.block_time(),
+                        &params.downtime_jail_duration.clone().unwrap_or_default(),
+                    ));
+
+                    // the validator starts with a clean window once it's unjailed
+                    info.missed_blocks_counter = 0;
+                    info.index_offset = 0;
+                    Slashing::clear_missed_blocks(ctx, &cons_address);
+                }
+                // the validator was already jailed, e.g. for double signing, but its updated
+                // power hasn't reached Tendermint yet
+                _ => {}
+            }
+        }
+
+        Slashing::set_signing_info(ctx, &cons_address, &info);
+    }
+
+    fn clear_missed_blocks(ctx: &mut Context, cons_address: &ConsAddress) {
+        let mut slashing_store = ctx.get_kv_store(SLASHING_STORE_PREFIX.into());
+        let missed: Vec<(ConsAddress, i64)> = VALIDATOR_MISSED_BLOCKS
+            .prefix_iter(&slashing_store, cons_address)
+            .collect();
+
+        for key in missed {
+            VALIDATOR_MISSED_BLOCKS.remove(&mut slashing_store, &key);
+        }
+    }
+}
+
+/// Returns the number of blocks of the window which must be signed, rounding half to even as
+/// with the cosmos SDK `Dec.RoundInt64`
+fn min_signed_per_window(params: &Params) -> i64 {
+    let min_signed = parse_dec_bytes(&params.min_signed_per_window);
+    let window = Uint256::from(params.signed_blocks_window.unsigned_abs());
+
+    let exact = min_signed * Decimal256::from_ratio(window, 1u8);
+    let floor = min_signed * window;
+    let fraction = exact - Decimal256::from_ratio(floor, 1u8);
+    let half = Decimal256::percent(50);
+    let is_odd = floor % Uint256::from(2u8) == Uint256::one();
+
+    let rounded = if fraction > half || (fraction == half && is_odd) {
+        floor + Uint256::one()
+    } else {
+        floor
+    };
+
+    let rounded = Uint128::try_from(rounded).expect("the min signed blocks are at most the window");
+    i64::try_from(rounded.u128()).expect("the min signed blocks are at most the window")
+}
+
+fn read_params<S: ReadKVStore + ?Sized>(slashing_store: &S) -> Params {
+    PARAMS
+        .get(slashing_store)
+        .expect("params are set in genesis")
+}
+
+/// Returns the cosmos SDK `Dec` param encoding, which is the bytes of the integer number of
+/// 10^-18 units
+fn dec_to_bytes(dec: Decimal256) -> Vec<u8> {
+    dec.to_cosmos_proto_string().into_bytes()
+}
+
+fn decode_dec_bytes(bytes: &[u8]) -> Option<Decimal256> {
+    let s = std::str::from_utf8(bytes).ok()?;
+    Decimal256::from_cosmos_proto_string(s).ok()
+}
+
+fn parse_dec_bytes(bytes: &[u8]) -> Decimal256 {
+    decode_dec_bytes(bytes).expect("params are validated in genesis")
+}
+
+/// Checks the validator address of MsgUnjail
+pub fn validate_unjail(msg: &MsgUnjail) -> Result<(), AppError> {
+    if msg.validator_addr.is_empty() {
+        return Err(ERR_BAD_VALIDATOR_ADDR.into());
+    }
+
+    ValAddress::from_bech32(&msg.validator_addr)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use tendermint_proto::{
+        abci::{Validator as VoteValidator, VoteInfo},
+        types::Header,
+    };
+
+    use super::*;
+    use crate::x::{
+        bank::Bank,
+        staking::testing::{bond_validators, context_with_accounts, uatom},
+    };
+
+    const FROM: &str = "cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux";
+
+    /// Returns a context where FROM has a bonded validator with 2 power and the signed blocks
+    /// window is 10 blocks
+    fn context_with_validator() -> (Context, ValAddress) {
+        let from = AccAddress::from_bech32(FROM).unwrap();
+        let mut ctx = context_with_accounts(&[from.clone()], staking::DEFAULT_MAX_VALIDATORS);
+        Slashing::init_genesis(
+            &mut ctx,
+            GenesisState {
+                params: Params {
+                    signed_blocks_window: 10,
+                    ..default_params()
+                },
+            },
+        );
+
+        let validator_address = bond_validators(&mut ctx, &[from], 2_000_000).remove(0);
+
+        (ctx, validator_address)
+    }
+
+    /// Runs begin_block at the height with a vote from the validator
+    fn begin_block_at(
+        ctx: Context,
+        height: i64,
+        cons_address: &ConsAddress,
+        signed: bool,
+    ) -> Context {
+        let mut ctx = ctx
+            .with_header(Header {
+                height,
+                time: Some(Timestamp {
+                    seconds: height * 5,
+                    nanos: 0,
+                }),
+                ..Default::default()
+            })
+            .with_vote_info(vec![VoteInfo {
+                validator: Some(VoteValidator {
+                    address: Vec::from(cons_address.clone()).into(),
+                    power: 2,
+                }),
+                signed_last_block: signed,
+            }]);
+        Slashing::begin_block(&mut ctx);
+        ctx
+    }
+
+    #[test]
+    fn downtime_jails_and_slashes_validator() {
+        let (mut ctx, validator_address) = context_with_validator();
+        let cons_address = Staking::get_validator(&mut ctx, &validator_address)
+            .unwrap()
+            .cons_address();
+
+        let info = Slashing::get_signing_info(&mut ctx, &cons_address).unwrap();
+        assert_eq!(info.start_height, 0);
+
+        // signed blocks don't count towards the missed blocks
+        ctx = begin_block_at(ctx, 1, &cons_address, true);
+        for height in 2..=10 {
+            ctx = begin_block_at(ctx, height, &cons_address, false);
+        }
+        let info = Slashing::get_signing_info(&mut ctx, &cons_address).unwrap();
+        assert_eq!(info.missed_blocks_counter, 9);
+        assert!(
+            !Staking::get_validator(&mut ctx, &validator_address)
+                .unwrap()
+                .jailed
+        );
+
+        // the validator is punished once it's been bonded for a whole window
+        ctx.event_manager().take_events();
+        ctx = begin_block_at(ctx, 11, &cons_address, false);
+        let events = ctx.event_manager().take_events();
+        assert!(events.contains(&new_event(
+            EVENT_TYPE_SLASH,
+            vec![
+                (ATTRIBUTE_KEY_ADDRESS, cons_address.to_string()),
+                (ATTRIBUTE_KEY_POWER, "2".into()),
+                (
+                    ATTRIBUTE_KEY_REASON,
+                    ATTRIBUTE_VALUE_MISSING_SIGNATURE.into()
+                ),
+                (ATTRIBUTE_KEY_JAILED, cons_address.to_string()),
+            ],
+        )));
+
+        // one percent of the validator's tokens are burned
+        let validator = Staking::get_validator(&mut ctx, &validator_address).unwrap();
+        assert!(validator.jailed);
+        assert_eq!(validator.tokens, Uint256::from(1_980_000u32));
+        assert_eq!(
+            Bank::spendable_coins(
+                &mut ctx,
+                &AccAddress::new_module_address(staking::BONDED_POOL_NAME)
+            ),
+            vec![uatom(1_980_000)]
+        );
+
+        let info = Slashing::get_signing_info(&mut ctx, &cons_address).unwrap();
+        assert_eq!(info.missed_blocks_counter, 0);
+        assert_eq!(info.index_offset, 0);
+        assert_eq!(
+            info.jailed_until,
+            Some(Timestamp {
+                seconds: 55 + DEFAULT_DOWNTIME_JAIL_DURATION,
+                nanos: 0
+            })
+        );
+
+        // the validator can only be unjailed once the jail duration has passed
+        let msg = MsgUnjail {
+            validator_addr: validator_address.to_string(),
+        };
+        validate_unjail(&msg).unwrap();
+        let err = Slashing::unjail_msg(&mut ctx, msg.clone()).unwrap_err();
+        assert_eq!(err.code(), ERR_VALIDATOR_JAILED.code);
+
+        let mut ctx = ctx.with_header(Header {
+            time: Some(Timestamp {
+                seconds: 55 + DEFAULT_DOWNTIME_JAIL_DURATION,
+                nanos: 0,
+            }),
+            ..Default::default()
+        });
+        Slashing::unjail_msg(&mut ctx, msg.clone()).unwrap();
+        assert!(
+            !Staking::get_validator(&mut ctx, &validator_address)
+                .unwrap()
+                .jailed
+        );
+
+        let err = Slashing::unjail_msg(&mut ctx, msg).unwrap_err();
+        assert_eq!(err.code(), ERR_VALIDATOR_NOT_JAILED.code);
+    }
+
+    #[test]
+    fn votes_without_signing_info_are_skipped() {
+        let (ctx, _) = context_with_validator();
+        let cons_address = ConsAddress::try_from(vec![9; 20]).unwrap();
+
+        let mut ctx = begin_block_at(ctx, 1, &cons_address, false);
+        assert!(Slashing::get_signing_info(&mut ctx, &cons_address).is_none());
+    }
+
+    #[test]
+    fn query_signing_info_works() {
+        let (mut ctx, validator_address) = context_with_validator();
+        let cons_address = Staking::get_validator(&mut ctx, &validator_address)
+            .unwrap()
+            .cons_address();
+        ctx = begin_block_at(ctx, 1, &cons_address, false);
+
+        let query_ctx = QueryContext::new(ctx.get_multi_store(), 0);
+        let res = Slashing::query_signing_info(
+            &query_ctx,
+            QuerySigningInfoRequest {
+                cons_address: cons_address.to_string(),
+            },
+        )
+        .unwrap();
+        let info = res.val_signing_info.unwrap();
+        assert_eq!(info.address, cons_address.to_string());
+        assert_eq!(info.missed_blocks_counter, 1);
+
+        let res =
+            Slashing::query_signing_infos(&query_ctx, QuerySigningInfosRequest::default()).unwrap();
+        assert_eq!(res.info, vec![info]);
+
+        let err = Slashing::query_signing_info(
+            &query_ctx,
+            QuerySigningInfoRequest {
+                cons_address: ConsAddress::try_from(vec![1; 20]).unwrap().to_string(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.code(), ERR_NOT_FOUND.code);
+    }
+
+    #[test]
+    fn min_signed_per_window_works() {
+        let min_signed = |window, percent| {
+            min_signed_per_window(&Params {
+                signed_blocks_window: window,
+                min_signed_per_window: dec_to_bytes(Decimal256::percent(percent)),
+                ..default_params()
+            })
+        };
+
+        assert_eq!(min_signed(100, 50), 50);
+        // halves round to even
+        assert_eq!(min_signed(5, 50), 2);
+        assert_eq!(min_signed(7, 50), 4);
+        assert_eq!(min_signed(10, 33), 3);
+        assert_eq!(min_signed(10, 37), 4);
+    }
+}
@@ -0,0 +1,3362 @@
+use std::str::FromStr;
+
+use cosmwasm_std::Uint256;
+use ibc_proto::cosmos::{
+    bank::v1beta1::QueryBalanceRequest,
+    base::v1beta1::Coin,
+    staking::v1beta1::{
+        BondStatus, Delegation as ProtoDelegation, DelegationResponse, Description, HistoricalInfo,
+        MsgBeginRedelegate, MsgBeginRedelegateResponse, MsgCreateValidator,
+        MsgCreateValidatorResponse, MsgDelegate, MsgDelegateResponse, MsgEditValidator,
+        MsgEditValidatorResponse, MsgUndelegate, MsgUndelegateResponse, Params, Pool,
+        QueryDelegationRequest, QueryDelegationResponse, QueryDelegatorDelegationsRequest,
+        QueryDelegatorDelegationsResponse, QueryDelegatorUnbondingDelegationsRequest,
+        QueryDelegatorUnbondingDelegationsResponse, QueryDelegatorValidatorsRequest,
+        QueryDelegatorValidatorsResponse, QueryHistoricalInfoRequest, QueryHistoricalInfoResponse,
+        QueryParamsRequest, QueryParamsResponse, QueryPoolRequest, QueryPoolResponse,
+        QueryRedelegationsRequest, QueryRedelegationsResponse, QueryUnbondingDelegationRequest,
+        QueryUnbondingDelegationResponse, QueryValidatorDelegationsRequest,
+        QueryValidatorDelegationsResponse, QueryValidatorRequest, QueryValidatorResponse,
+        QueryValidatorUnbondingDelegationsRequest, QueryValidatorUnbondingDelegationsResponse,
+        QueryValidatorsRequest, QueryValidatorsResponse, Redelegation as ProtoRedelegation,
+        RedelegationEntryResponse, RedelegationResponse,
+        UnbondingDelegation as ProtoUnbondingDelegation, Validator as ProtoValidator,
+    },
+};
+use math::Decimal256;
+use tendermint_proto::{
+    abci::ValidatorUpdate,
+    google::protobuf::{Duration, Timestamp},
+};
+
+use crate::{
+    baseapp::{QueryRouter, STAKING_STORE_PREFIX},
+    collections::{Item, KeySet, KeyValueCodec, Map, StringCodec},
+    crypto::SignerPubKey,
+    error::{AppError, RegisteredError, ERR_INVALID_REQUEST, ERR_INVALID_TYPE, ERR_NOT_FOUND},
+    store::ReadKVStore,
+    types::{
+        coins_to_string, new_event, paginate, AccAddress, ConsAddress, Context, QueryContext,
+        ValAddress, ATTRIBUTE_KEY_AMOUNT, ATTRIBUTE_KEY_MODULE, ATTRIBUTE_KEY_SENDER,
+        EVENT_TYPE_MESSAGE,
+    },
+    x::{bank::Bank, distribution::Distribution, slashing::Slashing},
+};
+
+mod msgs;
+#[cfg(test)]
+pub mod testing;
+mod unbonding;
+mod validator;
+
+pub use msgs::*;
+pub use unbonding::*;
+pub use validator::*;
+
+pub const MODULE_NAME: &str = "staking";
+
+/// The module account holding the tokens of bonded validators
+pub const BONDED_POOL_NAME: &str = "bonded_tokens_pool";
+/// The module account holding the tokens of unbonding and unbonded validators
+pub const NOT_BONDED_POOL_NAME: &str = "not_bonded_tokens_pool";
+
+pub const EVENT_TYPE_CREATE_VALIDATOR: &str = "create_validator";
+pub const EVENT_TYPE_EDIT_VALIDATOR: &str = "edit_validator";
+pub const EVENT_TYPE_DELEGATE: &str = "delegate";
+pub const EVENT_TYPE_UNBOND: &str = "unbond";
+pub const EVENT_TYPE_REDELEGATE: &str = "redelegate";
+pub const EVENT_TYPE_COMPLETE_UNBONDING: &str = "complete_unbonding";
+pub const EVENT_TYPE_COMPLETE_REDELEGATION: &str = "complete_redelegation";
+
+pub const ATTRIBUTE_KEY_VALIDATOR: &str = "validator";
+pub const ATTRIBUTE_KEY_COMMISSION_RATE: &str = "commission_rate";
+pub const ATTRIBUTE_KEY_MIN_SELF_DELEGATION: &str = "min_self_delegation";
+pub const ATTRIBUTE_KEY_NEW_SHARES: &str = "new_shares";
+pub const ATTRIBUTE_KEY_SRC_VALIDATOR: &str = "source_validator";
+pub const ATTRIBUTE_KEY_DST_VALIDATOR: &str = "destination_validator";
+pub const ATTRIBUTE_KEY_DELEGATOR: &str = "delegator";
+pub const ATTRIBUTE_KEY_COMPLETION_TIME: &str = "completion_time";
+
+/// The number of blocks before validator set changes reach Tendermint, as in the cosmos SDK
+/// `ValidatorUpdateDelay`
+pub const VALIDATOR_UPDATE_DELAY: i64 = 1;
+
+/// Description fields set to this value are left unchanged by MsgEditValidator
+pub const DO_NOT_MODIFY_DESCRIPTION: &str = "[do-not-modify]";
+
+// The same codes as the cosmos SDK staking module
+pub const ERR_EMPTY_VALIDATOR_ADDR: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 2, "empty validator address");
+pub const ERR_BAD_VALIDATOR_ADDR: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 3, "validator address is invalid");
+pub const ERR_NO_VALIDATOR_FOUND: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 4, "validator does not exist");
+pub const ERR_VALIDATOR_OWNER_EXISTS: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    5,
+    "validator already exist for this operator address; must use new validator operator address",
+);
+pub const ERR_VALIDATOR_PUB_KEY_EXISTS: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    6,
+    "validator already exist for this pubkey; must use new validator pubkey",
+);
+pub const ERR_VALIDATOR_PUB_KEY_TYPE_NOT_SUPPORTED: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 7, "validator pubkey type is not supported");
+pub const ERR_VALIDATOR_JAILED: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    8,
+    "validator for this address is currently jailed",
+);
+pub const ERR_COMMISSION_HUGE: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 11, "commission cannot be more than 100%");
+pub const ERR_COMMISSION_GT_MAX_RATE: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    12,
+    "commission cannot be more than the max rate",
+);
+pub const ERR_COMMISSION_UPDATE_TIME: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    13,
+    "commission cannot be changed more than once in 24h",
+);
+pub const ERR_COMMISSION_CHANGE_RATE_GT_MAX_RATE: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    15,
+    "commission change rate cannot be more than the max rate",
+);
+pub const ERR_COMMISSION_GT_MAX_CHANGE_RATE: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    16,
+    "commission cannot be changed more than max change rate",
+);
+pub const ERR_SELF_DELEGATION_BELOW_MINIMUM: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    17,
+    "validator's self delegation must be greater than their minimum self delegation",
+);
+pub const ERR_MIN_SELF_DELEGATION_DECREASED: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    19,
+    "minimum self delegation cannot be decrease",
+);
+pub const ERR_EMPTY_DELEGATOR_ADDR: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 20, "empty delegator address");
+pub const ERR_NO_DELEGATION: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    24,
+    "no delegation for (address, validator) tuple",
+);
+pub const ERR_NO_DELEGATOR_FOR_ADDRESS: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 26, "delegator does not contain delegation");
+pub const ERR_INSUFFICIENT_SHARES: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 27, "insufficient delegation shares");
+pub const ERR_NOT_ENOUGH_DELEGATION_SHARES: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 29, "not enough delegation shares");
+pub const ERR_BAD_SHARES_AMOUNT: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 30, "invalid shares amount");
+pub const ERR_NO_UNBONDING_DELEGATION: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 33, "no unbonding delegation found");
+pub const ERR_MAX_UNBONDING_DELEGATION_ENTRIES: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    34,
+    "too many unbonding delegation entries for (delegator, validator) tuple",
+);
+pub const ERR_NO_REDELEGATION: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 36, "no redelegation found");
+pub const ERR_SELF_REDELEGATION: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 37, "cannot redelegate to the same validator");
+pub const ERR_TINY_REDELEGATION_AMOUNT: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    38,
+    "too few tokens to redelegate (truncates to zero tokens)",
+);
+pub const ERR_BAD_REDELEGATION_DST: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    39,
+    "redelegation destination validator not found",
+);
+pub const ERR_TRANSITIVE_REDELEGATION: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    40,
+    "redelegation to this validator already in progress; first redelegation to this validator must complete before next redelegation",
+);
+pub const ERR_MAX_REDELEGATION_ENTRIES: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    41,
+    "too many redelegation entries for (delegator, src-validator, dst-validator) tuple",
+);
+pub const ERR_DELEGATOR_SHARE_EX_RATE_INVALID: RegisteredError = RegisteredError::register(
+    MODULE_NAME,
+    42,
+    "cannot delegate to validators with invalid (zero) ex-rate",
+);
+pub const ERR_EMPTY_VALIDATOR_PUB_KEY: RegisteredError =
+    RegisteredError::register(MODULE_NAME, 47, "empty validator public key");
+
+// The default params, the same as the cosmos SDK
+pub const DEFAULT_UNBONDING_TIME: i64 = 60 * 60 * 24 * 7 * 3; // three weeks
+pub const DEFAULT_MAX_VALIDATORS: u32 = 100;
+pub const DEFAULT_MAX_ENTRIES: u32 = 7;
+pub const DEFAULT_HISTORICAL_ENTRIES: u32 = 10000;
+pub const DEFAULT_BOND_DENOM: &str = "stake";
+
+// The maximum lengths of the description fields
+const MAX_MONIKER_LENGTH: usize = 70;
+const MAX_IDENTITY_LENGTH: usize = 3000;
+const MAX_WEBSITE_LENGTH: usize = 140;
+const MAX_SECURITY_CONTACT_LENGTH: usize = 140;
+const MAX_DETAILS_LENGTH: usize = 280;
+
+/// A validator may change its commission rate once in this period
+const COMMISSION_UPDATE_PERIOD: i64 = 60 * 60 * 24;
+
+// The same store prefixes as the cosmos SDK
+const LAST_TOTAL_POWER_KEY: [u8; 1] = [0x11];
+const LAST_VALIDATOR_POWER_STORE_PREFIX: [u8; 1] = [0x12];
+const VALIDATOR_STORE_PREFIX: [u8; 1] = [0x21];
+const VALIDATOR_BY_CONS_ADDR_STORE_PREFIX: [u8; 1] = [0x22];
+const VALIDATOR_BY_POWER_INDEX_STORE_PREFIX: [u8; 1] = [0x23];
+const DELEGATION_STORE_PREFIX: [u8; 1] = [0x31];
+const UNBONDING_DELEGATION_STORE_PREFIX: [u8; 1] = [0x32];
+const UNBONDING_DELEGATION_BY_VAL_INDEX_STORE_PREFIX: [u8; 1] = [0x33];
+const REDELEGATION_STORE_PREFIX: [u8; 1] = [0x34];
+const REDELEGATION_BY_VAL_SRC_INDEX_STORE_PREFIX: [u8; 1] = [0x35];
+const REDELEGATION_BY_VAL_DST_INDEX_STORE_PREFIX: [u8; 1] = [0x36];
+const UNBONDING_QUEUE_STORE_PREFIX: [u8; 1] = [0x41];
+const REDELEGATION_QUEUE_STORE_PREFIX: [u8; 1] = [0x42];
+const VALIDATOR_QUEUE_STORE_PREFIX: [u8; 1] = [0x43];
+const HISTORICAL_INFO_STORE_PREFIX: [u8; 1] = [0x50];
+const PARAMS_KEY: [u8; 1] = [0x51];
+
+const PARAMS: Item<Params> = Item::new(&PARAMS_KEY);
+const LAST_TOTAL_POWER: Item<i64, StringCodec> = Item::new(&LAST_TOTAL_POWER_KEY);
+/// The power of each validator in the last validator set given to Tendermint
+const LAST_VALIDATOR_POWERS: Map<ValAddress, i64, StringCodec> =
+    Map::new(&LAST_VALIDATOR_POWER_STORE_PREFIX);
+const VALIDATORS: Map<ValAddress, ProtoValidator> = Map::new(&VALIDATOR_STORE_PREFIX);
+const VALIDATORS_BY_CONS_ADDR: Map<ConsAddress, ValAddress, KeyValueCodec<ValAddress>> =
+    Map::new(&VALIDATOR_BY_CONS_ADDR_STORE_PREFIX);
+/// The unjailed validators keyed by their potential consensus power followed by their inverted
+/// operator address, so that iterating in reverse gives the validators by descending power with
+/// ties going to the lowest address
+const VALIDATORS_BY_POWER: Map<(u64, Vec<u8>), ValAddress, KeyValueCodec<ValAddress>> =
+    Map::new(&VALIDATOR_BY_POWER_INDEX_STORE_PREFIX);
+const DELEGATIONS: Map<(AccAddress, ValAddress), ProtoDelegation> =
+    Map::new(&DELEGATION_STORE_PREFIX);
+const UNBONDING_DELEGATIONS: Map<(AccAddress, ValAddress), ProtoUnbondingDelegation> =
+    Map::new(&UNBONDING_DELEGATION_STORE_PREFIX);
+const UNBONDING_DELEGATIONS_BY_VAL: KeySet<(ValAddress, AccAddress)> =
+    KeySet::new(&UNBONDING_DELEGATION_BY_VAL_INDEX_STORE_PREFIX);
+/// The redelegations keyed by delegator, source validator and destination validator
+const REDELEGATIONS: Map<(AccAddress, (ValAddress, ValAddress)), ProtoRedelegation> =
+    Map::new(&REDELEGATION_STORE_PREFIX);
+/// Redelegations keyed by source validator, delegator and destination validator
+const REDELEGATIONS_BY_VAL_SRC: KeySet<(ValAddress, (AccAddress, ValAddress))> =
+    KeySet::new(&REDELEGATION_BY_VAL_SRC_INDEX_STORE_PREFIX);
+/// Redelegations keyed by destination validator, delegator and source validator
+const REDELEGATIONS_BY_VAL_DST: KeySet<(ValAddress, (AccAddress, ValAddress))> =
+    KeySet::new(&REDELEGATION_BY_VAL_DST_INDEX_STORE_PREFIX);
+/// The unbonding delegations keyed by the completion time of an entry
+const UNBONDING_QUEUE: KeySet<(Timestamp, (AccAddress, ValAddress))> =
+    KeySet::new(&UNBONDING_QUEUE_STORE_PREFIX);
+/// The redelegations keyed by the completion time of an entry
+const REDELEGATION_QUEUE: KeySet<(Timestamp, (AccAddress, (ValAddress, ValAddress)))> =
+    KeySet::new(&REDELEGATION_QUEUE_STORE_PREFIX);
+/// The unbonding validators keyed by the time and height at which they finish unbonding
+const VALIDATOR_QUEUE: KeySet<((Timestamp, i64), ValAddress)> =
+    KeySet::new(&VALIDATOR_QUEUE_STORE_PREFIX);
+/// The header and validator set of the recent blocks, keyed by height
+const HISTORICAL_INFO: Map<i64, HistoricalInfo> = Map::new(&HISTORICAL_INFO_STORE_PREFIX);
+
+pub struct GenesisState {
+    pub params: Params,
+}
+
+/// Returns the default params, which are the same as the cosmos SDK's
+pub fn default_params() -> Params {
+    Params {
+        unbonding_time: Some(Duration {
+            seconds: DEFAULT_UNBONDING_TIME,
+            nanos: 0,
+        }),
+        max_validators: DEFAULT_MAX_VALIDATORS,
+        max_entries: DEFAULT_MAX_ENTRIES,
+        historical_entries: DEFAULT_HISTORICAL_ENTRIES,
+        bond_denom: DEFAULT_BOND_DENOM.into(),
+    }
+}
+
+/// Checks the params as in the cosmos SDK, historical entries may be zero
+fn validate_params(params: &Params) -> Result<(), String> {
+    let unbonding_time = params.unbonding_time.clone().unwrap_or_default();
+    if unbonding_time.seconds <= 0 && unbonding_time.nanos <= 0 {
+        return Err(format!(
+            "unbonding time must be positive: {}s",
+            unbonding_time.seconds
+        ));
+    }
+
+    if params.max_validators == 0 {
+        return Err("max validators must be positive".into());
+    }
+
+    if params.max_entries == 0 {
+        return Err("max entries must be positive".into());
+    }
+
+    if params.bond_denom.trim().is_empty() {
+        return Err("bond denom cannot be blank".into());
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone)]
+pub struct Staking {}
+
+impl Staking {
+    pub fn register_queries(router: QueryRouter) -> QueryRouter {
+        router
+            .register(
+                "/cosmos.staking.v1beta1.Query/Validators",
+                Staking::query_validators,
+            )
+            .register(
+                "/cosmos.staking.v1beta1.Query/Validator",
+                Staking::query_validator,
+            )
+            .register(
+                "/cosmos.staking.v1beta1.Query/ValidatorDelegations",
+                Staking::query_validator_delegations,
+            )
+            .register(
+                "/cosmos.staking.v1beta1.Query/ValidatorUnbondingDelegations",
+                Staking::query_validator_unbonding_delegations,
+            )
+            .register(
+                "/cosmos.staking.v1beta1.Query/Delegation",
+                Staking::query_delegation,
+            )
+            .register(
+                "/cosmos.staking.v1beta1.Query/UnbondingDelegation",
+                Staking::query_unbonding_delegation,
+            )
+            .register(
+                "/cosmos.staking.v1beta1.Query/DelegatorDelegations",
+                Staking::query_delegator_delegations,
+            )
+            .register(
+                "/cosmos.staking.v1beta1.Query/DelegatorUnbondingDelegations",
+                Staking::query_delegator_unbonding_delegations,
+            )
+            .register(
+                "/cosmos.staking.v1beta1.Query/Redelegations",
+                Staking::query_redelegations,
+            )
+            .register(
+                "/cosmos.staking.v1beta1.Query/DelegatorValidators",
+                Staking::query_delegator_validators,
+            )
+            .register(
+                "/cosmos.staking.v1beta1.Query/HistoricalInfo",
+                Staking::query_historical_info,
+            )
+            .register("/cosmos.staking.v1beta1.Query/Pool", Staking::query_pool)
+            .register(
+                "/cosmos.staking.v1beta1.Query/Params",
+                Staking::query_params,
+            )
+    }
+
+    /// Sets the params. Panics if they're invalid.
+    pub fn init_genesis(ctx: &mut Context, genesis: GenesisState) {
+        if let Err(e) = validate_params(&genesis.params) {
+            panic!("invalid staking genesis params: {}", e)
+        }
+
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        PARAMS.set(&mut staking_store, &genesis.params);
+    }
+
+    pub fn get_params(ctx: &Context) -> Params {
+        let staking_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(STAKING_STORE_PREFIX.into());
+        read_params(&staking_store)
+    }
+
+    /// Returns the validators, optionally only those with the given status, in address order
+    pub fn query_validators(
+        ctx: &QueryContext,
+        req: QueryValidatorsRequest,
+    ) -> Result<QueryValidatorsResponse, AppError> {
+        let status = match req.status.as_str() {
+            "" => None,
+            "BOND_STATUS_UNBONDED" => Some(BondStatus::Unbonded),
+            "BOND_STATUS_UNBONDING" => Some(BondStatus::Unbonding),
+            "BOND_STATUS_BONDED" => Some(BondStatus::Bonded),
+            status => {
+                return Err(ERR_INVALID_REQUEST.wrap(format!("invalid validator status {}", status)))
+            }
+        };
+
+        let staking_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(STAKING_STORE_PREFIX.into());
+
+        let validators = VALIDATORS
+            .iter(&staking_store)
+            .filter(|(_, validator)| {
+                status.map_or(true, |status| validator.status == status as i32)
+            })
+            .map(|(address, validator)| (VALIDATORS.key(&address), validator));
+        let (validators, pagination) = paginate(validators, req.pagination)?;
+
+        Ok(QueryValidatorsResponse {
+            validators,
+            pagination,
+        })
+    }
+
+    pub fn query_validator(
+        ctx: &QueryContext,
+        req: QueryValidatorRequest,
+    ) -> Result<QueryValidatorResponse, AppError> {
+        let validator_address = parse_query_validator_address(&req.validator_addr)?;
+
+        let staking_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(STAKING_STORE_PREFIX.into());
+
+        match VALIDATORS.get(&staking_store, &validator_address) {
+            Some(validator) => Ok(QueryValidatorResponse {
+                validator: Some(validator),
+            }),
+            None => Err(ERR_NOT_FOUND.wrap(format!("validator {} not found", req.validator_addr))),
+        }
+    }
+
+    /// Returns the delegations to the validator, as in the cosmos SDK this iterates over all
+    /// delegations
+    pub fn query_validator_delegations(
+        ctx: &QueryContext,
+        req: QueryValidatorDelegationsRequest,
+    ) -> Result<QueryValidatorDelegationsResponse, AppError> {
+        let validator_address = parse_query_validator_address(&req.validator_addr)?;
+
+        let staking_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(STAKING_STORE_PREFIX.into());
+
+        let delegations = DELEGATIONS
+            .iter(&staking_store)
+            .filter(|((_, address), _)| address == &validator_address)
+            .map(|(key, delegation)| (DELEGATIONS.key(&key), delegation));
+        let (delegations, pagination) = paginate(delegations, req.pagination)?;
+
+        Ok(QueryValidatorDelegationsResponse {
+            delegation_responses: delegation_responses(&staking_store, delegations)?,
+            pagination,
+        })
+    }
+
+    pub fn query_validator_unbonding_delegations(
+        ctx: &QueryContext,
+        req: QueryValidatorUnbondingDelegationsRequest,
+    ) -> Result<QueryValidatorUnbondingDelegationsResponse, AppError> {
+        let validator_address = parse_query_validator_address(&req.validator_addr)?;
+
+        let staking_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(STAKING_STORE_PREFIX.into());
+
+        let keys = UNBONDING_DELEGATIONS_BY_VAL
+            .prefix_iter(&staking_store, &validator_address)
+            .map(|key| (UNBONDING_DELEGATIONS_BY_VAL.key(&key), key));
+        let (keys, pagination) = paginate(keys, req.pagination)?;
+
+        let unbonding_responses = keys
+            .into_iter()
+            .map(|(validator_address, delegator_address)| {
+                UNBONDING_DELEGATIONS
+                    .get(&staking_store, &(delegator_address, validator_address))
+                    .expect("indexed unbonding delegations exist")
+            })
+            .collect();
+
+        Ok(QueryValidatorUnbondingDelegationsResponse {
+            unbonding_responses,
+            pagination,
+        })
+    }
+
+    pub fn query_delegation(
+        ctx: &QueryContext,
+        req: QueryDelegationRequest,
+    ) -> Result<QueryDelegationResponse, AppError> {
+        let delegator_address = parse_query_delegator_address(&req.delegator_addr)?;
+        let validator_address = parse_query_validator_address(&req.validator_addr)?;
+
+        let staking_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(STAKING_STORE_PREFIX.into());
+
+        let delegation = DELEGATIONS
+            .get(&staking_store, &(delegator_address, validator_address))
+            .ok_or_else(|| {
+                ERR_NOT_FOUND.wrap(format!(
+                    "delegation with delegator {} not found for validator {}",
+                    req.delegator_addr, req.validator_addr
+                ))
+            })?;
+
+        Ok(QueryDelegationResponse {
+            delegation_response: delegation_responses(&staking_store, vec![delegation])?.pop(),
+        })
+    }
+
+    pub fn query_delegator_delegations(
+        ctx: &QueryContext,
+        req: QueryDelegatorDelegationsRequest,
+    ) -> Result<QueryDelegatorDelegationsResponse, AppError> {
+        let delegator_address = parse_query_delegator_address(&req.delegator_addr)?;
+
+        let staking_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(STAKING_STORE_PREFIX.into());
+
+        let delegations = DELEGATIONS
+            .prefix_iter(&staking_store, &delegator_address)
+            .map(|(key, delegation)| (DELEGATIONS.key(&key), delegation));
+        let (delegations, pagination) = paginate(delegations, req.pagination)?;
+
+        Ok(QueryDelegatorDelegationsResponse {
+            delegation_responses: delegation_responses(&staking_store, delegations)?,
+            pagination,
+        })
+    }
+
+    pub fn query_unbonding_delegation(
+        ctx: &QueryContext,
+        req: QueryUnbondingDelegationRequest,
+    ) -> Result<QueryUnbondingDelegationResponse, AppError> {
+        let delegator_address = parse_query_delegator_address(&req.delegator_addr)?;
+        let validator_address = parse_query_validator_address(&req.validator_addr)?;
+
+        let staking_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(STAKING_STORE_PREFIX.into());
+
+        match UNBONDING_DELEGATIONS.get(&staking_store, &(delegator_address, validator_address)) {
+            Some(unbond) => Ok(QueryUnbondingDelegationResponse {
+                unbond: Some(unbond),
+            }),
+            None => Err(ERR_NOT_FOUND.wrap(format!(
+                "unbonding delegation with delegator {} not found for validator {}",
+                req.delegator_addr, req.validator_addr
+            ))),
+        }
+    }
+
+    pub fn query_delegator_unbonding_delegations(
+        ctx: &QueryContext,
+        req: QueryDelegatorUnbondingDelegationsRequest,
+    ) -> Result<QueryDelegatorUnbondingDelegationsResponse, AppError> {
+        let delegator_address = parse_query_delegator_address(&req.delegator_addr)?;
+
+        let staking_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(STAKING_STORE_PREFIX.into());
+
+        let unbonds = UNBONDING_DELEGATIONS
+            .prefix_iter(&staking_store, &delegator_address)
+            .map(|(key, ubd)| (UNBONDING_DELEGATIONS.key(&key), ubd));
+        let (unbonding_responses, pagination) = paginate(unbonds, req.pagination)?;
+
+        Ok(QueryDelegatorUnbondingDelegationsResponse {
+            unbonding_responses,
+            pagination,
+        })
+    }
+
+    /// Returns the redelegation of the delegator between the source and destination validators
+    /// if all are given, the redelegations from the source validator if only it's given, and
+    /// otherwise the redelegations of the delegator
+    pub fn query_redelegations(
+        ctx: &QueryContext,
+        req: QueryRedelegationsRequest,
+    ) -> Result<QueryRedelegationsResponse, AppError> {
+        let staking_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(STAKING_STORE_PREFIX.into());
+
+        let (redelegations, pagination) = match (
+            req.delegator_addr.is_empty(),
+            req.src_validator_addr.is_empty(),
+            req.dst_validator_addr.is_empty(),
+        ) {
+            (false, false, false) => {
+                let key = (
+                    AccAddress::from_bech32(&req.delegator_addr)?,
+                    (
+                        ValAddress::from_bech32(&req.src_validator_addr)?,
+                        ValAddress::from_bech32(&req.dst_validator_addr)?,
+                    ),
+                );
+                let redelegation = REDELEGATIONS.get(&staking_store, &key).ok_or_else(|| {
+                    ERR_NOT_FOUND.wrap(format!(
+                        "redelegation not found for delegator address {} from validator address {}",
+                        req.delegator_addr, req.src_validator_addr
+                    ))
+                })?;
+
+                (vec![redelegation], None)
+            }
+            (true, false, true) => {
+                let src_address = ValAddress::from_bech32(&req.src_validator_addr)?;
+                let keys = REDELEGATIONS_BY_VAL_SRC
+                    .prefix_iter(&staking_store, &src_address)
+                    .map(|key| (REDELEGATIONS_BY_VAL_SRC.key(&key), key));
+                let (keys, pagination) = paginate(keys, req.pagination)?;
+
+                let redelegations = keys
+                    .into_iter()
+                    .map(|(src_address, (delegator_address, dst_address))| {
+                        REDELEGATIONS
+                            .get(
+                                &staking_store,
+                                &(delegator_address, (src_address, dst_address)),
+                            )
+                            .expect("indexed redelegations exist")
+                    })
+                    .collect();
+
+                (redelegations, pagination)
+            }
+            _ => {
+                let delegator_address = parse_query_delegator_address(&req.delegator_addr)?;
+                let redelegations = REDELEGATIONS
+                    .prefix_iter(&staking_store, &delegator_address)
+                    .map(|(key, red)| (REDELEGATIONS.key(&key), red));
+
+                paginate(redelegations, req.pagination)?
+            }
+        };
+
+        let redelegation_responses = redelegations
+            .into_iter()
+            .map(|red| redelegation_response(&staking_store, red))
+            .collect::<Result<_, _>>()?;
+
+        Ok(QueryRedelegationsResponse {
+            redelegation_responses,
+            pagination,
+        })
+    }
+
+    /// Returns the validators the delegator has delegated to
+    pub fn query_delegator_validators(
+        ctx: &QueryContext,
+        req: QueryDelegatorValidatorsRequest,
+    ) -> Result<QueryDelegatorValidatorsResponse, AppError> {
+        let delegator_address = parse_query_delegator_address(&req.delegator_addr)?;
+
+        let staking_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(STAKING_STORE_PREFIX.into());
+
+        let delegations = DELEGATIONS
+            .prefix_iter(&staking_store, &delegator_address)
+            .map(|(key, _)| (DELEGATIONS.key(&key), key));
+        let (delegations, pagination) = paginate(delegations, req.pagination)?;
+
+        let validators = delegations
+            .into_iter()
+            .map(|(_, validator_address)| {
+                VALIDATORS
+                    .get(&staking_store, &validator_address)
+                    .ok_or(AppError::from(ERR_NO_VALIDATOR_FOUND))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(QueryDelegatorValidatorsResponse {
+            validators,
+            pagination,
+        })
+    }
+
+    pub fn query_historical_info(
+        ctx: &QueryContext,
+        req: QueryHistoricalInfoRequest,
+    ) -> Result<QueryHistoricalInfoResponse, AppError> {
+        if req.height < 0 {
+            return Err(ERR_INVALID_REQUEST.wrap("height cannot be negative"));
+        }
+
+        let staking_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(STAKING_STORE_PREFIX.into());
+
+        match HISTORICAL_INFO.get(&staking_store, &req.height) {
+            Some(hist) => Ok(QueryHistoricalInfoResponse { hist: Some(hist) }),
+            None => Err(ERR_NOT_FOUND.wrap(format!(
+                "historical info for height {} not found",
+                req.height
+            ))),
+        }
+    }
+
+    /// Returns the tokens in the bonded and not bonded pools
+    pub fn query_pool(
+        ctx: &QueryContext,
+        _req: QueryPoolRequest,
+    ) -> Result<QueryPoolResponse, AppError> {
+        let staking_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(STAKING_STORE_PREFIX.into());
+        let bond_denom = read_params(&staking_store).bond_denom;
+
+        let pool_balance = |name: &str| -> Result<String, AppError> {
+            let balance = Bank::query_balance(
+                ctx,
+                QueryBalanceRequest {
+                    address: AccAddress::new_module_address(name).to_string(),
+                    denom: bond_denom.clone(),
+                },
+            )?
+            .balance;
+
+            Ok(balance
+                .map_or(Uint256::zero(), |coin| coin.amount)
+                .to_string())
+        };
+
+        Ok(QueryPoolResponse {
+            pool: Some(Pool {
+                not_bonded_tokens: pool_balance(NOT_BONDED_POOL_NAME)?,
+                bonded_tokens: pool_balance(BONDED_POOL_NAME)?,
+            }),
+        })
+    }
+
+    pub fn query_params(
+        ctx: &QueryContext,
+        _req: QueryParamsRequest,
+    ) -> Result<QueryParamsResponse, AppError> {
+        let staking_store = ctx
+            .get_multi_store()
+            .get_immutable_sub_store(STAKING_STORE_PREFIX.into());
+
+        Ok(QueryParamsResponse {
+            params: Some(read_params(&staking_store)),
+        })
+    }
+
+    /// Stores the header and validator set of the block so that IBC light clients can verify
+    /// recent headers, pruning the entries older than the historical entries param
+    pub fn begin_block(ctx: &mut Context) {
+        let entries = i64::from(Staking::get_params(ctx).historical_entries);
+        let height = ctx.block_height();
+
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        for old_height in (0..=height - entries).rev() {
+            if !HISTORICAL_INFO.has(&staking_store, &old_height) {
+                break;
+            }
+            HISTORICAL_INFO.remove(&mut staking_store, &old_height);
+        }
+        drop(staking_store);
+
+        if entries == 0 {
+            return;
+        }
+
+        let mut valset = Staking::get_last_validators(ctx);
+        valset.sort_by(|a, b| {
+            b.consensus_power()
+                .cmp(&a.consensus_power())
+                .then_with(|| Vec::from(a.cons_address()).cmp(&Vec::from(b.cons_address())))
+        });
+
+        let hist = HistoricalInfo {
+            header: Some(ctx.header().clone()),
+            valset: valset.iter().map(Validator::to_proto).collect(),
+        };
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        HISTORICAL_INFO.set(&mut staking_store, &height, &hist);
+    }
+
+    /// Handles MsgCreateValidator, which creates an unbonded validator with the self delegation
+    /// of the message
+    pub fn create_validator(
+        ctx: &mut Context,
+        msg: MsgCreateValidator,
+    ) -> Result<MsgCreateValidatorResponse, AppError> {
+        let validator_address = ValAddress::from_bech32(&msg.validator_address)?;
+        let delegator_address = AccAddress::from_bech32(&msg.delegator_address)?;
+
+        if Staking::get_validator(ctx, &validator_address).is_some() {
+            return Err(ERR_VALIDATOR_OWNER_EXISTS.into());
+        }
+
+        let pubkey = msg.pubkey.clone().unwrap_or_default();
+        let pub_key = SignerPubKey::from_any(&pubkey).map_err(|_| {
+            ERR_INVALID_TYPE.wrap(format!(
+                "expecting cryptotypes.PubKey, got {}",
+                pubkey.type_url
+            ))
+        })?;
+        let pub_key_type = match pub_key {
+            SignerPubKey::Ed25519(_) => "ed25519",
+            SignerPubKey::Secp256k1(_) => "secp256k1",
+            SignerPubKey::Multisig { .. } => {
+                return Err(ERR_VALIDATOR_PUB_KEY_TYPE_NOT_SUPPORTED
+                    .wrap(format!("got: {}", pubkey.type_url)))
+            }
+        };
+
+        if Staking::get_validator_by_cons_addr(ctx, &cons_address(&pub_key)).is_some() {
+            return Err(ERR_VALIDATOR_PUB_KEY_EXISTS.into());
+        }
+
+        let value = msg.value.clone().unwrap_or_default();
+        let bond_denom = Staking::get_params(ctx).bond_denom;
+        if value.denom != bond_denom {
+            return Err(ERR_INVALID_REQUEST.wrap(format!(
+                "invalid coin denomination: got {}, expected {}",
+                value.denom, bond_denom
+            )));
+        }
+
+        let description = msg.description.clone().unwrap_or_default();
+        validate_description(&description)?;
+
+        let pub_key_types = ctx
+            .consensus_params()
+            .validator
+            .expect("the validator consensus params are set by init_chain")
+            .pub_key_types;
+        if !pub_key_types.iter().any(|t| t == pub_key_type) {
+            return Err(ERR_VALIDATOR_PUB_KEY_TYPE_NOT_SUPPORTED.wrap(format!(
+                "got: {}, expected: {:?}",
+                pub_key_type, pub_key_types
+            )));
+        }
+
+        let commission = Commission {
+            update_time: ctx.block_time(),
+            ..parse_commission_rates(&msg.commission.clone().unwrap_or_default())?
+        };
+        let min_self_delegation = parse_int(&msg.min_self_delegation, "minimum self delegation")?;
+
+        let validator = Validator::new(
+            validator_address,
+            pubkey,
+            description,
+            commission,
+            min_self_delegation,
+        );
+        Staking::set_validator(ctx, &validator);
+        Staking::set_validator_by_cons_addr(ctx, &validator);
+        Staking::set_validator_by_power_index(ctx, &validator);
+        Distribution::after_validator_created(ctx, &validator.operator_address);
+
+        Staking::delegate(ctx, &delegator_address, value.amount, validator)?;
+
+        ctx.event_manager().emit_events(vec![
+            new_event(
+                EVENT_TYPE_CREATE_VALIDATOR,
+                vec![
+                    (ATTRIBUTE_KEY_VALIDATOR, msg.validator_address),
+                    (ATTRIBUTE_KEY_AMOUNT, coin_to_string(&value)),
+                ],
+            ),
+            new_event(
+                EVENT_TYPE_MESSAGE,
+                vec![
+                    (ATTRIBUTE_KEY_MODULE, MODULE_NAME.into()),
+                    (ATTRIBUTE_KEY_SENDER, msg.delegator_address),
+                ],
+            ),
+        ]);
+
+        Ok(MsgCreateValidatorResponse {})
+    }
+
+    /// Handles MsgEditValidator, which updates the description, commission rate and minimum
+    /// self delegation of a validator
+    pub fn edit_validator(
+        ctx: &mut Context,
+        msg: MsgEditValidator,
+    ) -> Result<MsgEditValidatorResponse, AppError> {
+        let validator_address = ValAddress::from_bech32(&msg.validator_address)?;
+        let mut validator = Staking::get_validator(ctx, &validator_address)
+            .ok_or(AppError::from(ERR_NO_VALIDATOR_FOUND))?;
+
+        let description =
+            update_description(&validator.description, msg.description.unwrap_or_default());
+        validate_description(&description)?;
+        validator.description = description;
+
+        if !msg.commission_rate.is_empty() {
+            let rate = parse_dec(&msg.commission_rate, "commission rate")?;
+            validator.commission =
+                update_commission(&validator.commission, rate, ctx.block_time())?;
+        }
+
+        if !msg.min_self_delegation.is_empty() {
+            let min_self_delegation =
+                parse_int(&msg.min_self_delegation, "minimum self delegation")?;
+
+            if min_self_delegation <= validator.min_self_delegation {
+                return Err(ERR_MIN_SELF_DELEGATION_DECREASED.into());
+            }
+
+            if min_self_delegation > validator.tokens {
+                return Err(ERR_SELF_DELEGATION_BELOW_MINIMUM.into());
+            }
+
+            validator.min_self_delegation = min_self_delegation;
+        }
+
+        Staking::set_validator(ctx, &validator);
+
+        let sender = AccAddress::from(validator_address).to_string();
+        ctx.event_manager().emit_events(vec![
+            new_event(
+                EVENT_TYPE_EDIT_VALIDATOR,
+                vec![
+                    (
+                        ATTRIBUTE_KEY_COMMISSION_RATE,
+                        validator.commission.rate.to_string(),
+                    ),
+                    (
+                        ATTRIBUTE_KEY_MIN_SELF_DELEGATION,
+                        validator.min_self_delegation.to_string(),
+                    ),
+                ],
+            ),
+            new_event(
+                EVENT_TYPE_MESSAGE,
+                vec![
+                    (ATTRIBUTE_KEY_MODULE, MODULE_NAME.into()),
+                    (ATTRIBUTE_KEY_SENDER, sender),
+                ],
+            ),
+        ]);
+
+        Ok(MsgEditValidatorResponse {})
+    }
+
+    /// Handles MsgDelegate
+    pub fn delegate_msg(
+        ctx: &mut Context,
+        msg: MsgDelegate,
+    ) -> Result<MsgDelegateResponse, AppError> {
+        let validator_address = ValAddress::from_bech32(&msg.validator_address)?;
+        let delegator_address = AccAddress::from_bech32(&msg.delegator_address)?;
+        let validator = Staking::get_validator(ctx, &validator_address)
+            .ok_or(AppError::from(ERR_NO_VALIDATOR_FOUND))?;
+
+        let amount = msg.amount.unwrap_or_default();
+        let bond_denom = Staking::get_params(ctx).bond_denom;
+        if amount.denom != bond_denom {
+            return Err(ERR_INVALID_REQUEST.wrap(format!(
+                "invalid coin denomination: got {}, expected {}",
+                amount.denom, bond_denom
+            )));
+        }
+
+        let new_shares = Staking::delegate(ctx, &delegator_address, amount.amount, validator)?;
+
+        ctx.event_manager().emit_events(vec![
+            new_event(
+                EVENT_TYPE_DELEGATE,
+                vec![
+                    (ATTRIBUTE_KEY_VALIDATOR, msg.validator_address),
+                    (ATTRIBUTE_KEY_AMOUNT, coin_to_string(&amount)),
+                    (ATTRIBUTE_KEY_NEW_SHARES, new_shares.to_string()),
+                ],
+            ),
+            new_event(
+                EVENT_TYPE_MESSAGE,
+                vec![
+                    (ATTRIBUTE_KEY_MODULE, MODULE_NAME.into()),
+                    (ATTRIBUTE_KEY_SENDER, msg.delegator_address),
+                ],
+            ),
+        ]);
+
+        Ok(MsgDelegateResponse {})
+    }
+
+    /// Delegates tokens of the bond denom from the delegator to the validator, moving them to
+    /// the pool matching the validator's status, and returns the shares issued. As with the
+    /// cosmos SDK `Delegate`.
+    pub fn delegate(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        bond_amount: Uint256,
+        validator: Validator,
+    ) -> Result<Decimal256, AppError> {
+        if validator.invalid_ex_rate() {
+            return Err(ERR_DELEGATOR_SHARE_EX_RATE_INVALID.into());
+        }
+
+        Staking::before_delegation_modified(ctx, delegator_address, &validator.operator_address);
+
+        let pool = if validator.is_bonded() {
+            BONDED_POOL_NAME
+        } else {
+            NOT_BONDED_POOL_NAME
+        };
+        let coins = vec![Coin {
+            denom: Staking::get_params(ctx).bond_denom,
+            amount: bond_amount,
+        }];
+        Bank::delegate_coins_from_account_to_module(ctx, delegator_address, pool, &coins)?;
+
+        Ok(Staking::add_delegation(
+            ctx,
+            delegator_address,
+            bond_amount,
+            validator,
+        ))
+    }
+
+    /// Handles MsgUndelegate
+    pub fn undelegate_msg(
+        ctx: &mut Context,
+        msg: MsgUndelegate,
+    ) -> Result<MsgUndelegateResponse, AppError> {
+        let validator_address = ValAddress::from_bech32(&msg.validator_address)?;
+        let delegator_address = AccAddress::from_bech32(&msg.delegator_address)?;
+        let amount = msg.amount.unwrap_or_default();
+
+        let shares = Staking::validate_unbond_amount(
+            ctx,
+            &delegator_address,
+            &validator_address,
+            amount.amount,
+        )?;
+
+        let bond_denom = Staking::get_params(ctx).bond_denom;
+        if amount.denom != bond_denom {
+            return Err(ERR_INVALID_REQUEST.wrap(format!(
+                "invalid coin denomination: got {}, expected {}",
+                amount.denom, bond_denom
+            )));
+        }
+
+        let completion_time =
+            Staking::undelegate(ctx, &delegator_address, &validator_address, shares)?;
+
+        ctx.event_manager().emit_events(vec![
+            new_event(
+                EVENT_TYPE_UNBOND,
+                vec![
+                    (ATTRIBUTE_KEY_VALIDATOR, msg.validator_address),
+                    (ATTRIBUTE_KEY_AMOUNT, coin_to_string(&amount)),
+                    (ATTRIBUTE_KEY_COMPLETION_TIME, format_time(&completion_time)),
+                ],
+            ),
+            new_event(
+                EVENT_TYPE_MESSAGE,
+                vec![
+                    (ATTRIBUTE_KEY_MODULE, MODULE_NAME.into()),
+                    (ATTRIBUTE_KEY_SENDER, msg.delegator_address),
+                ],
+            ),
+        ]);
+
+        Ok(MsgUndelegateResponse {
+            completion_time: Some(completion_time),
+        })
+    }
+
+    /// Handles MsgBeginRedelegate
+    pub fn begin_redelegate_msg(
+        ctx: &mut Context,
+        msg: MsgBeginRedelegate,
+    ) -> Result<MsgBeginRedelegateResponse, AppError> {
+        let src_address = ValAddress::from_bech32(&msg.validator_src_address)?;
+        let dst_address = ValAddress::from_bech32(&msg.validator_dst_address)?;
+        let delegator_address = AccAddress::from_bech32(&msg.delegator_address)?;
+        let amount = msg.amount.unwrap_or_default();
+
+        let shares =
+            Staking::validate_unbond_amount(ctx, &delegator_address, &src_address, amount.amount)?;
+
+        let bond_denom = Staking::get_params(ctx).bond_denom;
+        if amount.denom != bond_denom {
+            return Err(ERR_INVALID_REQUEST.wrap(format!(
+                "invalid coin denomination: got {}, expected {}",
+                amount.denom, bond_denom
+            )));
+        }
+
+        let completion_time = Staking::begin_redelegation(
+            ctx,
+            &delegator_address,
+            &src_address,
+            &dst_address,
+            shares,
+        )?;
+
+        ctx.event_manager().emit_events(vec![
+            new_event(
+                EVENT_TYPE_REDELEGATE,
+                vec![
+                    (ATTRIBUTE_KEY_SRC_VALIDATOR, msg.validator_src_address),
+                    (ATTRIBUTE_KEY_DST_VALIDATOR, msg.validator_dst_address),
+                    (ATTRIBUTE_KEY_AMOUNT, coin_to_string(&amount)),
+                    (ATTRIBUTE_KEY_COMPLETION_TIME, format_time(&completion_time)),
+                ],
+            ),
+            new_event(
+                EVENT_TYPE_MESSAGE,
+                vec![
+                    (ATTRIBUTE_KEY_MODULE, MODULE_NAME.into()),
+                    (ATTRIBUTE_KEY_SENDER, msg.delegator_address),
+                ],
+            ),
+        ]);
+
+        Ok(MsgBeginRedelegateResponse {
+            completion_time: Some(completion_time),
+        })
+    }
+
+    /// Returns the shares worth the given tokens of the delegation, failing if the delegation
+    /// doesn't have enough
+    fn validate_unbond_amount(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        validator_address: &ValAddress,
+        amount: Uint256,
+    ) -> Result<Decimal256, AppError> {
+        let validator = Staking::get_validator(ctx, validator_address)
+            .ok_or(AppError::from(ERR_NO_VALIDATOR_FOUND))?;
+        let delegation = Staking::get_delegation(ctx, delegator_address, validator_address)
+            .ok_or(AppError::from(ERR_NO_DELEGATION))?;
+
+        if validator.tokens.is_zero() {
+            return Err(ERR_INSUFFICIENT_SHARES.into());
+        }
+
+        let shares = validator.shares_from_tokens(amount);
+        if shares > delegation.shares {
+            return Err(ERR_BAD_SHARES_AMOUNT.into());
+        }
+
+        Ok(shares)
+    }
+
+    /// Unbonds the shares of the delegation, adding an entry to the delegator's unbonding
+    /// delegation which completes after the unbonding time, and returns the completion time. As
+    /// with the cosmos SDK `Undelegate`.
+    pub fn undelegate(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        validator_address: &ValAddress,
+        shares: Decimal256,
+    ) -> Result<Timestamp, AppError> {
+        let validator = Staking::get_validator(ctx, validator_address)
+            .ok_or(AppError::from(ERR_NO_DELEGATOR_FOR_ADDRESS))?;
+
+        let params = Staking::get_params(ctx);
+        let entries = Staking::get_unbonding_delegation(ctx, delegator_address, validator_address)
+            .map_or(0, |ubd| ubd.entries.len());
+        if entries >= params.max_entries as usize {
+            return Err(ERR_MAX_UNBONDING_DELEGATION_ENTRIES.into());
+        }
+
+        let amount = Staking::unbond(ctx, delegator_address, validator_address, shares)?;
+
+        if validator.is_bonded() && !amount.is_zero() {
+            Staking::transfer_between_pools(
+                ctx,
+                BONDED_POOL_NAME,
+                NOT_BONDED_POOL_NAME,
+                amount,
+                &params.bond_denom,
+            );
+        }
+
+        let completion_time = add_duration(
+            &ctx.block_time(),
+            &params.unbonding_time.unwrap_or_default(),
+        );
+
+        let mut ubd = Staking::get_unbonding_delegation(ctx, delegator_address, validator_address)
+            .unwrap_or(UnbondingDelegation {
+                delegator_address: delegator_address.clone(),
+                validator_address: validator_address.clone(),
+                entries: vec![],
+            });
+        ubd.entries.push(UnbondingDelegationEntry {
+            creation_height: ctx.block_height(),
+            completion_time: completion_time.clone(),
+            initial_balance: amount,
+            balance: amount,
+        });
+        Staking::set_unbonding_delegation(ctx, &ubd);
+
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        UNBONDING_QUEUE.insert(
+            &mut staking_store,
+            &(
+                completion_time.clone(),
+                (delegator_address.clone(), validator_address.clone()),
+            ),
+        );
+
+        Ok(completion_time)
+    }
+
+    /// Moves the shares of the delegation to the destination validator, returning the time at
+    /// which the redelegation completes. Until then the redelegated tokens may be slashed for
+    /// faults of the source validator, so they can't be redelegated again. As with the cosmos
+    /// SDK `BeginRedelegation`.
+    pub fn begin_redelegation(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        src_address: &ValAddress,
+        dst_address: &ValAddress,
+        shares: Decimal256,
+    ) -> Result<Timestamp, AppError> {
+        if src_address == dst_address {
+            return Err(ERR_SELF_REDELEGATION.into());
+        }
+
+        let dst_validator = Staking::get_validator(ctx, dst_address)
+            .ok_or(AppError::from(ERR_BAD_REDELEGATION_DST))?;
+        let src_validator = Staking::get_validator(ctx, src_address)
+            .ok_or(AppError::from(ERR_BAD_REDELEGATION_DST))?;
+
+        if Staking::has_receiving_redelegation(ctx, delegator_address, src_address) {
+            return Err(ERR_TRANSITIVE_REDELEGATION.into());
+        }
+
+        let params = Staking::get_params(ctx);
+        let entries = Staking::get_redelegation(ctx, delegator_address, src_address, dst_address)
+            .map_or(0, |red| red.entries.len());
+        if entries >= params.max_entries as usize {
+            return Err(ERR_MAX_REDELEGATION_ENTRIES.into());
+        }
+
+        let amount = Staking::unbond(ctx, delegator_address, src_address, shares)?;
+        if amount.is_zero() {
+            return Err(ERR_TINY_REDELEGATION_AMOUNT.into());
+        }
+
+        if dst_validator.invalid_ex_rate() {
+            return Err(ERR_DELEGATOR_SHARE_EX_RATE_INVALID.into());
+        }
+
+        Staking::before_delegation_modified(ctx, delegator_address, dst_address);
+
+        // the tokens only move if the validators are in different pools
+        match (src_validator.is_bonded(), dst_validator.is_bonded()) {
+            (true, false) => Staking::transfer_between_pools(
+                ctx,
+                BONDED_POOL_NAME,
+                NOT_BONDED_POOL_NAME,
+                amount,
+                &params.bond_denom,
+            ),
+            (false, true) => Staking::transfer_between_pools(
+                ctx,
+                NOT_BONDED_POOL_NAME,
+                BONDED_POOL_NAME,
+                amount,
+                &params.bond_denom,
+            ),
+            _ => {}
+        }
+
+        let shares_dst = Staking::add_delegation(ctx, delegator_address, amount, dst_validator);
+
+        // as in the cosmos SDK the redelegation completes when the source validator finishes
+        // unbonding, and immediately if it's unbonded
+        let (completion_time, creation_height) = match Staking::get_validator(ctx, src_address) {
+            Some(validator) if validator.status == BondStatus::Unbonded => {
+                return Ok(Timestamp::default())
+            }
+            Some(validator) if validator.status == BondStatus::Unbonding => {
+                (validator.unbonding_time, validator.unbonding_height)
+            }
+            _ => (
+                add_duration(
+                    &ctx.block_time(),
+                    &params.unbonding_time.unwrap_or_default(),
+                ),
+                ctx.block_height(),
+            ),
+        };
+
+        let mut red = Staking::get_redelegation(ctx, delegator_address, src_address, dst_address)
+            .unwrap_or(Redelegation {
+                delegator_address: delegator_address.clone(),
+                validator_src_address: src_address.clone(),
+                validator_dst_address: dst_address.clone(),
+                entries: vec![],
+            });
+        red.entries.push(RedelegationEntry {
+            creation_height,
+            completion_time: completion_time.clone(),
+            initial_balance: amount,
+            shares_dst,
+        });
+        Staking::set_redelegation(ctx, &red);
+
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        REDELEGATION_QUEUE.insert(
+            &mut staking_store,
+            &(
+                completion_time.clone(),
+                (
+                    delegator_address.clone(),
+                    (src_address.clone(), dst_address.clone()),
+                ),
+            ),
+        );
+
+        Ok(completion_time)
+    }
+
+    /// Adds the tokens, which are already in the validator's pool, to the validator and the
+    /// delegation, returning the shares issued
+    fn add_delegation(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        amount: Uint256,
+        validator: Validator,
+    ) -> Decimal256 {
+        let mut delegation =
+            Staking::get_delegation(ctx, delegator_address, &validator.operator_address).unwrap_or(
+                Delegation {
+                    delegator_address: delegator_address.clone(),
+                    validator_address: validator.operator_address.clone(),
+                    shares: Decimal256::zero(),
+                },
+            );
+
+        let new_shares = Staking::add_validator_tokens_and_shares(ctx, validator, amount);
+
+        delegation.shares += new_shares;
+        Staking::set_delegation(ctx, &delegation);
+        Distribution::after_delegation_modified(
+            ctx,
+            delegator_address,
+            &delegation.validator_address,
+        );
+
+        new_shares
+    }
+
+    /// Ends the validator's distribution period before a delegation to it is created, or
+    /// withdraws the rewards of an existing delegation before its shares change
+    fn before_delegation_modified(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        validator_address: &ValAddress,
+    ) {
+        if Staking::get_delegation(ctx, delegator_address, validator_address).is_some() {
+            Distribution::before_delegation_shares_modified(
+                ctx,
+                delegator_address,
+                validator_address,
+            );
+        } else {
+            Distribution::before_delegation_created(ctx, validator_address);
+        }
+    }
+
+    /// Removes the shares from the delegation and the validator, returning the tokens they
+    /// were worth. As in the cosmos SDK an operator whose self delegation falls below their
+    /// minimum self delegation is jailed.
+    fn unbond(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        validator_address: &ValAddress,
+        shares: Decimal256,
+    ) -> Result<Uint256, AppError> {
+        let mut delegation = Staking::get_delegation(ctx, delegator_address, validator_address)
+            .ok_or(AppError::from(ERR_NO_DELEGATOR_FOR_ADDRESS))?;
+
+        if delegation.shares < shares {
+            return Err(
+                ERR_NOT_ENOUGH_DELEGATION_SHARES.wrap(format!("invalid shares amount: {}", shares))
+            );
+        }
+
+        Distribution::before_delegation_shares_modified(ctx, delegator_address, validator_address);
+
+        let mut validator = Staking::get_validator(ctx, validator_address)
+            .ok_or(AppError::from(ERR_NO_VALIDATOR_FOUND))?;
+
+        delegation.shares -= shares;
+
+        let is_operator = &AccAddress::from(validator_address.clone()) == delegator_address;
+        if is_operator
+            && !validator.jailed
+            && validator.tokens_from_shares(delegation.shares) < validator.min_self_delegation
+        {
+            validator = Staking::jail_validator(ctx, validator);
+        }
+
+        if delegation.shares.is_zero() {
+            Staking::remove_delegation(ctx, &delegation);
+        } else {
+            Staking::set_delegation(ctx, &delegation);
+            Distribution::after_delegation_modified(ctx, delegator_address, validator_address);
+        }
+
+        let (validator, amount) =
+            Staking::remove_validator_tokens_and_shares(ctx, validator, shares);
+
+        if validator.delegator_shares.is_zero() && validator.status == BondStatus::Unbonded {
+            Staking::remove_validator(ctx, &validator);
+        }
+
+        Ok(amount)
+    }
+
+    /// Slashes the validator with the consensus address for an infraction at the given height,
+    /// when it had the given power. The slash factor is first applied to the unbonding
+    /// delegations and redelegations away from the validator which started after the infraction,
+    /// then the rest comes from the validator's tokens. The slashed tokens are burned. As with the
+    /// cosmos SDK staking `Slash`.
+    pub fn slash(
+        ctx: &mut Context,
+        cons_address: &ConsAddress,
+        infraction_height: i64,
+        power: i64,
+        slash_factor: Decimal256,
+    ) {
+        let amount = tokens_from_consensus_power(power);
+        let slash_amount = slash_factor * amount;
+
+        // the validator may have been removed after unbonding, in which case there's nothing
+        // left to slash
+        let validator = match Staking::get_validator_by_cons_addr(ctx, cons_address) {
+            Some(validator) => validator,
+            None => return,
+        };
+
+        if validator.status == BondStatus::Unbonded {
+            panic!(
+                "should not be slashing unbonded validator: {}",
+                validator.operator_address
+            )
+        }
+
+        let height = ctx.block_height();
+        if infraction_height > height {
+            panic!(
+                "impossible attempt to slash future infraction at height {} but we are at height {}",
+                infraction_height, height
+            )
+        }
+
+        let mut remaining_slash_amount = slash_amount;
+
+        // unbonding delegations and redelegations which started at the infraction height
+        // weren't bonded when it happened
+        if infraction_height < height {
+            let staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+            let unbonding_delegations: Vec<UnbondingDelegation> = UNBONDING_DELEGATIONS_BY_VAL
+                .prefix_iter(&staking_store, &validator.operator_address)
+                .map(|(validator_address, delegator_address)| {
+                    UNBONDING_DELEGATIONS
+                        .get(&staking_store, &(delegator_address, validator_address))
+                        .map(UnbondingDelegation::from_proto)
+                        .expect("indexed unbonding delegations exist")
+                })
+                .collect();
+            let redelegations: Vec<Redelegation> = REDELEGATIONS_BY_VAL_SRC
+                .prefix_iter(&staking_store, &validator.operator_address)
+                .map(|(src_address, (delegator_address, dst_address))| {
+                    REDELEGATIONS
+                        .get(
+                            &staking_store,
+                            &(delegator_address, (src_address, dst_address)),
+                        )
+                        .map(Redelegation::from_proto)
+                        .expect("indexed redelegations exist")
+                })
+                .collect();
+            drop(staking_store);
+
+            for ubd in unbonding_delegations {
+                let amount_slashed =
+                    Staking::slash_unbonding_delegation(ctx, ubd, infraction_height, slash_factor);
+                remaining_slash_amount = remaining_slash_amount.saturating_sub(amount_slashed);
+            }
+
+            for red in redelegations {
+                let amount_slashed =
+                    Staking::slash_redelegation(ctx, red, infraction_height, slash_factor);
+                remaining_slash_amount = remaining_slash_amount.saturating_sub(amount_slashed);
+            }
+        }
+
+        // the validator may have been updated by slashing the redelegations
+        let validator = Staking::get_validator_by_cons_addr(ctx, cons_address)
+            .expect("slashing doesn't remove the validator");
+        let tokens_to_burn = remaining_slash_amount.min(validator.tokens);
+
+        // distribution needs the fraction of the validator's tokens which is actually slashed
+        if !validator.tokens.is_zero() {
+            let effective_fraction = Decimal256::from_ratio(tokens_to_burn, 1u8)
+                .div_rounded_up(Decimal256::from_ratio(validator.tokens, 1u8))
+                .min(Decimal256::one());
+            Distribution::before_validator_slashed(ctx, &validator, effective_fraction);
+        }
+
+        let validator = Staking::remove_validator_tokens(ctx, validator, tokens_to_burn);
+
+        let pool = match validator.status {
+            BondStatus::Bonded => BONDED_POOL_NAME,
+            _ => NOT_BONDED_POOL_NAME,
+        };
+        Staking::burn_tokens(ctx, pool, tokens_to_burn);
+    }
+
+    /// Jails the validator with the consensus address, removing it from the validator set at the
+    /// end of the block
+    pub fn jail(ctx: &mut Context, cons_address: &ConsAddress) {
+        let validator =
+            Staking::get_validator_by_cons_addr(ctx, cons_address).unwrap_or_else(|| {
+                panic!(
+                    "validator with consensus address {} not found",
+                    cons_address
+                )
+            });
+        Staking::jail_validator(ctx, validator);
+    }
+
+    /// Unjails the validator with the consensus address, returning it to the power index so that
+    /// it may rejoin the validator set
+    pub fn unjail(ctx: &mut Context, cons_address: &ConsAddress) {
+        let mut validator =
+            Staking::get_validator_by_cons_addr(ctx, cons_address).unwrap_or_else(|| {
+                panic!(
+                    "validator with consensus address {} not found",
+                    cons_address
+                )
+            });
+
+        if !validator.jailed {
+            panic!(
+                "cannot unjail already unjailed validator, validator: {}",
+                validator.operator_address
+            )
+        }
+
+        validator.jailed = false;
+        Staking::set_validator(ctx, &validator);
+        Staking::set_validator_by_power_index(ctx, &validator);
+    }
+
+    /// Slashes the entries of the unbonding delegation which started at or after the infraction
+    /// height and haven't completed, burning the tokens, and returns the amount which should
+    /// have been slashed. An entry's balance may be less than this if it was already slashed.
+    fn slash_unbonding_delegation(
+        ctx: &mut Context,
+        mut ubd: UnbondingDelegation,
+        infraction_height: i64,
+        slash_factor: Decimal256,
+    ) -> Uint256 {
+        let block_time = ctx.block_time();
+        let mut total_slash_amount = Uint256::zero();
+        let mut burned_amount = Uint256::zero();
+
+        for entry in ubd.entries.iter_mut() {
+            if entry.creation_height < infraction_height || entry.is_mature(&block_time) {
+                continue;
+            }
+
+            let slash_amount = slash_factor * entry.initial_balance;
+            total_slash_amount += slash_amount;
+
+            let unbonding_slash_amount = slash_amount.min(entry.balance);
+            entry.balance -= unbonding_slash_amount;
+            burned_amount += unbonding_slash_amount;
+        }
+
+        if !burned_amount.is_zero() {
+            Staking::set_unbonding_delegation(ctx, &ubd);
+            Staking::burn_tokens(ctx, NOT_BONDED_POOL_NAME, burned_amount);
+        }
+
+        total_slash_amount
+    }
+
+    /// Slashes the entries of the redelegation which started at or after the infraction height
+    /// and haven't completed by unbonding and burning the shares they created at the destination
+    /// validator, and returns the amount which should have been slashed
+    fn slash_redelegation(
+        ctx: &mut Context,
+        red: Redelegation,
+        infraction_height: i64,
+        slash_factor: Decimal256,
+    ) -> Uint256 {
+        let block_time = ctx.block_time();
+        let mut total_slash_amount = Uint256::zero();
+        let mut bonded_burned_amount = Uint256::zero();
+        let mut not_bonded_burned_amount = Uint256::zero();
+
+        for entry in &red.entries {
+            if entry.creation_height < infraction_height || entry.is_mature(&block_time) {
+                continue;
+            }
+
+            total_slash_amount += slash_factor * entry.initial_balance;
+
+            let shares_to_unbond = slash_factor * entry.shares_dst;
+            if shares_to_unbond.is_zero() {
+                continue;
+            }
+
+            let delegation = match Staking::get_delegation(
+                ctx,
+                &red.delegator_address,
+                &red.validator_dst_address,
+            ) {
+                Some(delegation) => delegation,
+                // the delegator may have undelegated, in which case the unbonding delegation is
+                // slashed instead if it's from the destination validator's infraction
+                None => continue,
+            };
+            let shares_to_unbond = shares_to_unbond.min(delegation.shares);
+
+            let tokens_to_burn = Staking::unbond(
+                ctx,
+                &red.delegator_address,
+                &red.validator_dst_address,
+                shares_to_unbond,
+            )
+            .expect("the delegation holds the shares");
+
+            match Staking::get_validator(ctx, &red.validator_dst_address) {
+                Some(validator) if validator.status == BondStatus::Bonded => {
+                    bonded_burned_amount += tokens_to_burn
+                }
+                _ => not_bonded_burned_amount += tokens_to_burn,
+            }
+        }
+
+        Staking::burn_tokens(ctx, BONDED_POOL_NAME, bonded_burned_amount);
+        Staking::burn_tokens(ctx, NOT_BONDED_POOL_NAME, not_bonded_burned_amount);
+
+        total_slash_amount
+    }
+
+    /// Burns the bond denom tokens from the pool
+    fn burn_tokens(ctx: &mut Context, pool: &str, amount: Uint256) {
+        if amount.is_zero() {
+            return;
+        }
+
+        let coins = vec![Coin {
+            denom: Staking::get_params(ctx).bond_denom,
+            amount,
+        }];
+        Bank::burn_coins(ctx, pool, &coins).expect("the pools hold the tokens of their validators");
+    }
+
+    /// Applies the changes to the validator set since the last block, bonding the validators
+    /// with the most power up to the max validators and unbonding the rest, and returns the
+    /// changes to give to Tendermint. Validators, unbonding delegations and redelegations which
+    /// have finished unbonding are then completed. As with the cosmos SDK staking `EndBlocker`.
+    pub fn end_block(ctx: &mut Context) -> Vec<ValidatorUpdate> {
+        let updates = Staking::apply_and_return_validator_set_updates(ctx);
+        Staking::unbond_all_mature_validators(ctx);
+
+        let block_time = ctx.block_time();
+
+        let staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        let mature_unbonds: Vec<(Timestamp, (AccAddress, ValAddress))> = UNBONDING_QUEUE
+            .iter(&staking_store)
+            .take_while(|(time, _)| !is_after(time, &block_time))
+            .collect();
+        drop(staking_store);
+
+        for key in mature_unbonds {
+            let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+            UNBONDING_QUEUE.remove(&mut staking_store, &key);
+            drop(staking_store);
+
+            let (_, (delegator_address, validator_address)) = key;
+            if let Ok(amount) =
+                Staking::complete_unbonding(ctx, &delegator_address, &validator_address)
+            {
+                ctx.event_manager().emit_event(new_event(
+                    EVENT_TYPE_COMPLETE_UNBONDING,
+                    vec![
+                        (ATTRIBUTE_KEY_AMOUNT, coins_to_string(&amount)),
+                        (ATTRIBUTE_KEY_VALIDATOR, validator_address.to_string()),
+                        (ATTRIBUTE_KEY_DELEGATOR, delegator_address.to_string()),
+                    ],
+                ));
+            }
+        }
+
+        let staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        let mature_redelegations: Vec<(Timestamp, (AccAddress, (ValAddress, ValAddress)))> =
+            REDELEGATION_QUEUE
+                .iter(&staking_store)
+                .take_while(|(time, _)| !is_after(time, &block_time))
+                .collect();
+        drop(staking_store);
+
+        for key in mature_redelegations {
+            let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+            REDELEGATION_QUEUE.remove(&mut staking_store, &key);
+            drop(staking_store);
+
+            let (_, (delegator_address, (src_address, dst_address))) = key;
+            if let Ok(amount) =
+                Staking::complete_redelegation(ctx, &delegator_address, &src_address, &dst_address)
+            {
+                ctx.event_manager().emit_event(new_event(
+                    EVENT_TYPE_COMPLETE_REDELEGATION,
+                    vec![
+                        (ATTRIBUTE_KEY_AMOUNT, coins_to_string(&amount)),
+                        (ATTRIBUTE_KEY_DELEGATOR, delegator_address.to_string()),
+                        (ATTRIBUTE_KEY_SRC_VALIDATOR, src_address.to_string()),
+                        (ATTRIBUTE_KEY_DST_VALIDATOR, dst_address.to_string()),
+                    ],
+                ));
+            }
+        }
+
+        updates
+    }
+
+    /// Removes the mature entries of the unbonding delegation, returning their balances to the
+    /// delegator, and returns the amount returned
+    fn complete_unbonding(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        validator_address: &ValAddress,
+    ) -> Result<Vec<Coin>, AppError> {
+        let mut ubd = Staking::get_unbonding_delegation(ctx, delegator_address, validator_address)
+            .ok_or(AppError::from(ERR_NO_UNBONDING_DELEGATION))?;
+
+        let bond_denom = Staking::get_params(ctx).bond_denom;
+        let block_time = ctx.block_time();
+
+        let (mature, entries) = ubd
+            .entries
+            .into_iter()
+            .partition::<Vec<_>, _>(|entry| entry.is_mature(&block_time));
+        ubd.entries = entries;
+
+        let balance = mature
+            .iter()
+            .fold(Uint256::zero(), |total, entry| total + entry.balance);
+        let amount = if balance.is_zero() {
+            vec![]
+        } else {
+            vec![Coin {
+                denom: bond_denom,
+                amount: balance,
+            }]
+        };
+
+        if !amount.is_empty() {
+            Bank::undelegate_coins_from_module_to_account(
+                ctx,
+                NOT_BONDED_POOL_NAME,
+                delegator_address,
+                &amount,
+            )?;
+        }
+
+        if ubd.entries.is_empty() {
+            Staking::remove_unbonding_delegation(ctx, &ubd);
+        } else {
+            Staking::set_unbonding_delegation(ctx, &ubd);
+        }
+
+        Ok(amount)
+    }
+
+    /// Removes the mature entries of the redelegation and returns the amount they redelegated
+    fn complete_redelegation(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        src_address: &ValAddress,
+        dst_address: &ValAddress,
+    ) -> Result<Vec<Coin>, AppError> {
+        let mut red = Staking::get_redelegation(ctx, delegator_address, src_address, dst_address)
+            .ok_or(AppError::from(ERR_NO_REDELEGATION))?;
+
+        let bond_denom = Staking::get_params(ctx).bond_denom;
+        let block_time = ctx.block_time();
+
+        let (mature, entries) = red
+            .entries
+            .into_iter()
+            .partition::<Vec<_>, _>(|entry| entry.is_mature(&block_time));
+        red.entries = entries;
+
+        let balance = mature.iter().fold(Uint256::zero(), |total, entry| {
+            total + entry.initial_balance
+        });
+
+        if red.entries.is_empty() {
+            Staking::remove_redelegation(ctx, &red);
+        } else {
+            Staking::set_redelegation(ctx, &red);
+        }
+
+        if balance.is_zero() {
+            Ok(vec![])
+        } else {
+            Ok(vec![Coin {
+                denom: bond_denom,
+                amount: balance,
+            }])
+        }
+    }
+
+    /// As with the cosmos SDK `ApplyAndReturnValidatorSetUpdates`
+    fn apply_and_return_validator_set_updates(ctx: &mut Context) -> Vec<ValidatorUpdate> {
+        let params = Staking::get_params(ctx);
+
+        let staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        let mut last: Vec<(ValAddress, i64)> = LAST_VALIDATOR_POWERS.iter(&staking_store).collect();
+        let mut by_power: Vec<ValAddress> = VALIDATORS_BY_POWER
+            .iter(&staking_store)
+            .map(|(_, address)| address)
+            .collect();
+        drop(staking_store);
+        by_power.reverse();
+
+        let mut updates = vec![];
+        let mut total_power = 0;
+        let mut not_bonded_to_bonded = Uint256::zero();
+        let mut bonded_to_not_bonded = Uint256::zero();
+
+        for address in by_power.into_iter().take(params.max_validators as usize) {
+            let mut validator =
+                Staking::get_validator(ctx, &address).expect("validators in the power index exist");
+
+            if validator.jailed {
+                panic!("should never retrieve a jailed validator from the power store")
+            }
+
+            // the validators are ordered by power so none of the rest have any power either
+            if validator.potential_consensus_power() == 0 {
+                break;
+            }
+
+            if !validator.is_bonded() {
+                not_bonded_to_bonded += validator.tokens;
+                validator = Staking::bond_validator(ctx, validator);
+            }
+
+            let new_power = validator.consensus_power();
+            let old_power = last
+                .iter()
+                .position(|(last_address, _)| last_address == &address)
+                .map(|i| last.remove(i).1);
+
+            if old_power != Some(new_power) {
+                updates.push(validator.abci_validator_update(new_power));
+
+                let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+                LAST_VALIDATOR_POWERS.set(&mut staking_store, &address, &new_power);
+            }
+
+            total_power += new_power;
+        }
+
+        // the remaining validators were in the last validator set but aren't any more, they're
+        // in address order as the last powers are keyed by address
+        for (address, _) in last {
+            let validator = Staking::get_validator(ctx, &address)
+                .expect("validators in the last validator set exist");
+            let validator = Staking::begin_unbonding_validator(ctx, validator, &params);
+            bonded_to_not_bonded += validator.tokens;
+
+            let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+            LAST_VALIDATOR_POWERS.remove(&mut staking_store, &address);
+
+            updates.push(validator.abci_validator_update(0));
+        }
+
+        // only the net change is transferred between the pools
+        if not_bonded_to_bonded > bonded_to_not_bonded {
+            Staking::transfer_between_pools(
+                ctx,
+                NOT_BONDED_POOL_NAME,
+                BONDED_POOL_NAME,
+                not_bonded_to_bonded - bonded_to_not_bonded,
+                &params.bond_denom,
+            );
+        } else if bonded_to_not_bonded > not_bonded_to_bonded {
+            Staking::transfer_between_pools(
+                ctx,
+                BONDED_POOL_NAME,
+                NOT_BONDED_POOL_NAME,
+                bonded_to_not_bonded - not_bonded_to_bonded,
+                &params.bond_denom,
+            );
+        }
+
+        if !updates.is_empty() {
+            let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+            LAST_TOTAL_POWER.set(&mut staking_store, &total_power);
+        }
+
+        updates
+    }
+
+    /// Moves validators whose unbonding period has ended from unbonding to unbonded, removing
+    /// those which have no delegations left
+    fn unbond_all_mature_validators(ctx: &mut Context) {
+        let block_time = ctx.block_time();
+        let block_height = ctx.block_height();
+
+        let staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        let mature: Vec<((Timestamp, i64), ValAddress)> = VALIDATOR_QUEUE
+            .iter(&staking_store)
+            .take_while(|((time, _), _)| !is_after(time, &block_time))
+            .filter(|((_, height), _)| *height <= block_height)
+            .collect();
+        drop(staking_store);
+
+        for (key, address) in mature {
+            let mut validator = Staking::get_validator(ctx, &address)
+                .expect("validators in the unbonding queue exist");
+
+            if validator.status != BondStatus::Unbonding {
+                panic!("unexpected validator in unbonding queue; status was not unbonding")
+            }
+
+            validator.status = BondStatus::Unbonded;
+            Staking::set_validator(ctx, &validator);
+
+            let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+            VALIDATOR_QUEUE.remove(&mut staking_store, &(key, address));
+            drop(staking_store);
+
+            if validator.delegator_shares.is_zero() {
+                Staking::remove_validator(ctx, &validator);
+            }
+        }
+    }
+
+    fn bond_validator(ctx: &mut Context, mut validator: Validator) -> Validator {
+        Staking::delete_validator_by_power_index(ctx, &validator);
+        Staking::delete_validator_queue(ctx, &validator);
+
+        validator.status = BondStatus::Bonded;
+        Staking::set_validator(ctx, &validator);
+        Staking::set_validator_by_power_index(ctx, &validator);
+
+        Slashing::after_validator_bonded(ctx, &validator.cons_address());
+
+        validator
+    }
+
+    /// Starts the unbonding period of a bonded validator which has left the validator set
+    fn begin_unbonding_validator(
+        ctx: &mut Context,
+        mut validator: Validator,
+        params: &Params,
+    ) -> Validator {
+        if !validator.is_bonded() {
+            panic!(
+                "should not already be unbonded or unbonding, validator: {}",
+                validator.operator_address
+            )
+        }
+
+        Staking::delete_validator_by_power_index(ctx, &validator);
+
+        validator.status = BondStatus::Unbonding;
+        validator.unbonding_time = add_duration(
+            &ctx.block_time(),
+            &params.unbonding_time.clone().unwrap_or_default(),
+        );
+        validator.unbonding_height = ctx.block_height();
+        Staking::set_validator(ctx, &validator);
+        Staking::set_validator_by_power_index(ctx, &validator);
+
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        VALIDATOR_QUEUE.insert(&mut staking_store, &validator_queue_key(&validator));
+
+        validator
+    }
+
+    /// Jails the validator, removing it from the power index so it leaves the validator set at
+    /// the end of the block
+    fn jail_validator(ctx: &mut Context, mut validator: Validator) -> Validator {
+        if validator.jailed {
+            panic!(
+                "cannot jail already jailed validator, validator: {}",
+                validator.operator_address
+            )
+        }
+
+        validator.jailed = true;
+        Staking::set_validator(ctx, &validator);
+        Staking::delete_validator_by_power_index(ctx, &validator);
+
+        validator
+    }
+
+    /// Removes delegator shares from the validator, keeping the power index up to date, and
+    /// returns the tokens they were worth
+    fn remove_validator_tokens_and_shares(
+        ctx: &mut Context,
+        mut validator: Validator,
+        shares: Decimal256,
+    ) -> (Validator, Uint256) {
+        Staking::delete_validator_by_power_index(ctx, &validator);
+        let tokens = validator.remove_del_shares(shares);
+        Staking::set_validator(ctx, &validator);
+        Staking::set_validator_by_power_index(ctx, &validator);
+
+        (validator, tokens)
+    }
+
+    /// Removes tokens from the validator without removing shares, e.g. when it's slashed,
+    /// keeping the power index up to date
+    fn remove_validator_tokens(
+        ctx: &mut Context,
+        mut validator: Validator,
+        tokens: Uint256,
+    ) -> Validator {
+        Staking::delete_validator_by_power_index(ctx, &validator);
+        validator.tokens -= tokens;
+        Staking::set_validator(ctx, &validator);
+        Staking::set_validator_by_power_index(ctx, &validator);
+
+        validator
+    }
+
+    /// Adds delegated tokens to the validator, keeping the power index up to date, and returns
+    /// the shares issued
+    fn add_validator_tokens_and_shares(
+        ctx: &mut Context,
+        mut validator: Validator,
+        tokens: Uint256,
+    ) -> Decimal256 {
+        Staking::delete_validator_by_power_index(ctx, &validator);
+        let shares = validator.add_tokens_from_del(tokens);
+        Staking::set_validator(ctx, &validator);
+        Staking::set_validator_by_power_index(ctx, &validator);
+
+        shares
+    }
+
+    fn transfer_between_pools(
+        ctx: &mut Context,
+        from_pool: &str,
+        to_pool: &str,
+        amount: Uint256,
+        bond_denom: &str,
+    ) {
+        let coins = vec![Coin {
+            denom: bond_denom.into(),
+            amount,
+        }];
+        Bank::send_coins_from_module_to_module(ctx, from_pool, to_pool, &coins)
+            .expect("the pools hold the tokens of their validators");
+    }
+
+    pub fn get_validator(ctx: &mut Context, address: &ValAddress) -> Option<Validator> {
+        let staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        VALIDATORS
+            .get(&staking_store, address)
+            .map(Validator::from_proto)
+    }
+
+    pub fn get_validator_by_cons_addr(
+        ctx: &mut Context,
+        cons_address: &ConsAddress,
+    ) -> Option<Validator> {
+        let staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        let address = VALIDATORS_BY_CONS_ADDR.get(&staking_store, cons_address)?;
+        drop(staking_store);
+
+        Staking::get_validator(ctx, &address)
+    }
+
+    pub fn set_validator(ctx: &mut Context, validator: &Validator) {
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        VALIDATORS.set(
+            &mut staking_store,
+            &validator.operator_address,
+            &validator.to_proto(),
+        );
+    }
+
+    fn set_validator_by_cons_addr(ctx: &mut Context, validator: &Validator) {
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        VALIDATORS_BY_CONS_ADDR.set(
+            &mut staking_store,
+            &validator.cons_address(),
+            &validator.operator_address,
+        );
+    }
+
+    /// Adds the validator to the power index, jailed validators aren't indexed
+    fn set_validator_by_power_index(ctx: &mut Context, validator: &Validator) {
+        if validator.jailed {
+            return;
+        }
+
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        VALIDATORS_BY_POWER.set(
+            &mut staking_store,
+            &power_index_key(validator),
+            &validator.operator_address,
+        );
+    }
+
+    fn delete_validator_by_power_index(ctx: &mut Context, validator: &Validator) {
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        VALIDATORS_BY_POWER.remove(&mut staking_store, &power_index_key(validator));
+    }
+
+    fn delete_validator_queue(ctx: &mut Context, validator: &Validator) {
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        VALIDATOR_QUEUE.remove(&mut staking_store, &validator_queue_key(validator));
+    }
+
+    /// Removes an unbonded validator which has no tokens or delegations left
+    fn remove_validator(ctx: &mut Context, validator: &Validator) {
+        if validator.is_bonded() {
+            panic!("cannot call remove_validator on bonded validators")
+        }
+
+        if !validator.tokens.is_zero() {
+            panic!("attempting to remove a validator which still contains tokens")
+        }
+
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        VALIDATORS.remove(&mut staking_store, &validator.operator_address);
+        VALIDATORS_BY_CONS_ADDR.remove(&mut staking_store, &validator.cons_address());
+        VALIDATORS_BY_POWER.remove(&mut staking_store, &power_index_key(validator));
+        drop(staking_store);
+
+        Distribution::after_validator_removed(ctx, &validator.operator_address);
+    }
+
+    pub fn get_delegation(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        validator_address: &ValAddress,
+    ) -> Option<Delegation> {
+        let staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        DELEGATIONS
+            .get(
+                &staking_store,
+                &(delegator_address.clone(), validator_address.clone()),
+            )
+            .map(Delegation::from_proto)
+    }
+
+    /// Returns the validator from the staking store, for use where there's no context such as
+    /// in queries
+    pub fn read_validator<S: ReadKVStore + ?Sized>(
+        staking_store: &S,
+        address: &ValAddress,
+    ) -> Option<Validator> {
+        VALIDATORS
+            .get(staking_store, address)
+            .map(Validator::from_proto)
+    }
+
+    pub fn read_delegation<S: ReadKVStore + ?Sized>(
+        staking_store: &S,
+        delegator_address: &AccAddress,
+        validator_address: &ValAddress,
+    ) -> Option<Delegation> {
+        DELEGATIONS
+            .get(
+                staking_store,
+                &(delegator_address.clone(), validator_address.clone()),
+            )
+            .map(Delegation::from_proto)
+    }
+
+    /// Returns the delegations of the delegator in validator address order
+    pub fn read_delegator_delegations<S: ReadKVStore + ?Sized>(
+        staking_store: &S,
+        delegator_address: &AccAddress,
+    ) -> Vec<Delegation> {
+        DELEGATIONS
+            .prefix_iter(staking_store, delegator_address)
+            .map(|(_, delegation)| Delegation::from_proto(delegation))
+            .collect()
+    }
+
+    pub fn set_delegation(ctx: &mut Context, delegation: &Delegation) {
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        DELEGATIONS.set(
+            &mut staking_store,
+            &(
+                delegation.delegator_address.clone(),
+                delegation.validator_address.clone(),
+            ),
+            &delegation.to_proto(),
+        );
+    }
+
+    fn remove_delegation(ctx: &mut Context, delegation: &Delegation) {
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        DELEGATIONS.remove(
+            &mut staking_store,
+            &(
+                delegation.delegator_address.clone(),
+                delegation.validator_address.clone(),
+            ),
+        );
+    }
+
+    pub fn get_unbonding_delegation(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        validator_address: &ValAddress,
+    ) -> Option<UnbondingDelegation> {
+        let staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        UNBONDING_DELEGATIONS
+            .get(
+                &staking_store,
+                &(delegator_address.clone(), validator_address.clone()),
+            )
+            .map(UnbondingDelegation::from_proto)
+    }
+
+    pub fn set_unbonding_delegation(ctx: &mut Context, ubd: &UnbondingDelegation) {
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        UNBONDING_DELEGATIONS.set(
+            &mut staking_store,
+            &(ubd.delegator_address.clone(), ubd.validator_address.clone()),
+            &ubd.to_proto(),
+        );
+        UNBONDING_DELEGATIONS_BY_VAL.insert(
+            &mut staking_store,
+            &(ubd.validator_address.clone(), ubd.delegator_address.clone()),
+        );
+    }
+
+    fn remove_unbonding_delegation(ctx: &mut Context, ubd: &UnbondingDelegation) {
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        UNBONDING_DELEGATIONS.remove(
+            &mut staking_store,
+            &(ubd.delegator_address.clone(), ubd.validator_address.clone()),
+        );
+        UNBONDING_DELEGATIONS_BY_VAL.remove(
+            &mut staking_store,
+            &(ubd.validator_address.clone(), ubd.delegator_address.clone()),
+        );
+    }
+
+    pub fn get_redelegation(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        src_address: &ValAddress,
+        dst_address: &ValAddress,
+    ) -> Option<Redelegation> {
+        let staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        REDELEGATIONS
+            .get(
+                &staking_store,
+                &(
+                    delegator_address.clone(),
+                    (src_address.clone(), dst_address.clone()),
+                ),
+            )
+            .map(Redelegation::from_proto)
+    }
+
+    pub fn set_redelegation(ctx: &mut Context, red: &Redelegation) {
+        let (delegator, src, dst) = (
+            &red.delegator_address,
+            &red.validator_src_address,
+            &red.validator_dst_address,
+        );
+
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        REDELEGATIONS.set(
+            &mut staking_store,
+            &(delegator.clone(), (src.clone(), dst.clone())),
+            &red.to_proto(),
+        );
+        REDELEGATIONS_BY_VAL_SRC.insert(
+            &mut staking_store,
+            &(src.clone(), (delegator.clone(), dst.clone())),
+        );
+        REDELEGATIONS_BY_VAL_DST.insert(
+            &mut staking_store,
+            &(dst.clone(), (delegator.clone(), src.clone())),
+        );
+    }
+
+    fn remove_redelegation(ctx: &mut Context, red: &Redelegation) {
+        let (delegator, src, dst) = (
+            &red.delegator_address,
+            &red.validator_src_address,
+            &red.validator_dst_address,
+        );
+
+        let mut staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        REDELEGATIONS.remove(
+            &mut staking_store,
+            &(delegator.clone(), (src.clone(), dst.clone())),
+        );
+        REDELEGATIONS_BY_VAL_SRC.remove(
+            &mut staking_store,
+            &(src.clone(), (delegator.clone(), dst.clone())),
+        );
+        REDELEGATIONS_BY_VAL_DST.remove(
+            &mut staking_store,
+            &(dst.clone(), (delegator.clone(), src.clone())),
+        );
+    }
+
+    /// Returns whether the delegator has an incomplete redelegation to the validator
+    fn has_receiving_redelegation(
+        ctx: &mut Context,
+        delegator_address: &AccAddress,
+        dst_address: &ValAddress,
+    ) -> bool {
+        let staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        let has_redelegation = REDELEGATIONS_BY_VAL_DST
+            .prefix_iter(
+                &staking_store,
+                &(dst_address.clone(), delegator_address.clone()),
+            )
+            .next()
+            .is_some();
+        has_redelegation
+    }
+
+    /// Returns the validators in the last validator set given to Tendermint, in address order
+    pub fn get_last_validators(ctx: &mut Context) -> Vec<Validator> {
+        let staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        let addresses: Vec<ValAddress> = LAST_VALIDATOR_POWERS
+            .iter(&staking_store)
+            .map(|(address, _)| address)
+            .collect();
+        drop(staking_store);
+
+        addresses
+            .iter()
+            .map(|address| {
+                Staking::get_validator(ctx, address)
+                    .expect("validators in the last validator set exist")
+            })
+            .collect()
+    }
+
+    /// Returns the total power of the last validator set given to Tendermint
+    pub fn get_last_total_power(ctx: &mut Context) -> i64 {
+        let staking_store = ctx.get_kv_store(STAKING_STORE_PREFIX.into());
+        LAST_TOTAL_POWER.get(&staking_store).unwrap_or_default()
+    }
+}
+
+fn read_params<S: ReadKVStore + ?Sized>(staking_store: &S) -> Params {
+    PARAMS
+        .get(staking_store)
+        .expect("staking params are set in init_genesis")
+}
+
+fn power_index_key(validator: &Validator) -> (u64, Vec<u8>) {
+    let power = validator.potential_consensus_power() as u64;
+    let inverted_address = Vec::from(validator.operator_address.clone())
+        .into_iter()
+        .map(|b| !b)
+        .collect();
+
+    (power, inverted_address)
+}
+
+fn validator_queue_key(validator: &Validator) -> ((Timestamp, i64), ValAddress) {
+    (
+        (validator.unbonding_time.clone(), validator.unbonding_height),
+        validator.operator_address.clone(),
+    )
+}
+
+/// Returns whether the first timestamp is after the second
+pub fn is_after(a: &Timestamp, b: &Timestamp) -> bool {
+    (a.seconds, a.nanos) > (b.seconds, b.nanos)
+}
+
+pub fn add_duration(time: &Timestamp, duration: &Duration) -> Timestamp {
+    let nanos = time.nanos + duration.nanos;
+
+    Timestamp {
+        seconds: time.seconds + duration.seconds + (nanos / 1_000_000_000) as i64,
+        nanos: nanos % 1_000_000_000,
+    }
+}
+
+/// Checks the lengths of the description fields
+fn validate_description(description: &Description) -> Result<(), AppError> {
+    let checks = [
+        ("moniker", description.moniker.len(), MAX_MONIKER_LENGTH),
+        ("identity", description.identity.len(), MAX_IDENTITY_LENGTH),
+        ("website", description.website.len(), MAX_WEBSITE_LENGTH),
+        (
+            "security contact",
+            description.security_contact.len(),
+            MAX_SECURITY_CONTACT_LENGTH,
+        ),
+        ("details", description.details.len(), MAX_DETAILS_LENGTH),
+    ];
+
+    match checks.iter().find(|(_, len, max)| len > max) {
+        Some((name, len, max)) => Err(ERR_INVALID_REQUEST.wrap(format!(
+            "invalid {} length; got: {}, max: {}",
+            name, len, max
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Returns the description with each field of the update, unless it's
+/// `DO_NOT_MODIFY_DESCRIPTION`
+fn update_description(description: &Description, update: Description) -> Description {
+    let field = |old: &String, new: String| {
+        if new == DO_NOT_MODIFY_DESCRIPTION {
+            old.clone()
+        } else {
+            new
+        }
+    };
+
+    Description {
+        moniker: field(&description.moniker, update.moniker),
+        identity: field(&description.identity, update.identity),
+        website: field(&description.website, update.website),
+        security_contact: field(&description.security_contact, update.security_contact),
+        details: field(&description.details, update.details),
+    }
+}
+
+/// Returns the commission with the new rate, as with the cosmos SDK `ValidateNewRate` the rate
+/// may be changed once a day by at most the max change rate
+fn update_commission(
+    commission: &Commission,
+    rate: Decimal256,
+    block_time: Timestamp,
+) -> Result<Commission, AppError> {
+    if block_time.seconds - commission.update_time.seconds < COMMISSION_UPDATE_PERIOD {
+        return Err(ERR_COMMISSION_UPDATE_TIME.into());
+    }
+
+    if rate > commission.max_rate {
+        return Err(ERR_COMMISSION_GT_MAX_RATE.into());
+    }
+
+    let change = if rate > commission.rate {
+        rate - commission.rate
+    } else {
+        commission.rate - rate
+    };
+    if change > commission.max_change_rate {
+        return Err(ERR_COMMISSION_GT_MAX_CHANGE_RATE.into());
+    }
+
+    Ok(Commission {
+        rate,
+        update_time: block_time,
+        ..commission.clone()
+    })
+}
+
+/// Returns the delegations with the tokens they're worth
+fn delegation_responses<S: ReadKVStore + ?Sized>(
+    staking_store: &S,
+    delegations: Vec<ProtoDelegation>,
+) -> Result<Vec<DelegationResponse>, AppError> {
+    let bond_denom = read_params(staking_store).bond_denom;
+
+    delegations
+        .into_iter()
+        .map(|proto| {
+            let delegation = Delegation::from_proto(proto.clone());
+            let validator = VALIDATORS
+                .get(staking_store, &delegation.validator_address)
+                .map(Validator::from_proto)
+                .ok_or(AppError::from(ERR_NO_VALIDATOR_FOUND))?;
+
+            Ok(DelegationResponse {
+                delegation: Some(proto),
+                balance: Some(Coin {
+                    denom: bond_denom.clone(),
+                    amount: validator.tokens_from_shares(delegation.shares),
+                }),
+            })
+        })
+        .collect()
+}
+
+/// Returns the redelegation with the tokens each entry is currently worth at the destination
+/// validator
+fn redelegation_response<S: ReadKVStore + ?Sized>(
+    staking_store: &S,
+    proto: ProtoRedelegation,
+) -> Result<RedelegationResponse, AppError> {
+    let redelegation = Redelegation::from_proto(proto.clone());
+    let validator = VALIDATORS
+        .get(staking_store, &redelegation.validator_dst_address)
+        .map(Validator::from_proto)
+        .ok_or(AppError::from(ERR_NO_VALIDATOR_FOUND))?;
+
+    let entries = redelegation
+        .entries
+        .iter()
+        .zip(proto.entries.clone())
+        .map(|(entry, proto_entry)| RedelegationEntryResponse {
+            redelegation_entry: Some(proto_entry),
+            balance: validator.tokens_from_shares(entry.shares_dst).to_string(),
+        })
+        .collect();
+
+    Ok(RedelegationResponse {
+        redelegation: Some(proto),
+        entries,
+    })
+}
+
+fn parse_query_delegator_address(address: &str) -> Result<AccAddress, AppError> {
+    if address.is_empty() {
+        return Err(ERR_INVALID_REQUEST.wrap("delegator address cannot be empty"));
+    }
+
+    AccAddress::from_bech32(address)
+}
+
+fn parse_query_validator_address(address: &str) -> Result<ValAddress, AppError> {
+    if address.is_empty() {
+        return Err(ERR_INVALID_REQUEST.wrap("validator address cannot be empty"));
+    }
+
+    ValAddress::from_bech32(address)
+}
+
+fn coin_to_string(coin: &Coin) -> String {
+    format!("{}{}", coin.amount, coin.denom)
+}
+
+/// Formats the time as RFC 3339 in UTC, without fractional seconds, as with Go's
+/// `time.RFC3339` layout
+fn format_time(time: &Timestamp) -> String {
+    let days = time.seconds.div_euclid(86400);
+    let secs = time.seconds.rem_euclid(86400);
+
+    // the civil date of the day number, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+/// Parses a cosmos SDK `Int` from a message
+fn parse_int(s: &str, name: &str) -> Result<Uint256, AppError> {
+    Uint256::from_str(s).map_err(|_| ERR_INVALID_REQUEST.wrap(format!("invalid {}: {}", name, s)))
+}
+
+/// Parses a cosmos SDK `Dec` from a message, which is encoded as an integer number of 10^-18
+/// units
+fn parse_dec(s: &str, name: &str) -> Result<Decimal256, AppError> {
+    Decimal256::from_cosmos_proto_string(s)
+        .map_err(|_| ERR_INVALID_REQUEST.wrap(format!("invalid {}: {}", name, s)))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use ibc_proto::cosmos::base::query::v1beta1::PageRequest;
+    use tendermint_proto::{
+        abci::ConsensusParams,
+        crypto::{public_key::Sum, PublicKey},
+        types::{Header, ValidatorParams},
+    };
+
+    use super::{
+        testing::{consensus_params, context_with_accounts, create_validator_msg, uatom},
+        *,
+    };
+
+    const FROM: &str = "cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux";
+    const TO: &str = "cosmos1qyqszqgpqyqszqgpqyqszqgpqyqszqgpjnp7du";
+
+    fn balance(ctx: &mut Context, address: &AccAddress) -> Vec<Coin> {
+        Bank::spendable_coins(ctx, address)
+    }
+
+    fn pool_address(name: &str) -> AccAddress {
+        AccAddress::new_module_address(name)
+    }
+
+    /// Returns a context at the block time where FROM and TO each hold 10atom
+    fn context_at(seconds: i64, max_validators: u32) -> Context {
+        let accounts = [
+            AccAddress::from_bech32(FROM).unwrap(),
+            AccAddress::from_bech32(TO).unwrap(),
+        ];
+        at_time(context_with_accounts(&accounts, max_validators), seconds)
+    }
+
+    fn at_time(ctx: Context, seconds: i64) -> Context {
+        ctx.with_header(Header {
+            time: Some(Timestamp { seconds, nanos: 0 }),
+            ..Default::default()
+        })
+    }
+
+    fn ed25519_update(seed: u8, power: i64) -> ValidatorUpdate {
+        ValidatorUpdate {
+            pub_key: Some(PublicKey {
+                sum: Some(Sum::Ed25519(vec![seed; 32])),
+            }),
+            power,
+        }
+    }
+
+    #[test]
+    fn create_validator_and_delegate_works() {
+        let mut ctx = context_at(100, 100);
+        let msg = create_validator_msg(FROM, 1, 2_000_000);
+        let validator_address = ValAddress::from_bech32(&msg.validator_address).unwrap();
+        validate_create_validator(&msg).unwrap();
+        Staking::create_validator(&mut ctx, msg).unwrap();
+
+        // the validator starts unbonded with the self delegation in the not bonded pool
+        let validator = Staking::get_validator(&mut ctx, &validator_address).unwrap();
+        assert_eq!(validator.status, BondStatus::Unbonded);
+        assert_eq!(validator.tokens, Uint256::from(2_000_000u32));
+        assert_eq!(validator.commission.update_time.seconds, 100);
+        assert_eq!(
+            balance(&mut ctx, &pool_address(NOT_BONDED_POOL_NAME)),
+            vec![uatom(2_000_000)]
+        );
+
+        let from = AccAddress::from_bech32(FROM).unwrap();
+        let delegation = Staking::get_delegation(&mut ctx, &from, &validator_address).unwrap();
+        assert_eq!(delegation.shares, Decimal256::from_ratio(2_000_000u32, 1u8));
+
+        // the validator joins the validator set at the end of the block
+        let updates = Staking::end_block(&mut ctx);
+        assert_eq!(updates, vec![ed25519_update(1, 2)]);
+        let validator = Staking::get_validator(&mut ctx, &validator_address).unwrap();
+        assert_eq!(validator.status, BondStatus::Bonded);
+        assert_eq!(
+            balance(&mut ctx, &pool_address(BONDED_POOL_NAME)),
+            vec![uatom(2_000_000)]
+        );
+        assert_eq!(
+            balance(&mut ctx, &pool_address(NOT_BONDED_POOL_NAME)),
+            vec![uatom(0)]
+        );
+        assert_eq!(Staking::get_last_total_power(&mut ctx), 2);
+
+        // nothing changes without any new delegations
+        assert_eq!(Staking::end_block(&mut ctx), vec![]);
+
+        // delegations to a bonded validator go straight to the bonded pool
+        let msg = MsgDelegate {
+            delegator_address: TO.into(),
+            validator_address: validator_address.to_string(),
+            amount: Some(uatom(1_000_000)),
+        };
+        validate_delegate(&msg).unwrap();
+        Staking::delegate_msg(&mut ctx, msg).unwrap();
+        assert_eq!(
+            balance(&mut ctx, &pool_address(BONDED_POOL_NAME)),
+            vec![uatom(3_000_000)]
+        );
+        assert_eq!(
+            balance(&mut ctx, &AccAddress::from_bech32(TO).unwrap()),
+            vec![uatom(9_000_000)]
+        );
+
+        assert_eq!(Staking::end_block(&mut ctx), vec![ed25519_update(1, 3)]);
+        assert_eq!(Staking::get_last_total_power(&mut ctx), 3);
+    }
+
+    #[test]
+    fn create_validator_fails_for_existing_keys() {
+        let mut ctx = context_at(100, 100);
+        Staking::create_validator(&mut ctx, create_validator_msg(FROM, 1, 1_000_000)).unwrap();
+
+        let err = Staking::create_validator(&mut ctx, create_validator_msg(FROM, 2, 1_000_000))
+            .unwrap_err();
+        assert_eq!(err.code(), ERR_VALIDATOR_OWNER_EXISTS.code);
+
+        let err = Staking::create_validator(&mut ctx, create_validator_msg(TO, 1, 1_000_000))
+            .unwrap_err();
+        assert_eq!(err.code(), ERR_VALIDATOR_PUB_KEY_EXISTS.code);
+
+        let msg = MsgCreateValidator {
+            value: Some(Coin {
+                denom: "stake".into(),
+                amount: 1_000_000u32.into(),
+            }),
+            ..create_validator_msg(TO, 2, 1_000_000)
+        };
+        let err = Staking::create_validator(&mut ctx, msg).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid coin denomination: got stake, expected uatom: invalid request"
+        );
+
+        ctx.set_consensus_params(&ConsensusParams {
+            validator: Some(ValidatorParams {
+                pub_key_types: vec!["secp256k1".into()],
+            }),
+            ..consensus_params()
+        });
+        let err = Staking::create_validator(&mut ctx, create_validator_msg(TO, 2, 1_000_000))
+            .unwrap_err();
+        assert_eq!(err.code(), ERR_VALIDATOR_PUB_KEY_TYPE_NOT_SUPPORTED.code);
+    }
+
+    #[test]
+    fn max_validators_works() {
+        let mut ctx = context_at(100, 1);
+        let msg = create_validator_msg(FROM, 1, 2_000_000);
+        let validator_address = ValAddress::from_bech32(&msg.validator_address).unwrap();
+        Staking::create_validator(&mut ctx, msg).unwrap();
+        assert_eq!(Staking::end_block(&mut ctx), vec![ed25519_update(1, 2)]);
+
+        // a validator with more power takes the only place in the validator set
+        Staking::create_validator(&mut ctx, create_validator_msg(TO, 2, 3_000_000)).unwrap();
+        assert_eq!(
+            Staking::end_block(&mut ctx),
+            vec![ed25519_update(2, 3), ed25519_update(1, 0)]
+        );
+        assert_eq!(Staking::get_last_total_power(&mut ctx), 3);
+        assert_eq!(
+            balance(&mut ctx, &pool_address(BONDED_POOL_NAME)),
+            vec![uatom(3_000_000)]
+        );
+        assert_eq!(
+            balance(&mut ctx, &pool_address(NOT_BONDED_POOL_NAME)),
+            vec![uatom(2_000_000)]
+        );
+
+        let validator = Staking::get_validator(&mut ctx, &validator_address).unwrap();
+        assert_eq!(validator.status, BondStatus::Unbonding);
+        assert_eq!(
+            validator.unbonding_time.seconds,
+            100 + DEFAULT_UNBONDING_TIME
+        );
+
+        // the validator is unbonded once the unbonding time has passed, but kept as it still
+        // has delegations
+        let mut ctx = at_time(ctx, 100 + DEFAULT_UNBONDING_TIME);
+        assert_eq!(Staking::end_block(&mut ctx), vec![]);
+        let validator = Staking::get_validator(&mut ctx, &validator_address).unwrap();
+        assert_eq!(validator.status, BondStatus::Unbonded);
+    }
+
+    #[test]
+    fn edit_validator_works() {
+        let mut ctx = context_at(100, 100);
+        let msg = create_validator_msg(FROM, 1, 2_000_000);
+        let validator_address = msg.validator_address.clone();
+        Staking::create_validator(&mut ctx, msg).unwrap();
+
+        let edit = |commission_rate: Decimal256| MsgEditValidator {
+            description: Some(Description {
+                moniker: DO_NOT_MODIFY_DESCRIPTION.into(),
+                details: "details".into(),
+                ..Default::default()
+            }),
+            validator_address: validator_address.clone(),
+            commission_rate: commission_rate.to_cosmos_proto_string(),
+            min_self_delegation: "".into(),
+        };
+
+        // the rate can only change once a day
+        let err = Staking::edit_validator(&mut ctx, edit(Decimal256::percent(11))).unwrap_err();
+        assert_eq!(err.code(), ERR_COMMISSION_UPDATE_TIME.code);
+
+        let mut ctx = at_time(ctx, 100 + COMMISSION_UPDATE_PERIOD);
+        let err = Staking::edit_validator(&mut ctx, edit(Decimal256::percent(12))).unwrap_err();
+        assert_eq!(err.code(), ERR_COMMISSION_GT_MAX_CHANGE_RATE.code);
+        let err = Staking::edit_validator(&mut ctx, edit(Decimal256::percent(8))).unwrap_err();
+        assert_eq!(err.code(), ERR_COMMISSION_GT_MAX_CHANGE_RATE.code);
+
+        Staking::edit_validator(&mut ctx, edit(Decimal256::percent(9))).unwrap();
+        let validator = Staking::get_validator(
+            &mut ctx,
+            &ValAddress::from_bech32(&validator_address).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(validator.commission.rate, Decimal256::percent(9));
+        assert_eq!(validator.description.moniker, "validator");
+        assert_eq!(validator.description.details, "details");
+
+        // the minimum self delegation can only increase
+        let msg = MsgEditValidator {
+            commission_rate: "".into(),
+            min_self_delegation: "1".into(),
+            ..edit(Decimal256::zero())
+        };
+        let err = Staking::edit_validator(&mut ctx, msg).unwrap_err();
+        assert_eq!(err.code(), ERR_MIN_SELF_DELEGATION_DECREASED.code);
+    }
+
+    #[test]
+    fn undelegate_works() {
+        let mut ctx = context_at(100, 100);
+        let msg = create_validator_msg(FROM, 1, 2_000_000);
+        let validator_address = ValAddress::from_bech32(&msg.validator_address).unwrap();
+        Staking::create_validator(&mut ctx, msg).unwrap();
+        let msg = MsgDelegate {
+            delegator_address: TO.into(),
+            validator_address: validator_address.to_string(),
+            amount: Some(uatom(1_000_000)),
+        };
+        Staking::delegate_msg(&mut ctx, msg).unwrap();
+        assert_eq!(Staking::end_block(&mut ctx), vec![ed25519_update(1, 3)]);
+
+        let to = AccAddress::from_bech32(TO).unwrap();
+        let undelegate = |amount| MsgUndelegate {
+            delegator_address: TO.into(),
+            validator_address: validator_address.to_string(),
+            amount: Some(uatom(amount)),
+        };
+        validate_undelegate(&undelegate(400_000)).unwrap();
+        ctx.event_manager().take_events();
+        let res = Staking::undelegate_msg(&mut ctx, undelegate(400_000)).unwrap();
+        let completion_time = Timestamp {
+            seconds: 100 + DEFAULT_UNBONDING_TIME,
+            nanos: 0,
+        };
+        assert_eq!(res.completion_time, Some(completion_time.clone()));
+        let events = ctx.event_manager().take_events();
+        assert_eq!(
+            events[events.len() - 2],
+            new_event(
+                EVENT_TYPE_UNBOND,
+                vec![
+                    (ATTRIBUTE_KEY_VALIDATOR, validator_address.to_string()),
+                    (ATTRIBUTE_KEY_AMOUNT, "400000uatom".into()),
+                    (ATTRIBUTE_KEY_COMPLETION_TIME, "1970-01-22T00:01:40Z".into()),
+                ],
+            )
+        );
+
+        // the unbonding tokens move to the not bonded pool
+        assert_eq!(
+            balance(&mut ctx, &pool_address(BONDED_POOL_NAME)),
+            vec![uatom(2_600_000)]
+        );
+        assert_eq!(
+            balance(&mut ctx, &pool_address(NOT_BONDED_POOL_NAME)),
+            vec![uatom(400_000)]
+        );
+        let delegation = Staking::get_delegation(&mut ctx, &to, &validator_address).unwrap();
+        assert_eq!(delegation.shares, Decimal256::from_ratio(600_000u32, 1u8));
+        assert_eq!(Staking::end_block(&mut ctx), vec![ed25519_update(1, 2)]);
+
+        let query_ctx = QueryContext::new(ctx.get_multi_store(), 0);
+        let res = Staking::query_unbonding_delegation(
+            &query_ctx,
+            QueryUnbondingDelegationRequest {
+                delegator_addr: TO.into(),
+                validator_addr: validator_address.to_string(),
+            },
+        )
+        .unwrap();
+        let ubd = UnbondingDelegation::from_proto(res.unbond.unwrap());
+        assert_eq!(
+            ubd.entries,
+            vec![UnbondingDelegationEntry {
+                creation_height: 0,
+                completion_time,
+                initial_balance: Uint256::from(400_000u32),
+                balance: Uint256::from(400_000u32),
+            }]
+        );
+
+        // more than the delegation can't be undelegated
+        let err = Staking::undelegate_msg(&mut ctx, undelegate(600_001)).unwrap_err();
+        assert_eq!(err.code(), ERR_BAD_SHARES_AMOUNT.code);
+
+        // the tokens are returned once the unbonding time has passed
+        let mut ctx = at_time(ctx, 100 + DEFAULT_UNBONDING_TIME - 1);
+        Staking::end_block(&mut ctx);
+        assert_eq!(balance(&mut ctx, &to), vec![uatom(9_000_000)]);
+
+        let mut ctx = at_time(ctx, 100 + DEFAULT_UNBONDING_TIME);
+        ctx.event_manager().take_events();
+        Staking::end_block(&mut ctx);
+        assert_eq!(balance(&mut ctx, &to), vec![uatom(9_400_000)]);
+        assert_eq!(
+            ctx.event_manager().take_events().last(),
+            Some(&new_event(
+                EVENT_TYPE_COMPLETE_UNBONDING,
+                vec![
+                    (ATTRIBUTE_KEY_AMOUNT, "400000uatom".into()),
+                    (ATTRIBUTE_KEY_VALIDATOR, validator_address.to_string()),
+                    (ATTRIBUTE_KEY_DELEGATOR, TO.into()),
+                ],
+            ))
+        );
+        assert_eq!(
+            Staking::get_unbonding_delegation(&mut ctx, &to, &validator_address),
+            None
+        );
+
+        // each unbonding delegation has at most max entries
+        for _ in 0..DEFAULT_MAX_ENTRIES {
+            Staking::undelegate_msg(&mut ctx, undelegate(1)).unwrap();
+        }
+        let err = Staking::undelegate_msg(&mut ctx, undelegate(1)).unwrap_err();
+        assert_eq!(err.code(), ERR_MAX_UNBONDING_DELEGATION_ENTRIES.code);
+    }
+
+    #[test]
+    fn undelegating_below_min_self_delegation_jails_validator() {
+        let mut ctx = context_at(100, 100);
+        let msg = create_validator_msg(FROM, 1, 2_000_000);
+        let validator_address = ValAddress::from_bech32(&msg.validator_address).unwrap();
+        Staking::create_validator(&mut ctx, msg).unwrap();
+        Staking::end_block(&mut ctx);
+
+        let msg = MsgUndelegate {
+            delegator_address: FROM.into(),
+            validator_address: validator_address.to_string(),
+            amount: Some(uatom(2_000_000)),
+        };
+        Staking::undelegate_msg(&mut ctx, msg).unwrap();
+
+        let validator = Staking::get_validator(&mut ctx, &validator_address).unwrap();
+        assert!(validator.jailed);
+        assert!(validator.tokens.is_zero());
+
+        // the jailed validator leaves the validator set
+        assert_eq!(Staking::end_block(&mut ctx), vec![ed25519_update(1, 0)]);
+        let validator = Staking::get_validator(&mut ctx, &validator_address).unwrap();
+        assert_eq!(validator.status, BondStatus::Unbonding);
+
+        // and is removed once it's unbonded as it has no delegations
+        let mut ctx = at_time(ctx, 100 + DEFAULT_UNBONDING_TIME);
+        Staking::end_block(&mut ctx);
+        assert_eq!(Staking::get_validator(&mut ctx, &validator_address), None);
+        assert_eq!(
+            balance(&mut ctx, &AccAddress::from_bech32(FROM).unwrap()),
+            vec![uatom(10_000_000)]
+        );
+    }
+
+    #[test]
+    fn slash_works() {
+        let mut ctx = context_at(100, 100);
+        let msg = create_validator_msg(FROM, 1, 2_000_000);
+        let validator_address = ValAddress::from_bech32(&msg.validator_address).unwrap();
+        Staking::create_validator(&mut ctx, msg).unwrap();
+        let msg = MsgDelegate {
+            delegator_address: TO.into(),
+            validator_address: validator_address.to_string(),
+            amount: Some(uatom(1_000_000)),
+        };
+        Staking::delegate_msg(&mut ctx, msg).unwrap();
+        Staking::end_block(&mut ctx);
+
+        let mut ctx = ctx.with_header(Header {
+            height: 5,
+            time: Some(Timestamp {
+                seconds: 100,
+                nanos: 0,
+            }),
+            ..Default::default()
+        });
+        let msg = MsgUndelegate {
+            delegator_address: TO.into(),
+            validator_address: validator_address.to_string(),
+            amount: Some(uatom(500_000)),
+        };
+        Staking::undelegate_msg(&mut ctx, msg).unwrap();
+
+        let mut ctx = ctx.with_header(Header {
+            height: 10,
+            time: Some(Timestamp {
+                seconds: 200,
+                nanos: 0,
+            }),
+            ..Default::default()
+        });
+        let validator = Staking::get_validator(&mut ctx, &validator_address).unwrap();
+        Staking::slash(
+            &mut ctx,
+            &validator.cons_address(),
+            3,
+            3,
+            Decimal256::percent(10),
+        );
+
+        // the undelegation started after the infraction so it's slashed first, the rest of the
+        // 300000 comes from the validator
+        let to = AccAddress::from_bech32(TO).unwrap();
+        let ubd = Staking::get_unbonding_delegation(&mut ctx, &to, &validator_address).unwrap();
+        assert_eq!(ubd.entries[0].balance, Uint256::from(450_000u32));
+        let validator = Staking::get_validator(&mut ctx, &validator_address).unwrap();
+        assert_eq!(validator.tokens, Uint256::from(2_250_000u32));
+        assert_eq!(
+            balance(&mut ctx, &pool_address(BONDED_POOL_NAME)),
+            vec![uatom(2_250_000)]
+        );
+        assert_eq!(
+            balance(&mut ctx, &pool_address(NOT_BONDED_POOL_NAME)),
+            vec![uatom(450_000)]
+        );
+
+        // the delegators' shares are now worth less
+        let delegation = Staking::get_delegation(&mut ctx, &to, &validator_address).unwrap();
+        assert_eq!(
+            validator.tokens_from_shares(delegation.shares),
+            Uint256::from(450_000u32)
+        );
+    }
+
+    #[test]
+    fn begin_redelegate_works() {
+        let mut ctx = context_at(100, 100);
+        let msg = create_validator_msg(FROM, 1, 2_000_000);
+        let src_address = ValAddress::from_bech32(&msg.validator_address).unwrap();
+        Staking::create_validator(&mut ctx, msg).unwrap();
+        let msg = create_validator_msg(TO, 2, 3_000_000);
+        let dst_address = ValAddress::from_bech32(&msg.validator_address).unwrap();
+        Staking::create_validator(&mut ctx, msg).unwrap();
+        Staking::end_block(&mut ctx);
+
+        let redelegate = |src: &ValAddress, dst: &ValAddress, amount| MsgBeginRedelegate {
+            delegator_address: FROM.into(),
+            validator_src_address: src.to_string(),
+            validator_dst_address: dst.to_string(),
+            amount: Some(uatom(amount)),
+        };
+
+        let err = Staking::begin_redelegate_msg(
+            &mut ctx,
+            redelegate(&src_address, &src_address, 500_000),
+        )
+        .unwrap_err();
+        assert_eq!(err.code(), ERR_SELF_REDELEGATION.code);
+
+        validate_begin_redelegate(&redelegate(&src_address, &dst_address, 500_000)).unwrap();
+        let res = Staking::begin_redelegate_msg(
+            &mut ctx,
+            redelegate(&src_address, &dst_address, 500_000),
+        )
+        .unwrap();
+        assert_eq!(
+            res.completion_time,
+            Some(Timestamp {
+                seconds: 100 + DEFAULT_UNBONDING_TIME,
+                nanos: 0
+            })
+        );
+
+        // both validators are bonded so the tokens stay in the bonded pool
+        assert_eq!(
+            balance(&mut ctx, &pool_address(BONDED_POOL_NAME)),
+            vec![uatom(5_000_000)]
+        );
+        let from = AccAddress::from_bech32(FROM).unwrap();
+        let delegation = Staking::get_delegation(&mut ctx, &from, &dst_address).unwrap();
+        assert_eq!(delegation.shares, Decimal256::from_ratio(500_000u32, 1u8));
+        assert_eq!(Staking::end_block(&mut ctx), vec![ed25519_update(1, 1)]);
+
+        // the redelegated tokens can't be redelegated again until the redelegation completes
+        let err = Staking::begin_redelegate_msg(
+            &mut ctx,
+            redelegate(&dst_address, &src_address, 500_000),
+        )
+        .unwrap_err();
+        assert_eq!(err.code(), ERR_TRANSITIVE_REDELEGATION.code);
+
+        let mut ctx = at_time(ctx, 100 + DEFAULT_UNBONDING_TIME);
+        Staking::end_block(&mut ctx);
+        assert_eq!(
+            Staking::get_redelegation(&mut ctx, &from, &src_address, &dst_address),
+            None
+        );
+        Staking::begin_redelegate_msg(&mut ctx, redelegate(&dst_address, &src_address, 500_000))
+            .unwrap();
+    }
+
+    #[test]
+    fn query_redelegations_works() {
+        let mut ctx = context_at(100, 100);
+        let msg = create_validator_msg(FROM, 1, 2_000_000);
+        let src_address = ValAddress::from_bech32(&msg.validator_address).unwrap();
+        Staking::create_validator(&mut ctx, msg).unwrap();
+        let msg = create_validator_msg(TO, 2, 3_000_000);
+        let dst_address = ValAddress::from_bech32(&msg.validator_address).unwrap();
+        Staking::create_validator(&mut ctx, msg).unwrap();
+        let msg = MsgBeginRedelegate {
+            delegator_address: FROM.into(),
+            validator_src_address: src_address.to_string(),
+            validator_dst_address: dst_address.to_string(),
+            amount: Some(uatom(500_000)),
+        };
+        Staking::begin_redelegate_msg(&mut ctx, msg).unwrap();
+
+        let query_ctx = QueryContext::new(ctx.get_multi_store(), 0);
+        let query = |delegator: &str, src: &str, dst: &str| {
+            Staking::query_redelegations(
+                &query_ctx,
+                QueryRedelegationsRequest {
+                    delegator_addr: delegator.into(),
+                    src_validator_addr: src.into(),
+                    dst_validator_addr: dst.into(),
+                    pagination: None,
+                },
+            )
+        };
+
+        let res = query(FROM, "", "").unwrap();
+        assert_eq!(res.redelegation_responses.len(), 1);
+        let entries = &res.redelegation_responses[0].entries;
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].balance, "500000");
+
+        let by_src = query("", &src_address.to_string(), "").unwrap();
+        assert_eq!(by_src.redelegation_responses, res.redelegation_responses);
+        let single = query(FROM, &src_address.to_string(), &dst_address.to_string()).unwrap();
+        assert_eq!(single.redelegation_responses, res.redelegation_responses);
+
+        let err = query(TO, &src_address.to_string(), &dst_address.to_string()).unwrap_err();
+        assert_eq!(err.code(), ERR_NOT_FOUND.code);
+        assert!(query(TO, "", "").unwrap().redelegation_responses.is_empty());
+    }
+
+    #[test]
+    fn query_validators_works() {
+        let mut ctx = context_at(100, 1);
+        let msg = create_validator_msg(FROM, 1, 2_000_000);
+        let unbonded_address = msg.validator_address.clone();
+        Staking::create_validator(&mut ctx, msg).unwrap();
+        let msg = create_validator_msg(TO, 2, 3_000_000);
+        let bonded_address = msg.validator_address.clone();
+        Staking::create_validator(&mut ctx, msg).unwrap();
+        Staking::end_block(&mut ctx);
+
+        let query_ctx = QueryContext::new(ctx.get_multi_store(), 0);
+        let query = |status: &str, pagination| {
+            Staking::query_validators(
+                &query_ctx,
+                QueryValidatorsRequest {
+                    status: status.into(),
+                    pagination,
+                },
+            )
+        };
+
+        let res = query("BOND_STATUS_BONDED", None).unwrap();
+        assert_eq!(res.validators.len(), 1);
+        assert_eq!(res.validators[0].operator_address, bonded_address);
+        let res = query("BOND_STATUS_UNBONDED", None).unwrap();
+        assert_eq!(res.validators.len(), 1);
+        assert_eq!(res.validators[0].operator_address, unbonded_address);
+        assert!(query("BOND_STATUS_UNBONDING", None)
+            .unwrap()
+            .validators
+            .is_empty());
+
+        let page = PageRequest {
+            limit: 1,
+            count_total: true,
+            ..Default::default()
+        };
+        let res = query("", Some(page)).unwrap();
+        assert_eq!(res.validators.len(), 1);
+        assert_eq!(res.pagination.unwrap().total, 2);
+
+        let err = query("BOND_STATUS_UNSPECIFIED", None).unwrap_err();
+        assert_eq!(err.code(), ERR_INVALID_REQUEST.code);
+
+        let res = Staking::query_validator(
+            &query_ctx,
+            QueryValidatorRequest {
+                validator_addr: bonded_address.clone(),
+            },
+        )
+        .unwrap();
+        assert_eq!(res.validator.unwrap().operator_address, bonded_address);
+        let err = Staking::query_validator(
+            &query_ctx,
+            QueryValidatorRequest {
+                validator_addr: ValAddress::try_from(vec![1; 20]).unwrap().to_string(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.code(), ERR_NOT_FOUND.code);
+
+        let res = Staking::query_pool(&query_ctx, QueryPoolRequest {}).unwrap();
+        assert_eq!(
+            res.pool,
+            Some(Pool {
+                not_bonded_tokens: "2000000".into(),
+                bonded_tokens: "3000000".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn query_delegations_works() {
+        let mut ctx = context_at(100, 100);
+        let msg = create_validator_msg(FROM, 1, 2_000_000);
+        let validator_address = msg.validator_address.clone();
+        Staking::create_validator(&mut ctx, msg).unwrap();
+        let msg = MsgDelegate {
+            delegator_address: TO.into(),
+            validator_address: validator_address.clone(),
+            amount: Some(uatom(1_000_000)),
+        };
+        Staking::delegate_msg(&mut ctx, msg).unwrap();
+
+        let query_ctx = QueryContext::new(ctx.get_multi_store(), 0);
+        let res = Staking::query_delegation(
+            &query_ctx,
+            QueryDelegationRequest {
+                delegator_addr: TO.into(),
+                validator_addr: validator_address.clone(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            res.delegation_response.unwrap().balance,
+            Some(uatom(1_000_000))
+        );
+
+        let res = Staking::query_validator_delegations(
+            &query_ctx,
+            QueryValidatorDelegationsRequest {
+                validator_addr: validator_address.clone(),
+                pagination: None,
+            },
+        )
+        .unwrap();
+        let mut balances: Vec<_> = res
+            .delegation_responses
+            .into_iter()
+            .map(|res| res.balance.unwrap())
+            .collect();
+        balances.sort_by_key(|coin| coin.amount);
+        assert_eq!(balances, vec![uatom(1_000_000), uatom(2_000_000)]);
+
+        let res = Staking::query_delegator_delegations(
+            &query_ctx,
+            QueryDelegatorDelegationsRequest {
+                delegator_addr: FROM.into(),
+                pagination: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(res.delegation_responses.len(), 1);
+        assert_eq!(res.delegation_responses[0].balance, Some(uatom(2_000_000)));
+
+        let res = Staking::query_delegator_validators(
+            &query_ctx,
+            QueryDelegatorValidatorsRequest {
+                delegator_addr: TO.into(),
+                pagination: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(res.validators.len(), 1);
+        assert_eq!(res.validators[0].operator_address, validator_address);
+
+        let err = Staking::query_delegation(
+            &query_ctx,
+            QueryDelegationRequest {
+                delegator_addr: TO.into(),
+                validator_addr: ValAddress::try_from(vec![1; 20]).unwrap().to_string(),
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err.code(), ERR_NOT_FOUND.code);
+    }
+
+    #[test]
+    fn begin_block_tracks_historical_info() {
+        let mut ctx = context_at(100, 100);
+        Staking::init_genesis(
+            &mut ctx,
+            GenesisState {
+                params: Params {
+                    bond_denom: "uatom".into(),
+                    historical_entries: 2,
+                    ..default_params()
+                },
+            },
+        );
+        Staking::create_validator(&mut ctx, create_validator_msg(FROM, 1, 2_000_000)).unwrap();
+        Staking::end_block(&mut ctx);
+
+        for height in 1..=4 {
+            ctx = ctx.with_header(Header {
+                height,
+                ..Default::default()
+            });
+            Staking::begin_block(&mut ctx);
+        }
+
+        let query_ctx = QueryContext::new(ctx.get_multi_store(), 0);
+        let query = |height| {
+            Staking::query_historical_info(&query_ctx, QueryHistoricalInfoRequest { height })
+        };
+
+        // only the last two entries are kept
+        for height in 1..=2 {
+            let err = query(height).unwrap_err();
+            assert_eq!(err.code(), ERR_NOT_FOUND.code);
+        }
+        for height in 3..=4 {
+            let hist = query(height).unwrap().hist.unwrap();
+            assert_eq!(hist.header.unwrap().height, height);
+            assert_eq!(hist.valset.len(), 1);
+        }
+
+        let err = query(-1).unwrap_err();
+        assert_eq!(err.code(), ERR_INVALID_REQUEST.code);
+    }
+
+    #[test]
+    fn format_time_works() {
+        let format = |seconds| format_time(&Timestamp { seconds, nanos: 5 });
+
+        assert_eq!(format(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format(1_700_000_000), "2023-11-14T22:13:20Z");
+        assert_eq!(format(-1), "1969-12-31T23:59:59Z");
+    }
+}
@@ -0,0 +1,160 @@
+use cosmwasm_std::Uint256;
+use ibc_proto::cosmos::{
+    base::v1beta1::Coin,
+    staking::v1beta1::{
+        CommissionRates, Description, MsgBeginRedelegate, MsgCreateValidator, MsgDelegate,
+        MsgEditValidator, MsgUndelegate,
+    },
+};
+use math::Decimal256;
+use tendermint_proto::google::protobuf::Timestamp;
+
+use crate::{
+    error::{AppError, ERR_INVALID_REQUEST},
+    types::{validate_coins, AccAddress, ValAddress},
+};
+
+use super::{
+    parse_dec, parse_int, Commission, ERR_BAD_VALIDATOR_ADDR,
+    ERR_COMMISSION_CHANGE_RATE_GT_MAX_RATE, ERR_COMMISSION_GT_MAX_RATE, ERR_COMMISSION_HUGE,
+    ERR_EMPTY_DELEGATOR_ADDR, ERR_EMPTY_VALIDATOR_ADDR, ERR_EMPTY_VALIDATOR_PUB_KEY,
+    ERR_SELF_DELEGATION_BELOW_MINIMUM,
+};
+
+/// The stateless checks of MsgCreateValidator, as with the cosmos SDK `ValidateBasic`. The
+/// validator's operator address must have the same bytes as the delegator's address.
+pub fn validate_create_validator(msg: &MsgCreateValidator) -> Result<(), AppError> {
+    let delegator_address = AccAddress::from_bech32(&msg.delegator_address)?;
+    let validator_address = parse_validator_address(&msg.validator_address)?;
+
+    if AccAddress::from(validator_address) != delegator_address {
+        return Err(ERR_BAD_VALIDATOR_ADDR.into());
+    }
+
+    if msg.pubkey.is_none() {
+        return Err(ERR_EMPTY_VALIDATOR_PUB_KEY.into());
+    }
+
+    let value = validate_amount(msg.value.as_ref(), "invalid delegation amount")?;
+
+    if msg.description.clone().unwrap_or_default() == Description::default() {
+        return Err(ERR_INVALID_REQUEST.wrap("empty description"));
+    }
+
+    let commission = msg.commission.clone().unwrap_or_default();
+    if commission == CommissionRates::default() {
+        return Err(ERR_INVALID_REQUEST.wrap("empty commission"));
+    }
+    parse_commission_rates(&commission)?;
+
+    let min_self_delegation = parse_int(&msg.min_self_delegation, "minimum self delegation")?;
+    if min_self_delegation.is_zero() {
+        return Err(ERR_INVALID_REQUEST.wrap("minimum self delegation must be a positive integer"));
+    }
+
+    if value < min_self_delegation {
+        return Err(ERR_SELF_DELEGATION_BELOW_MINIMUM.into());
+    }
+
+    Ok(())
+}
+
+/// The stateless checks of MsgEditValidator, an empty commission rate or minimum self
+/// delegation is left unchanged
+pub fn validate_edit_validator(msg: &MsgEditValidator) -> Result<(), AppError> {
+    parse_validator_address(&msg.validator_address)?;
+
+    if msg.description.clone().unwrap_or_default() == Description::default() {
+        return Err(ERR_INVALID_REQUEST.wrap("empty description"));
+    }
+
+    if !msg.min_self_delegation.is_empty()
+        && parse_int(&msg.min_self_delegation, "minimum self delegation")?.is_zero()
+    {
+        return Err(ERR_INVALID_REQUEST.wrap("minimum self delegation must be a positive integer"));
+    }
+
+    if !msg.commission_rate.is_empty()
+        && parse_dec(&msg.commission_rate, "commission rate")? > Decimal256::one()
+    {
+        return Err(ERR_INVALID_REQUEST.wrap("commission rate must be between 0 and 1 (inclusive)"));
+    }
+
+    Ok(())
+}
+
+pub fn validate_delegate(msg: &MsgDelegate) -> Result<(), AppError> {
+    parse_delegator_address(&msg.delegator_address)?;
+    parse_validator_address(&msg.validator_address)?;
+    validate_amount(msg.amount.as_ref(), "invalid delegation amount")?;
+
+    Ok(())
+}
+
+pub fn validate_undelegate(msg: &MsgUndelegate) -> Result<(), AppError> {
+    parse_delegator_address(&msg.delegator_address)?;
+    parse_validator_address(&msg.validator_address)?;
+    validate_amount(msg.amount.as_ref(), "invalid shares amount")?;
+
+    Ok(())
+}
+
+pub fn validate_begin_redelegate(msg: &MsgBeginRedelegate) -> Result<(), AppError> {
+    parse_delegator_address(&msg.delegator_address)?;
+    parse_validator_address(&msg.validator_src_address)?;
+    parse_validator_address(&msg.validator_dst_address)?;
+    validate_amount(msg.amount.as_ref(), "invalid shares amount")?;
+
+    Ok(())
+}
+
+/// Returns the commission with the given rates, which must satisfy
+/// `rate <= max_rate <= 1` and `max_change_rate <= max_rate`. The update time isn't set.
+pub fn parse_commission_rates(rates: &CommissionRates) -> Result<Commission, AppError> {
+    let rate = parse_dec(&rates.rate, "commission rate")?;
+    let max_rate = parse_dec(&rates.max_rate, "commission max rate")?;
+    let max_change_rate = parse_dec(&rates.max_change_rate, "commission max change rate")?;
+
+    if max_rate > Decimal256::one() {
+        return Err(ERR_COMMISSION_HUGE.into());
+    }
+
+    if rate > max_rate {
+        return Err(ERR_COMMISSION_GT_MAX_RATE.into());
+    }
+
+    if max_change_rate > max_rate {
+        return Err(ERR_COMMISSION_CHANGE_RATE_GT_MAX_RATE.into());
+    }
+
+    Ok(Commission {
+        rate,
+        max_rate,
+        max_change_rate,
+        update_time: Timestamp::default(),
+    })
+}
+
+fn parse_delegator_address(address: &str) -> Result<AccAddress, AppError> {
+    if address.is_empty() {
+        return Err(ERR_EMPTY_DELEGATOR_ADDR.into());
+    }
+
+    AccAddress::from_bech32(address)
+}
+
+fn parse_validator_address(address: &str) -> Result<ValAddress, AppError> {
+    if address.is_empty() {
+        return Err(ERR_EMPTY_VALIDATOR_ADDR.into());
+    }
+
+    ValAddress::from_bech32(address)
+}
+
+/// Checks that the amount is a valid positive coin, returning the amount
+fn validate_amount(amount: Option<&Coin>, err_msg: &str) -> Result<Uint256, AppError> {
+    match amount {
+        Some(coin) if validate_coins(std::slice::from_ref(coin)).is_ok() => Ok(coin.amount),
+        _ => Err(ERR_INVALID_REQUEST.wrap(err_msg)),
+    }
+}
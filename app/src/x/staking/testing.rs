@@ -0,0 +1,137 @@
+//! Fixtures shared by the tests of staking and of the modules built on it
+
+use ibc_proto::{
+    cosmos::{
+        base::v1beta1::Coin,
+        staking::v1beta1::{CommissionRates, Description, MsgCreateValidator, Params},
+    },
+    google::protobuf::Any,
+};
+use math::Decimal256;
+use prost::Message;
+use tendermint_proto::{
+    abci::{BlockParams, ConsensusParams},
+    google::protobuf::Duration,
+    types::{EvidenceParams, ValidatorParams},
+};
+
+use super::{default_params, GenesisState, Staking};
+use crate::{
+    crypto::{PubKey, ED25519_PUB_KEY_TYPE_URL},
+    store::MultiStore,
+    types::{AccAddress, Context, ValAddress},
+    x::bank::{Balance, Bank, GenesisState as BankGenesisState},
+};
+
+pub fn uatom(amount: u32) -> Coin {
+    Coin {
+        denom: "uatom".into(),
+        amount: amount.into(),
+    }
+}
+
+/// Returns Tendermint's default consensus params
+pub fn consensus_params() -> ConsensusParams {
+    ConsensusParams {
+        block: Some(BlockParams {
+            max_bytes: 22_020_096,
+            max_gas: -1,
+        }),
+        evidence: Some(EvidenceParams {
+            max_age_num_blocks: 100_000,
+            max_age_duration: Some(Duration {
+                seconds: 48 * 60 * 60,
+                nanos: 0,
+            }),
+            max_bytes: 1_048_576,
+        }),
+        validator: Some(ValidatorParams {
+            pub_key_types: vec!["ed25519".into()],
+        }),
+        version: None,
+    }
+}
+
+/// Returns an ed25519 consensus key whose bytes are all the seed
+pub fn consensus_pubkey(seed: u8) -> Any {
+    Any {
+        type_url: ED25519_PUB_KEY_TYPE_URL.into(),
+        value: PubKey {
+            key: vec![seed; 32],
+        }
+        .encode_to_vec(),
+    }
+}
+
+/// Returns a message creating a validator, with a 10% commission, operated by the delegator
+pub fn create_validator_msg(delegator: &str, seed: u8, amount: u32) -> MsgCreateValidator {
+    let delegator_address = AccAddress::from_bech32(delegator).unwrap();
+
+    MsgCreateValidator {
+        description: Some(Description {
+            moniker: "validator".into(),
+            ..Default::default()
+        }),
+        commission: Some(CommissionRates {
+            rate: Decimal256::percent(10).to_cosmos_proto_string(),
+            max_rate: Decimal256::percent(20).to_cosmos_proto_string(),
+            max_change_rate: Decimal256::percent(1).to_cosmos_proto_string(),
+        }),
+        min_self_delegation: "1".into(),
+        delegator_address: delegator.into(),
+        validator_address: ValAddress::from(delegator_address).to_string(),
+        pubkey: Some(consensus_pubkey(seed)),
+        value: Some(uatom(amount)),
+    }
+}
+
+/// Returns a context with the default consensus params where each account holds 10atom and
+/// uatom is the bond denom
+pub fn context_with_accounts(accounts: &[AccAddress], max_validators: u32) -> Context {
+    let mut ctx = Context::new(MultiStore::new());
+    ctx.set_consensus_params(&consensus_params());
+    Bank::init_genesis(
+        &mut ctx,
+        BankGenesisState {
+            balances: accounts
+                .iter()
+                .map(|address| Balance {
+                    address: address.clone(),
+                    coins: vec![uatom(10_000_000)],
+                })
+                .collect(),
+        },
+    );
+    Staking::init_genesis(
+        &mut ctx,
+        GenesisState {
+            params: Params {
+                bond_denom: "uatom".into(),
+                max_validators,
+                ..default_params()
+            },
+        },
+    );
+    ctx
+}
+
+/// Creates a validator operated by each account, which self delegates the amount and whose
+/// consensus key is seeded by its position starting from 1, and bonds them at the end of the
+/// block
+pub fn bond_validators(ctx: &mut Context, accounts: &[AccAddress], amount: u32) -> Vec<ValAddress> {
+    let validators = accounts
+        .iter()
+        .zip(1..)
+        .map(|(account, seed)| {
+            Staking::create_validator(
+                ctx,
+                create_validator_msg(&account.to_string(), seed, amount),
+            )
+            .unwrap();
+            ValAddress::from(account.clone())
+        })
+        .collect();
+    Staking::end_block(ctx);
+
+    validators
+}
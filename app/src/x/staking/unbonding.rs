@@ -0,0 +1,188 @@
+use std::str::FromStr;
+
+use cosmwasm_std::Uint256;
+use ibc_proto::cosmos::staking::v1beta1::{
+    Redelegation as ProtoRedelegation, RedelegationEntry as ProtoRedelegationEntry,
+    UnbondingDelegation as ProtoUnbondingDelegation,
+    UnbondingDelegationEntry as ProtoUnbondingDelegationEntry,
+};
+use math::Decimal256;
+use tendermint_proto::google::protobuf::Timestamp;
+
+use crate::types::{AccAddress, ValAddress};
+
+use super::is_after;
+
+/// The tokens a delegator is unbonding from a validator, with an entry for each undelegation
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnbondingDelegation {
+    pub delegator_address: AccAddress,
+    pub validator_address: ValAddress,
+    pub entries: Vec<UnbondingDelegationEntry>,
+}
+
+/// An undelegation, the balance is the initial balance less any slashing
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnbondingDelegationEntry {
+    pub creation_height: i64,
+    pub completion_time: Timestamp,
+    pub initial_balance: Uint256,
+    pub balance: Uint256,
+}
+
+/// The tokens a delegator is redelegating from one validator to another, with an entry for
+/// each redelegation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Redelegation {
+    pub delegator_address: AccAddress,
+    pub validator_src_address: ValAddress,
+    pub validator_dst_address: ValAddress,
+    pub entries: Vec<RedelegationEntry>,
+}
+
+/// A redelegation, the shares are those created at the destination validator
+#[derive(Debug, Clone, PartialEq)]
+pub struct RedelegationEntry {
+    pub creation_height: i64,
+    pub completion_time: Timestamp,
+    pub initial_balance: Uint256,
+    pub shares_dst: Decimal256,
+}
+
+impl UnbondingDelegationEntry {
+    /// Returns whether the entry has completed at the given time
+    pub fn is_mature(&self, time: &Timestamp) -> bool {
+        !is_after(&self.completion_time, time)
+    }
+}
+
+impl RedelegationEntry {
+    /// Returns whether the entry has completed at the given time
+    pub fn is_mature(&self, time: &Timestamp) -> bool {
+        !is_after(&self.completion_time, time)
+    }
+}
+
+impl UnbondingDelegation {
+    /// Decodes a stored unbonding delegation, panicking if it's invalid
+    pub fn from_proto(ubd: ProtoUnbondingDelegation) -> UnbondingDelegation {
+        UnbondingDelegation {
+            delegator_address: AccAddress::from_bech32(&ubd.delegator_address)
+                .expect("delegator addresses are valid"),
+            validator_address: ValAddress::from_bech32(&ubd.validator_address)
+                .expect("validator addresses are valid"),
+            entries: ubd
+                .entries
+                .into_iter()
+                .map(|entry| UnbondingDelegationEntry {
+                    creation_height: entry.creation_height,
+                    completion_time: entry.completion_time.unwrap_or_default(),
+                    initial_balance: parse_int(&entry.initial_balance),
+                    balance: parse_int(&entry.balance),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_proto(&self) -> ProtoUnbondingDelegation {
+        ProtoUnbondingDelegation {
+            delegator_address: self.delegator_address.to_string(),
+            validator_address: self.validator_address.to_string(),
+            entries: self
+                .entries
+                .iter()
+                .map(|entry| ProtoUnbondingDelegationEntry {
+                    creation_height: entry.creation_height,
+                    completion_time: Some(entry.completion_time.clone()),
+                    initial_balance: entry.initial_balance.to_string(),
+                    balance: entry.balance.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Redelegation {
+    /// Decodes a stored redelegation, panicking if it's invalid
+    pub fn from_proto(red: ProtoRedelegation) -> Redelegation {
+        Redelegation {
+            delegator_address: AccAddress::from_bech32(&red.delegator_address)
+                .expect("delegator addresses are valid"),
+            validator_src_address: ValAddress::from_bech32(&red.validator_src_address)
+                .expect("validator addresses are valid"),
+            validator_dst_address: ValAddress::from_bech32(&red.validator_dst_address)
+                .expect("validator addresses are valid"),
+            entries: red
+                .entries
+                .into_iter()
+                .map(|entry| RedelegationEntry {
+                    creation_height: entry.creation_height,
+                    completion_time: entry.completion_time.unwrap_or_default(),
+                    initial_balance: parse_int(&entry.initial_balance),
+                    shares_dst: Decimal256::from_cosmos_proto_string(&entry.shares_dst)
+                        .expect("store should contain valid decimals"),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_proto(&self) -> ProtoRedelegation {
+        ProtoRedelegation {
+            delegator_address: self.delegator_address.to_string(),
+            validator_src_address: self.validator_src_address.to_string(),
+            validator_dst_address: self.validator_dst_address.to_string(),
+            entries: self
+                .entries
+                .iter()
+                .map(|entry| ProtoRedelegationEntry {
+                    creation_height: entry.creation_height,
+                    completion_time: Some(entry.completion_time.clone()),
+                    initial_balance: entry.initial_balance.to_string(),
+                    shares_dst: entry.shares_dst.to_cosmos_proto_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn parse_int(s: &str) -> Uint256 {
+    Uint256::from_str(s).expect("store should contain valid integers")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn proto_round_trip_works() {
+        let red = Redelegation {
+            delegator_address: AccAddress::try_from(vec![1; 20]).unwrap(),
+            validator_src_address: ValAddress::try_from(vec![2; 20]).unwrap(),
+            validator_dst_address: ValAddress::try_from(vec![3; 20]).unwrap(),
+            entries: vec![RedelegationEntry {
+                creation_height: 4,
+                completion_time: Timestamp {
+                    seconds: 5,
+                    nanos: 6,
+                },
+                initial_balance: Uint256::from(7u32),
+                shares_dst: Decimal256::percent(750),
+            }],
+        };
+
+        let proto = red.to_proto();
+        assert_eq!(proto.entries[0].shares_dst, "7500000000000000000");
+        assert_eq!(Redelegation::from_proto(proto), red);
+
+        let entry = &red.entries[0];
+        assert!(!entry.is_mature(&Timestamp {
+            seconds: 5,
+            nanos: 5
+        }));
+        assert!(entry.is_mature(&Timestamp {
+            seconds: 5,
+            nanos: 6
+        }));
+    }
+}
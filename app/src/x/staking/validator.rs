@@ -0,0 +1,403 @@
+use std::str::FromStr;
+
+use cosmwasm_std::{Uint128, Uint256};
+use ibc_proto::{
+    cosmos::staking::v1beta1::{
+        BondStatus, Commission as ProtoCommission, CommissionRates, Delegation as ProtoDelegation,
+        Description, Validator as ProtoValidator,
+    },
+    google::protobuf::Any,
+};
+use math::Decimal256;
+use tendermint_proto::{
+    abci::ValidatorUpdate,
+    crypto::{public_key::Sum, PublicKey},
+    google::protobuf::Timestamp,
+};
+
+use crate::{
+    crypto::SignerPubKey,
+    types::{AccAddress, ConsAddress, ValAddress},
+};
+
+/// The number of tokens which give one unit of consensus power, as in the cosmos SDK
+pub const POWER_REDUCTION: u128 = 1_000_000;
+
+/// A validator, as in the cosmos SDK `Validator` but with typed fields. It's stored as a
+/// `cosmos.staking.v1beta1.Validator`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Validator {
+    pub operator_address: ValAddress,
+    pub consensus_pubkey: Any,
+    pub jailed: bool,
+    pub status: BondStatus,
+    pub tokens: Uint256,
+    pub delegator_shares: Decimal256,
+    pub description: Description,
+    pub unbonding_height: i64,
+    pub unbonding_time: Timestamp,
+    pub commission: Commission,
+    pub min_self_delegation: Uint256,
+}
+
+/// The commission rates of a validator and the time the rate was last changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commission {
+    pub rate: Decimal256,
+    pub max_rate: Decimal256,
+    pub max_change_rate: Decimal256,
+    pub update_time: Timestamp,
+}
+
+/// The shares of a delegator in a validator
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delegation {
+    pub delegator_address: AccAddress,
+    pub validator_address: ValAddress,
+    pub shares: Decimal256,
+}
+
+impl Validator {
+    /// Returns a new unbonded validator with no tokens, as with the cosmos SDK `NewValidator`
+    pub fn new(
+        operator_address: ValAddress,
+        consensus_pubkey: Any,
+        description: Description,
+        commission: Commission,
+        min_self_delegation: Uint256,
+    ) -> Validator {
+        Validator {
+            operator_address,
+            consensus_pubkey,
+            jailed: false,
+            status: BondStatus::Unbonded,
+            tokens: Uint256::zero(),
+            delegator_shares: Decimal256::zero(),
+            description,
+            unbonding_height: 0,
+            unbonding_time: Timestamp::default(),
+            commission,
+            min_self_delegation,
+        }
+    }
+
+    /// Decodes a stored validator, panicking if it's invalid
+    pub fn from_proto(validator: ProtoValidator) -> Validator {
+        let commission = validator.commission.unwrap_or_default();
+        let rates = commission.commission_rates.unwrap_or_default();
+
+        Validator {
+            operator_address: ValAddress::from_bech32(&validator.operator_address)
+                .expect("validator addresses are valid"),
+            consensus_pubkey: validator.consensus_pubkey.unwrap_or_default(),
+            jailed: validator.jailed,
+            status: BondStatus::from_i32(validator.status).expect("validator status is valid"),
+            tokens: parse_int(&validator.tokens),
+            delegator_shares: parse_dec(&validator.delegator_shares),
+            description: validator.description.unwrap_or_default(),
+            unbonding_height: validator.unbonding_height,
+            unbonding_time: validator.unbonding_time.unwrap_or_default(),
+            commission: Commission {
+                rate: parse_dec(&rates.rate),
+                max_rate: parse_dec(&rates.max_rate),
+                max_change_rate: parse_dec(&rates.max_change_rate),
+                update_time: commission.update_time.unwrap_or_default(),
+            },
+            min_self_delegation: parse_int(&validator.min_self_delegation),
+        }
+    }
+
+    pub fn to_proto(&self) -> ProtoValidator {
+        ProtoValidator {
+            operator_address: self.operator_address.to_string(),
+            consensus_pubkey: Some(self.consensus_pubkey.clone()),
+            jailed: self.jailed,
+            status: self.status as i32,
+            tokens: self.tokens.to_string(),
+            delegator_shares: self.delegator_shares.to_cosmos_proto_string(),
+            description: Some(self.description.clone()),
+            unbonding_height: self.unbonding_height,
+            unbonding_time: Some(self.unbonding_time.clone()),
+            commission: Some(ProtoCommission {
+                commission_rates: Some(CommissionRates {
+                    rate: self.commission.rate.to_cosmos_proto_string(),
+                    max_rate: self.commission.max_rate.to_cosmos_proto_string(),
+                    max_change_rate: self.commission.max_change_rate.to_cosmos_proto_string(),
+                }),
+                update_time: Some(self.commission.update_time.clone()),
+            }),
+            min_self_delegation: self.min_self_delegation.to_string(),
+        }
+    }
+
+    pub fn is_bonded(&self) -> bool {
+        self.status == BondStatus::Bonded
+    }
+
+    /// Returns the consensus key, which is checked when the validator is created
+    pub fn cons_pub_key(&self) -> SignerPubKey {
+        SignerPubKey::from_any(&self.consensus_pubkey).expect("validator consensus keys are valid")
+    }
+
+    pub fn cons_address(&self) -> ConsAddress {
+        cons_address(&self.cons_pub_key())
+    }
+
+    /// Returns whether the validator has lost all its tokens, e.g. by slashing, while still
+    /// having delegators. New delegations to such a validator aren't allowed.
+    pub fn invalid_ex_rate(&self) -> bool {
+        self.tokens.is_zero() && !self.delegator_shares.is_zero()
+    }
+
+    /// Returns the shares worth the given tokens, rounded down
+    pub fn shares_from_tokens(&self, amount: Uint256) -> Decimal256 {
+        self.delegator_shares * Decimal256::from_ratio(amount, 1u8) / self.tokens
+    }
+
+    /// Returns the tokens worth the given shares, rounded down
+    pub fn tokens_from_shares(&self, shares: Decimal256) -> Uint256 {
+        self.tokens
+            .multiply_ratio(shares.atomics(), self.delegator_shares.atomics())
+    }
+
+    /// Returns the decimal tokens worth the given shares, as with the cosmos SDK
+    /// `TokensFromShares` which rounds the quotient half to even
+    pub fn dec_tokens_from_shares(&self, shares: Decimal256) -> Decimal256 {
+        (shares * Decimal256::from_ratio(self.tokens, 1u8)).div_rounded(self.delegator_shares)
+    }
+
+    /// Returns the decimal tokens worth the given shares, rounded down as with the cosmos SDK
+    /// `TokensFromSharesTruncated`
+    pub fn dec_tokens_from_shares_truncated(&self, shares: Decimal256) -> Decimal256 {
+        shares * Decimal256::from_ratio(self.tokens, 1u8) / self.delegator_shares
+    }
+
+    /// Removes the shares, returning the tokens they were worth. The last shares take all the
+    /// remaining tokens.
+    pub fn remove_del_shares(&mut self, shares: Decimal256) -> Uint256 {
+        let remaining_shares = self.delegator_shares - shares;
+
+        let issued_tokens = if remaining_shares.is_zero() {
+            self.tokens
+        } else {
+            self.tokens_from_shares(shares)
+        };
+
+        self.tokens -= issued_tokens;
+        self.delegator_shares = remaining_shares;
+
+        issued_tokens
+    }
+
+    /// Adds the delegated tokens, returning the shares issued for them. As in the cosmos SDK
+    /// the first delegation sets the exchange rate to one share per token.
+    pub fn add_tokens_from_del(&mut self, amount: Uint256) -> Decimal256 {
+        let issued_shares = if self.delegator_shares.is_zero() {
+            Decimal256::from_ratio(amount, 1u8)
+        } else {
+            self.shares_from_tokens(amount)
+        };
+
+        self.tokens += amount;
+        self.delegator_shares += issued_shares;
+
+        issued_shares
+    }
+
+    /// Returns the consensus power the validator would have if it were bonded
+    pub fn potential_consensus_power(&self) -> i64 {
+        tokens_to_consensus_power(self.tokens)
+    }
+
+    /// Returns the consensus power of the validator, which is zero unless it's bonded
+    pub fn consensus_power(&self) -> i64 {
+        if self.is_bonded() {
+            self.potential_consensus_power()
+        } else {
+            0
+        }
+    }
+
+    /// Returns the update which sets the validator's power in Tendermint to the given power,
+    /// zero removes the validator from the validator set
+    pub fn abci_validator_update(&self, power: i64) -> ValidatorUpdate {
+        ValidatorUpdate {
+            pub_key: tm_public_key(&self.cons_pub_key()),
+            power,
+        }
+    }
+}
+
+impl Delegation {
+    /// Decodes a stored delegation, panicking if it's invalid
+    pub fn from_proto(delegation: ProtoDelegation) -> Delegation {
+        Delegation {
+            delegator_address: AccAddress::from_bech32(&delegation.delegator_address)
+                .expect("delegator addresses are valid"),
+            validator_address: ValAddress::from_bech32(&delegation.validator_address)
+                .expect("validator addresses are valid"),
+            shares: parse_dec(&delegation.shares),
+        }
+    }
+
+    pub fn to_proto(&self) -> ProtoDelegation {
+        ProtoDelegation {
+            delegator_address: self.delegator_address.to_string(),
+            validator_address: self.validator_address.to_string(),
+            shares: self.shares.to_cosmos_proto_string(),
+        }
+    }
+}
+
+pub fn tokens_to_consensus_power(tokens: Uint256) -> i64 {
+    let power = Uint128::try_from(tokens / Uint256::from(POWER_REDUCTION))
+        .expect("consensus power fits in an i64");
+    i64::try_from(power.u128()).expect("consensus power fits in an i64")
+}
+
+/// Returns the tokens worth the given consensus power
+pub fn tokens_from_consensus_power(power: i64) -> Uint256 {
+    Uint256::from(power.unsigned_abs()) * Uint256::from(POWER_REDUCTION)
+}
+
+/// Returns the consensus address of a consensus key, which as in the cosmos SDK is the address
+/// of the key
+pub fn cons_address(pub_key: &SignerPubKey) -> ConsAddress {
+    ConsAddress::try_from(Vec::from(pub_key.address())).expect("20 byte addresses are valid")
+}
+
+/// Returns the key as a Tendermint public key, Tendermint only supports ed25519 and secp256k1
+/// consensus keys
+pub fn tm_public_key(pub_key: &SignerPubKey) -> Option<PublicKey> {
+    let sum = match pub_key {
+        SignerPubKey::Ed25519(key) => Sum::Ed25519(key.clone()),
+        SignerPubKey::Secp256k1(key) => Sum::Secp256k1(key.serialize().to_vec()),
+        SignerPubKey::Multisig { .. } => return None,
+    };
+
+    Some(PublicKey { sum: Some(sum) })
+}
+
+fn parse_int(s: &str) -> Uint256 {
+    Uint256::from_str(s).expect("store should contain valid integers")
+}
+
+fn parse_dec(s: &str) -> Decimal256 {
+    Decimal256::from_cosmos_proto_string(s).expect("store should contain valid decimals")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn add_tokens_from_del_works() {
+        let mut validator = Validator::new(
+            ValAddress::try_from(vec![1; 20]).unwrap(),
+            Any::default(),
+            Description::default(),
+            Commission {
+                rate: Decimal256::zero(),
+                max_rate: Decimal256::zero(),
+                max_change_rate: Decimal256::zero(),
+                update_time: Timestamp::default(),
+            },
+            Uint256::one(),
+        );
+
+        let shares = validator.add_tokens_from_del(Uint256::from(100u32));
+        assert_eq!(shares, Decimal256::from_ratio(100u32, 1u32));
+
+        // halving the exchange rate doubles the shares issued per token
+        validator.tokens = Uint256::from(50u32);
+        let shares = validator.add_tokens_from_del(Uint256::from(10u32));
+        assert_eq!(shares, Decimal256::from_ratio(20u32, 1u32));
+        assert_eq!(validator.tokens, Uint256::from(60u32));
+        assert_eq!(
+            validator.delegator_shares,
+            Decimal256::from_ratio(120u32, 1u32)
+        );
+
+        // a third of a share per token is rounded down
+        validator.tokens = Uint256::from(360u32);
+        let shares = validator.add_tokens_from_del(Uint256::from(1u32));
+        assert_eq!(
+            shares,
+            Decimal256::from_str("0.333333333333333333").unwrap()
+        );
+    }
+
+    #[test]
+    fn remove_del_shares_works() {
+        let mut validator = Validator::new(
+            ValAddress::try_from(vec![1; 20]).unwrap(),
+            Any::default(),
+            Description::default(),
+            Commission {
+                rate: Decimal256::zero(),
+                max_rate: Decimal256::zero(),
+                max_change_rate: Decimal256::zero(),
+                update_time: Timestamp::default(),
+            },
+            Uint256::one(),
+        );
+        validator.tokens = Uint256::from(10u32);
+        validator.delegator_shares = Decimal256::from_ratio(3u32, 1u32);
+
+        // a share is worth 3.33 tokens, which is rounded down
+        assert_eq!(
+            validator.remove_del_shares(Decimal256::one()),
+            Uint256::from(3u32)
+        );
+        assert_eq!(validator.tokens, Uint256::from(7u32));
+
+        // the last shares take the remaining tokens
+        assert_eq!(
+            validator.remove_del_shares(Decimal256::from_ratio(2u32, 1u32)),
+            Uint256::from(7u32)
+        );
+        assert!(validator.tokens.is_zero());
+        assert!(validator.delegator_shares.is_zero());
+    }
+
+    #[test]
+    fn proto_round_trip_works() {
+        let mut validator = Validator::new(
+            ValAddress::try_from(vec![1; 20]).unwrap(),
+            Any::default(),
+            Description {
+                moniker: "validator".into(),
+                ..Default::default()
+            },
+            Commission {
+                rate: Decimal256::percent(10),
+                max_rate: Decimal256::percent(20),
+                max_change_rate: Decimal256::percent(1),
+                update_time: Timestamp {
+                    seconds: 5,
+                    nanos: 0,
+                },
+            },
+            Uint256::one(),
+        );
+        validator.add_tokens_from_del(Uint256::from(2_500_000u32));
+
+        let proto = validator.to_proto();
+        assert_eq!(proto.delegator_shares, "2500000000000000000000000");
+        assert_eq!(
+            proto
+                .commission
+                .as_ref()
+                .unwrap()
+                .commission_rates
+                .as_ref()
+                .unwrap()
+                .rate,
+            "100000000000000000"
+        );
+        assert_eq!(Validator::from_proto(proto), validator);
+        assert_eq!(validator.potential_consensus_power(), 2);
+        assert_eq!(validator.consensus_power(), 0);
+    }
+}
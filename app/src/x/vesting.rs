@@ -0,0 +1,504 @@
+use cosmwasm_std::Uint256;
+use ibc_proto::{
+    cosmos::{
+        auth::v1beta1::BaseAccount,
+        base::v1beta1::Coin,
+        vesting::v1beta1::{
+            BaseVestingAccount, ContinuousVestingAccount, DelayedVestingAccount,
+            MsgCreateVestingAccount, MsgCreateVestingAccountResponse,
+        },
+    },
+    google::protobuf::Any,
+};
+use math::Decimal256;
+use prost::Message;
+
+use crate::{
+    baseapp::AUTH_STORE_PREFIX,
+    collections::Map,
+    error::{AppError, ERR_INVALID_REQUEST, ERR_UNAUTHORIZED},
+    store::ReadKVStore,
+    types::{
+        add_coins, amount_of, new_event, sub_coins_saturating, AccAddress, Context,
+        ATTRIBUTE_KEY_MODULE, EVENT_TYPE_MESSAGE,
+    },
+    x::{auth::Auth, bank::Bank},
+};
+
+pub const MODULE_NAME: &str = "vesting";
+
+pub const CONTINUOUS_VESTING_ACCOUNT_TYPE_URL: &str =
+    "/cosmos.vesting.v1beta1.ContinuousVestingAccount";
+pub const DELAYED_VESTING_ACCOUNT_TYPE_URL: &str = "/cosmos.vesting.v1beta1.DelayedVestingAccount";
+
+// In the cosmos SDK vesting accounts are auth accounts, so the vesting state is kept in the auth
+// store next to the base accounts
+const VESTING_ACCOUNT_STORE_PREFIX: [u8; 1] = [3];
+
+/// The vesting state is stored as a `ContinuousVestingAccount` or `DelayedVestingAccount` without
+/// its base account, which is stored by auth
+const VESTING_ACCOUNTS: Map<AccAddress, Any> = Map::new(&VESTING_ACCOUNT_STORE_PREFIX);
+
+/// When the original vesting coins of a vesting account vest
+#[derive(Debug, Clone, PartialEq)]
+pub enum VestingSchedule {
+    /// The coins vest linearly from the start time until the end time
+    Continuous { start_time: i64 },
+    /// All the coins vest at the end time
+    Delayed,
+}
+
+/// The vesting state of an account, as in the cosmos SDK `BaseVestingAccount`. Times are unix
+/// times in seconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VestingAccount {
+    pub original_vesting: Vec<Coin>,
+    pub delegated_free: Vec<Coin>,
+    pub delegated_vesting: Vec<Coin>,
+    pub end_time: i64,
+    pub schedule: VestingSchedule,
+}
+
+impl VestingAccount {
+    /// Decodes a `ContinuousVestingAccount` or `DelayedVestingAccount`, returning the vesting
+    /// state along with the base account
+    pub fn from_any(any: &Any) -> Result<(VestingAccount, Option<BaseAccount>), AppError> {
+        let decode_err = |e: prost::DecodeError| ERR_INVALID_REQUEST.wrap(e.to_string());
+
+        let (base, schedule) = match any.type_url.as_str() {
+            CONTINUOUS_VESTING_ACCOUNT_TYPE_URL => {
+                let account =
+                    ContinuousVestingAccount::decode(&any.value[..]).map_err(decode_err)?;
+                let schedule = VestingSchedule::Continuous {
+                    start_time: account.start_time,
+                };
+                (account.base_vesting_account, schedule)
+            }
+            DELAYED_VESTING_ACCOUNT_TYPE_URL => {
+                let account = DelayedVestingAccount::decode(&any.value[..]).map_err(decode_err)?;
+                (account.base_vesting_account, VestingSchedule::Delayed)
+            }
+            _ => {
+                return Err(ERR_INVALID_REQUEST
+                    .wrap(format!("not a vesting account type: {}", any.type_url)))
+            }
+        };
+        let base = base.unwrap_or_default();
+
+        let account = VestingAccount {
+            original_vesting: base.original_vesting,
+            delegated_free: base.delegated_free,
+            delegated_vesting: base.delegated_vesting,
+            end_time: base.end_time,
+            schedule,
+        };
+
+        Ok((account, base.base_account))
+    }
+
+    /// Encodes the account as a `ContinuousVestingAccount` or `DelayedVestingAccount` with the
+    /// given base account
+    pub fn to_any(&self, base_account: Option<BaseAccount>) -> Any {
+        let base_vesting_account = Some(BaseVestingAccount {
+            base_account,
+            original_vesting: self.original_vesting.clone(),
+            delegated_free: self.delegated_free.clone(),
+            delegated_vesting: self.delegated_vesting.clone(),
+            end_time: self.end_time,
+        });
+
+        match self.schedule {
+            VestingSchedule::Continuous { start_time } => Any {
+                type_url: CONTINUOUS_VESTING_ACCOUNT_TYPE_URL.into(),
+                value: ContinuousVestingAccount {
+                    base_vesting_account,
+                    start_time,
+                }
+                .encode_to_vec(),
+            },
+            VestingSchedule::Delayed => Any {
+                type_url: DELAYED_VESTING_ACCOUNT_TYPE_URL.into(),
+                value: DelayedVestingAccount {
+                    base_vesting_account,
+                }
+                .encode_to_vec(),
+            },
+        }
+    }
+
+    /// Returns the original vesting coins which have vested by the block time
+    pub fn vested_coins(&self, block_time: i64) -> Vec<Coin> {
+        match self.schedule {
+            VestingSchedule::Continuous { start_time } if block_time <= start_time => vec![],
+            VestingSchedule::Continuous { start_time } if block_time < self.end_time => {
+                // as in the cosmos SDK the vested fraction is a rounded Dec
+                let elapsed = Decimal256::from_ratio((block_time - start_time) as u64, 1u8);
+                let duration = Decimal256::from_ratio((self.end_time - start_time) as u64, 1u8);
+                let fraction = elapsed.div_rounded(duration);
+
+                self.original_vesting
+                    .iter()
+                    .map(|coin| Coin {
+                        denom: coin.denom.clone(),
+                        amount: round_int(
+                            Decimal256::from_ratio(coin.amount, 1u8).mul_rounded(fraction),
+                        ),
+                    })
+                    .filter(|coin| !coin.amount.is_zero())
+                    .collect()
+            }
+            VestingSchedule::Delayed if block_time < self.end_time => vec![],
+            _ => self.original_vesting.clone(),
+        }
+    }
+
+    /// Returns the original vesting coins which are still vesting at the block time
+    pub fn vesting_coins(&self, block_time: i64) -> Vec<Coin> {
+        sub_coins_saturating(&self.original_vesting, &self.vested_coins(block_time))
+    }
+
+    /// Returns the coins which can't be spent at the block time, these are the vesting coins
+    /// less any which have been delegated
+    pub fn locked_coins(&self, block_time: i64) -> Vec<Coin> {
+        sub_coins_saturating(&self.vesting_coins(block_time), &self.delegated_vesting)
+    }
+
+    /// Records a delegation, which as in the cosmos SDK is made from the vesting coins before
+    /// the free coins
+    pub fn track_delegation(&mut self, block_time: i64, amount: &[Coin]) {
+        let vesting_coins = self.vesting_coins(block_time);
+
+        for coin in amount {
+            let vesting_amount = amount_of(&vesting_coins, &coin.denom);
+            let delegated_vesting = amount_of(&self.delegated_vesting, &coin.denom);
+
+            let vesting = vesting_amount
+                .saturating_sub(delegated_vesting)
+                .min(coin.amount);
+            let free = coin.amount - vesting;
+
+            self.delegated_vesting = add_coins(
+                &self.delegated_vesting,
+                &[Coin {
+                    denom: coin.denom.clone(),
+                    amount: vesting,
+                }],
+            );
+            self.delegated_free = add_coins(
+                &self.delegated_free,
+                &[Coin {
+                    denom: coin.denom.clone(),
+                    amount: free,
+                }],
+            );
+        }
+    }
+
+    /// Records an undelegation, which as in the cosmos SDK is taken from the delegated free
+    /// coins before the delegated vesting coins
+    pub fn track_undelegation(&mut self, amount: &[Coin]) {
+        for coin in amount {
+            let free = amount_of(&self.delegated_free, &coin.denom).min(coin.amount);
+            let vesting = amount_of(&self.delegated_vesting, &coin.denom).min(coin.amount - free);
+
+            self.delegated_free = sub_coins_saturating(
+                &self.delegated_free,
+                &[Coin {
+                    denom: coin.denom.clone(),
+                    amount: free,
+                }],
+            );
+            self.delegated_vesting = sub_coins_saturating(
+                &self.delegated_vesting,
+                &[Coin {
+                    denom: coin.denom.clone(),
+                    amount: vesting,
+                }],
+            );
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Vesting {}
+
+impl Vesting {
+    /// Handles MsgCreateVestingAccount, which creates a vesting account funded by the sender.
+    /// Continuous vesting starts at the block time.
+    pub fn create_vesting_account(
+        ctx: &mut Context,
+        msg: MsgCreateVestingAccount,
+    ) -> Result<MsgCreateVestingAccountResponse, AppError> {
+        let from_address = AccAddress::from_bech32(&msg.from_address)?;
+        let to_address = AccAddress::from_bech32(&msg.to_address)?;
+
+        if Bank::blocked_addr(&to_address) {
+            return Err(ERR_UNAUTHORIZED.wrap(format!(
+                "{} is not allowed to receive funds",
+                msg.to_address
+            )));
+        }
+
+        if Auth::has_account(ctx, &to_address) {
+            return Err(
+                ERR_INVALID_REQUEST.wrap(format!("account {} already exists", msg.to_address))
+            );
+        }
+
+        let account = Auth::new_account_with_address(ctx, &to_address);
+        Auth::set_account(ctx, account);
+
+        let schedule = if msg.delayed {
+            VestingSchedule::Delayed
+        } else {
+            VestingSchedule::Continuous {
+                start_time: ctx.block_time().seconds,
+            }
+        };
+        let vesting_account = VestingAccount {
+            original_vesting: msg.amount.clone(),
+            delegated_free: vec![],
+            delegated_vesting: vec![],
+            end_time: msg.end_time,
+            schedule,
+        };
+        Vesting::set_vesting_account(ctx, &to_address, &vesting_account);
+
+        Bank::send_coins(ctx, &from_address, &to_address, &msg.amount)?;
+
+        ctx.event_manager().emit_event(new_event(
+            EVENT_TYPE_MESSAGE,
+            vec![(ATTRIBUTE_KEY_MODULE, MODULE_NAME.into())],
+        ));
+
+        Ok(MsgCreateVestingAccountResponse {})
+    }
+
+    /// Returns the vesting state of the address, if it's a vesting account
+    pub fn get_vesting_account(ctx: &mut Context, address: &AccAddress) -> Option<VestingAccount> {
+        let auth_store = ctx.get_kv_store(AUTH_STORE_PREFIX.into());
+        Vesting::read_vesting_account(&auth_store, address)
+    }
+
+    /// Returns the vesting state of the address from the auth store, e.g. when serving queries
+    pub fn read_vesting_account<S: ReadKVStore + ?Sized>(
+        auth_store: &S,
+        address: &AccAddress,
+    ) -> Option<VestingAccount> {
+        VESTING_ACCOUNTS.get(auth_store, address).map(|any| {
+            VestingAccount::from_any(&any)
+                .expect("store should contain valid data")
+                .0
+        })
+    }
+
+    pub fn set_vesting_account(
+        ctx: &mut Context,
+        address: &AccAddress,
+        vesting_account: &VestingAccount,
+    ) {
+        let mut auth_store = ctx.get_kv_store(AUTH_STORE_PREFIX.into());
+        VESTING_ACCOUNTS.set(&mut auth_store, address, &vesting_account.to_any(None));
+    }
+}
+
+/// Rounds half to even, as with the cosmos SDK `Dec.RoundInt`
+fn round_int(dec: Decimal256) -> Uint256 {
+    let floor = dec * Uint256::one();
+    let fraction = dec - Decimal256::from_ratio(floor, 1u8);
+    let half = Decimal256::percent(50);
+    let is_odd = floor % Uint256::from(2u8) == Uint256::one();
+
+    if fraction > half || (fraction == half && is_odd) {
+        floor + Uint256::one()
+    } else {
+        floor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use tendermint_proto::{google::protobuf::Timestamp, types::Header};
+
+    use super::*;
+    use crate::{
+        store::MultiStore,
+        x::bank::{Balance, GenesisState},
+    };
+
+    const FROM: &str = "cosmos1syavy2npfyt9tcncdtsdzf7kny9lh777pahuux";
+    const TO: &str = "cosmos1qyqszqgpqyqszqgpqyqszqgpqyqszqgpjnp7du";
+
+    fn from() -> AccAddress {
+        AccAddress::from_bech32(FROM).unwrap()
+    }
+
+    fn uatom(amount: u32) -> Vec<Coin> {
+        vec![Coin {
+            denom: "uatom".into(),
+            amount: amount.into(),
+        }]
+    }
+
+    fn continuous_account() -> VestingAccount {
+        VestingAccount {
+            original_vesting: uatom(100),
+            delegated_free: vec![],
+            delegated_vesting: vec![],
+            end_time: 200,
+            schedule: VestingSchedule::Continuous { start_time: 100 },
+        }
+    }
+
+    /// Returns a context at the block time where FROM holds 100uatom
+    fn context_at(seconds: i64) -> Context {
+        let mut ctx = Context::new(MultiStore::new()).with_header(Header {
+            time: Some(Timestamp { seconds, nanos: 0 }),
+            ..Default::default()
+        });
+        Bank::init_genesis(
+            &mut ctx,
+            GenesisState {
+                balances: vec![Balance {
+                    address: AccAddress::from_bech32(FROM).unwrap(),
+                    coins: uatom(100),
+                }],
+            },
+        );
+        ctx
+    }
+
+    #[test]
+    fn continuous_vesting_works() {
+        let account = continuous_account();
+
+        assert_eq!(account.vested_coins(50), vec![]);
+        assert_eq!(account.vested_coins(125), uatom(25));
+        assert_eq!(account.vesting_coins(125), uatom(75));
+        assert_eq!(account.vested_coins(200), uatom(100));
+        assert_eq!(account.locked_coins(300), vec![]);
+    }
+
+    #[test]
+    fn continuous_vesting_rounds_half_to_even() {
+        let account = VestingAccount {
+            original_vesting: uatom(10),
+            ..continuous_account()
+        };
+
+        assert_eq!(account.vested_coins(127), uatom(3));
+        assert_eq!(account.vested_coins(125), uatom(2));
+        assert_eq!(account.vested_coins(135), uatom(4));
+    }
+
+    #[test]
+    fn delayed_vesting_works() {
+        let account = VestingAccount {
+            schedule: VestingSchedule::Delayed,
+            ..continuous_account()
+        };
+
+        assert_eq!(account.vested_coins(199), vec![]);
+        assert_eq!(account.locked_coins(199), uatom(100));
+        assert_eq!(account.vested_coins(200), uatom(100));
+    }
+
+    #[test]
+    fn delegation_tracking_works() {
+        let mut account = continuous_account();
+
+        // 75uatom is vesting, so a delegation of 80uatom is 75 vesting and 5 free
+        account.track_delegation(125, &uatom(80));
+        assert_eq!(account.delegated_vesting, uatom(75));
+        assert_eq!(account.delegated_free, uatom(5));
+        assert_eq!(account.locked_coins(125), vec![]);
+
+        // undelegations come from the free coins first
+        account.track_undelegation(&uatom(10));
+        assert_eq!(account.delegated_free, vec![]);
+        assert_eq!(account.delegated_vesting, uatom(70));
+        assert_eq!(account.locked_coins(125), uatom(5));
+    }
+
+    #[test]
+    fn any_round_trip_works() {
+        let account = continuous_account();
+        let base_account = BaseAccount {
+            address: TO.into(),
+            ..Default::default()
+        };
+
+        let (decoded, decoded_base) =
+            VestingAccount::from_any(&account.to_any(Some(base_account.clone()))).unwrap();
+        assert_eq!(decoded, account);
+        assert_eq!(decoded_base, Some(base_account));
+    }
+
+    #[test]
+    fn create_vesting_account_works() {
+        let mut ctx = context_at(100);
+        let msg = MsgCreateVestingAccount {
+            from_address: FROM.into(),
+            to_address: TO.into(),
+            amount: uatom(40),
+            end_time: 200,
+            delayed: false,
+        };
+        Vesting::create_vesting_account(&mut ctx, msg.clone()).unwrap();
+
+        let to = AccAddress::from_bech32(TO).unwrap();
+        let account = Vesting::get_vesting_account(&mut ctx, &to).unwrap();
+        assert_eq!(account.locked_coins(100), uatom(40));
+        assert_eq!(
+            account.schedule,
+            VestingSchedule::Continuous { start_time: 100 }
+        );
+
+        // the vesting coins can't be sent
+        let err = Bank::send_coins(&mut ctx, &to, &from(), &uatom(1)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "0uatom is smaller than 1uatom: insufficient funds"
+        );
+
+        // the account already exists
+        let err = Vesting::create_vesting_account(&mut ctx, msg).unwrap_err();
+        assert_eq!(err.code(), 18);
+    }
+
+    #[test]
+    fn vested_coins_can_be_sent() {
+        let mut ctx = context_at(100);
+        Vesting::create_vesting_account(
+            &mut ctx,
+            MsgCreateVestingAccount {
+                from_address: FROM.into(),
+                to_address: TO.into(),
+                amount: uatom(40),
+                end_time: 200,
+                delayed: false,
+            },
+        )
+        .unwrap();
+
+        // half way through 20uatom has vested
+        let to = AccAddress::from_bech32(TO).unwrap();
+        let mut ctx = Context::new(ctx.multi_store).with_header(Header {
+            time: Some(Timestamp {
+                seconds: 150,
+                nanos: 0,
+            }),
+            ..Default::default()
+        });
+        assert!(Bank::send_coins(&mut ctx, &to, &from(), &uatom(21)).is_err());
+        Bank::send_coins(&mut ctx, &to, &from(), &uatom(20)).unwrap();
+
+        // vesting coins can be delegated
+        Bank::delegate_coins_from_account_to_module(&mut ctx, &to, "fee_collector", &uatom(20))
+            .unwrap();
+        let account = Vesting::get_vesting_account(&mut ctx, &to).unwrap();
+        assert_eq!(account.delegated_vesting, uatom(20));
+        assert_eq!(Bank::spendable_coins(&mut ctx, &to), vec![]);
+    }
+}
@@ -18,6 +18,8 @@ use cosmwasm_std::Fraction;
 use cosmwasm_std::Isqrt;
 use cosmwasm_std::Uint256;
 
+use crate::error::RoundUpOverflowError;
+
 /// A fixed-point decimal value with 18 fractional digits, i.e. Decimal256(1_000_000_000_000_000_000) == 1.0
 ///
 /// The greatest possible value that can be represented is
@@ -210,25 +212,25 @@ impl Decimal256 {
         Self((self.0 / Self::DECIMAL_FRACTIONAL) * Self::DECIMAL_FRACTIONAL)
     }
 
-    // /// Rounds value up after decimal places. Panics on overflow.
-    // pub fn ceil(&self) -> Self {
-    //     match self.checked_ceil() {
-    //         Ok(value) => value,
-    //         Err(_) => panic!("attempt to ceil with overflow"),
-    //     }
-    // }
-
-    // /// Rounds value up after decimal places. Returns OverflowError on overflow.
-    // pub fn checked_ceil(&self) -> Result<Self, RoundUpOverflowError> {
-    //     let floor = self.floor();
-    //     if floor == self {
-    //         Ok(floor)
-    //     } else {
-    //         floor
-    //             .checked_add(Decimal256::one())
-    //             .map_err(|_| RoundUpOverflowError)
-    //     }
-    // }
+    /// Rounds value up after decimal places. Panics on overflow.
+    pub fn ceil(&self) -> Self {
+        match self.checked_ceil() {
+            Ok(value) => value,
+            Err(_) => panic!("attempt to ceil with overflow"),
+        }
+    }
+
+    /// Rounds value up after decimal places. Returns OverflowError on overflow.
+    pub fn checked_ceil(&self) -> Result<Self, RoundUpOverflowError> {
+        let floor = self.floor();
+        if floor == self {
+            Ok(floor)
+        } else {
+            floor
+                .checked_add(Decimal256::one())
+                .map_err(|_| RoundUpOverflowError)
+        }
+    }
 
     pub fn checked_add(self, other: Self) -> Result<Self, OverflowError> {
         self.0
@@ -386,6 +388,64 @@ impl Decimal256 {
         let internal = cosmwasm_std::Uint256::from_str(input)?;
         Ok(Self(internal))
     }
+
+    /// Multiplies, rounding half to even as with the cosmos SDK `Dec.Mul`. The `Mul` impl
+    /// truncates, as with `Dec.MulTruncate`. Panics on overflow.
+    pub fn mul_rounded(self, other: Self) -> Self {
+        let product = self.0.full_mul(other.0);
+        Self(div_round_half_to_even(
+            product,
+            Uint512::from_uint256(Self::DECIMAL_FRACTIONAL),
+        ))
+    }
+
+    /// Divides, rounding half to even as with the cosmos SDK `Dec.Quo`. The `Div` impl truncates,
+    /// as with `Dec.QuoTruncate`. Panics if other is zero.
+    pub fn div_rounded(self, other: Self) -> Self {
+        // as in the SDK the quotient is first truncated to 36 decimal places
+        let quotient =
+            self.0.full_mul(Self::DECIMAL_FRACTIONAL_SQUARED) / Uint512::from_uint256(other.0);
+        Self(div_round_half_to_even(
+            quotient,
+            Uint512::from_uint256(Self::DECIMAL_FRACTIONAL),
+        ))
+    }
+
+    /// Divides, rounding up as with the cosmos SDK `Dec.QuoRoundUp`. Panics if other is zero.
+    pub fn div_rounded_up(self, other: Self) -> Self {
+        // as in the SDK the quotient is first truncated to 36 decimal places
+        let quotient =
+            self.0.full_mul(Self::DECIMAL_FRACTIONAL_SQUARED) / Uint512::from_uint256(other.0);
+        let fractional = Uint512::from_uint256(Self::DECIMAL_FRACTIONAL);
+
+        let mut rounded = quotient / fractional;
+        if !(quotient % fractional).is_zero() {
+            rounded += Uint512::from(1u32);
+        }
+
+        match rounded.try_into() {
+            Ok(result) => Self(result),
+            Err(_) => panic!("attempt to divide with overflow"),
+        }
+    }
+}
+
+/// Returns numerator / denominator rounded half to even, panicking if it doesn't fit in a Uint256
+fn div_round_half_to_even(numerator: Uint512, denominator: Uint512) -> Uint256 {
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+
+    let rounded = match (remainder + remainder).cmp(&denominator) {
+        Ordering::Less => quotient,
+        Ordering::Greater => quotient + Uint512::from(1u32),
+        Ordering::Equal if (quotient % Uint512::from(2u32)).is_zero() => quotient,
+        Ordering::Equal => quotient + Uint512::from(1u32),
+    };
+
+    match rounded.try_into() {
+        Ok(result) => result,
+        Err(_) => panic!("attempt to multiply with overflow"),
+    }
 }
 
 impl Fraction<Uint256> for Decimal256 {
@@ -1908,6 +1968,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decimal256_rounded_methods_work() {
+        let atomics = |x: u128| Decimal256(Uint256::from(x));
+
+        // 0.000000000000000005 * 0.1 = 0.0000000000000000005, which rounds to even
+        assert_eq!(
+            atomics(5).mul_rounded(Decimal256::permille(100)),
+            atomics(0)
+        );
+        assert_eq!(
+            atomics(15).mul_rounded(Decimal256::permille(100)),
+            atomics(2)
+        );
+        assert_eq!(
+            atomics(16).mul_rounded(Decimal256::permille(100)),
+            atomics(2)
+        );
+        assert_eq!(
+            atomics(14).mul_rounded(Decimal256::permille(100)),
+            atomics(1)
+        );
+        assert_eq!(
+            Decimal256::percent(150).mul_rounded(Decimal256::percent(200)),
+            Decimal256::percent(300)
+        );
+
+        // 2 / 3 = 0.666666666666666667 rather than the truncated 0.666666666666666666
+        let two = Decimal256::from_str("2").unwrap();
+        let three = Decimal256::from_str("3").unwrap();
+        assert_eq!(two.div_rounded(three), atomics(666666666666666667));
+        assert_eq!(two / three, atomics(666666666666666666));
+        assert_eq!(
+            Decimal256::one().div_rounded(three),
+            atomics(333333333333333333)
+        );
+
+        assert_eq!(
+            Decimal256::one().div_rounded_up(three),
+            atomics(333333333333333334)
+        );
+        assert_eq!(
+            Decimal256::one().div_rounded_up(Decimal256::percent(50)),
+            two
+        );
+    }
+
     #[test]
     fn decimal256_iter_sum() {
         let items = vec![
@@ -2149,27 +2255,27 @@ mod tests {
         assert_eq!(Decimal256::percent(200).floor(), Decimal256::percent(200));
         assert_eq!(Decimal256::percent(99).floor(), Decimal256::zero());
 
-        // assert_eq!(Decimal256::one().ceil(), Decimal256::one());
-        // assert_eq!(Decimal256::percent(150).ceil(), Decimal256::percent(200));
-        // assert_eq!(Decimal256::percent(199).ceil(), Decimal256::percent(200));
-        // assert_eq!(Decimal256::percent(99).ceil(), Decimal256::one());
-        // assert_eq!(Decimal256(Uint256::from(1u128)).ceil(), Decimal256::one());
-    }
-
-    // #[test]
-    // #[should_panic(expected = "attempt to ceil with overflow")]
-    // fn decimal256_ceil_panics() {
-    //     let _ = Decimal256::MAX.ceil();
-    // }
-
-    // #[test]
-    // fn decimal256_checked_ceil() {
-    //     assert_eq!(
-    //         Decimal256::percent(199).checked_ceil(),
-    //         Ok(Decimal256::percent(200))
-    //     );
-    //     assert_eq!(Decimal256::MAX.checked_ceil(), Err(RoundUpOverflowError));
-    // }
+        assert_eq!(Decimal256::one().ceil(), Decimal256::one());
+        assert_eq!(Decimal256::percent(150).ceil(), Decimal256::percent(200));
+        assert_eq!(Decimal256::percent(199).ceil(), Decimal256::percent(200));
+        assert_eq!(Decimal256::percent(99).ceil(), Decimal256::one());
+        assert_eq!(Decimal256(Uint256::from(1u128)).ceil(), Decimal256::one());
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to ceil with overflow")]
+    fn decimal256_ceil_panics() {
+        let _ = Decimal256::MAX.ceil();
+    }
+
+    #[test]
+    fn decimal256_checked_ceil() {
+        assert_eq!(
+            Decimal256::percent(199).checked_ceil(),
+            Ok(Decimal256::percent(200))
+        );
+        assert_eq!(Decimal256::MAX.checked_ceil(), Err(RoundUpOverflowError));
+    }
 
     #[test]
     fn decimal256_partial_eq() {
@@ -2,3 +2,4 @@ mod decimal256;
 mod error;
 
 pub use decimal256::Decimal256;
+pub use error::RoundUpOverflowError;